@@ -1,6 +1,11 @@
 use std::{env::args, fs, process::exit};
 
-use img_parts::DynImage;
+use img_parts::{
+    gif::GifBlock,
+    isobmff::IsoBox,
+    tiff::{TiffIfd, TiffValue},
+    DynImage,
+};
 
 fn main() {
     let mut args = args();
@@ -8,7 +13,9 @@ fn main() {
     let input_path = match args.nth(1) {
         Some(path) => path,
         None => {
-            eprintln!("Please specify the input file path (must be a jpeg, png or webp)");
+            eprintln!(
+                "Please specify the input file path (must be a jpeg, png, webp, gif, tiff or heif)"
+            );
             exit(1);
         }
     };
@@ -58,5 +65,67 @@ fn main() {
                 println!("| {i: <3} | {id: <6} | {len: <12} |");
             }
         }
+        DynImage::Gif(gif) => {
+            println!("---------------------------------");
+            println!(
+                "| {: <3} | {: <6} | {: <12} |",
+                "i", "block", "total length"
+            );
+            println!("---------------------------------");
+
+            for (i, block) in gif.blocks().iter().enumerate() {
+                // extension label printed in HEX
+                let kind = match block {
+                    GifBlock::Extension(extension) => format!("{:X}", extension.label()),
+                    GifBlock::Image(_) => "image".to_string(),
+                };
+                let len = block.len();
+                println!("| {i: <3} | {kind: <6} | {len: <12} |");
+            }
+        }
+        DynImage::Tiff(tiff) => {
+            println!("---------------------------------");
+            println!("| {: <3} | {: <6} | {: <12} |", "i", "tag", "count");
+            println!("---------------------------------");
+
+            for (i, ifd) in tiff.ifds().iter().enumerate() {
+                print_ifd(&i.to_string(), ifd);
+            }
+        }
+        DynImage::Heif(heif) => {
+            println!("---------------------------------");
+            println!("| {: <3} | {: <6} | {: <12} |", "i", "type", "total length");
+            println!("---------------------------------");
+
+            for (i, b) in heif.boxes().iter().enumerate() {
+                print_box(&i.to_string(), b);
+            }
+        }
+        _ => println!("unsupported image format"),
+    }
+}
+
+fn print_box(path: &str, b: &IsoBox) {
+    let kind = b.kind();
+    let kind = String::from_utf8_lossy(&kind);
+    let len = b.len();
+    println!("| {path: <3} | {kind: <6} | {len: <12} |");
+
+    for (i, child) in b.children().iter().enumerate() {
+        print_box(&format!("{path}.{i}"), child);
+    }
+}
+
+fn print_ifd(path: &str, ifd: &TiffIfd) {
+    for entry in ifd.entries() {
+        let tag = entry.tag();
+        let count = entry.count();
+        println!("| {path: <3} | {tag: <6} | {count: <12} |");
+
+        if let TiffValue::Ifds(ifds) = entry.value() {
+            for (i, ifd) in ifds.iter().enumerate() {
+                print_ifd(&format!("{path}.{i}"), ifd);
+            }
+        }
     }
 }
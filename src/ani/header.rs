@@ -0,0 +1,73 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{util::read_checked, Result};
+
+// the size of the header, stored in its first field
+const HEADER_SIZE: u32 = 36;
+
+/// The frames are stored as ICO or CUR data, rather than raw bitmaps
+pub const FLAG_ICON: u32 = 0x0000_0001;
+/// The animation has a `seq ` chunk
+pub const FLAG_SEQUENCE: u32 = 0x0000_0002;
+
+/// The contents of the `anih` chunk of an [`Ani`][super::Ani]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AniHeader {
+    /// The number of frames in the `fram` list
+    pub frames: u32,
+    /// The number of steps of the animation, which can show a frame more than once
+    pub steps: u32,
+    /// The width of raw bitmap frames, `0` for ICO frames
+    pub width: u32,
+    /// The height of raw bitmap frames, `0` for ICO frames
+    pub height: u32,
+    /// The number of bits per pixel of raw bitmap frames, `0` for ICO frames
+    pub bit_count: u32,
+    /// The number of planes of raw bitmap frames, `0` for ICO frames
+    pub planes: u32,
+    /// The duration of every step without a `rate` chunk, in 1/60th of a second
+    pub display_rate: u32,
+    /// The [`FLAG_ICON`] and [`FLAG_SEQUENCE`] flags
+    pub flags: u32,
+}
+
+impl AniHeader {
+    /// Parse the contents of an `anih` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<AniHeader> {
+        let mut b = b.clone();
+        let mut fields = [0; 9];
+        for field in &mut fields {
+            *field = read_checked(&mut b, |b| b.get_u32_le())?;
+        }
+
+        Ok(AniHeader {
+            frames: fields[1],
+            steps: fields[2],
+            width: fields[3],
+            height: fields[4],
+            bit_count: fields[5],
+            planes: fields[6],
+            display_rate: fields[7],
+            flags: fields[8],
+        })
+    }
+
+    /// Encode this `AniHeader` into the contents of an `anih` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(HEADER_SIZE as usize);
+        b.put_u32_le(HEADER_SIZE);
+        b.put_u32_le(self.frames);
+        b.put_u32_le(self.steps);
+        b.put_u32_le(self.width);
+        b.put_u32_le(self.height);
+        b.put_u32_le(self.bit_count);
+        b.put_u32_le(self.planes);
+        b.put_u32_le(self.display_rate);
+        b.put_u32_le(self.flags);
+        b.freeze()
+    }
+}
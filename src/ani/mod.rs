@@ -0,0 +1,305 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::ImageEncoder,
+    riff::{impl_set_info, RiffChunk, RiffContent, RiffInfo, RiffPreset},
+    util::{impl_subchunks, read_checked},
+    Error, Result,
+};
+
+pub use self::header::{AniHeader, FLAG_ICON, FLAG_SEQUENCE};
+
+mod header;
+
+pub const CHUNK_ANIH: [u8; 4] = [b'a', b'n', b'i', b'h'];
+pub const CHUNK_ICON: [u8; 4] = [b'i', b'c', b'o', b'n'];
+pub const CHUNK_LIST: [u8; 4] = [b'L', b'I', b'S', b'T'];
+pub const CHUNK_RATE: [u8; 4] = [b'r', b'a', b't', b'e'];
+pub const CHUNK_SEQ: [u8; 4] = [b's', b'e', b'q', b' '];
+
+pub const KIND_ACON: [u8; 4] = [b'A', b'C', b'O', b'N'];
+pub const LIST_FRAM: [u8; 4] = [b'f', b'r', b'a', b'm'];
+
+/// The representation of a Windows animated cursor
+///
+/// Every frame is an `icon` chunk holding the contents of an ICO or
+/// CUR file, stored in a `LIST` chunk with a form type of `fram`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ani {
+    riff: RiffChunk,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Ani {
+    /// Construct a new `Ani` from a [`RiffChunk`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns a [`Error::WrongSignature`]
+    /// if the [`RiffChunk`] isn't a `RIFF` chunk, if its
+    /// content isn't a `List` or if the list's kind isn't "ACON".
+    pub fn new(riff: RiffChunk) -> Result<Ani> {
+        match riff.content().list() {
+            Some((kind, _)) if riff.id() == *b"RIFF" && kind == Some(KIND_ACON) => Ok(Ani { riff }),
+            _ => Err(Error::WrongSignature),
+        }
+    }
+
+    /// Create a new `Ani` from a Reader.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    #[inline]
+    pub fn from_bytes(b: Bytes) -> Result<Ani> {
+        let riff = RiffChunk::from_bytes_with_schema(b, &RiffPreset::Ani)?;
+        Ani::new(riff)
+    }
+
+    /// Construct a new `Ani` from `frames`, the contents of ICO or CUR files,
+    /// each one shown for `display_rate` 1/60th of a second.
+    pub fn from_frames(frames: Vec<Bytes>, display_rate: u32) -> Ani {
+        let header = AniHeader {
+            frames: 0,
+            steps: 0,
+            width: 0,
+            height: 0,
+            bit_count: 0,
+            planes: 0,
+            display_rate,
+            flags: FLAG_ICON,
+        };
+
+        let riff = RiffChunk::new(
+            *b"RIFF",
+            RiffContent::List {
+                kind: Some(KIND_ACON),
+                subchunks: alloc::vec![RiffChunk::new(
+                    CHUNK_ANIH,
+                    RiffContent::Data(header.to_bytes())
+                )],
+            },
+        );
+
+        let mut ani = Ani { riff };
+        ani.set_frames(frames);
+        ani
+    }
+
+    /// Get the parsed `anih` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `anih` chunk is missing, malformed or truncated.
+    pub fn header(&self) -> Result<AniHeader> {
+        let data = self
+            .chunk_by_id(CHUNK_ANIH)
+            .and_then(|chunk| chunk.content().data())
+            .ok_or(Error::InvalidData)?;
+        AniHeader::from_bytes(data)
+    }
+
+    /// Replace the `anih` chunk with `header`.
+    pub fn set_header(&mut self, header: &AniHeader) {
+        let chunk = RiffChunk::new(CHUNK_ANIH, RiffContent::Data(header.to_bytes()));
+        match self
+            .chunks()
+            .iter()
+            .position(|chunk| chunk.id() == CHUNK_ANIH)
+        {
+            Some(pos) => self.chunks_mut()[pos] = chunk,
+            None => {
+                // the header goes before any other chunk, except for the INFO list
+                let pos = self
+                    .chunks()
+                    .iter()
+                    .position(|chunk| chunk.id() != CHUNK_LIST || is_fram(chunk))
+                    .unwrap_or(self.chunks().len());
+                self.chunks_mut().insert(pos, chunk);
+            }
+        }
+    }
+
+    /// Update the `anih` chunk with `f`, ignoring a missing or invalid header.
+    fn update_header(&mut self, f: impl FnOnce(&mut AniHeader)) {
+        if let Ok(mut header) = self.header() {
+            f(&mut header);
+            self.set_header(&header);
+        }
+    }
+
+    /// Get the contents of the ICO or CUR file of every frame.
+    pub fn frames(&self) -> Vec<Bytes> {
+        self.fram()
+            .map(|subchunks| {
+                subchunks
+                    .iter()
+                    .filter(|chunk| chunk.id() == CHUNK_ICON)
+                    .filter_map(|chunk| chunk.content().data().cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Replace the frames with `frames`, the contents of ICO or CUR files.
+    ///
+    /// The number of frames in the `anih` chunk is updated, as well as the
+    /// number of steps if this `Ani` doesn't have a `seq ` chunk.
+    pub fn set_frames(&mut self, frames: Vec<Bytes>) {
+        let count = frames.len() as u32;
+        let fram = RiffChunk::new(
+            CHUNK_LIST,
+            RiffContent::List {
+                kind: Some(LIST_FRAM),
+                subchunks: frames
+                    .into_iter()
+                    .map(|frame| RiffChunk::new(CHUNK_ICON, RiffContent::Data(frame)))
+                    .collect(),
+            },
+        );
+
+        match self.chunks().iter().position(is_fram) {
+            Some(pos) => self.chunks_mut()[pos] = fram,
+            None => self.chunks_mut().push(fram),
+        }
+
+        let has_sequence = self.has_chunk(CHUNK_SEQ);
+        self.update_header(|header| {
+            header.frames = count;
+            if !has_sequence {
+                header.steps = count;
+            }
+        });
+    }
+
+    fn fram(&self) -> Option<&Vec<RiffChunk>> {
+        let fram = self.chunks().iter().find(|chunk| is_fram(chunk))?;
+        fram.content().list().map(|(_, subchunks)| subchunks)
+    }
+
+    /// Get the duration of every step, in 1/60th of a second, stored in the `rate` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `rate` chunk is truncated.
+    pub fn rates(&self) -> Result<Option<Vec<u32>>> {
+        self.u32_chunk(CHUNK_RATE)
+    }
+
+    /// Set the duration of every step, or remove the `rate` chunk if `rates` is `None`.
+    pub fn set_rates(&mut self, rates: Option<&[u32]>) {
+        self.set_u32_chunk(CHUNK_RATE, rates);
+    }
+
+    /// Get the index of the frame shown at every step, stored in the `seq ` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `seq ` chunk is truncated.
+    pub fn sequence(&self) -> Result<Option<Vec<u32>>> {
+        self.u32_chunk(CHUNK_SEQ)
+    }
+
+    /// Set the index of the frame shown at every step, or remove the `seq `
+    /// chunk if `sequence` is `None`.
+    ///
+    /// The number of steps and the [`FLAG_SEQUENCE`] flag of the `anih` chunk are updated.
+    pub fn set_sequence(&mut self, sequence: Option<&[u32]>) {
+        self.set_u32_chunk(CHUNK_SEQ, sequence);
+
+        self.update_header(|header| match sequence {
+            Some(sequence) => {
+                header.steps = sequence.len() as u32;
+                header.flags |= FLAG_SEQUENCE;
+            }
+            None => {
+                header.steps = header.frames;
+                header.flags &= !FLAG_SEQUENCE;
+            }
+        });
+    }
+
+    fn u32_chunk(&self, id: [u8; 4]) -> Result<Option<Vec<u32>>> {
+        let mut b = match self
+            .chunk_by_id(id)
+            .and_then(|chunk| chunk.content().data())
+        {
+            Some(data) => data.clone(),
+            None => return Ok(None),
+        };
+
+        let mut values = Vec::with_capacity(b.len() / 4);
+        while !b.is_empty() {
+            values.push(read_checked(&mut b, |b| b.get_u32_le())?);
+        }
+        Ok(Some(values))
+    }
+
+    fn set_u32_chunk(&mut self, id: [u8; 4], values: Option<&[u32]>) {
+        let pos = self.chunks().iter().position(|chunk| chunk.id() == id);
+        self.remove_chunks_by_id(id);
+
+        if let Some(values) = values {
+            let mut b = BytesMut::with_capacity(values.len() * 4);
+            for &value in values {
+                b.put_u32_le(value);
+            }
+
+            // `rate` and `seq ` go before the frames
+            let pos = pos.unwrap_or_else(|| {
+                self.chunks()
+                    .iter()
+                    .position(is_fram)
+                    .unwrap_or(self.chunks().len())
+            });
+            let chunk = RiffChunk::new(id, RiffContent::Data(b.freeze()));
+            self.chunks_mut().insert(pos, chunk);
+        }
+    }
+
+    /// Get the parsed `LIST` chunk with a form type of `INFO`, holding the title
+    /// and the author of the cursor.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `INFO` list is malformed or truncated.
+    #[inline]
+    pub fn info(&self) -> Result<Option<RiffInfo>> {
+        self.riff.info()
+    }
+
+    impl_set_info!(riff);
+
+    impl_subchunks!("Ani", riff);
+
+    /// Get the total size of the `Ani` once it is encoded.
+    ///
+    /// Internally calls [`RiffChunk::len`][crate::riff::RiffChunk::len] on the
+    /// inner `RiffChunk`
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.riff.len()
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Ani`
+    ///
+    /// Internally calls [`RiffChunk::encoder`][crate::riff::RiffChunk::encoder] on the
+    /// inner `RiffChunk`
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<RiffChunk> {
+        self.riff.encoder()
+    }
+
+    /// Get the inner [`RiffChunk`] of this `Ani`
+    #[inline]
+    pub fn into_inner(self) -> RiffChunk {
+        self.riff
+    }
+}
+
+fn is_fram(chunk: &RiffChunk) -> bool {
+    chunk.id() == CHUNK_LIST && matches!(chunk.content().list(), Some((Some(LIST_FRAM), _)))
+}
@@ -0,0 +1,263 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{read_checked, read_u8_array},
+    Result,
+};
+
+const MAIN_HEADER_SIZE: usize = 56;
+const STREAM_HEADER_SIZE: usize = 56;
+const BITMAP_INFO_HEADER_SIZE: usize = 40;
+
+/// The contents of the `avih` chunk of an [`Avi`][super::Avi]
+///
+/// See <https://learn.microsoft.com/en-us/previous-versions/ms779632(v=vs.85)>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AviMainHeader {
+    /// The duration of a frame, in microseconds
+    pub micro_sec_per_frame: u32,
+    /// The maximum data rate, in bytes per second
+    pub max_bytes_per_sec: u32,
+    /// The alignment of the data, in bytes
+    pub padding_granularity: u32,
+    /// The `AVIF_*` flags, like `AVIF_HASINDEX`
+    pub flags: u32,
+    /// The number of frames in the first `RIFF` chunk
+    pub total_frames: u32,
+    /// The number of frames before the first frame of interleaved files
+    pub initial_frames: u32,
+    /// The number of streams
+    pub streams: u32,
+    /// The suggested size of the read buffer
+    pub suggested_buffer_size: u32,
+    /// The width of the video, in pixels
+    pub width: u32,
+    /// The height of the video, in pixels
+    pub height: u32,
+    /// The reserved fields
+    pub reserved: [u32; 4],
+}
+
+/// The contents of the `strh` chunk of an [`AviStream`][super::AviStream]
+///
+/// See <https://learn.microsoft.com/en-us/previous-versions/ms779638(v=vs.85)>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AviStreamHeader {
+    /// The kind of stream, like `vids` or `auds`
+    pub kind: [u8; 4],
+    /// The codec of the stream, like `MJPG`
+    pub handler: [u8; 4],
+    /// The `AVISF_*` flags
+    pub flags: u32,
+    /// The priority of the stream
+    pub priority: u16,
+    /// The language of the stream
+    pub language: u16,
+    /// The number of frames before the first frame of interleaved files
+    pub initial_frames: u32,
+    /// The time scale, `rate / scale` being the number of samples per second
+    pub scale: u32,
+    /// The rate, `rate / scale` being the number of samples per second
+    pub rate: u32,
+    /// The start time of the stream, in units of `scale / rate` seconds
+    pub start: u32,
+    /// The length of the stream, in units of `scale / rate` seconds
+    pub length: u32,
+    /// The suggested size of the read buffer
+    pub suggested_buffer_size: u32,
+    /// The quality of the data, between `0` and `10000`, or `-1` for the default
+    pub quality: u32,
+    /// The size of a sample, or `0` if it varies
+    pub sample_size: u32,
+    /// The destination rectangle of the stream, as `[left, top, right, bottom]`
+    pub frame: [i16; 4],
+}
+
+/// The `BITMAPINFOHEADER` stored in the `strf` chunk of a video stream
+///
+/// See <https://learn.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-bitmapinfoheader>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AviBitmapInfo {
+    /// The size of the structure, including any extra data following it
+    pub size: u32,
+    /// The width of the frames, in pixels
+    pub width: i32,
+    /// The height of the frames, in pixels
+    pub height: i32,
+    /// The number of planes, always `1`
+    pub planes: u16,
+    /// The number of bits per pixel
+    pub bit_count: u16,
+    /// The codec of the frames, like `MJPG`
+    pub compression: [u8; 4],
+    /// The size of a frame, in bytes
+    pub size_image: u32,
+    /// The horizontal resolution, in pixels per meter
+    pub x_pels_per_meter: i32,
+    /// The vertical resolution, in pixels per meter
+    pub y_pels_per_meter: i32,
+    /// The number of colors used
+    pub clr_used: u32,
+    /// The number of important colors
+    pub clr_important: u32,
+}
+
+impl AviMainHeader {
+    /// Parse the contents of an `avih` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<AviMainHeader> {
+        let mut b = b.clone();
+        let mut fields = [0; 14];
+        for field in &mut fields {
+            *field = read_checked(&mut b, |b| b.get_u32_le())?;
+        }
+
+        Ok(AviMainHeader {
+            micro_sec_per_frame: fields[0],
+            max_bytes_per_sec: fields[1],
+            padding_granularity: fields[2],
+            flags: fields[3],
+            total_frames: fields[4],
+            initial_frames: fields[5],
+            streams: fields[6],
+            suggested_buffer_size: fields[7],
+            width: fields[8],
+            height: fields[9],
+            reserved: fields[10..].try_into().unwrap(),
+        })
+    }
+
+    /// Encode this `AviMainHeader` into the contents of an `avih` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(MAIN_HEADER_SIZE);
+        b.put_u32_le(self.micro_sec_per_frame);
+        b.put_u32_le(self.max_bytes_per_sec);
+        b.put_u32_le(self.padding_granularity);
+        b.put_u32_le(self.flags);
+        b.put_u32_le(self.total_frames);
+        b.put_u32_le(self.initial_frames);
+        b.put_u32_le(self.streams);
+        b.put_u32_le(self.suggested_buffer_size);
+        b.put_u32_le(self.width);
+        b.put_u32_le(self.height);
+        for reserved in self.reserved {
+            b.put_u32_le(reserved);
+        }
+        b.freeze()
+    }
+}
+
+impl AviStreamHeader {
+    /// Parse the contents of a `strh` chunk
+    ///
+    /// The destination rectangle is missing from some old files,
+    /// in which case it is set to zeros.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<AviStreamHeader> {
+        let mut b = b.clone();
+
+        let kind = read_u8_array(&mut b)?;
+        let handler = read_u8_array(&mut b)?;
+        let flags = read_checked(&mut b, |b| b.get_u32_le())?;
+        let priority = read_checked(&mut b, |b| b.get_u16_le())?;
+        let language = read_checked(&mut b, |b| b.get_u16_le())?;
+        let mut fields = [0; 8];
+        for field in &mut fields {
+            *field = read_checked(&mut b, |b| b.get_u32_le())?;
+        }
+
+        let mut frame = [0; 4];
+        if b.len() >= 8 {
+            for side in &mut frame {
+                *side = b.get_i16_le();
+            }
+        }
+
+        Ok(AviStreamHeader {
+            kind,
+            handler,
+            flags,
+            priority,
+            language,
+            initial_frames: fields[0],
+            scale: fields[1],
+            rate: fields[2],
+            start: fields[3],
+            length: fields[4],
+            suggested_buffer_size: fields[5],
+            quality: fields[6],
+            sample_size: fields[7],
+            frame,
+        })
+    }
+
+    /// Encode this `AviStreamHeader` into the contents of a `strh` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(STREAM_HEADER_SIZE);
+        b.extend_from_slice(&self.kind);
+        b.extend_from_slice(&self.handler);
+        b.put_u32_le(self.flags);
+        b.put_u16_le(self.priority);
+        b.put_u16_le(self.language);
+        b.put_u32_le(self.initial_frames);
+        b.put_u32_le(self.scale);
+        b.put_u32_le(self.rate);
+        b.put_u32_le(self.start);
+        b.put_u32_le(self.length);
+        b.put_u32_le(self.suggested_buffer_size);
+        b.put_u32_le(self.quality);
+        b.put_u32_le(self.sample_size);
+        for side in self.frame {
+            b.put_i16_le(side);
+        }
+        b.freeze()
+    }
+}
+
+impl AviBitmapInfo {
+    /// Parse the `BITMAPINFOHEADER` at the start of a `strf` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<AviBitmapInfo> {
+        let mut b = b.clone();
+
+        Ok(AviBitmapInfo {
+            size: read_checked(&mut b, |b| b.get_u32_le())?,
+            width: read_checked(&mut b, |b| b.get_i32_le())?,
+            height: read_checked(&mut b, |b| b.get_i32_le())?,
+            planes: read_checked(&mut b, |b| b.get_u16_le())?,
+            bit_count: read_checked(&mut b, |b| b.get_u16_le())?,
+            compression: read_u8_array(&mut b)?,
+            size_image: read_checked(&mut b, |b| b.get_u32_le())?,
+            x_pels_per_meter: read_checked(&mut b, |b| b.get_i32_le())?,
+            y_pels_per_meter: read_checked(&mut b, |b| b.get_i32_le())?,
+            clr_used: read_checked(&mut b, |b| b.get_u32_le())?,
+            clr_important: read_checked(&mut b, |b| b.get_u32_le())?,
+        })
+    }
+
+    /// Encode this `AviBitmapInfo` into a `BITMAPINFOHEADER`
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(BITMAP_INFO_HEADER_SIZE);
+        b.put_u32_le(self.size);
+        b.put_i32_le(self.width);
+        b.put_i32_le(self.height);
+        b.put_u16_le(self.planes);
+        b.put_u16_le(self.bit_count);
+        b.extend_from_slice(&self.compression);
+        b.put_u32_le(self.size_image);
+        b.put_i32_le(self.x_pels_per_meter);
+        b.put_i32_le(self.y_pels_per_meter);
+        b.put_u32_le(self.clr_used);
+        b.put_u32_le(self.clr_important);
+        b.freeze()
+    }
+}
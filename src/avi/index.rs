@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, Bytes};
+
+use super::{AviChunk, LIST_MOVI};
+use crate::{
+    riff::{RiffChunk, RiffContent},
+    util::{read_checked, read_u8_array, split_to_checked},
+    Error, Result,
+};
+
+const AVIIF_LIST: u32 = 0x0000_0001;
+const AVIIF_KEYFRAME: u32 = 0x0000_0010;
+
+const AVI_INDEX_OF_INDEXES: u8 = 0x00;
+const AVI_INDEX_OF_CHUNKS: u8 = 0x01;
+// set on the size of the standard index entries of non key frames
+const AVI_INDEX_DELTA_FRAME: u32 = 0x8000_0000;
+
+/// Finds the chunks of a RIFF tree from their position in the encoded file
+#[derive(Debug)]
+pub(super) struct Locator<'a> {
+    // the chunks holding data, sorted by the position of their data
+    chunks: Vec<(u64, &'a RiffChunk)>,
+    // the position of the form type of the first `movi` list
+    movi: Option<u64>,
+}
+
+impl<'a> Locator<'a> {
+    pub(super) fn new(riffs: &'a [RiffChunk]) -> Locator<'a> {
+        let mut locator = Locator {
+            chunks: Vec::new(),
+            movi: None,
+        };
+
+        let mut offset = 0;
+        for riff in riffs {
+            locator.visit(riff, offset);
+            offset += riff.len();
+        }
+        locator
+    }
+
+    fn visit(&mut self, chunk: &'a RiffChunk, offset: u64) {
+        match chunk.content() {
+            RiffContent::Data(_) => self.chunks.push((offset + 8, chunk)),
+            RiffContent::List { kind, subchunks } => {
+                if *kind == Some(LIST_MOVI) && self.movi.is_none() {
+                    self.movi = Some(offset + 8);
+                }
+
+                let mut offset = offset + 8 + if kind.is_some() { 4 } else { 0 };
+                for subchunk in subchunks {
+                    self.visit(subchunk, offset);
+                    offset += subchunk.len();
+                }
+            }
+        }
+    }
+
+    /// Get the chunk with its data starting at `offset`
+    fn find(&self, offset: u64) -> Option<&'a RiffChunk> {
+        let pos = self
+            .chunks
+            .binary_search_by_key(&offset, |(offset, _)| *offset)
+            .ok()?;
+        Some(self.chunks[pos].1)
+    }
+
+    /// Read the chunks of `stream` listed in the `idx1` chunk
+    ///
+    /// The offsets are usually relative to the form type of the `movi` list,
+    /// but some writers use absolute offsets.
+    pub(super) fn read_idx1(&self, idx1: &Bytes, stream: u16) -> Result<Vec<AviChunk>> {
+        let mut b = idx1.clone();
+
+        let mut chunks = Vec::new();
+        while !b.is_empty() {
+            let id = read_u8_array(&mut b)?;
+            let flags = read_checked(&mut b, |b| b.get_u32_le())?;
+            let offset = read_checked(&mut b, |b| b.get_u32_le())?;
+            let _size = read_checked(&mut b, |b| b.get_u32_le())?;
+            if flags & AVIIF_LIST != 0 || stream_number(id) != Some(stream) {
+                continue;
+            }
+
+            let offset = u64::from(offset) + 8;
+            let chunk = self
+                .movi
+                .and_then(|movi| self.find(movi + offset))
+                .filter(|chunk| chunk.id() == id)
+                .or_else(|| self.find(offset).filter(|chunk| chunk.id() == id))
+                .ok_or(Error::InvalidData)?;
+            chunks.push(AviChunk::new(chunk, flags & AVIIF_KEYFRAME != 0)?);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Read the chunks listed in an OpenDML `indx` chunk
+    ///
+    /// The `indx` chunk is either a super index, listing the `ix##`
+    /// standard indexes, or a standard index itself.
+    pub(super) fn read_indx(&self, indx: &Bytes) -> Result<Vec<AviChunk>> {
+        let mut b = indx.clone();
+
+        let longs_per_entry = read_checked(&mut b, |b| b.get_u16_le())?;
+        let _sub_type = read_checked(&mut b, |b| b.get_u8())?;
+        let index_type = read_checked(&mut b, |b| b.get_u8())?;
+        let entries = read_checked(&mut b, |b| b.get_u32_le())?;
+
+        let mut chunks = Vec::new();
+        match index_type {
+            AVI_INDEX_OF_CHUNKS => self.read_standard_index(indx, &mut chunks)?,
+            AVI_INDEX_OF_INDEXES => {
+                // chunk id (4 bytes) + reserved (12 bytes)
+                split_to_checked(&mut b, 16)?;
+                let stride = usize::from(longs_per_entry) * 4;
+                if stride < 16 {
+                    return Err(Error::InvalidData);
+                }
+
+                for _ in 0..entries {
+                    let mut entry = split_to_checked(&mut b, stride)?;
+                    let offset = entry.get_u64_le();
+                    let ix = self.find(offset + 8).ok_or(Error::InvalidData)?;
+                    let ix = ix.content().data().ok_or(Error::InvalidData)?;
+                    self.read_standard_index(ix, &mut chunks)?;
+                }
+            }
+            _ => return Err(Error::InvalidData),
+        }
+
+        Ok(chunks)
+    }
+
+    fn read_standard_index(&self, index: &Bytes, chunks: &mut Vec<AviChunk>) -> Result<()> {
+        let mut b = index.clone();
+
+        let longs_per_entry = read_checked(&mut b, |b| b.get_u16_le())?;
+        let _sub_type = read_checked(&mut b, |b| b.get_u8())?;
+        let index_type = read_checked(&mut b, |b| b.get_u8())?;
+        let entries = read_checked(&mut b, |b| b.get_u32_le())?;
+        let _chunk_id: [u8; 4] = read_u8_array(&mut b)?;
+        let base_offset = read_checked(&mut b, |b| b.get_u64_le())?;
+        let _reserved = read_checked(&mut b, |b| b.get_u32_le())?;
+
+        let stride = usize::from(longs_per_entry) * 4;
+        if index_type != AVI_INDEX_OF_CHUNKS || stride < 8 {
+            return Err(Error::InvalidData);
+        }
+
+        chunks.reserve((entries as usize).min(b.len() / stride));
+        for _ in 0..entries {
+            let mut entry = split_to_checked(&mut b, stride)?;
+            let offset = entry.get_u32_le();
+            let size = entry.get_u32_le();
+
+            let chunk = self
+                .find(base_offset + u64::from(offset))
+                .ok_or(Error::InvalidData)?;
+            chunks.push(AviChunk::new(chunk, size & AVI_INDEX_DELTA_FRAME == 0)?);
+        }
+
+        Ok(())
+    }
+}
+
+/// Collect the chunks of `stream` found in the `movi` lists of `riffs`
+pub(super) fn scan_movi(riffs: &[RiffChunk], stream: u16) -> Result<Vec<AviChunk>> {
+    fn visit(chunks: &[RiffChunk], stream: u16, found: &mut Vec<AviChunk>) -> Result<()> {
+        for chunk in chunks {
+            match chunk.content() {
+                RiffContent::List { subchunks, .. } => visit(subchunks, stream, found)?,
+                RiffContent::Data(_) if stream_number(chunk.id()) == Some(stream) => {
+                    found.push(AviChunk::new(chunk, true)?);
+                }
+                RiffContent::Data(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    let mut found = Vec::new();
+    for riff in riffs {
+        let subchunks = riff.content().list().map_or(&[][..], |(_, s)| s);
+        for movi in subchunks {
+            if let Some((Some(LIST_MOVI), subchunks)) = movi.content().list() {
+                visit(subchunks, stream, &mut found)?;
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Get the number of the stream of the chunk with `id`, like `1` for `01wb`
+pub(super) fn stream_number(id: [u8; 4]) -> Option<u16> {
+    match id[..2] {
+        [tens @ b'0'..=b'9', units @ b'0'..=b'9'] => {
+            Some(u16::from(tens - b'0') * 10 + u16::from(units - b'0'))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,316 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    jpeg::Jpeg,
+    riff::{RiffChunk, RiffContent, RiffPreset},
+    Error, Result,
+};
+
+pub use self::header::{AviBitmapInfo, AviMainHeader, AviStreamHeader};
+
+mod header;
+mod index;
+
+pub const CHUNK_AVIH: [u8; 4] = [b'a', b'v', b'i', b'h'];
+pub const CHUNK_DMLH: [u8; 4] = [b'd', b'm', b'l', b'h'];
+pub const CHUNK_IDX1: [u8; 4] = [b'i', b'd', b'x', b'1'];
+pub const CHUNK_INDX: [u8; 4] = [b'i', b'n', b'd', b'x'];
+pub const CHUNK_LIST: [u8; 4] = [b'L', b'I', b'S', b'T'];
+pub const CHUNK_RIFF: [u8; 4] = [b'R', b'I', b'F', b'F'];
+pub const CHUNK_STRF: [u8; 4] = [b's', b't', b'r', b'f'];
+pub const CHUNK_STRH: [u8; 4] = [b's', b't', b'r', b'h'];
+
+pub const KIND_AVI: [u8; 4] = [b'A', b'V', b'I', b' '];
+pub const KIND_AVIX: [u8; 4] = [b'A', b'V', b'I', b'X'];
+
+pub const LIST_HDRL: [u8; 4] = [b'h', b'd', b'r', b'l'];
+pub const LIST_MOVI: [u8; 4] = [b'm', b'o', b'v', b'i'];
+pub const LIST_ODML: [u8; 4] = [b'o', b'd', b'm', b'l'];
+pub const LIST_STRL: [u8; 4] = [b's', b't', b'r', b'l'];
+
+/// The representation of an AVI video
+///
+/// OpenDML files bigger than 1 GiB are made of a `RIFF` chunk with a form
+/// type of `AVI `, followed by `RIFF` chunks with a form type of `AVIX`
+/// holding the rest of the data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Avi {
+    riffs: Vec<RiffChunk>,
+}
+
+/// A stream of an [`Avi`], described by a `LIST` chunk with a form type of `strl`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AviStream {
+    /// The parsed `strh` chunk
+    pub header: AviStreamHeader,
+    /// The contents of the `strf` chunk, like a `BITMAPINFOHEADER` for video streams
+    pub format: Bytes,
+    // the contents of the OpenDML `indx` chunk
+    index: Option<Bytes>,
+}
+
+/// A chunk of the `movi` list of an [`Avi`], like a video frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AviChunk {
+    /// The id of the chunk, like `00dc` for the compressed frames of stream `0`
+    pub id: [u8; 4],
+    /// The contents of the chunk
+    pub data: Bytes,
+    /// Whether the chunk is a key frame
+    pub keyframe: bool,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Avi {
+    /// Construct a new `Avi` from its `RIFF` chunks.
+    ///
+    /// # Errors
+    ///
+    /// This method returns a [`Error::WrongSignature`] if `riffs` is empty,
+    /// if the first chunk isn't a `RIFF` list with a kind of "AVI " or if
+    /// any other chunk isn't a `RIFF` list with a kind of "AVIX".
+    pub fn new(riffs: Vec<RiffChunk>) -> Result<Avi> {
+        let valid = !riffs.is_empty()
+            && riffs.iter().enumerate().all(|(i, riff)| {
+                let kind = if i == 0 { KIND_AVI } else { KIND_AVIX };
+                riff.id() == CHUNK_RIFF
+                    && matches!(riff.content().list(), Some((Some(k), _)) if k == kind)
+            });
+
+        if valid {
+            Ok(Avi { riffs })
+        } else {
+            Err(Error::WrongSignature)
+        }
+    }
+
+    /// Create a new `Avi` from a Reader.
+    ///
+    /// Anything following the last `RIFF` chunk is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    pub fn from_bytes(mut b: Bytes) -> Result<Avi> {
+        let mut riffs = Vec::with_capacity(1);
+        loop {
+            riffs.push(RiffChunk::from_bytes_impl(&mut b, true, &RiffPreset::Avi)?);
+            if !b.starts_with(&CHUNK_RIFF) {
+                break;
+            }
+        }
+
+        Avi::new(riffs)
+    }
+
+    /// Get the `RIFF` chunks of this `Avi`.
+    #[inline]
+    pub fn riffs(&self) -> &Vec<RiffChunk> {
+        &self.riffs
+    }
+
+    /// Get a mutable reference to the `RIFF` chunks of this `Avi`.
+    ///
+    /// The headers and the streams are read from the first chunk, so the
+    /// methods reading them fail with [`Error::InvalidData`] once it is removed.
+    #[inline]
+    pub fn riffs_mut(&mut self) -> &mut Vec<RiffChunk> {
+        &mut self.riffs
+    }
+
+    /// Get the first `RIFF` chunk, with a kind of "AVI ".
+    ///
+    /// It can be missing once removed through [`riffs_mut`][Avi::riffs_mut].
+    fn first_riff(&self) -> Result<&RiffChunk> {
+        self.riffs.first().ok_or(Error::InvalidData)
+    }
+
+    /// Get the chunks of the `LIST` chunk with a form type of `hdrl`.
+    fn hdrl(&self) -> Result<&Vec<RiffChunk>> {
+        self.first_riff()?
+            .tree_chunk_by_path("RIFF(AVI )/LIST(hdrl)")
+            .and_then(|hdrl| hdrl.content().list())
+            .map(|(_, subchunks)| subchunks)
+            .ok_or(Error::InvalidData)
+    }
+
+    /// Get the parsed `avih` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `avih` chunk is missing, malformed or truncated.
+    pub fn main_header(&self) -> Result<AviMainHeader> {
+        let avih = self
+            .hdrl()?
+            .iter()
+            .find(|chunk| chunk.id() == CHUNK_AVIH)
+            .and_then(|chunk| chunk.content().data())
+            .ok_or(Error::InvalidData)?;
+        AviMainHeader::from_bytes(avih)
+    }
+
+    /// Get the total number of frames.
+    ///
+    /// The number is read from the OpenDML `dmlh` chunk if there is one,
+    /// as the `avih` chunk only counts the frames of the first `RIFF` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `dmlh` or `avih` chunks are missing, malformed or truncated.
+    pub fn total_frames(&self) -> Result<u32> {
+        let dmlh = self
+            .first_riff()?
+            .tree_chunk_by_path("RIFF(AVI )/LIST(hdrl)/LIST(odml)/dmlh")
+            .and_then(|chunk| chunk.content().data());
+
+        match dmlh {
+            Some(dmlh) => {
+                let b = dmlh.get(..4).ok_or(Error::Truncated)?;
+                Ok(u32::from_le_bytes(b.try_into().unwrap()))
+            }
+            None => Ok(self.main_header()?.total_frames),
+        }
+    }
+
+    /// Get the streams, in the order of their stream number.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if a `strh` or `strf` chunk is missing, malformed or truncated.
+    pub fn streams(&self) -> Result<Vec<AviStream>> {
+        self.hdrl()?
+            .iter()
+            .filter_map(|chunk| match chunk.content().list() {
+                Some((Some(LIST_STRL), subchunks)) => Some(subchunks),
+                _ => None,
+            })
+            .map(|subchunks| {
+                let data = |id| {
+                    subchunks
+                        .iter()
+                        .find(|chunk| chunk.id() == id)
+                        .and_then(|chunk| chunk.content().data())
+                };
+
+                let header = data(CHUNK_STRH).ok_or(Error::InvalidData)?;
+                let format = data(CHUNK_STRF).ok_or(Error::InvalidData)?;
+                Ok(AviStream {
+                    header: AviStreamHeader::from_bytes(header)?,
+                    format: format.clone(),
+                    index: data(CHUNK_INDX).cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the chunks of the `movi` lists belonging to `stream`, in playback order.
+    ///
+    /// The chunks are found through the OpenDML `indx` chunk of the stream,
+    /// falling back to the `idx1` chunk. Files without an index are scanned,
+    /// in which case every chunk is reported as a key frame.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the index is malformed or truncated,
+    /// or if it points to a chunk that doesn't exist.
+    pub fn stream_chunks(&self, stream: u16) -> Result<Vec<AviChunk>> {
+        let indx = self
+            .streams()?
+            .into_iter()
+            .nth(usize::from(stream))
+            .and_then(|stream| stream.index);
+        let idx1 = self
+            .first_riff()?
+            .tree_chunk_by_path("RIFF(AVI )/idx1")
+            .and_then(|chunk| chunk.content().data());
+
+        match (indx, idx1) {
+            (Some(indx), _) => index::Locator::new(&self.riffs).read_indx(&indx),
+            (None, Some(idx1)) => index::Locator::new(&self.riffs).read_idx1(idx1, stream),
+            (None, None) => index::scan_movi(&self.riffs, stream),
+        }
+    }
+
+    /// Get the compressed video frames of `stream` as [`Jpeg`] images,
+    /// like the `00dc` chunks of Motion JPEG videos.
+    ///
+    /// The images are parsed from the same [`Bytes`] as this `Avi`, without
+    /// copying them. Empty chunks, used to repeat the previous frame, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This method fails for the same reasons as [`stream_chunks`][Avi::stream_chunks]
+    /// or if a frame isn't a valid JPEG image.
+    pub fn jpeg_frames(&self, stream: u16) -> Result<Vec<Jpeg>> {
+        self.stream_chunks(stream)?
+            .into_iter()
+            .filter(|chunk| &chunk.id[2..] == b"dc" && !chunk.data.is_empty())
+            .map(|chunk| Jpeg::from_bytes(chunk.data))
+            .collect()
+    }
+
+    /// Get the total size of the `Avi` once it is encoded.
+    pub fn len(&self) -> u64 {
+        self.riffs.iter().map(RiffChunk::len).sum()
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Avi`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LimitExceeded`] if one of the `RIFF`
+    /// chunks is bigger than 4 GiB. Bigger files split their frames
+    /// between multiple `AVIX` chunks.
+    pub fn encoder(self) -> Result<ImageEncoder<Self>> {
+        if self.riffs.iter().any(RiffChunk::requires_rf64) {
+            return Err(Error::LimitExceeded);
+        }
+
+        Ok(ImageEncoder::from(self))
+    }
+}
+
+impl EncodeAt for Avi {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        for riff in &self.riffs {
+            if let Some(bytes) = riff.encode_at(pos) {
+                return Some(bytes);
+            }
+        }
+
+        None
+    }
+
+    fn len(&self) -> usize {
+        self.len() as usize
+    }
+}
+
+impl AviStream {
+    /// Get the `BITMAPINFOHEADER` of a video stream.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `strf` chunk is truncated.
+    #[inline]
+    pub fn bitmap_info(&self) -> Result<AviBitmapInfo> {
+        AviBitmapInfo::from_bytes(&self.format)
+    }
+}
+
+impl AviChunk {
+    fn new(chunk: &RiffChunk, keyframe: bool) -> Result<AviChunk> {
+        match chunk.content() {
+            RiffContent::Data(data) => Ok(AviChunk {
+                id: chunk.id(),
+                data: data.clone(),
+                keyframe,
+            }),
+            RiffContent::List { .. } => Err(Error::InvalidData),
+        }
+    }
+}
@@ -2,18 +2,25 @@ use bytes::Bytes;
 
 use crate::{
     encoder::{EncodeAt, ImageEncoder},
+    gif::{is_gif, Gif},
+    heif::{is_heif, Heif},
     jpeg::{is_jpeg, Jpeg},
     png::{is_png, Png},
+    tiff::{is_tiff, Tiff},
     webp::{is_webp, WebP},
     ImageEXIF, ImageICC, Result,
 };
 
 /// An enum wrapping the common functions shared by the different image formats
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DynImage {
     Jpeg(Jpeg),
     Png(Png),
     WebP(WebP),
+    Gif(Gif),
+    Tiff(Tiff),
+    Heif(Heif),
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -36,6 +43,15 @@ impl DynImage {
         } else if is_webp(&b) {
             let webp = WebP::from_bytes(b)?;
             Ok(Some(webp.into()))
+        } else if is_gif(&b) {
+            let gif = Gif::from_bytes(b)?;
+            Ok(Some(gif.into()))
+        } else if is_tiff(&b) {
+            let tiff = Tiff::from_bytes(b)?;
+            Ok(Some(tiff.into()))
+        } else if is_heif(&b) {
+            let heif = Heif::from_bytes(b)?;
+            Ok(Some(heif.into()))
         } else {
             Ok(None)
         }
@@ -47,12 +63,17 @@ impl DynImage {
             Self::Jpeg(jpeg) => jpeg.len(),
             Self::Png(png) => png.len(),
             Self::WebP(webp) => webp.len() as usize,
+            Self::Gif(gif) => gif.len(),
+            Self::Tiff(tiff) => tiff.len() as usize,
+            Self::Heif(heif) => heif.len() as usize,
         }
     }
 
     /// Create an [encoder][crate::ImageEncoder] for the inner image
-    #[inline]
-    pub fn encoder(self) -> ImageEncoder<Self> {
+    pub fn encoder(mut self) -> ImageEncoder<Self> {
+        if let Self::Heif(heif) = &mut self {
+            heif.prepare_layout();
+        }
         ImageEncoder::from(self)
     }
 }
@@ -63,6 +84,9 @@ impl EncodeAt for DynImage {
             Self::Jpeg(jpeg) => jpeg.encode_at(pos),
             Self::Png(png) => png.encode_at(pos),
             Self::WebP(webp) => webp.inner().encode_at(pos),
+            Self::Gif(gif) => gif.encode_at(pos),
+            Self::Tiff(tiff) => tiff.encode_at(pos),
+            Self::Heif(heif) => heif.encode_at(pos),
         }
     }
 
@@ -77,6 +101,9 @@ impl ImageICC for DynImage {
             Self::Jpeg(jpeg) => jpeg.icc_profile(),
             Self::Png(png) => png.icc_profile(),
             Self::WebP(webp) => webp.icc_profile(),
+            Self::Gif(gif) => gif.icc_profile(),
+            Self::Tiff(tiff) => tiff.icc_profile(),
+            Self::Heif(heif) => heif.icc_profile(),
         }
     }
 
@@ -85,6 +112,9 @@ impl ImageICC for DynImage {
             Self::Jpeg(jpeg) => jpeg.set_icc_profile(profile),
             Self::Png(png) => png.set_icc_profile(profile),
             Self::WebP(webp) => webp.set_icc_profile(profile),
+            Self::Gif(gif) => gif.set_icc_profile(profile),
+            Self::Tiff(tiff) => tiff.set_icc_profile(profile),
+            Self::Heif(heif) => heif.set_icc_profile(profile),
         }
     }
 }
@@ -95,6 +125,10 @@ impl ImageEXIF for DynImage {
             Self::Jpeg(jpeg) => jpeg.exif(),
             Self::Png(png) => png.exif(),
             Self::WebP(webp) => webp.exif(),
+            // GIF has no standard way of storing EXIF metadata
+            Self::Gif(_) => None,
+            Self::Tiff(tiff) => tiff.exif(),
+            Self::Heif(heif) => heif.exif(),
         }
     }
 
@@ -103,6 +137,9 @@ impl ImageEXIF for DynImage {
             Self::Jpeg(jpeg) => jpeg.set_exif(exif),
             Self::Png(png) => png.set_exif(exif),
             Self::WebP(webp) => webp.set_exif(exif),
+            Self::Gif(_) => {}
+            Self::Tiff(tiff) => tiff.set_exif(exif),
+            Self::Heif(heif) => heif.set_exif(exif),
         }
     }
 }
@@ -127,3 +164,24 @@ impl From<WebP> for DynImage {
         DynImage::WebP(webp)
     }
 }
+
+impl From<Gif> for DynImage {
+    #[inline]
+    fn from(gif: Gif) -> DynImage {
+        DynImage::Gif(gif)
+    }
+}
+
+impl From<Tiff> for DynImage {
+    #[inline]
+    fn from(tiff: Tiff) -> DynImage {
+        DynImage::Tiff(tiff)
+    }
+}
+
+impl From<Heif> for DynImage {
+    #[inline]
+    fn from(heif: Heif) -> DynImage {
+        DynImage::Heif(heif)
+    }
+}
@@ -52,6 +52,15 @@ pub use read::ImageEncoderReader;
 /// * [`RiffChunk`][crate::riff::RiffContent::encoder]
 /// * [`RiffContent`][crate::riff::RiffContent::encoder]
 /// * [`WebP`][crate::webp::WebP::encoder]
+/// * [`IffChunk`][crate::iff::IffChunk::encoder]
+/// * [`Ilbm`][crate::iff::Ilbm::encoder]
+/// * [`Wav`][crate::wav::Wav::encoder]
+/// * [`Avi`][crate::avi::Avi::encoder]
+/// * [`Gif`][crate::gif::Gif::encoder]
+/// * [`Tiff`][crate::tiff::Tiff::encoder]
+/// * [`IsoBox`][crate::isobmff::IsoBox::encoder]
+/// * [`Heif`][crate::heif::Heif::encoder]
+/// * [`Ani`][crate::ani::Ani::encoder]
 /// * [`Jpeg`][crate::jpeg::Jpeg::encoder]
 /// * [`JpegSegment`][crate::jpeg::JpegSegment::encoder]
 /// * [`Png`][crate::png::Png::encoder]
@@ -6,6 +6,7 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 /// The Errors that may occur when processing an image.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
     /// The file signature didn't match the expected signature
     WrongSignature,
@@ -15,6 +16,15 @@ pub enum Error {
 
     /// A truncated chunk was read
     Truncated,
+
+    /// The compressed data couldn't be decompressed
+    Inflate,
+
+    /// The data contained an invalid or unsupported value
+    InvalidData,
+
+    /// The data exceeded the maximum allowed size
+    LimitExceeded,
 }
 
 impl fmt::Display for Error {
@@ -25,6 +35,9 @@ impl fmt::Display for Error {
             }
             Self::BadCRC => f.write_str("the chunk CRC didn't match the expected calculated CRC"),
             Self::Truncated => f.write_str("a truncated chunk was read"),
+            Self::Inflate => f.write_str("the compressed data couldn't be decompressed"),
+            Self::InvalidData => f.write_str("the data contained an invalid or unsupported value"),
+            Self::LimitExceeded => f.write_str("the data exceeded the maximum allowed size"),
         }
     }
 }
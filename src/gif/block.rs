@@ -0,0 +1,365 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::EncodeAt,
+    util::{read_checked, split_to_checked},
+    Error, Result,
+};
+
+pub const EXTENSION_INTRODUCER: u8 = 0x21;
+pub const IMAGE_SEPARATOR: u8 = 0x2C;
+pub const TRAILER: u8 = 0x3B;
+
+/// A block of a [`Gif`][super::Gif], following the global color table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GifBlock {
+    /// An extension, like a graphic control or a comment
+    Extension(GifExtension),
+    /// An image, usually the frame of an animation
+    Image(GifImage),
+}
+
+/// An extension block of a [`Gif`][super::Gif]
+///
+/// The data of the extension is made of sub-blocks, each one holding
+/// a length byte followed by up to 255 bytes. It is kept as is,
+/// including the empty sub-block terminating it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifExtension {
+    label: u8,
+    data: Bytes,
+}
+
+/// An image block of a [`Gif`][super::Gif]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifImage {
+    /// The horizontal position of the image on the logical screen
+    pub left: u16,
+    /// The vertical position of the image on the logical screen
+    pub top: u16,
+    /// The width of the image, in pixels
+    pub width: u16,
+    /// The height of the image, in pixels
+    pub height: u16,
+    /// The packed fields, describing the local color table and the interlacing
+    pub flags: u8,
+    /// The local color table, made of `[red, green, blue]` triplets
+    pub local_color_table: Option<Bytes>,
+    /// The minimum code size of the LZW compressed data
+    pub lzw_min_code_size: u8,
+    data: Bytes,
+}
+
+/// An iterator over the sub-blocks of a [`GifExtension`] or a [`GifImage`]
+///
+/// Every sub-block is yielded without its length byte, up to the
+/// terminating empty sub-block.
+#[derive(Debug, Clone)]
+pub struct GifSubBlocks {
+    data: Bytes,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl GifBlock {
+    pub(super) fn from_bytes(b: &mut Bytes, introducer: u8) -> Result<GifBlock> {
+        match introducer {
+            EXTENSION_INTRODUCER => {
+                let label = read_checked(b, |b| b.get_u8())?;
+                let data = split_sub_blocks(b)?;
+                Ok(GifBlock::Extension(GifExtension { label, data }))
+            }
+            IMAGE_SEPARATOR => GifImage::from_bytes(b).map(GifBlock::Image),
+            _ => Err(Error::InvalidData),
+        }
+    }
+
+    /// Get the `GifExtension` if this `GifBlock` is an extension
+    pub fn extension(&self) -> Option<&GifExtension> {
+        match self {
+            GifBlock::Extension(extension) => Some(extension),
+            GifBlock::Image(_) => None,
+        }
+    }
+
+    /// Get the `GifImage` if this `GifBlock` is an image
+    pub fn image(&self) -> Option<&GifImage> {
+        match self {
+            GifBlock::Extension(_) => None,
+            GifBlock::Image(image) => Some(image),
+        }
+    }
+
+    /// Get the total size of this `GifBlock` once it is encoded.
+    pub fn len(&self) -> usize {
+        match self {
+            GifBlock::Extension(extension) => extension.len(),
+            GifBlock::Image(image) => image.len(),
+        }
+    }
+}
+
+impl EncodeAt for GifBlock {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match self {
+            GifBlock::Extension(extension) => extension.encode_at(pos),
+            GifBlock::Image(image) => image.encode_at(pos),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl GifExtension {
+    /// Construct a new `GifExtension` with the raw `data`, made of
+    /// sub-blocks including the empty sub-block terminating them.
+    #[inline]
+    pub fn new(label: u8, data: Bytes) -> GifExtension {
+        GifExtension { label, data }
+    }
+
+    /// Construct a new `GifExtension` holding `blocks`, split into sub-blocks
+    /// of up to 255 bytes.
+    pub fn from_blocks(label: u8, blocks: &[&[u8]]) -> GifExtension {
+        let mut b = BytesMut::new();
+        for block in blocks {
+            put_sub_blocks(&mut b, block);
+        }
+        b.put_u8(0);
+
+        GifExtension::new(label, b.freeze())
+    }
+
+    /// Get the label of this `GifExtension`, like `0xF9` for a graphic control extension
+    #[inline]
+    pub fn label(&self) -> u8 {
+        self.label
+    }
+
+    /// Get the raw data of this `GifExtension`, made of sub-blocks
+    #[inline]
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Get an iterator over the sub-blocks of this `GifExtension`
+    #[inline]
+    pub fn sub_blocks(&self) -> GifSubBlocks {
+        GifSubBlocks::new(self.data.clone())
+    }
+
+    /// Get the total size of this `GifExtension` once it is encoded.
+    #[inline]
+    pub fn len(&self) -> usize {
+        // introducer (1 byte) + label (1 byte) + data
+        2 + self.data.len()
+    }
+}
+
+impl EncodeAt for GifExtension {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match pos {
+            0 => Some(Bytes::copy_from_slice(&[EXTENSION_INTRODUCER, self.label])),
+            1 => Some(self.data.clone()),
+            _ => {
+                *pos -= 2;
+                None
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl GifImage {
+    fn from_bytes(b: &mut Bytes) -> Result<GifImage> {
+        let left = read_checked(b, |b| b.get_u16_le())?;
+        let top = read_checked(b, |b| b.get_u16_le())?;
+        let width = read_checked(b, |b| b.get_u16_le())?;
+        let height = read_checked(b, |b| b.get_u16_le())?;
+        let flags = read_checked(b, |b| b.get_u8())?;
+        let local_color_table = read_color_table(b, flags)?;
+        let lzw_min_code_size = read_checked(b, |b| b.get_u8())?;
+        let data = split_sub_blocks(b)?;
+
+        Ok(GifImage {
+            left,
+            top,
+            width,
+            height,
+            flags,
+            local_color_table,
+            lzw_min_code_size,
+            data,
+        })
+    }
+
+    /// Check if the rows of this `GifImage` are interlaced
+    #[inline]
+    pub fn is_interlaced(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    /// Get the raw LZW compressed data of this `GifImage`, made of sub-blocks
+    #[inline]
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Get an iterator over the sub-blocks of the LZW compressed data
+    #[inline]
+    pub fn sub_blocks(&self) -> GifSubBlocks {
+        GifSubBlocks::new(self.data.clone())
+    }
+
+    /// Get the total size of this `GifImage` once it is encoded.
+    pub fn len(&self) -> usize {
+        // separator (1 byte) + descriptor (9 bytes) + local color table
+        // + LZW minimum code size (1 byte) + data
+        1 + 9 + self.local_color_table.as_ref().map_or(0, Bytes::len) + 1 + self.data.len()
+    }
+}
+
+impl EncodeAt for GifImage {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match pos {
+            0 => {
+                let mut b = BytesMut::with_capacity(10);
+                b.put_u8(IMAGE_SEPARATOR);
+                b.put_u16_le(self.left);
+                b.put_u16_le(self.top);
+                b.put_u16_le(self.width);
+                b.put_u16_le(self.height);
+                b.put_u8(self.flags);
+                Some(b.freeze())
+            }
+            1 => Some(self.local_color_table.clone().unwrap_or_default()),
+            2 => Some(Bytes::copy_from_slice(&[self.lzw_min_code_size])),
+            3 => Some(self.data.clone()),
+            _ => {
+                *pos -= 4;
+                None
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl GifSubBlocks {
+    fn new(data: Bytes) -> GifSubBlocks {
+        GifSubBlocks { data }
+    }
+
+    /// Concatenate the remaining sub-blocks
+    ///
+    /// The data isn't copied if there's a single sub-block.
+    pub fn concat(mut self) -> Bytes {
+        let first = match self.next() {
+            Some(first) => first,
+            None => return Bytes::new(),
+        };
+        let second = match self.next() {
+            Some(second) => second,
+            None => return first,
+        };
+
+        let mut b = BytesMut::with_capacity(first.len() + second.len());
+        b.extend_from_slice(&first);
+        b.extend_from_slice(&second);
+        for block in self {
+            b.extend_from_slice(&block);
+        }
+        b.freeze()
+    }
+}
+
+impl Iterator for GifSubBlocks {
+    type Item = Bytes;
+
+    fn next(&mut self) -> Option<Bytes> {
+        let len = usize::from(*self.data.first()?);
+        if len == 0 || self.data.len() < 1 + len {
+            self.data = Bytes::new();
+            return None;
+        }
+
+        let block = self.data.slice(1..1 + len);
+        self.data.advance(1 + len);
+        Some(block)
+    }
+}
+
+/// Read the color table following the packed fields `flags`, if there's one
+pub(super) fn read_color_table(b: &mut Bytes, flags: u8) -> Result<Option<Bytes>> {
+    if flags & 0x80 == 0 {
+        return Ok(None);
+    }
+
+    let len = 3 * (1 << ((flags & 0x07) + 1));
+    split_to_checked(b, len).map(Some)
+}
+
+/// Split the sub-blocks at the start of `b`, including the terminating empty sub-block
+fn split_sub_blocks(b: &mut Bytes) -> Result<Bytes> {
+    let mut len = 0;
+    loop {
+        let block_len = usize::from(*b.get(len).ok_or(Error::Truncated)?);
+        len += 1 + block_len;
+        if block_len == 0 {
+            break;
+        }
+    }
+
+    split_to_checked(b, len)
+}
+
+/// Write `data` as sub-blocks of up to 255 bytes, without the terminating empty sub-block
+pub(super) fn put_sub_blocks(b: &mut BytesMut, data: &[u8]) {
+    b.reserve(data.len() + data.len() / 255 + 1);
+    for block in data.chunks(255) {
+        b.put_u8(block.len() as u8);
+        b.extend_from_slice(block);
+    }
+}
+
+/// Collect the first sub-block and the raw remaining sub-blocks of `extension`
+pub(super) fn split_first_sub_block(extension: &GifExtension) -> Option<(Bytes, Bytes)> {
+    let data = extension.data();
+    let len = usize::from(*data.first()?);
+    if len == 0 || data.len() < 1 + len {
+        return None;
+    }
+
+    Some((data.slice(1..1 + len), data.slice(1 + len..)))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn sub_blocks_roundtrip() {
+        let data = (0..600).map(|n| n as u8).collect::<Vec<u8>>();
+        let extension = GifExtension::from_blocks(0xFE, &[&data]);
+        assert_eq!(extension.data().len(), 600 + 3 + 1);
+        assert_eq!(extension.sub_blocks().count(), 3);
+        assert_eq!(extension.sub_blocks().concat(), data);
+
+        let mut b = extension.data().clone();
+        assert_eq!(split_sub_blocks(&mut b).unwrap(), extension.data());
+        assert!(b.is_empty());
+
+        let mut truncated = extension.data().slice(..300);
+        assert_eq!(split_sub_blocks(&mut truncated), Err(Error::Truncated));
+    }
+}
@@ -0,0 +1,194 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::block::{put_sub_blocks, split_first_sub_block, GifExtension};
+use crate::{
+    util::{read_checked, read_u8_array},
+    Error, Result,
+};
+
+pub const LABEL_PLAIN_TEXT: u8 = 0x01;
+pub const LABEL_GRAPHIC_CONTROL: u8 = 0xF9;
+pub const LABEL_COMMENT: u8 = 0xFE;
+pub const LABEL_APPLICATION: u8 = 0xFF;
+
+const FLAG_TRANSPARENT: u8 = 0x01;
+const FLAG_USER_INPUT: u8 = 0x02;
+
+/// The contents of a graphic control extension, describing the next image
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GifGraphicControl {
+    /// What to do with the image once its delay is over, like `2` to restore the background
+    pub disposal: u8,
+    /// Whether the viewer waits for the user before showing the next image
+    pub user_input: bool,
+    /// The delay before showing the next image, in 1/100th of a second
+    pub delay: u16,
+    /// The color index of the transparent pixels
+    pub transparent_color: Option<u8>,
+}
+
+/// The contents of an application extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifApplication {
+    /// The identifier of the application, like `NETSCAPE`
+    pub identifier: [u8; 8],
+    /// The authentication code of the application, like `2.0`
+    pub auth_code: [u8; 3],
+    /// The raw data of the application, made of sub-blocks including
+    /// the empty sub-block terminating them
+    pub data: Bytes,
+}
+
+/// The contents of a plain text extension, showing text on a grid of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifPlainText {
+    /// The horizontal position of the grid on the logical screen
+    pub left: u16,
+    /// The vertical position of the grid on the logical screen
+    pub top: u16,
+    /// The width of the grid, in pixels
+    pub width: u16,
+    /// The height of the grid, in pixels
+    pub height: u16,
+    /// The width of a cell, in pixels
+    pub cell_width: u8,
+    /// The height of a cell, in pixels
+    pub cell_height: u8,
+    /// The color index of the text
+    pub foreground_color: u8,
+    /// The color index of the background
+    pub background_color: u8,
+    /// The text
+    pub text: Bytes,
+}
+
+impl GifGraphicControl {
+    /// Parse a graphic control extension
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `extension` isn't a graphic
+    /// control extension, or with [`Error::Truncated`] if it is truncated.
+    pub fn from_extension(extension: &GifExtension) -> Result<GifGraphicControl> {
+        if extension.label() != LABEL_GRAPHIC_CONTROL {
+            return Err(Error::InvalidData);
+        }
+
+        let (mut b, _) = split_first_sub_block(extension).ok_or(Error::Truncated)?;
+        let flags = read_checked(&mut b, |b| b.get_u8())?;
+        let delay = read_checked(&mut b, |b| b.get_u16_le())?;
+        let transparent_color = read_checked(&mut b, |b| b.get_u8())?;
+
+        Ok(GifGraphicControl {
+            disposal: (flags >> 2) & 0x07,
+            user_input: flags & FLAG_USER_INPUT != 0,
+            delay,
+            transparent_color: if flags & FLAG_TRANSPARENT != 0 {
+                Some(transparent_color)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Encode this `GifGraphicControl` into a graphic control extension
+    pub fn to_extension(&self) -> GifExtension {
+        let mut flags = (self.disposal & 0x07) << 2;
+        if self.user_input {
+            flags |= FLAG_USER_INPUT;
+        }
+        if self.transparent_color.is_some() {
+            flags |= FLAG_TRANSPARENT;
+        }
+
+        let mut b = BytesMut::with_capacity(6);
+        b.put_u8(4);
+        b.put_u8(flags);
+        b.put_u16_le(self.delay);
+        b.put_u8(self.transparent_color.unwrap_or(0));
+        b.put_u8(0);
+        GifExtension::new(LABEL_GRAPHIC_CONTROL, b.freeze())
+    }
+}
+
+impl GifApplication {
+    /// Parse an application extension
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `extension` isn't an
+    /// application extension, or with [`Error::Truncated`] if it is truncated.
+    pub fn from_extension(extension: &GifExtension) -> Result<GifApplication> {
+        if extension.label() != LABEL_APPLICATION {
+            return Err(Error::InvalidData);
+        }
+
+        let (mut b, data) = split_first_sub_block(extension).ok_or(Error::Truncated)?;
+        Ok(GifApplication {
+            identifier: read_u8_array(&mut b)?,
+            auth_code: read_u8_array(&mut b)?,
+            data,
+        })
+    }
+
+    /// Encode this `GifApplication` into an application extension
+    pub fn to_extension(&self) -> GifExtension {
+        let mut b = BytesMut::with_capacity(12 + self.data.len());
+        b.put_u8(11);
+        b.extend_from_slice(&self.identifier);
+        b.extend_from_slice(&self.auth_code);
+        b.extend_from_slice(&self.data);
+        GifExtension::new(LABEL_APPLICATION, b.freeze())
+    }
+
+    /// Check if this `GifApplication` is identified by `identifier` and `auth_code`
+    #[inline]
+    pub fn is(&self, identifier: &[u8; 8], auth_code: &[u8; 3]) -> bool {
+        self.identifier == *identifier && self.auth_code == *auth_code
+    }
+}
+
+impl GifPlainText {
+    /// Parse a plain text extension
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `extension` isn't a plain
+    /// text extension, or with [`Error::Truncated`] if it is truncated.
+    pub fn from_extension(extension: &GifExtension) -> Result<GifPlainText> {
+        if extension.label() != LABEL_PLAIN_TEXT {
+            return Err(Error::InvalidData);
+        }
+
+        let (mut b, text) = split_first_sub_block(extension).ok_or(Error::Truncated)?;
+        let text = GifExtension::new(LABEL_PLAIN_TEXT, text);
+        Ok(GifPlainText {
+            left: read_checked(&mut b, |b| b.get_u16_le())?,
+            top: read_checked(&mut b, |b| b.get_u16_le())?,
+            width: read_checked(&mut b, |b| b.get_u16_le())?,
+            height: read_checked(&mut b, |b| b.get_u16_le())?,
+            cell_width: read_checked(&mut b, |b| b.get_u8())?,
+            cell_height: read_checked(&mut b, |b| b.get_u8())?,
+            foreground_color: read_checked(&mut b, |b| b.get_u8())?,
+            background_color: read_checked(&mut b, |b| b.get_u8())?,
+            text: text.sub_blocks().concat(),
+        })
+    }
+
+    /// Encode this `GifPlainText` into a plain text extension
+    pub fn to_extension(&self) -> GifExtension {
+        let mut b = BytesMut::with_capacity(14 + self.text.len() + self.text.len() / 255 + 1);
+        b.put_u8(12);
+        b.put_u16_le(self.left);
+        b.put_u16_le(self.top);
+        b.put_u16_le(self.width);
+        b.put_u16_le(self.height);
+        b.put_u8(self.cell_width);
+        b.put_u8(self.cell_height);
+        b.put_u8(self.foreground_color);
+        b.put_u8(self.background_color);
+        put_sub_blocks(&mut b, &self.text);
+        b.put_u8(0);
+        GifExtension::new(LABEL_PLAIN_TEXT, b.freeze())
+    }
+}
@@ -0,0 +1,438 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    util::{read_checked, read_u8_array},
+    Error, ImageICC, Result,
+};
+
+pub use self::{
+    block::{
+        GifBlock, GifExtension, GifImage, GifSubBlocks, EXTENSION_INTRODUCER, IMAGE_SEPARATOR,
+        TRAILER,
+    },
+    extension::{
+        GifApplication, GifGraphicControl, GifPlainText, LABEL_APPLICATION, LABEL_COMMENT,
+        LABEL_GRAPHIC_CONTROL, LABEL_PLAIN_TEXT,
+    },
+};
+
+mod block;
+mod extension;
+
+pub const SIGNATURE: &[u8] = b"GIF";
+pub const VERSION_87A: [u8; 3] = [b'8', b'7', b'a'];
+pub const VERSION_89A: [u8; 3] = [b'8', b'9', b'a'];
+
+// the applications storing the loop count, the XMP metadata and the ICC Profile
+const APP_NETSCAPE: (&[u8; 8], &[u8; 3]) = (b"NETSCAPE", b"2.0");
+const APP_ANIMEXTS: (&[u8; 8], &[u8; 3]) = (b"ANIMEXTS", b"1.0");
+const APP_XMP: (&[u8; 8], &[u8; 3]) = (b"XMP Data", b"XMP");
+const APP_ICC: (&[u8; 8], &[u8; 3]) = (b"ICCRGBG1", b"012");
+
+// the XMP packet is stored as is, followed by a trailer turning it into valid sub-blocks
+const XMP_TRAILER_LEN: usize = 1 + 256 + 1;
+
+pub(crate) fn is_gif(buf: &[u8]) -> bool {
+    buf.len() > 6
+        && buf.starts_with(SIGNATURE)
+        && (buf[3..6] == VERSION_87A || buf[3..6] == VERSION_89A)
+}
+
+/// The representation of a GIF image
+///
+/// Extensions and images are kept as slices of the original
+/// [`Bytes`], without decompressing the LZW data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gif {
+    version: [u8; 3],
+    screen: GifScreen,
+    global_color_table: Option<Bytes>,
+    blocks: Vec<GifBlock>,
+}
+
+/// The logical screen descriptor of a [`Gif`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GifScreen {
+    /// The width of the canvas, in pixels
+    pub width: u16,
+    /// The height of the canvas, in pixels
+    pub height: u16,
+    /// The packed fields, describing the global color table
+    pub flags: u8,
+    /// The color index of the background in the global color table
+    pub background_color: u8,
+    /// The pixel aspect ratio, or `0` if it isn't set
+    pub pixel_aspect_ratio: u8,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Gif {
+    /// Create a `Gif` from `Bytes`
+    ///
+    /// Anything following the trailer is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    pub fn from_bytes(mut b: Bytes) -> Result<Gif> {
+        let signature: [u8; 3] = read_u8_array(&mut b)?;
+        let version = read_u8_array(&mut b)?;
+        if signature != SIGNATURE || (version != VERSION_87A && version != VERSION_89A) {
+            return Err(Error::WrongSignature);
+        }
+
+        let screen = GifScreen {
+            width: read_checked(&mut b, |b| b.get_u16_le())?,
+            height: read_checked(&mut b, |b| b.get_u16_le())?,
+            flags: read_checked(&mut b, |b| b.get_u8())?,
+            background_color: read_checked(&mut b, |b| b.get_u8())?,
+            pixel_aspect_ratio: read_checked(&mut b, |b| b.get_u8())?,
+        };
+        let global_color_table = block::read_color_table(&mut b, screen.flags)?;
+
+        let mut blocks = Vec::with_capacity(8);
+        loop {
+            let introducer = read_checked(&mut b, |b| b.get_u8())?;
+            if introducer == block::TRAILER {
+                break;
+            }
+
+            blocks.push(GifBlock::from_bytes(&mut b, introducer)?);
+        }
+
+        Ok(Gif {
+            version,
+            screen,
+            global_color_table,
+            blocks,
+        })
+    }
+
+    /// Get the version of this `Gif`, like `89a`
+    #[inline]
+    pub fn version(&self) -> [u8; 3] {
+        self.version
+    }
+
+    /// Set the version of this `Gif`
+    ///
+    /// Extensions require version `89a`, which is set
+    /// by the methods adding them.
+    #[inline]
+    pub fn set_version(&mut self, version: [u8; 3]) {
+        self.version = version;
+    }
+
+    /// Get the logical screen descriptor
+    #[inline]
+    pub fn screen(&self) -> &GifScreen {
+        &self.screen
+    }
+
+    /// Get a mutable reference to the logical screen descriptor
+    #[inline]
+    pub fn screen_mut(&mut self) -> &mut GifScreen {
+        &mut self.screen
+    }
+
+    /// Get the global color table, made of `[red, green, blue]` triplets
+    #[inline]
+    pub fn global_color_table(&self) -> Option<&Bytes> {
+        self.global_color_table.as_ref()
+    }
+
+    /// Set the global color table, or remove it if `table` is `None`.
+    ///
+    /// The packed fields of the logical screen descriptor are updated.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if the table doesn't
+    /// hold 2, 4, 8, 16, 32, 64, 128 or 256 colors.
+    pub fn set_global_color_table(&mut self, table: Option<Bytes>) -> Result<()> {
+        let flags = match &table {
+            Some(table) => {
+                let size = (1..=8u8)
+                    .find(|size| table.len() == 3 << size)
+                    .ok_or(Error::InvalidData)?;
+                0x80 | (size - 1)
+            }
+            None => 0,
+        };
+
+        self.screen.flags = (self.screen.flags & !0x87) | flags;
+
+        self.global_color_table = table;
+        Ok(())
+    }
+
+    /// Get the blocks of this `Gif`
+    #[inline]
+    pub fn blocks(&self) -> &Vec<GifBlock> {
+        &self.blocks
+    }
+
+    /// Get a mutable reference to the blocks of this `Gif`
+    #[inline]
+    pub fn blocks_mut(&mut self) -> &mut Vec<GifBlock> {
+        &mut self.blocks
+    }
+
+    /// Get the images of this `Gif`
+    pub fn images(&self) -> impl Iterator<Item = &GifImage> {
+        self.blocks.iter().filter_map(GifBlock::image)
+    }
+
+    /// Get every extension with a label of `label`
+    pub fn extensions_by_label(&self, label: u8) -> impl Iterator<Item = &GifExtension> {
+        self.blocks
+            .iter()
+            .filter_map(GifBlock::extension)
+            .filter(move |extension| extension.label() == label)
+    }
+
+    /// Get the parsed application extensions
+    pub fn applications(&self) -> impl Iterator<Item = GifApplication> + '_ {
+        self.extensions_by_label(LABEL_APPLICATION)
+            .filter_map(|extension| GifApplication::from_extension(extension).ok())
+    }
+
+    fn application(&self, (identifier, auth_code): (&[u8; 8], &[u8; 3])) -> Option<GifApplication> {
+        self.applications()
+            .find(|application| application.is(identifier, auth_code))
+    }
+
+    /// Remove the application extensions identified by `identifier` and `auth_code`
+    ///
+    /// Returns the position of the first one.
+    fn remove_application(
+        &mut self,
+        (identifier, auth_code): (&[u8; 8], &[u8; 3]),
+    ) -> Option<usize> {
+        let is_application = |block: &GifBlock| {
+            block
+                .extension()
+                .filter(|extension| extension.label() == LABEL_APPLICATION)
+                .and_then(|extension| GifApplication::from_extension(extension).ok())
+                .map_or(false, |application| application.is(identifier, auth_code))
+        };
+
+        let pos = self.blocks.iter().position(is_application);
+        self.blocks.retain(|block| !is_application(block));
+        pos
+    }
+
+    /// Get the position before the first image and its graphic control extension
+    fn first_image_position(&self) -> usize {
+        let pos = match self.blocks.iter().position(|block| block.image().is_some()) {
+            Some(pos) => pos,
+            None => return self.blocks.len(),
+        };
+
+        match pos.checked_sub(1).map(|pos| &self.blocks[pos]) {
+            Some(GifBlock::Extension(extension)) if extension.label() == LABEL_GRAPHIC_CONTROL => {
+                pos - 1
+            }
+            _ => pos,
+        }
+    }
+
+    fn insert_extension(&mut self, pos: usize, extension: GifExtension) {
+        self.blocks.insert(pos, GifBlock::Extension(extension));
+        self.version = VERSION_89A;
+    }
+
+    /// Get the number of times the animation is played after the first time,
+    /// `0` meaning infinitely, stored in the `NETSCAPE2.0` application extension.
+    ///
+    /// Returns `None` if the animation is played once.
+    pub fn loop_count(&self) -> Option<u16> {
+        let application = self
+            .application(APP_NETSCAPE)
+            .or_else(|| self.application(APP_ANIMEXTS))?;
+        let extension = GifExtension::new(LABEL_APPLICATION, application.data);
+        extension
+            .sub_blocks()
+            .find(|block| block.len() >= 3 && block[0] == 1)
+            .map(|block| u16::from_le_bytes([block[1], block[2]]))
+    }
+
+    /// Set the number of times the animation is played after the first time,
+    /// `0` meaning infinitely, or play it once if `loop_count` is `None`.
+    pub fn set_loop_count(&mut self, loop_count: Option<u16>) {
+        let pos = self.remove_application(APP_NETSCAPE);
+        let pos = self.remove_application(APP_ANIMEXTS).or(pos);
+
+        if let Some(loop_count) = loop_count {
+            let [lo, hi] = loop_count.to_le_bytes();
+            let application = GifApplication {
+                identifier: *APP_NETSCAPE.0,
+                auth_code: *APP_NETSCAPE.1,
+                data: Bytes::copy_from_slice(&[3, 1, lo, hi, 0]),
+            };
+
+            // the loop count goes right after the global color table
+            self.insert_extension(pos.unwrap_or(0), application.to_extension());
+        }
+    }
+
+    /// Get the text of every comment extension
+    pub fn comments(&self) -> Vec<Bytes> {
+        self.extensions_by_label(LABEL_COMMENT)
+            .map(|extension| extension.sub_blocks().concat())
+            .collect()
+    }
+
+    /// Replace the comment extensions with `comments`.
+    ///
+    /// The comments take the place of the first pre-existing one,
+    /// or go before the first image.
+    pub fn set_comments(&mut self, comments: Vec<Bytes>) {
+        let is_comment = |block: &GifBlock| {
+            block
+                .extension()
+                .map_or(false, |extension| extension.label() == LABEL_COMMENT)
+        };
+
+        let pos = self.blocks.iter().position(is_comment);
+        self.blocks.retain(|block| !is_comment(block));
+        let pos = pos.unwrap_or_else(|| self.first_image_position());
+
+        for (i, comment) in comments.iter().enumerate() {
+            let extension = GifExtension::from_blocks(LABEL_COMMENT, &[comment]);
+            self.insert_extension(pos + i, extension);
+        }
+    }
+
+    /// Get the XMP packet, stored in the `XMP DataXMP` application extension.
+    ///
+    /// The packet isn't copied.
+    pub fn xmp(&self) -> Option<Bytes> {
+        let data = self.application(APP_XMP)?.data;
+        let len = data.len().checked_sub(XMP_TRAILER_LEN)?;
+        if data[len..] != xmp_trailer()[..] {
+            return None;
+        }
+
+        Some(data.slice(..len))
+    }
+
+    /// Set the XMP packet, or remove it if `xmp` is `None`.
+    ///
+    /// The packet goes before the first image.
+    pub fn set_xmp(&mut self, xmp: Option<Bytes>) {
+        let pos = self.remove_application(APP_XMP);
+
+        if let Some(xmp) = xmp {
+            let mut data = BytesMut::with_capacity(xmp.len() + XMP_TRAILER_LEN);
+            data.extend_from_slice(&xmp);
+            data.extend_from_slice(&xmp_trailer());
+
+            let application = GifApplication {
+                identifier: *APP_XMP.0,
+                auth_code: *APP_XMP.1,
+                data: data.freeze(),
+            };
+            let pos = pos.unwrap_or_else(|| self.first_image_position());
+            self.insert_extension(pos, application.to_extension());
+        }
+    }
+
+    /// Get the total size of the `Gif` once it is encoded.
+    pub fn len(&self) -> usize {
+        // header (6 bytes) + logical screen descriptor (7 bytes)
+        // + global color table + blocks + trailer (1 byte)
+        6 + 7
+            + self.global_color_table.as_ref().map_or(0, Bytes::len)
+            + self.blocks.iter().map(GifBlock::len).sum::<usize>()
+            + 1
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Gif`
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<Self> {
+        ImageEncoder::from(self)
+    }
+}
+
+/// Build the magic trailer following the XMP packet, made of the
+/// bytes `0x01, 0xFF, 0xFE, ..., 0x01, 0x00` and an empty sub-block
+fn xmp_trailer() -> [u8; XMP_TRAILER_LEN] {
+    let mut trailer = [0; XMP_TRAILER_LEN];
+    trailer[0] = 0x01;
+    for (i, b) in trailer[1..257].iter_mut().enumerate() {
+        *b = 0xFF - i as u8;
+    }
+    trailer
+}
+
+impl EncodeAt for Gif {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match pos {
+            0 => {
+                let mut b = BytesMut::with_capacity(13);
+                b.extend_from_slice(SIGNATURE);
+                b.extend_from_slice(&self.version);
+                b.put_u16_le(self.screen.width);
+                b.put_u16_le(self.screen.height);
+                b.put_u8(self.screen.flags);
+                b.put_u8(self.screen.background_color);
+                b.put_u8(self.screen.pixel_aspect_ratio);
+                Some(b.freeze())
+            }
+            1 => Some(self.global_color_table.clone().unwrap_or_default()),
+            _ => {
+                *pos -= 2;
+
+                for block in &self.blocks {
+                    if let Some(bytes) = block.encode_at(pos) {
+                        return Some(bytes);
+                    }
+                }
+
+                match pos {
+                    0 => Some(Bytes::from_static(&[block::TRAILER])),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+// http://www.color.org/icc1V42.pdf, annex B.6
+impl ImageICC for Gif {
+    fn icc_profile(&self) -> Option<Bytes> {
+        let data = self.application(APP_ICC)?.data;
+        Some(
+            GifExtension::new(LABEL_APPLICATION, data)
+                .sub_blocks()
+                .concat(),
+        )
+    }
+
+    fn set_icc_profile(&mut self, profile: Option<Bytes>) {
+        let pos = self.remove_application(APP_ICC);
+
+        if let Some(profile) = profile {
+            let mut data = BytesMut::new();
+            block::put_sub_blocks(&mut data, &profile);
+            data.put_u8(0);
+
+            let application = GifApplication {
+                identifier: *APP_ICC.0,
+                auth_code: *APP_ICC.1,
+                data: data.freeze(),
+            };
+            let pos = pos.unwrap_or_else(|| self.first_image_position());
+            self.insert_extension(pos, application.to_extension());
+        }
+    }
+}
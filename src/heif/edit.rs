@@ -0,0 +1,454 @@
+use alloc::{vec, vec::Vec};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::{Heif, HeifExtent, HeifItemData, BOX_CLAP, BOX_IMIR, BOX_IROT};
+use crate::{
+    isobmff::{
+        put_full_box, IsoBox, IsoContent, IsoItemInfo, IsoItemLocation, IsoItemLocations,
+        IsoItemProperties, IsoItemReference, IsoPropertyAssociation, IsoPropertyAssociations,
+        BOX_IINF, BOX_ILOC, BOX_INFE, BOX_IPCO, BOX_IPMA, BOX_IPRP, BOX_IREF, BOX_META,
+    },
+    Result,
+};
+
+// the type of the references from the metadata items to the items they describe
+const REFERENCE_CDSC: [u8; 4] = [b'c', b'd', b's', b'c'];
+
+// the properties transforming an image, which must follow the descriptive ones
+const TRANSFORMATIVE: [[u8; 4]; 3] = [BOX_CLAP, BOX_IROT, BOX_IMIR];
+
+impl Heif {
+    fn meta_mut(&mut self) -> Option<&mut IsoBox> {
+        self.boxes.iter_mut().find(|b| b.kind() == BOX_META)
+    }
+
+    /// Get the ids of the items matching `f`
+    pub(super) fn find_items<F>(&self, f: F) -> Vec<u32>
+    where
+        F: Fn(&IsoItemInfo) -> bool,
+    {
+        self.items()
+            .into_iter()
+            .filter(|item| f(item))
+            .map(|item| item.id)
+            .collect()
+    }
+
+    /// Replace the data of the first item matching `f` and remove the other ones,
+    /// or add a new item described by `info` if none matches
+    ///
+    /// The matching items are all removed if `data` is `None`.
+    pub(super) fn set_metadata_item<F>(&mut self, f: F, info: IsoItemInfo, data: Option<Bytes>)
+    where
+        F: Fn(&IsoItemInfo) -> bool,
+    {
+        let mut ids = self.find_items(f).into_iter();
+
+        if let Some(data) = data {
+            let extents = new_extents(data);
+            let replaced = ids.next().map_or(false, |id| {
+                match self.data.iter_mut().find(|data| data.id == id) {
+                    Some(data) => {
+                        data.extents = extents.clone();
+                        true
+                    }
+                    // the data is in another file or is made of the data of other items
+                    None => {
+                        self.remove_item(id);
+                        false
+                    }
+                }
+            });
+
+            if !replaced {
+                self.add_item(info, extents);
+            }
+        }
+
+        for id in ids {
+            self.remove_item(id);
+        }
+    }
+
+    /// Add an item described by `info`, stored in the `mdat` box,
+    /// which describes the primary item
+    fn add_item(&mut self, mut info: IsoItemInfo, extents: Vec<HeifExtent>) {
+        let ids = self.items().into_iter().map(|item| item.id);
+        let ids = ids.chain(self.data.iter().map(|data| data.id));
+        let id = match ids.max().unwrap_or_default().checked_add(1) {
+            Some(id) => id,
+            None => return,
+        };
+        let primary_id = self.primary_item_id();
+        let meta = match self.meta_mut() {
+            Some(meta) => meta,
+            None => return,
+        };
+
+        info.id = id;
+        let iinf = match child_or_insert(meta, BOX_IINF, full_container) {
+            Some(iinf) => iinf,
+            None => return,
+        };
+        if let Some(children) = iinf.children_mut() {
+            children.push(IsoBox::new(BOX_INFE, IsoContent::Data(info.to_bytes())));
+        }
+        update_item_count(iinf);
+
+        let iloc = child_or_insert(meta, BOX_ILOC, || {
+            let iloc = IsoItemLocations {
+                version: 0,
+                offset_size: 4,
+                length_size: 4,
+                base_offset_size: 0,
+                index_size: 0,
+                items: Vec::new(),
+            };
+            IsoContent::Data(iloc.to_bytes().unwrap_or_default())
+        });
+        if let Some(iloc) = iloc {
+            // the extents are written when encoding
+            edit(iloc, IsoItemLocations::from_bytes, |iloc| {
+                if id > u16::MAX.into() {
+                    iloc.version = 2;
+                }
+                iloc.items.push(IsoItemLocation {
+                    id,
+                    construction_method: 0,
+                    data_reference_index: 0,
+                    base_offset: 0,
+                    extents: Vec::new(),
+                });
+                iloc.to_bytes()
+            });
+        }
+
+        if let Some(primary_id) = primary_id {
+            add_reference(meta, REFERENCE_CDSC, id, primary_id);
+        }
+
+        self.data.push(HeifItemData {
+            id,
+            construction_method: 0,
+            extents,
+        });
+    }
+
+    /// Remove the item with id `id`, with its references and its properties
+    fn remove_item(&mut self, id: u32) {
+        self.data.retain(|data| data.id != id);
+        let meta = match self.meta_mut() {
+            Some(meta) => meta,
+            None => return,
+        };
+
+        if let Some(iinf) = meta.child_mut(BOX_IINF) {
+            if let Some(children) = iinf.children_mut() {
+                children.retain(|infe| {
+                    let info = infe.data().map(IsoItemInfo::from_bytes);
+                    !matches!(info, Some(Ok(info)) if info.id == id)
+                });
+            }
+            update_item_count(iinf);
+        }
+
+        if let Some(iloc) = meta.child_mut(BOX_ILOC) {
+            edit(iloc, IsoItemLocations::from_bytes, |iloc| {
+                iloc.items.retain(|location| location.id != id);
+                iloc.to_bytes()
+            });
+        }
+
+        if let Some(iref) = meta.child_mut(BOX_IREF) {
+            remove_references(iref, id);
+        }
+
+        let iprp = meta.child_mut(BOX_IPRP).and_then(IsoBox::children_mut);
+        for ipma in iprp.into_iter().flatten() {
+            if ipma.kind() == BOX_IPMA {
+                edit(ipma, IsoPropertyAssociations::from_bytes, |ipma| {
+                    ipma.items.retain(|item| item.id != id);
+                    ipma.to_bytes()
+                });
+            }
+        }
+    }
+
+    /// Add `property` to the `ipco` box and associate it with the item with id `id`
+    pub(super) fn add_property(&mut self, id: u32, property: IsoBox, essential: bool) {
+        let meta = match self.meta_mut() {
+            Some(meta) => meta,
+            None => return,
+        };
+        let iprp = match child_or_insert(meta, BOX_IPRP, container) {
+            Some(iprp) => iprp,
+            None => return,
+        };
+
+        let properties = child_or_insert(iprp, BOX_IPCO, container).and_then(IsoBox::children_mut);
+        let properties = match properties {
+            Some(properties) => properties,
+            None => return,
+        };
+        let index = match u16::try_from(properties.len() + 1) {
+            Ok(index) if index <= 0x7FFF => index,
+            _ => return,
+        };
+        properties.push(property);
+        let transformative = properties
+            .iter()
+            .map(|property| TRANSFORMATIVE.contains(&property.kind()))
+            .collect::<Vec<_>>();
+
+        let ipma = child_or_insert(iprp, BOX_IPMA, || {
+            let ipma = IsoPropertyAssociations {
+                version: 0,
+                flags: 0,
+                items: Vec::new(),
+            };
+            IsoContent::Data(ipma.to_bytes().unwrap_or_default())
+        });
+        if let Some(ipma) = ipma {
+            edit(ipma, IsoPropertyAssociations::from_bytes, |ipma| {
+                if index > 0x7F {
+                    ipma.flags |= 1;
+                }
+                if id > u16::MAX.into() {
+                    ipma.version = 1;
+                }
+
+                // the items are sorted by id
+                let item = match ipma.items.binary_search_by_key(&id, |item| item.id) {
+                    Ok(item) => item,
+                    Err(item) => {
+                        let properties = Vec::new();
+                        ipma.items
+                            .insert(item, IsoItemProperties { id, properties });
+                        item
+                    }
+                };
+
+                let properties = &mut ipma.items[item].properties;
+                let pos = properties
+                    .iter()
+                    .position(|property| {
+                        let index = usize::from(property.index).checked_sub(1);
+                        index.and_then(|index| transformative.get(index)) == Some(&true)
+                    })
+                    .unwrap_or(properties.len());
+                properties.insert(pos, IsoPropertyAssociation { essential, index });
+
+                ipma.to_bytes()
+            });
+        }
+    }
+
+    /// Replace the property with the 1-based index `index`
+    pub(super) fn replace_property(&mut self, index: u16, property: IsoBox) {
+        let ipco = self
+            .meta_mut()
+            .and_then(|meta| meta.child_mut(BOX_IPRP))
+            .and_then(|iprp| iprp.child_mut(BOX_IPCO))
+            .and_then(IsoBox::children_mut);
+
+        let index = usize::from(index).checked_sub(1);
+        if let Some(b) = ipco
+            .zip(index)
+            .and_then(|(ipco, index)| ipco.get_mut(index))
+        {
+            *b = property;
+        }
+    }
+
+    /// Remove the property with the 1-based index `index` and its associations,
+    /// shifting the indexes of the following properties
+    pub(super) fn remove_property(&mut self, index: u16) {
+        let iprp = match self.meta_mut().and_then(|meta| meta.child_mut(BOX_IPRP)) {
+            Some(iprp) => iprp,
+            None => return,
+        };
+
+        let ipco = iprp.child_mut(BOX_IPCO).and_then(IsoBox::children_mut);
+        match (ipco, usize::from(index).checked_sub(1)) {
+            (Some(ipco), Some(pos)) if pos < ipco.len() => {
+                ipco.remove(pos);
+            }
+            _ => return,
+        }
+
+        for ipma in iprp.children_mut().into_iter().flatten() {
+            if ipma.kind() != BOX_IPMA {
+                continue;
+            }
+
+            edit(ipma, IsoPropertyAssociations::from_bytes, |ipma| {
+                for item in &mut ipma.items {
+                    item.properties.retain(|property| property.index != index);
+                    for property in &mut item.properties {
+                        if property.index > index {
+                            property.index -= 1;
+                        }
+                    }
+                }
+                ipma.to_bytes()
+            });
+        }
+    }
+}
+
+/// Create the extents of new data, without any extent if it is empty
+fn new_extents(data: Bytes) -> Vec<HeifExtent> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    vec![HeifExtent { origin: None, data }]
+}
+
+fn container() -> IsoContent {
+    IsoContent::Container {
+        header: Bytes::new(),
+        children: Vec::new(),
+    }
+}
+
+fn full_container() -> IsoContent {
+    let mut header = BytesMut::with_capacity(4);
+    put_full_box(&mut header, 0, 0);
+    IsoContent::Container {
+        header: header.freeze(),
+        children: Vec::new(),
+    }
+}
+
+/// Get the first child of type `kind` of `parent`, appending one with `content` if there isn't any
+fn child_or_insert<F>(parent: &mut IsoBox, kind: [u8; 4], content: F) -> Option<&mut IsoBox>
+where
+    F: FnOnce() -> IsoContent,
+{
+    let children = parent.children_mut()?;
+    let pos = match children.iter().position(|child| child.kind() == kind) {
+        Some(pos) => pos,
+        None => {
+            children.push(IsoBox::new(kind, content()));
+            children.len() - 1
+        }
+    };
+
+    children.get_mut(pos)
+}
+
+/// Parse the data of `b` and replace it with the result of `f`,
+/// leaving it unchanged if it can't be parsed or encoded
+fn edit<T, P, F>(b: &mut IsoBox, parse: P, f: F)
+where
+    P: FnOnce(&Bytes) -> Result<T>,
+    F: FnOnce(&mut T) -> Result<Bytes>,
+{
+    let mut value = match b.data().map(parse) {
+        Some(Ok(value)) => value,
+        _ => return,
+    };
+
+    if let Ok(data) = f(&mut value) {
+        *b.content_mut() = IsoContent::Data(data);
+    }
+}
+
+/// Update the item count of the header of `iinf`, which takes 32 bits from version 1
+fn update_item_count(iinf: &mut IsoBox) {
+    if let IsoContent::Container { header, children } = iinf.content_mut() {
+        let mut b = BytesMut::with_capacity(8);
+        match u16::try_from(children.len()) {
+            Ok(count) if header.first() == Some(&0) => {
+                put_full_box(&mut b, 0, 0);
+                b.put_u16(count);
+            }
+            _ => {
+                put_full_box(&mut b, 1, 0);
+                b.put_u32(children.len() as u32);
+            }
+        }
+
+        *header = b.freeze();
+    }
+}
+
+/// Whether the item ids of the `iref` box take 32 bits, from version 1
+fn has_large_ids(iref: &IsoBox) -> bool {
+    match iref.content() {
+        IsoContent::Container { header, .. } => header.first().map_or(false, |&v| v > 0),
+        IsoContent::Data(_) => false,
+    }
+}
+
+/// Add a reference of type `kind` from the item `from_id` to the item `to_id`
+fn add_reference(meta: &mut IsoBox, kind: [u8; 4], from_id: u32, to_id: u32) {
+    let iref = match child_or_insert(meta, BOX_IREF, full_container) {
+        Some(iref) => iref,
+        None => return,
+    };
+
+    let large_ids = has_large_ids(iref) || from_id.max(to_id) > u16::MAX.into();
+    if large_ids && !has_large_ids(iref) {
+        // switch to version 1, re-encoding the existing references
+        if let IsoContent::Container { header, children } = iref.content_mut() {
+            for child in children {
+                let reference = child
+                    .data()
+                    .and_then(|b| IsoItemReference::from_bytes(child.kind(), b, false).ok());
+                if let Some(Ok(b)) = reference.map(|reference| reference.to_bytes(true)) {
+                    *child.content_mut() = IsoContent::Data(b);
+                }
+            }
+
+            let mut b = BytesMut::with_capacity(4);
+            put_full_box(&mut b, 1, 0);
+            *header = b.freeze();
+        }
+    }
+
+    let reference = IsoItemReference {
+        kind,
+        from_id,
+        to_ids: vec![to_id],
+    };
+    if let (Ok(b), Some(children)) = (reference.to_bytes(large_ids), iref.children_mut()) {
+        children.push(IsoBox::new(kind, IsoContent::Data(b)));
+    }
+}
+
+/// Remove the references from and to the item with id `id`
+fn remove_references(iref: &mut IsoBox, id: u32) {
+    let large_ids = has_large_ids(iref);
+    let children = match iref.children_mut() {
+        Some(children) => children,
+        None => return,
+    };
+
+    children.retain_mut(|child| {
+        let reference = child
+            .data()
+            .map(|b| IsoItemReference::from_bytes(child.kind(), b, large_ids));
+        let mut reference = match reference {
+            Some(Ok(reference)) => reference,
+            _ => return true,
+        };
+
+        if reference.from_id == id {
+            return false;
+        }
+        if reference.to_ids.contains(&id) {
+            reference.to_ids.retain(|&to_id| to_id != id);
+            if reference.to_ids.is_empty() {
+                return false;
+            }
+            if let Ok(b) = reference.to_bytes(large_ids) {
+                *child.content_mut() = IsoContent::Data(b);
+            }
+        }
+
+        true
+    });
+}
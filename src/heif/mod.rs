@@ -0,0 +1,486 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    isobmff::{
+        self, read_full_box, IsoBox, IsoContent, IsoExtent, IsoFileType, IsoItemInfo,
+        IsoItemLocation, IsoItemLocations, IsoPropertyAssociations, BOX_FTYP, BOX_IDAT, BOX_IINF,
+        BOX_ILOC, BOX_INFE, BOX_IPCO, BOX_IPMA, BOX_IPRP, BOX_META, BOX_PITM,
+    },
+    util::read_checked,
+    Error, ImageEXIF, ImageICC, Result,
+};
+
+pub use self::property::{HeifColor, HeifMirror, HeifNclx, HeifRotation, HeifSize};
+
+mod edit;
+mod property;
+mod write;
+
+use self::write::Layout;
+
+pub const BOX_ISPE: [u8; 4] = [b'i', b's', b'p', b'e'];
+pub const BOX_IROT: [u8; 4] = [b'i', b'r', b'o', b't'];
+pub const BOX_IMIR: [u8; 4] = [b'i', b'm', b'i', b'r'];
+pub const BOX_COLR: [u8; 4] = [b'c', b'o', b'l', b'r'];
+pub const BOX_CLAP: [u8; 4] = [b'c', b'l', b'a', b'p'];
+
+pub const ITEM_EXIF: [u8; 4] = [b'E', b'x', b'i', b'f'];
+pub const ITEM_MIME: [u8; 4] = [b'm', b'i', b'm', b'e'];
+
+const CONTENT_TYPE_XMP: &[u8] = b"application/rdf+xml";
+
+// the brands of HEIF, HEIC and AVIF images and image sequences
+const BRANDS: [[u8; 4]; 13] = [
+    *b"mif1", *b"mif2", *b"msf1", *b"heic", *b"heix", *b"heim", *b"heis", *b"hevc", *b"hevx",
+    *b"hevm", *b"hevs", *b"avif", *b"avis",
+];
+
+pub(crate) fn is_heif(buf: &[u8]) -> bool {
+    if buf.len() < 16 || buf[4..8] != BOX_FTYP {
+        return false;
+    }
+
+    let size = u32::from_be_bytes(buf[..4].try_into().unwrap()) as usize;
+    let ftyp = &buf[8..size.clamp(16, buf.len())];
+    // the major brand, followed by the minor version and the compatible brands
+    BRANDS.contains(&ftyp[..4].try_into().unwrap())
+        || ftyp[8..]
+            .chunks_exact(4)
+            .any(|brand| BRANDS.iter().any(|b| b == brand))
+}
+
+/// The representation of a HEIF image, like a HEIC or an AVIF
+///
+/// The images and the metadata are items described by the `meta` box,
+/// whose data is located by the `iloc` box, usually in the `mdat` box.
+/// The data of the items is resolved when parsing the file, so changes
+/// made to the `iloc` box through [`boxes_mut`][Heif::boxes_mut] aren't
+/// reflected by [`item_data`][Heif::item_data].
+///
+/// When encoding, the original `mdat` boxes are kept unchanged and the new
+/// data of the items is written to an additional `mdat` box at the end of
+/// the file. The offsets of the `iloc` box, and the chunk offsets of the
+/// tracks of image sequences, are rewritten to follow the boxes which moved,
+/// so the metadata can change size without touching the data of the images.
+#[derive(Clone)]
+pub struct Heif {
+    boxes: Vec<IsoBox>,
+    data: Vec<HeifItemData>,
+    // the parsed file, to find the original location of its boxes
+    file: Bytes,
+    // the layout computed once by `encoder`, which the encoder streams from
+    layout: Option<Layout<'static>>,
+}
+
+/// The data of an item, sliced from the file or from the `idat` box
+#[derive(Debug, Clone, PartialEq)]
+struct HeifItemData {
+    id: u32,
+    construction_method: u8,
+    extents: Vec<HeifExtent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HeifExtent {
+    /// The location of the extent when it was parsed, with an offset relative
+    /// to the file or to the `idat` box, or `None` for new data
+    origin: Option<IsoExtent>,
+    data: Bytes,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Heif {
+    /// Create a new `Heif` image from a Reader.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    pub fn from_bytes(b: Bytes) -> Result<Heif> {
+        if !is_heif(&b) {
+            return Err(Error::WrongSignature);
+        }
+
+        let boxes = isobmff::read_boxes(b.clone())?;
+        let meta = boxes
+            .iter()
+            .find(|b| b.kind() == BOX_META)
+            .ok_or(Error::InvalidData)?;
+
+        let mut data = Vec::new();
+        if let Some(iloc) = meta.child(BOX_ILOC) {
+            let iloc = IsoItemLocations::from_bytes(iloc.data().ok_or(Error::InvalidData)?)?;
+            let idat = meta.child(BOX_IDAT).and_then(IsoBox::data);
+
+            for location in &iloc.items {
+                let source = match (location.construction_method, location.data_reference_index) {
+                    (0, 0) => &b,
+                    (1, 0) => idat.ok_or(Error::InvalidData)?,
+                    // the data is in another file or is made of the data of other items
+                    _ => continue,
+                };
+
+                data.push(HeifItemData {
+                    id: location.id,
+                    construction_method: location.construction_method,
+                    extents: resolve(source, location)?,
+                });
+            }
+        }
+
+        Ok(Heif {
+            boxes,
+            data,
+            file: b,
+            layout: None,
+        })
+    }
+
+    /// Get the top level boxes of this `Heif`
+    #[inline]
+    pub fn boxes(&self) -> &Vec<IsoBox> {
+        &self.boxes
+    }
+
+    /// Get a mutable reference to the top level boxes of this `Heif`
+    #[inline]
+    pub fn boxes_mut(&mut self) -> &mut Vec<IsoBox> {
+        &mut self.boxes
+    }
+
+    fn meta(&self) -> Option<&IsoBox> {
+        self.boxes.iter().find(|b| b.kind() == BOX_META)
+    }
+
+    /// Get the contents of the `ftyp` box
+    pub fn file_type(&self) -> Option<IsoFileType> {
+        let ftyp = self.boxes.iter().find(|b| b.kind() == BOX_FTYP)?;
+        IsoFileType::from_bytes(ftyp.data()?).ok()
+    }
+
+    /// Get the id of the primary item, the image which should be displayed
+    pub fn primary_item_id(&self) -> Option<u32> {
+        let mut pitm = self.meta()?.child(BOX_PITM)?.data()?.clone();
+        let (version, _flags) = read_full_box(&mut pitm).ok()?;
+
+        if version == 0 {
+            read_checked(&mut pitm, |b| b.get_u16()).ok().map(u32::from)
+        } else {
+            read_checked(&mut pitm, |b| b.get_u32()).ok()
+        }
+    }
+
+    /// Get the description of every item
+    ///
+    /// Malformed `infe` boxes are skipped.
+    pub fn items(&self) -> Vec<IsoItemInfo> {
+        let iinf = match self.meta().and_then(|meta| meta.child(BOX_IINF)) {
+            Some(iinf) => iinf,
+            None => return Vec::new(),
+        };
+
+        iinf.children()
+            .iter()
+            .filter(|infe| infe.kind() == BOX_INFE)
+            .filter_map(|infe| IsoItemInfo::from_bytes(infe.data()?).ok())
+            .collect()
+    }
+
+    /// Get the data of the item with id `id`
+    ///
+    /// The data is only copied if it is split into multiple extents.
+    ///
+    /// Returns `None` if the item doesn't exist or if its data is stored
+    /// in another file or made of the data of other items.
+    pub fn item_data(&self, id: u32) -> Option<Bytes> {
+        let data = self.data.iter().find(|data| data.id == id)?;
+        match data.extents.as_slice() {
+            [extent] => Some(extent.data.clone()),
+            extents => {
+                let extents = extents.iter().map(|extent| &extent.data[..]);
+                Some(extents.collect::<Vec<_>>().concat().into())
+            }
+        }
+    }
+
+    /// Get the properties associated with the item with id `id`, in order
+    pub fn item_properties(&self, id: u32) -> Vec<&IsoBox> {
+        self.property_indexes(id)
+            .into_iter()
+            .filter_map(|index| self.property(index))
+            .collect()
+    }
+
+    /// Get the 1-based indexes of the properties associated with the item with id `id`
+    fn property_indexes(&self, id: u32) -> Vec<u16> {
+        let iprp = match self.meta().and_then(|meta| meta.child(BOX_IPRP)) {
+            Some(iprp) => iprp,
+            None => return Vec::new(),
+        };
+
+        let mut indexes = Vec::new();
+        for ipma in iprp.children().iter().filter(|b| b.kind() == BOX_IPMA) {
+            if let Some(Ok(ipma)) = ipma.data().map(IsoPropertyAssociations::from_bytes) {
+                let properties = ipma.item(id).map_or(&[][..], |item| &item.properties);
+                indexes.extend(properties.iter().map(|property| property.index));
+            }
+        }
+
+        indexes
+    }
+
+    /// Get the property with the 1-based index `index`, 0 meaning no property
+    fn property(&self, index: u16) -> Option<&IsoBox> {
+        let ipco = self.meta()?.child(BOX_IPRP)?.child(BOX_IPCO)?;
+        ipco.children().get(usize::from(index).checked_sub(1)?)
+    }
+
+    /// Get the first property of type `kind` of the primary item
+    fn primary_property(&self, kind: [u8; 4]) -> Option<&Bytes> {
+        let id = self.primary_item_id()?;
+        self.item_properties(id)
+            .into_iter()
+            .find(|property| property.kind() == kind)?
+            .data()
+    }
+
+    /// Get the size of the primary item, from its `ispe` property
+    pub fn dimensions(&self) -> Option<HeifSize> {
+        HeifSize::from_bytes(self.primary_property(BOX_ISPE)?).ok()
+    }
+
+    /// Get the rotation of the primary item, from its `irot` property
+    pub fn rotation(&self) -> Option<HeifRotation> {
+        HeifRotation::from_bytes(self.primary_property(BOX_IROT)?).ok()
+    }
+
+    /// Get the mirroring of the primary item, from its `imir` property
+    pub fn mirror(&self) -> Option<HeifMirror> {
+        HeifMirror::from_bytes(self.primary_property(BOX_IMIR)?).ok()
+    }
+
+    /// Get the `colr` properties of the primary item
+    pub fn colors(&self) -> Vec<HeifColor> {
+        let id = match self.primary_item_id() {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+
+        self.item_properties(id)
+            .into_iter()
+            .filter(|property| property.kind() == BOX_COLR)
+            .filter_map(|colr| HeifColor::from_bytes(colr.data()?).ok())
+            .collect()
+    }
+
+    /// Get the CICP code points of the primary item, from its `nclx` `colr` property
+    pub fn nclx(&self) -> Option<HeifNclx> {
+        self.colors().into_iter().find_map(|color| match color {
+            HeifColor::Nclx(nclx) => Some(nclx),
+            _ => None,
+        })
+    }
+
+    /// Get the XMP packet, the data of the first `mime` item of
+    /// type `application/rdf+xml`
+    pub fn xmp(&self) -> Option<Bytes> {
+        let id = *self.find_items(is_xmp).first()?;
+        self.item_data(id)
+    }
+
+    /// Set the XMP packet, or remove it if `xmp` is `None`
+    ///
+    /// The XMP item describes the primary item.
+    pub fn set_xmp(&mut self, xmp: Option<Bytes>) {
+        let info = IsoItemInfo {
+            id: 0,
+            protection_index: 0,
+            kind: ITEM_MIME,
+            name: Bytes::new(),
+            content_type: Some(Bytes::from_static(CONTENT_TYPE_XMP)),
+            content_encoding: None,
+            hidden: false,
+        };
+        self.set_metadata_item(is_xmp, info, xmp);
+    }
+
+    /// Get the total size of this `Heif` once it is encoded.
+    pub fn len(&self) -> u64 {
+        match &self.layout {
+            Some(layout) => layout.len(),
+            None => self.layout().len(),
+        }
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Heif`
+    ///
+    /// The new offsets of the boxes and of the data of the items are computed here.
+    pub fn encoder(mut self) -> ImageEncoder<Self> {
+        self.prepare_layout();
+        ImageEncoder::from(self)
+    }
+
+    /// Compute the layout of this `Heif` once, for the encoder to stream from
+    pub(crate) fn prepare_layout(&mut self) {
+        self.layout = Some(self.layout().into_owned());
+    }
+}
+
+fn is_xmp(item: &IsoItemInfo) -> bool {
+    item.kind == ITEM_MIME
+        && item.content_type.as_deref() == Some(CONTENT_TYPE_XMP)
+        && item.content_encoding.is_none()
+}
+
+/// Slice the extents of `location` from `source`
+fn resolve(source: &Bytes, location: &IsoItemLocation) -> Result<Vec<HeifExtent>> {
+    location
+        .extents
+        .iter()
+        .map(|extent| {
+            let start = location
+                .base_offset
+                .checked_add(extent.offset)
+                .ok_or(Error::Truncated)?;
+            let end = match extent.len {
+                0 => source.len() as u64,
+                len => start.checked_add(len).ok_or(Error::Truncated)?,
+            };
+            if start > end || end > source.len() as u64 {
+                return Err(Error::Truncated);
+            }
+
+            Ok(HeifExtent {
+                origin: Some(IsoExtent {
+                    index: 0,
+                    offset: start,
+                    len: extent.len,
+                }),
+                data: source.slice(start as usize..end as usize),
+            })
+        })
+        .collect()
+}
+
+impl fmt::Debug for Heif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heif")
+            .field("boxes", &self.boxes)
+            .field("data", &self.data)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Heif {
+    fn eq(&self, other: &Self) -> bool {
+        self.boxes == other.boxes && self.data == other.data && self.file == other.file
+    }
+}
+
+impl EncodeAt for Heif {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match &self.layout {
+            Some(layout) => layout.encode_at(pos),
+            None => self.layout().encode_at(pos),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len() as usize
+    }
+}
+
+impl ImageICC for Heif {
+    fn icc_profile(&self) -> Option<Bytes> {
+        self.colors()
+            .iter()
+            .find_map(|color| color.icc_profile().cloned())
+    }
+
+    /// Set the ICC Profile of the primary item, or remove it if `profile` is `None`
+    ///
+    /// The `colr` property holding the profile is replaced, so the other
+    /// items sharing it, like the tiles of a grid, get the new profile too.
+    fn set_icc_profile(&mut self, profile: Option<Bytes>) {
+        let id = match self.primary_item_id() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let mut indexes = self
+            .property_indexes(id)
+            .into_iter()
+            .filter(|&index| {
+                let colr = self.property(index).filter(|b| b.kind() == BOX_COLR);
+                let color = colr.and_then(|colr| HeifColor::from_bytes(colr.data()?).ok());
+                color.map_or(false, |color| color.icc_profile().is_some())
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(profile) = profile {
+            let colr = HeifColor::Icc(profile).to_bytes();
+            let colr = IsoBox::new(BOX_COLR, IsoContent::Data(colr));
+            if indexes.is_empty() {
+                self.add_property(id, colr, false);
+                return;
+            }
+
+            let index = indexes.remove(0);
+            self.replace_property(index, colr);
+            indexes.retain(|&other| other != index);
+        }
+
+        // the indexes of the following properties decrease
+        indexes.sort_unstable();
+        indexes.dedup();
+        for index in indexes.into_iter().rev() {
+            self.remove_property(index);
+        }
+    }
+}
+
+impl ImageEXIF for Heif {
+    fn exif(&self) -> Option<Bytes> {
+        let item = self
+            .items()
+            .into_iter()
+            .find(|item| item.kind == ITEM_EXIF)?;
+        let mut data = self.item_data(item.id)?;
+
+        // the TIFF header is preceded by its offset, skipping a prefix like "Exif\0\0"
+        let offset = read_checked(&mut data, |b| b.get_u32()).ok()? as usize;
+        if offset > data.len() {
+            return None;
+        }
+
+        Some(data.slice(offset..))
+    }
+
+    /// Set the EXIF metadata, or remove it if `exif` is `None`
+    ///
+    /// The `Exif` item describes the primary item.
+    fn set_exif(&mut self, exif: Option<Bytes>) {
+        let info = IsoItemInfo {
+            id: 0,
+            protection_index: 0,
+            kind: ITEM_EXIF,
+            name: Bytes::new(),
+            content_type: None,
+            content_encoding: None,
+            hidden: false,
+        };
+
+        // the TIFF header directly follows its offset
+        let data = exif.map(|exif| {
+            let mut data = BytesMut::with_capacity(4 + exif.len());
+            data.put_u32(0);
+            data.extend_from_slice(&exif);
+            data.freeze()
+        });
+        self.set_metadata_item(|item| item.kind == ITEM_EXIF, info, data);
+    }
+}
@@ -0,0 +1,181 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    isobmff::read_full_box,
+    util::{read_checked, read_u8_array},
+    Error, Result,
+};
+
+const COLOUR_TYPE_NCLX: [u8; 4] = [b'n', b'c', b'l', b'x'];
+const COLOUR_TYPE_PROF: [u8; 4] = [b'p', b'r', b'o', b'f'];
+const COLOUR_TYPE_RICC: [u8; 4] = [b'r', b'I', b'C', b'C'];
+
+/// The contents of an `ispe` property, holding the size of an image
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeifSize {
+    /// The width of the image, in pixels
+    pub width: u32,
+    /// The height of the image, in pixels
+    pub height: u32,
+}
+
+/// The contents of an `irot` property, rotating an image anti-clockwise
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeifRotation {
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+/// The contents of an `imir` property, mirroring an image
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeifMirror {
+    /// Mirror along the vertical axis, swapping left and right
+    Vertical,
+    /// Mirror along the horizontal axis, swapping top and bottom
+    Horizontal,
+}
+
+/// The contents of a `colr` property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeifColor {
+    /// The CICP code points of the image
+    Nclx(HeifNclx),
+    /// An ICC Profile (`prof`)
+    Icc(Bytes),
+    /// A restricted ICC Profile (`rICC`)
+    RestrictedIcc(Bytes),
+    /// An unknown colour type, with its data
+    Other([u8; 4], Bytes),
+}
+
+/// The CICP code points of an `nclx` [`HeifColor`], as defined by ITU-T H.273
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeifNclx {
+    /// The colour primaries, like `1` for BT.709 or `9` for BT.2020
+    pub colour_primaries: u16,
+    /// The transfer characteristics, like `13` for sRGB or `16` for PQ
+    pub transfer_characteristics: u16,
+    /// The matrix coefficients, like `6` for BT.601 or `0` for RGB
+    pub matrix_coefficients: u16,
+    /// Whether the full range of the samples is used
+    pub full_range: bool,
+}
+
+impl HeifSize {
+    /// Parse the contents of an `ispe` property
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the property is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<HeifSize> {
+        let mut b = b.clone();
+        read_full_box(&mut b)?;
+
+        Ok(HeifSize {
+            width: read_checked(&mut b, |b| b.get_u32())?,
+            height: read_checked(&mut b, |b| b.get_u32())?,
+        })
+    }
+}
+
+impl HeifRotation {
+    /// Parse the contents of an `irot` property
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the property is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<HeifRotation> {
+        match b.first().ok_or(Error::Truncated)? & 0b11 {
+            0 => Ok(HeifRotation::Rotate0),
+            1 => Ok(HeifRotation::Rotate90),
+            2 => Ok(HeifRotation::Rotate180),
+            _ => Ok(HeifRotation::Rotate270),
+        }
+    }
+
+    /// Get the anti-clockwise angle of this `HeifRotation`, in degrees
+    pub fn degrees(self) -> u16 {
+        match self {
+            HeifRotation::Rotate0 => 0,
+            HeifRotation::Rotate90 => 90,
+            HeifRotation::Rotate180 => 180,
+            HeifRotation::Rotate270 => 270,
+        }
+    }
+}
+
+impl HeifMirror {
+    /// Parse the contents of an `imir` property
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the property is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<HeifMirror> {
+        match b.first().ok_or(Error::Truncated)? & 1 {
+            0 => Ok(HeifMirror::Vertical),
+            _ => Ok(HeifMirror::Horizontal),
+        }
+    }
+}
+
+impl HeifColor {
+    /// Parse the contents of a `colr` property
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the property is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<HeifColor> {
+        let mut b = b.clone();
+
+        let colour_type = read_u8_array(&mut b)?;
+        match colour_type {
+            COLOUR_TYPE_NCLX => Ok(HeifColor::Nclx(HeifNclx {
+                colour_primaries: read_checked(&mut b, |b| b.get_u16())?,
+                transfer_characteristics: read_checked(&mut b, |b| b.get_u16())?,
+                matrix_coefficients: read_checked(&mut b, |b| b.get_u16())?,
+                full_range: read_checked(&mut b, |b| b.get_u8())? & 0x80 != 0,
+            })),
+            COLOUR_TYPE_PROF => Ok(HeifColor::Icc(b)),
+            COLOUR_TYPE_RICC => Ok(HeifColor::RestrictedIcc(b)),
+            _ => Ok(HeifColor::Other(colour_type, b)),
+        }
+    }
+
+    /// Encode this `HeifColor` into the contents of a `colr` property
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::new();
+        match self {
+            HeifColor::Nclx(nclx) => {
+                b.extend_from_slice(&COLOUR_TYPE_NCLX);
+                b.put_u16(nclx.colour_primaries);
+                b.put_u16(nclx.transfer_characteristics);
+                b.put_u16(nclx.matrix_coefficients);
+                b.put_u8(u8::from(nclx.full_range) << 7);
+            }
+            HeifColor::Icc(profile) => {
+                b.extend_from_slice(&COLOUR_TYPE_PROF);
+                b.extend_from_slice(profile);
+            }
+            HeifColor::RestrictedIcc(profile) => {
+                b.extend_from_slice(&COLOUR_TYPE_RICC);
+                b.extend_from_slice(profile);
+            }
+            HeifColor::Other(colour_type, data) => {
+                b.extend_from_slice(colour_type);
+                b.extend_from_slice(data);
+            }
+        }
+
+        b.freeze()
+    }
+
+    /// Get the ICC Profile of this `HeifColor`, restricted or not
+    pub fn icc_profile(&self) -> Option<&Bytes> {
+        match self {
+            HeifColor::Icc(profile) | HeifColor::RestrictedIcc(profile) => Some(profile),
+            HeifColor::Nclx(_) | HeifColor::Other(..) => None,
+        }
+    }
+}
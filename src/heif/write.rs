@@ -0,0 +1,475 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::Heif;
+use crate::{
+    encoder::EncodeAt,
+    isobmff::{
+        put_box_header, put_full_box, read_full_box, IsoBox, IsoContent, IsoExtent,
+        IsoItemLocation, IsoItemLocations, BOX_CO64, BOX_IDAT, BOX_ILOC, BOX_MDAT, BOX_META,
+        BOX_MOOV, BOX_STCO,
+    },
+    util::read_checked,
+    Result,
+};
+
+// the layout converges in a few passes, as the sizes of the fields only grow
+const MAX_PASSES: usize = 8;
+
+/// The boxes of an encoded [`Heif`]
+///
+/// The top level boxes are kept in place, with their data unchanged, except
+/// for the `meta` box, whose `iloc` and `idat` boxes are rewritten, and the
+/// `moov` box, whose chunk offsets are shifted. The data of the items which
+/// isn't in one of the original boxes is written to a new `mdat` box, at the
+/// end of the file, so a file which wasn't edited is encoded unchanged.
+#[derive(Clone)]
+pub(super) struct Layout<'a> {
+    boxes: Vec<Cow<'a, IsoBox>>,
+    /// The header of the new `mdat` box, followed by its contents
+    mdat: Option<(Bytes, Vec<Bytes>)>,
+}
+
+/// Where the extents of the items are written
+struct Placement {
+    /// The position of every extent of every item
+    positions: Vec<Vec<Position>>,
+    /// The contents of the new `mdat` box
+    mdat: Vec<Bytes>,
+    /// The contents of the `idat` box
+    idat: Vec<Bytes>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Position {
+    /// At an offset of the contents of the top level box at an index
+    Box(usize, u64),
+    /// At an offset of the contents of the new `mdat` box
+    Mdat(u64),
+    /// At an offset of the contents of the `idat` box
+    Idat(u64),
+}
+
+/// The offsets of the contents of the encoded boxes
+#[derive(Debug, PartialEq, Eq)]
+struct Starts {
+    boxes: Vec<u64>,
+    mdat: u64,
+    len: u64,
+}
+
+/// The sizes of the offsets and of the lengths of the `iloc` box, and
+/// whether the chunk offsets need 64 bits, which only grow between passes
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+struct Widths {
+    offset_size: u8,
+    length_size: u8,
+    co64: bool,
+}
+
+impl Heif {
+    pub(super) fn layout(&self) -> Layout<'_> {
+        let placement = self.place();
+        let mdat = (!placement.mdat.is_empty()).then(|| {
+            let len = placement.mdat.iter().map(|b| b.len() as u64).sum::<u64>();
+            let header_len = if len > u64::from(u32::MAX) - 8 { 16 } else { 8 };
+
+            let mut header = BytesMut::with_capacity(16);
+            put_box_header(&mut header, BOX_MDAT, header_len + len, header_len);
+            (header.freeze(), placement.mdat.clone())
+        });
+
+        let mut widths = Widths::default();
+        let mut boxes = self.boxes.iter().map(Cow::Borrowed).collect::<Vec<_>>();
+        let mut starts = starts(&boxes, mdat.as_ref());
+        for _ in 0..MAX_PASSES {
+            let previous = widths;
+            boxes = self.relocate(&placement, &starts, &mut widths);
+
+            let new_starts = self::starts(&boxes, mdat.as_ref());
+            if widths == previous && new_starts == starts {
+                break;
+            }
+            starts = new_starts;
+        }
+
+        Layout { boxes, mdat }
+    }
+
+    /// Find where the extents of the items go: in the original box they
+    /// were sliced from, or in order at the end of the new `mdat` box or of
+    /// the `idat` box
+    fn place(&self) -> Placement {
+        let mut positions = Vec::with_capacity(self.data.len());
+        let mut moved = Vec::new();
+        for (i, data) in self.data.iter().enumerate() {
+            let mut item_positions = Vec::with_capacity(data.extents.len());
+            for (j, extent) in data.extents.iter().enumerate() {
+                let kept = self.boxes.iter().enumerate().find_map(|(k, b)| {
+                    let offset = offset_in(b.data()?, &extent.data)?;
+                    Some(Position::Box(k, offset))
+                });
+                let position = match (data.construction_method, kept) {
+                    (0, Some(position)) => position,
+                    (method, _) => {
+                        let origin = extent.origin.map_or(u64::MAX, |origin| origin.offset);
+                        moved.push((method, origin, i, j));
+                        Position::Mdat(0)
+                    }
+                };
+                item_positions.push(position);
+            }
+            positions.push(item_positions);
+        }
+        moved.sort_by_key(|&(method, origin, ..)| (method, origin));
+
+        let (mut mdat, mut idat) = (Vec::new(), Vec::new());
+        let (mut mdat_len, mut idat_len) = (0, 0);
+        for (method, _, i, j) in moved {
+            let data = self.data[i].extents[j].data.clone();
+            let len = data.len() as u64;
+            positions[i][j] = if method == 0 {
+                mdat.push(data);
+                mdat_len += len;
+                Position::Mdat(mdat_len - len)
+            } else {
+                idat.push(data);
+                idat_len += len;
+                Position::Idat(idat_len - len)
+            };
+        }
+
+        Placement {
+            positions,
+            mdat,
+            idat,
+        }
+    }
+
+    /// Rewrite the boxes referencing the data of the file, for the contents
+    /// of the boxes starting at `starts`
+    fn relocate(
+        &self,
+        placement: &Placement,
+        starts: &Starts,
+        widths: &mut Widths,
+    ) -> Vec<Cow<'_, IsoBox>> {
+        let mut meta_found = false;
+        let mut boxes = Vec::with_capacity(self.boxes.len());
+        for b in &self.boxes {
+            let relocated = match b.kind() {
+                // the items are described by the first `meta` box
+                BOX_META if !meta_found => {
+                    meta_found = true;
+                    self.relocate_meta(b, placement, starts, widths)
+                }
+                BOX_MOOV => {
+                    let map = |offset| self.relocate_offset(offset, starts);
+                    relocate_chunks(b, &map, widths)
+                }
+                _ => None,
+            };
+            boxes.push(relocated.map_or(Cow::Borrowed(b), Cow::Owned));
+        }
+
+        boxes
+    }
+
+    /// Get the new offset of the byte at `offset` in the parsed file
+    ///
+    /// Offsets outside of the data of the top level boxes are unchanged.
+    fn relocate_offset(&self, offset: u64, starts: &Starts) -> u64 {
+        for (b, &start) in self.boxes.iter().zip(&starts.boxes) {
+            let data = match b.data() {
+                Some(data) => data,
+                None => continue,
+            };
+
+            if let Some(origin) = offset_in(&self.file, data) {
+                if (origin..origin + data.len() as u64).contains(&offset) {
+                    return start + (offset - origin);
+                }
+            }
+        }
+
+        offset
+    }
+
+    /// Rewrite the `iloc` and `idat` boxes of `meta` to locate the data of the items
+    ///
+    /// Returns `None` if `meta` doesn't have a valid `iloc` box.
+    fn relocate_meta(
+        &self,
+        meta: &IsoBox,
+        placement: &Placement,
+        starts: &Starts,
+        widths: &mut Widths,
+    ) -> Option<IsoBox> {
+        let iloc = IsoItemLocations::from_bytes(meta.child(BOX_ILOC)?.data()?).ok()?;
+        let idat_len = placement.idat.iter().map(|b| b.len() as u64).sum::<u64>();
+
+        let mut relocated = IsoItemLocations {
+            items: Vec::with_capacity(iloc.items.len()),
+            ..iloc
+        };
+        for location in iloc.items {
+            let i = match self.data.iter().position(|data| data.id == location.id) {
+                Some(i) => i,
+                // the data is in another file or is made of the data of other items
+                None => {
+                    relocated.items.push(location);
+                    continue;
+                }
+            };
+
+            let data = &self.data[i];
+            let source_len = match data.construction_method {
+                0 => starts.len,
+                _ => idat_len,
+            };
+
+            let extents = data
+                .extents
+                .iter()
+                .zip(&placement.positions[i])
+                .map(|(extent, position)| IsoExtent {
+                    index: 0,
+                    offset: match *position {
+                        Position::Box(k, offset) => starts.boxes[k] + offset,
+                        Position::Mdat(offset) => starts.mdat + offset,
+                        Position::Idat(offset) => offset,
+                    },
+                    len: extent.data.len() as u64,
+                })
+                .collect::<Vec<_>>();
+
+            // keep the location of the items which didn't move,
+            // whose extents may have a length of 0 meaning up to the end
+            let moved = location.construction_method != data.construction_method
+                || location.extents.len() != extents.len()
+                || data.extents.iter().zip(&extents).any(|(extent, new)| {
+                    extent.origin.map_or(true, |origin| {
+                        let len = match origin.len {
+                            0 => source_len.saturating_sub(new.offset),
+                            len => len,
+                        };
+                        origin.offset != new.offset || len != new.len
+                    })
+                });
+            if !moved {
+                relocated.items.push(location);
+                continue;
+            }
+
+            for extent in &extents {
+                widths.offset_size = widths.offset_size.max(uint_size(extent.offset));
+                widths.length_size = widths.length_size.max(uint_size(extent.len));
+            }
+            if data.construction_method > 0 {
+                relocated.version = relocated.version.max(1);
+            }
+            relocated.items.push(IsoItemLocation {
+                id: location.id,
+                construction_method: data.construction_method,
+                data_reference_index: 0,
+                base_offset: 0,
+                extents,
+            });
+        }
+
+        relocated.offset_size = relocated.offset_size.max(widths.offset_size);
+        relocated.length_size = relocated.length_size.max(widths.length_size);
+        let large_ids = relocated.items.iter().any(|item| item.id > u16::MAX.into());
+        if large_ids || relocated.items.len() > u16::MAX.into() {
+            relocated.version = 2;
+        }
+
+        let mut meta = meta.clone();
+        let iloc = relocated.to_bytes().ok()?;
+        *meta.child_mut(BOX_ILOC)?.content_mut() = IsoContent::Data(iloc);
+
+        if let Some(idat) = meta.child_mut(BOX_IDAT) {
+            let data = match placement.idat.as_slice() {
+                [extent] => extent.clone(),
+                extents => extents.concat().into(),
+            };
+            *idat.content_mut() = IsoContent::Data(data);
+        }
+
+        Some(meta)
+    }
+}
+
+/// Get the offset of `data` in `b`, if it was sliced from it
+fn offset_in(b: &Bytes, data: &Bytes) -> Option<u64> {
+    let offset = (data.as_ptr() as usize).checked_sub(b.as_ptr() as usize)?;
+    if offset + data.len() > b.len() {
+        return None;
+    }
+
+    Some(offset as u64)
+}
+
+/// Get the size of the smallest `iloc` field which fits `n`
+fn uint_size(n: u64) -> u8 {
+    if n > u32::MAX.into() {
+        8
+    } else {
+        4
+    }
+}
+
+/// Get the offsets of the contents of `boxes`, followed by the new `mdat` box
+fn starts(boxes: &[Cow<'_, IsoBox>], mdat: Option<&(Bytes, Vec<Bytes>)>) -> Starts {
+    let mut pos = 0;
+    let boxes = boxes
+        .iter()
+        .map(|b| {
+            let start = pos + b.header_len();
+            pos += b.len();
+            start
+        })
+        .collect();
+
+    let mut start = pos;
+    if let Some((header, extents)) = mdat {
+        start += header.len() as u64;
+        pos = start + extents.iter().map(|b| b.len() as u64).sum::<u64>();
+    }
+
+    Starts {
+        boxes,
+        mdat: start,
+        len: pos,
+    }
+}
+
+/// Map the chunk offsets of the `stco` and `co64` boxes in `b`,
+/// switching from `stco` to `co64` if they don't fit in 32 bits
+///
+/// Returns `None` if no chunk offset changed.
+fn relocate_chunks<F>(b: &IsoBox, map: &F, widths: &mut Widths) -> Option<IsoBox>
+where
+    F: Fn(u64) -> u64,
+{
+    match b.kind() {
+        BOX_STCO | BOX_CO64 => {
+            let large = b.kind() == BOX_CO64;
+            let (version, flags, offsets) = read_chunk_offsets(b.data()?, large).ok()?;
+            let mapped = offsets
+                .iter()
+                .map(|&offset| map(offset))
+                .collect::<Vec<_>>();
+            if mapped.iter().any(|&offset| offset > u32::MAX.into()) {
+                widths.co64 = true;
+            }
+
+            let large_mapped = large || widths.co64;
+            if mapped == offsets && large_mapped == large {
+                return None;
+            }
+
+            let mut data = BytesMut::with_capacity(8 + mapped.len() * 8);
+            put_full_box(&mut data, version, flags);
+            data.put_u32(mapped.len() as u32);
+            for offset in mapped {
+                if large_mapped {
+                    data.put_u64(offset);
+                } else {
+                    data.put_u32(offset as u32);
+                }
+            }
+
+            let kind = if large_mapped { BOX_CO64 } else { BOX_STCO };
+            Some(IsoBox::new(kind, IsoContent::Data(data.freeze())))
+        }
+        _ => {
+            let children = b.children();
+            let mut relocated: Option<Vec<IsoBox>> = None;
+            for (i, child) in children.iter().enumerate() {
+                if let Some(child) = relocate_chunks(child, map, widths) {
+                    relocated.get_or_insert_with(|| children.to_vec())[i] = child;
+                }
+            }
+
+            let relocated = relocated?;
+            let mut b = b.clone();
+            *b.children_mut()? = relocated;
+            Some(b)
+        }
+    }
+}
+
+/// Read the version, the flags and the chunk offsets of a `stco` box,
+/// or of a `co64` box if `large`
+fn read_chunk_offsets(b: &Bytes, large: bool) -> Result<(u8, u32, Vec<u64>)> {
+    let mut b = b.clone();
+    let (version, flags) = read_full_box(&mut b)?;
+
+    let count = read_checked(&mut b, |b| b.get_u32())?;
+    let offsets = (0..count)
+        .map(|_| {
+            if large {
+                read_checked(&mut b, |b| b.get_u64())
+            } else {
+                read_checked(&mut b, |b| b.get_u32()).map(u64::from)
+            }
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((version, flags, offsets))
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Layout<'_> {
+    /// Clone the boxes borrowed from the [`Heif`]
+    pub(super) fn into_owned(self) -> Layout<'static> {
+        let boxes = self
+            .boxes
+            .into_iter()
+            .map(|b| Cow::Owned(b.into_owned()))
+            .collect();
+        Layout {
+            boxes,
+            mdat: self.mdat,
+        }
+    }
+
+    pub(super) fn len(&self) -> u64 {
+        let mdat = self.mdat.iter().flat_map(|(header, extents)| {
+            core::iter::once(header)
+                .chain(extents)
+                .map(|b| b.len() as u64)
+        });
+        self.boxes.iter().map(|b| b.len()).chain(mdat).sum()
+    }
+}
+
+impl EncodeAt for Layout<'_> {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        for b in &self.boxes {
+            if let Some(bytes) = b.encode_at(pos) {
+                return Some(bytes);
+            }
+        }
+
+        let (header, extents) = self.mdat.as_ref()?;
+        if *pos == 0 {
+            return Some(header.clone());
+        }
+
+        *pos -= 1;
+        match extents.get(*pos) {
+            Some(extent) => Some(extent.clone()),
+            None => {
+                *pos -= extents.len();
+                None
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len() as usize
+    }
+}
@@ -0,0 +1,378 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{IffChunk, CHUNK_FORM};
+use crate::{
+    encoder::ImageEncoder,
+    riff::{RiffChunk, RiffContent},
+    util::{impl_subchunks, read_checked, trim_nul},
+    Error, Result,
+};
+
+pub const CHUNK_ANNO: [u8; 4] = [b'A', b'N', b'N', b'O'];
+pub const CHUNK_AUTH: [u8; 4] = [b'A', b'U', b'T', b'H'];
+pub const CHUNK_BMHD: [u8; 4] = [b'B', b'M', b'H', b'D'];
+pub const CHUNK_BODY: [u8; 4] = [b'B', b'O', b'D', b'Y'];
+pub const CHUNK_CAMG: [u8; 4] = [b'C', b'A', b'M', b'G'];
+pub const CHUNK_CMAP: [u8; 4] = [b'C', b'M', b'A', b'P'];
+pub const CHUNK_COPYRIGHT: [u8; 4] = [b'(', b'c', b')', b' '];
+pub const CHUNK_NAME: [u8; 4] = [b'N', b'A', b'M', b'E'];
+
+pub const KIND_ILBM: [u8; 4] = [b'I', b'L', b'B', b'M'];
+pub const KIND_PBM: [u8; 4] = [b'P', b'B', b'M', b' '];
+
+/// The representation of an Amiga ILBM image
+///
+/// The `PBM ` images saved by Deluxe Paint, which use the same
+/// chunks with chunky pixels, are supported too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ilbm {
+    form: IffChunk,
+}
+
+/// The contents of the `BMHD` chunk of an [`Ilbm`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IlbmHeader {
+    /// The width of the image, in pixels
+    pub width: u16,
+    /// The height of the image, in pixels
+    pub height: u16,
+    /// The horizontal position of the image
+    pub x: i16,
+    /// The vertical position of the image
+    pub y: i16,
+    /// The number of bitplanes, without the mask
+    pub planes: u8,
+    /// How the transparent pixels are found
+    pub masking: IlbmMasking,
+    /// The compression of the `BODY` chunk
+    pub compression: IlbmCompression,
+    /// The color index of the transparent pixels
+    pub transparent_color: u16,
+    /// The horizontal aspect of a pixel
+    pub x_aspect: u8,
+    /// The vertical aspect of a pixel
+    pub y_aspect: u8,
+    /// The width of the source page, in pixels
+    pub page_width: i16,
+    /// The height of the source page, in pixels
+    pub page_height: i16,
+}
+
+/// The masking technique of an [`Ilbm`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IlbmMasking {
+    /// Every pixel is opaque
+    None,
+    /// The `BODY` chunk holds a mask plane after the bitplanes
+    HasMask,
+    /// The pixels with the `transparent_color` are transparent
+    TransparentColor,
+    /// The background pixels surrounding the image are transparent
+    Lasso,
+    /// Any other masking technique
+    Other(u8),
+}
+
+/// The compression of the `BODY` chunk of an [`Ilbm`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IlbmCompression {
+    /// The rows are stored as is
+    None,
+    /// The rows are compressed with the `ByteRun1` run length encoding
+    ByteRun1,
+    /// Any other compression
+    Other(u8),
+}
+
+impl IlbmHeader {
+    /// Parse the contents of a `BMHD` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<IlbmHeader> {
+        let mut b = b.clone();
+
+        let width = read_checked(&mut b, |b| b.get_u16())?;
+        let height = read_checked(&mut b, |b| b.get_u16())?;
+        let x = read_checked(&mut b, |b| b.get_i16())?;
+        let y = read_checked(&mut b, |b| b.get_i16())?;
+        let planes = read_checked(&mut b, |b| b.get_u8())?;
+        let masking = read_checked(&mut b, |b| b.get_u8())?;
+        let compression = read_checked(&mut b, |b| b.get_u8())?;
+        let _pad = read_checked(&mut b, |b| b.get_u8())?;
+        let transparent_color = read_checked(&mut b, |b| b.get_u16())?;
+        let x_aspect = read_checked(&mut b, |b| b.get_u8())?;
+        let y_aspect = read_checked(&mut b, |b| b.get_u8())?;
+        let page_width = read_checked(&mut b, |b| b.get_i16())?;
+        let page_height = read_checked(&mut b, |b| b.get_i16())?;
+
+        Ok(IlbmHeader {
+            width,
+            height,
+            x,
+            y,
+            planes,
+            masking: IlbmMasking::from_u8(masking),
+            compression: IlbmCompression::from_u8(compression),
+            transparent_color,
+            x_aspect,
+            y_aspect,
+            page_width,
+            page_height,
+        })
+    }
+
+    /// Encode this `IlbmHeader` into the contents of a `BMHD` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(20);
+        b.put_u16(self.width);
+        b.put_u16(self.height);
+        b.put_i16(self.x);
+        b.put_i16(self.y);
+        b.put_u8(self.planes);
+        b.put_u8(self.masking.to_u8());
+        b.put_u8(self.compression.to_u8());
+        b.put_u8(0);
+        b.put_u16(self.transparent_color);
+        b.put_u8(self.x_aspect);
+        b.put_u8(self.y_aspect);
+        b.put_i16(self.page_width);
+        b.put_i16(self.page_height);
+        b.freeze()
+    }
+}
+
+impl IlbmMasking {
+    fn from_u8(n: u8) -> IlbmMasking {
+        match n {
+            0 => IlbmMasking::None,
+            1 => IlbmMasking::HasMask,
+            2 => IlbmMasking::TransparentColor,
+            3 => IlbmMasking::Lasso,
+            n => IlbmMasking::Other(n),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            IlbmMasking::None => 0,
+            IlbmMasking::HasMask => 1,
+            IlbmMasking::TransparentColor => 2,
+            IlbmMasking::Lasso => 3,
+            IlbmMasking::Other(n) => n,
+        }
+    }
+}
+
+impl IlbmCompression {
+    fn from_u8(n: u8) -> IlbmCompression {
+        match n {
+            0 => IlbmCompression::None,
+            1 => IlbmCompression::ByteRun1,
+            n => IlbmCompression::Other(n),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            IlbmCompression::None => 0,
+            IlbmCompression::ByteRun1 => 1,
+            IlbmCompression::Other(n) => n,
+        }
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Ilbm {
+    /// Construct a new `Ilbm` from an [`IffChunk`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns a [`Error::WrongSignature`] if the [`IffChunk`]
+    /// isn't a `FORM` chunk with a kind of "ILBM" or "PBM ".
+    pub fn new(form: IffChunk) -> Result<Ilbm> {
+        match form.content().list() {
+            Some((Some(KIND_ILBM | KIND_PBM), _)) if form.id() == CHUNK_FORM => Ok(Ilbm { form }),
+            _ => Err(Error::WrongSignature),
+        }
+    }
+
+    /// Create a new `Ilbm` from a Reader.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    #[inline]
+    pub fn from_bytes(b: Bytes) -> Result<Ilbm> {
+        let form = IffChunk::from_bytes(b)?;
+        Ilbm::new(form)
+    }
+
+    /// Get the parsed `BMHD` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `BMHD` chunk is missing, malformed or truncated.
+    pub fn header(&self) -> Result<IlbmHeader> {
+        let data = self.data(CHUNK_BMHD).ok_or(Error::InvalidData)?;
+        IlbmHeader::from_bytes(data)
+    }
+
+    /// Replace the `BMHD` chunk with `header`.
+    pub fn set_header(&mut self, header: &IlbmHeader) {
+        self.set_data(CHUNK_BMHD, Some(header.to_bytes()));
+    }
+
+    /// Get the colors of the palette, as `[red, green, blue]`, stored in the `CMAP` chunk.
+    ///
+    /// Any incomplete color at the end of the chunk is ignored.
+    pub fn palette(&self) -> Option<Vec<[u8; 3]>> {
+        let data = self.data(CHUNK_CMAP)?;
+        let colors = data
+            .chunks_exact(3)
+            .map(|color| [color[0], color[1], color[2]])
+            .collect();
+        Some(colors)
+    }
+
+    /// Set the colors of the palette, or remove the `CMAP` chunk if `palette` is `None`.
+    pub fn set_palette(&mut self, palette: Option<&[[u8; 3]]>) {
+        let data = palette.map(|palette| Bytes::from(palette.concat()));
+        self.set_data(CHUNK_CMAP, data);
+    }
+
+    /// Get the Amiga display mode, stored in the `CAMG` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `CAMG` chunk is truncated.
+    pub fn camg(&self) -> Result<Option<u32>> {
+        self.data(CHUNK_CAMG)
+            .map(|data| read_checked(&mut data.clone(), |b| b.get_u32()))
+            .transpose()
+    }
+
+    /// Set the Amiga display mode, or remove the `CAMG` chunk if `camg` is `None`.
+    pub fn set_camg(&mut self, camg: Option<u32>) {
+        let data = camg.map(|camg| Bytes::copy_from_slice(&camg.to_be_bytes()));
+        self.set_data(CHUNK_CAMG, data);
+    }
+
+    /// Get the name of the image, stored in the `NAME` chunk.
+    pub fn name(&self) -> Option<Bytes> {
+        self.data(CHUNK_NAME).map(trim_nul)
+    }
+
+    /// Set the name of the image, or remove the `NAME` chunk if `name` is `None`.
+    pub fn set_name(&mut self, name: Option<Bytes>) {
+        self.set_data(CHUNK_NAME, name);
+    }
+
+    /// Get the author of the image, stored in the `AUTH` chunk.
+    pub fn author(&self) -> Option<Bytes> {
+        self.data(CHUNK_AUTH).map(trim_nul)
+    }
+
+    /// Set the author of the image, or remove the `AUTH` chunk if `author` is `None`.
+    pub fn set_author(&mut self, author: Option<Bytes>) {
+        self.set_data(CHUNK_AUTH, author);
+    }
+
+    /// Get the copyright notice of the image, stored in the `(c) ` chunk.
+    pub fn copyright(&self) -> Option<Bytes> {
+        self.data(CHUNK_COPYRIGHT).map(trim_nul)
+    }
+
+    /// Set the copyright notice of the image, or remove
+    /// the `(c) ` chunk if `copyright` is `None`.
+    pub fn set_copyright(&mut self, copyright: Option<Bytes>) {
+        self.set_data(CHUNK_COPYRIGHT, copyright);
+    }
+
+    /// Get the annotations of the image, stored in the `ANNO` chunks.
+    pub fn annotations(&self) -> Vec<Bytes> {
+        self.chunks_by_id(CHUNK_ANNO)
+            .filter_map(|chunk| chunk.content().data())
+            .map(trim_nul)
+            .collect()
+    }
+
+    /// Replace the annotations of the image with `annotations`.
+    pub fn set_annotations(&mut self, annotations: Vec<Bytes>) {
+        let pos = self.insert_position(CHUNK_ANNO);
+        self.remove_chunks_by_id(CHUNK_ANNO);
+
+        let chunks = annotations
+            .into_iter()
+            .map(|annotation| RiffChunk::new(CHUNK_ANNO, RiffContent::Data(annotation)));
+        self.chunks_mut().splice(pos..pos, chunks);
+    }
+
+    /// Get the pixels, stored in the `BODY` chunk.
+    pub fn body(&self) -> Option<&Bytes> {
+        self.data(CHUNK_BODY)
+    }
+
+    fn data(&self, id: [u8; 4]) -> Option<&Bytes> {
+        self.chunk_by_id(id)?.content().data()
+    }
+
+    /// Replace the chunks with an id of `id` with a single chunk holding
+    /// `data`, or remove them if `data` is `None`.
+    fn set_data(&mut self, id: [u8; 4], data: Option<Bytes>) {
+        let pos = self.insert_position(id);
+        self.remove_chunks_by_id(id);
+
+        if let Some(data) = data {
+            let chunk = RiffChunk::new(id, RiffContent::Data(data));
+            self.chunks_mut().insert(pos, chunk);
+        }
+    }
+
+    /// Get the position of the first chunk with an id of `id` once
+    /// removed, or where a new one goes.
+    ///
+    /// `BMHD` goes first and the other chunks go before `BODY`, as the
+    /// properties of an image must be set before its pixels.
+    fn insert_position(&self, id: [u8; 4]) -> usize {
+        let chunks = self.chunks();
+        if let Some(pos) = chunks.iter().position(|chunk| chunk.id() == id) {
+            return pos;
+        }
+
+        if id == CHUNK_BMHD {
+            return 0;
+        }
+        chunks
+            .iter()
+            .position(|chunk| chunk.id() == CHUNK_BODY)
+            .unwrap_or(chunks.len())
+    }
+
+    impl_subchunks!("Ilbm", form);
+
+    /// Get the total size of the `Ilbm` once it is encoded.
+    ///
+    /// Internally calls [`IffChunk::len`] on the inner `IffChunk`
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.form.len()
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Ilbm`
+    ///
+    /// Internally calls [`IffChunk::encoder`] on the inner `IffChunk`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LimitExceeded`] if one of
+    /// the chunks is too big to have its size stored in 32 bits.
+    #[inline]
+    pub fn encoder(self) -> Result<ImageEncoder<IffChunk>> {
+        self.form.encoder()
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use bytes::Bytes;
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    riff::{EncodeContext, ParseContext, RiffChunk, RiffContent, RiffSchema},
+    util::read_u8_array,
+    Error, Result,
+};
+
+pub use self::ilbm::{
+    Ilbm, IlbmCompression, IlbmHeader, IlbmMasking, CHUNK_ANNO, CHUNK_AUTH, CHUNK_BMHD, CHUNK_BODY,
+    CHUNK_CAMG, CHUNK_CMAP, CHUNK_COPYRIGHT, CHUNK_NAME, KIND_ILBM, KIND_PBM,
+};
+
+mod ilbm;
+
+pub const CHUNK_FORM: [u8; 4] = [b'F', b'O', b'R', b'M'];
+pub const CHUNK_LIST: [u8; 4] = [b'L', b'I', b'S', b'T'];
+pub const CHUNK_CAT: [u8; 4] = [b'C', b'A', b'T', b' '];
+pub const CHUNK_PROP: [u8; 4] = [b'P', b'R', b'O', b'P'];
+
+/// The representation of an EA IFF 85 file
+///
+/// IFF is the big-endian ancestor of RIFF, so the chunks are stored as
+/// [`RiffChunk`]s. The `FORM`, `LIST`, `CAT ` and `PROP` group chunks
+/// are a [`RiffContent::List`] with a kind, every other chunk holds
+/// [`RiffContent::Data`].
+///
+/// Only the root `IffChunk` is encoded with big-endian sizes, the
+/// encoder of a [`RiffChunk`] taken out of it uses little-endian sizes.
+#[derive(Clone, PartialEq)]
+pub struct IffChunk {
+    chunk: RiffChunk,
+}
+
+/// The [`RiffSchema`] of IFF files, where only the group chunks contain subchunks
+struct IffSchema;
+
+/// Check if the chunks with `id` are group chunks, holding subchunks
+#[inline]
+pub fn is_group(id: [u8; 4]) -> bool {
+    matches!(id, CHUNK_FORM | CHUNK_LIST | CHUNK_CAT | CHUNK_PROP)
+}
+
+impl RiffSchema for IffSchema {
+    fn has_subchunks(&self, id: [u8; 4]) -> bool {
+        is_group(id)
+    }
+
+    fn has_kind(&self, id: [u8; 4]) -> bool {
+        is_group(id)
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl IffChunk {
+    /// Construct a new IFF chunk.
+    #[inline]
+    pub fn new(id: [u8; 4], content: RiffContent) -> IffChunk {
+        IffChunk {
+            chunk: RiffChunk::new(id, content),
+        }
+    }
+
+    /// Create a new `IffChunk` from a Reader.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file doesn't start with a `FORM`, `LIST`
+    /// or `CAT ` chunk, or if one of the chunks is corrupted or truncated.
+    pub fn from_bytes(mut b: Bytes) -> Result<IffChunk> {
+        let id = read_u8_array(&mut b.clone())?;
+        if !matches!(id, CHUNK_FORM | CHUNK_LIST | CHUNK_CAT) {
+            return Err(Error::WrongSignature);
+        }
+
+        let chunk = RiffChunk::parse(&mut b, false, &IffSchema, &mut ParseContext::iff())?;
+        Ok(IffChunk { chunk })
+    }
+
+    /// Get the id of this `IffChunk`
+    #[inline]
+    pub fn id(&self) -> [u8; 4] {
+        self.chunk.id()
+    }
+
+    /// Get the content of this `IffChunk`
+    #[inline]
+    pub fn content(&self) -> &RiffContent {
+        self.chunk.content()
+    }
+
+    /// Get a mutable reference to the content of this `IffChunk`
+    #[inline]
+    pub fn content_mut(&mut self) -> &mut RiffContent {
+        self.chunk.content_mut()
+    }
+
+    /// Get the subchunks of this `IffChunk`, which is the root list of an [`Ilbm`]
+    #[inline]
+    pub(crate) fn root_subchunks(&self) -> &Vec<RiffChunk> {
+        self.chunk.root_subchunks()
+    }
+
+    /// Get a mutable reference to the subchunks of this `IffChunk`,
+    /// which is the root list of an [`Ilbm`]
+    #[inline]
+    pub(crate) fn root_subchunks_mut(&mut self) -> &mut Vec<RiffChunk> {
+        self.chunk.root_subchunks_mut()
+    }
+
+    /// Get the total size of this `IffChunk` once it is encoded.
+    ///
+    /// Internally calls [`RiffChunk::len`] on the inner `RiffChunk`
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.chunk.len()
+    }
+
+    /// Returns an encoder for this `IffChunk`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LimitExceeded`] if this `IffChunk`
+    /// or any of its subchunks is too big to have its size stored in 32 bits.
+    pub fn encoder(self) -> Result<ImageEncoder<Self>> {
+        if self.chunk.requires_rf64() {
+            return Err(Error::LimitExceeded);
+        }
+
+        Ok(ImageEncoder::from(self))
+    }
+}
+
+impl From<RiffChunk> for IffChunk {
+    #[inline]
+    fn from(chunk: RiffChunk) -> IffChunk {
+        IffChunk { chunk }
+    }
+}
+
+impl From<IffChunk> for RiffChunk {
+    #[inline]
+    fn from(iff: IffChunk) -> RiffChunk {
+        iff.chunk
+    }
+}
+
+impl EncodeAt for IffChunk {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        self.chunk.encode_at_impl(pos, EncodeContext::iff(), false)
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.len()).unwrap_or(usize::MAX)
+    }
+}
+
+impl fmt::Debug for IffChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IffChunk").field("id", &self.id()).finish()
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{read_checked, read_u8_array},
+    Result,
+};
+
+/// The contents of an `ftyp` box, identifying the specifications a file conforms to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoFileType {
+    /// The brand of the main specification, like `heic` or `avif`
+    pub major_brand: [u8; 4],
+    /// The version of the major brand
+    pub minor_version: u32,
+    /// The brands of all of the specifications the file conforms to
+    pub compatible_brands: Vec<[u8; 4]>,
+}
+
+impl IsoFileType {
+    /// Parse the contents of an `ftyp` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the box is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<IsoFileType> {
+        let mut b = b.clone();
+
+        let major_brand = read_u8_array(&mut b)?;
+        let minor_version = read_checked(&mut b, |b| b.get_u32())?;
+        let mut compatible_brands = Vec::with_capacity(b.len() / 4);
+        // a trailing incomplete brand is ignored
+        while b.len() >= 4 {
+            compatible_brands.push(read_u8_array(&mut b)?);
+        }
+
+        Ok(IsoFileType {
+            major_brand,
+            minor_version,
+            compatible_brands,
+        })
+    }
+
+    /// Check if `brand` is the major brand or one of the compatible brands
+    pub fn has_brand(&self, brand: [u8; 4]) -> bool {
+        self.major_brand == brand || self.compatible_brands.contains(&brand)
+    }
+
+    /// Encode this `IsoFileType` into the contents of an `ftyp` box
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(8 + self.compatible_brands.len() * 4);
+        b.extend_from_slice(&self.major_brand);
+        b.put_u32(self.minor_version);
+        for brand in &self.compatible_brands {
+            b.extend_from_slice(brand);
+        }
+
+        b.freeze()
+    }
+}
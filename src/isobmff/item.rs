@@ -0,0 +1,413 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{put_full_box, read_full_box, read_uint};
+use crate::{
+    util::{read_checked, read_u8_array},
+    Error, Result,
+};
+
+const ITEM_TYPE_MIME: [u8; 4] = [b'm', b'i', b'm', b'e'];
+const ITEM_TYPE_URI: [u8; 4] = [b'u', b'r', b'i', b' '];
+
+// the `infe` flag hiding the item from the user
+const FLAG_HIDDEN: u32 = 1;
+
+/// The contents of an `infe` box, describing an item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoItemInfo {
+    /// The id of the item
+    pub id: u32,
+    /// The index of the protection scheme of the item, or `0`
+    pub protection_index: u16,
+    /// The type of the item, like `hvc1`, `av01`, `Exif` or `mime`
+    ///
+    /// Items described by version 0 and 1 boxes are `mime` items.
+    pub kind: [u8; 4],
+    /// The name of the item
+    pub name: Bytes,
+    /// The MIME type of `mime` items, or the URI of `uri ` items
+    pub content_type: Option<Bytes>,
+    /// The content encoding of `mime` items, like `deflate`
+    pub content_encoding: Option<Bytes>,
+    /// Whether the item shouldn't be displayed
+    pub hidden: bool,
+}
+
+/// The contents of an `iloc` box, locating the data of the items
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoItemLocations {
+    /// The version of the box, from 0 to 2
+    pub version: u8,
+    /// The size of the extent offsets, in bytes: 0, 4 or 8
+    pub offset_size: u8,
+    /// The size of the extent lengths, in bytes: 0, 4 or 8
+    pub length_size: u8,
+    /// The size of the base offsets, in bytes: 0, 4 or 8
+    pub base_offset_size: u8,
+    /// The size of the extent indexes, in bytes: 0, 4 or 8
+    pub index_size: u8,
+    /// The locations of the items
+    pub items: Vec<IsoItemLocation>,
+}
+
+/// The location of the data of an item, in an [`IsoItemLocations`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoItemLocation {
+    /// The id of the item
+    pub id: u32,
+    /// Where the offsets point to: `0` for the file, `1` for
+    /// the `idat` box and `2` for the data of other items
+    pub construction_method: u8,
+    /// The index of the data reference, `0` for the file itself
+    pub data_reference_index: u16,
+    /// The offset added to the offset of every extent
+    pub base_offset: u64,
+    /// The parts of the data of the item, which is their concatenation
+    pub extents: Vec<IsoExtent>,
+}
+
+/// A contiguous part of the data of an item, in an [`IsoItemLocation`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IsoExtent {
+    /// The index of the item the offset is relative to,
+    /// for the construction method `2`
+    pub index: u64,
+    /// The offset of the extent, relative to the base offset
+    pub offset: u64,
+    /// The length of the extent, `0` meaning up to the end of the data
+    pub len: u64,
+}
+
+/// A reference from an item to other items, a child of the `iref` box
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoItemReference {
+    /// The type of the reference, like `cdsc` for the metadata describing
+    /// an image, `thmb` for a thumbnail or `dimg` for the tiles of a grid
+    pub kind: [u8; 4],
+    /// The id of the referencing item
+    pub from_id: u32,
+    /// The ids of the referenced items
+    pub to_ids: Vec<u32>,
+}
+
+/// Read a NUL terminated string, which isn't terminated at the end of some boxes
+fn read_string(b: &mut Bytes) -> Bytes {
+    match b.iter().position(|&b| b == 0) {
+        Some(nul) => {
+            let s = b.split_to(nul);
+            b.advance(1);
+            s
+        }
+        None => b.split_off(0),
+    }
+}
+
+fn put_string(b: &mut BytesMut, s: &[u8]) {
+    b.extend_from_slice(s);
+    b.put_u8(0);
+}
+
+impl IsoItemInfo {
+    /// Parse the contents of an `infe` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the box is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<IsoItemInfo> {
+        let mut b = b.clone();
+        let (version, flags) = read_full_box(&mut b)?;
+
+        let id = if version == 3 {
+            read_checked(&mut b, |b| b.get_u32())?
+        } else {
+            read_checked(&mut b, |b| b.get_u16())?.into()
+        };
+        let protection_index = read_checked(&mut b, |b| b.get_u16())?;
+        let kind = if version >= 2 {
+            read_u8_array(&mut b)?
+        } else {
+            ITEM_TYPE_MIME
+        };
+        let name = read_string(&mut b);
+
+        let (content_type, content_encoding) = match kind {
+            ITEM_TYPE_MIME => {
+                let content_type = read_string(&mut b);
+                let content_encoding = Some(read_string(&mut b)).filter(|s| !s.is_empty());
+                (Some(content_type), content_encoding)
+            }
+            ITEM_TYPE_URI => (Some(read_string(&mut b)), None),
+            _ => (None, None),
+        };
+
+        Ok(IsoItemInfo {
+            id,
+            protection_index,
+            kind,
+            name,
+            content_type,
+            content_encoding,
+            hidden: flags & FLAG_HIDDEN != 0,
+        })
+    }
+
+    /// Encode this `IsoItemInfo` into the contents of a version 2 `infe` box,
+    /// or a version 3 one if the id doesn't fit in 16 bits
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(16 + self.name.len());
+        let flags = if self.hidden { FLAG_HIDDEN } else { 0 };
+
+        match u16::try_from(self.id) {
+            Ok(id) => {
+                put_full_box(&mut b, 2, flags);
+                b.put_u16(id);
+            }
+            Err(_) => {
+                put_full_box(&mut b, 3, flags);
+                b.put_u32(self.id);
+            }
+        }
+        b.put_u16(self.protection_index);
+        b.extend_from_slice(&self.kind);
+        put_string(&mut b, &self.name);
+
+        if matches!(self.kind, ITEM_TYPE_MIME | ITEM_TYPE_URI) {
+            put_string(&mut b, self.content_type.as_deref().unwrap_or_default());
+        }
+        if let (ITEM_TYPE_MIME, Some(content_encoding)) = (self.kind, &self.content_encoding) {
+            put_string(&mut b, content_encoding);
+        }
+
+        b.freeze()
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl IsoItemLocations {
+    /// Parse the contents of an `iloc` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the box is truncated, or with [`Error::InvalidData`]
+    /// if its version or the size of one of its fields is unsupported.
+    pub fn from_bytes(b: &Bytes) -> Result<IsoItemLocations> {
+        let mut b = b.clone();
+        let (version, _flags) = read_full_box(&mut b)?;
+        if version > 2 {
+            return Err(Error::InvalidData);
+        }
+
+        let sizes = read_checked(&mut b, |b| b.get_u8())?;
+        let (offset_size, length_size) = (sizes >> 4, sizes & 0xF);
+        let sizes = read_checked(&mut b, |b| b.get_u8())?;
+        let base_offset_size = sizes >> 4;
+        let index_size = if version > 0 { sizes & 0xF } else { 0 };
+
+        let count = if version < 2 {
+            read_checked(&mut b, |b| b.get_u16())?.into()
+        } else {
+            read_checked(&mut b, |b| b.get_u32())?
+        };
+
+        let mut items = Vec::new();
+        for _ in 0..count {
+            let id = if version < 2 {
+                read_checked(&mut b, |b| b.get_u16())?.into()
+            } else {
+                read_checked(&mut b, |b| b.get_u32())?
+            };
+            let construction_method = if version > 0 {
+                (read_checked(&mut b, |b| b.get_u16())? & 0xF) as u8
+            } else {
+                0
+            };
+            let data_reference_index = read_checked(&mut b, |b| b.get_u16())?;
+            let base_offset = read_uint(&mut b, base_offset_size)?;
+
+            let extent_count = read_checked(&mut b, |b| b.get_u16())?;
+            let mut extents = Vec::with_capacity(extent_count.into());
+            for _ in 0..extent_count {
+                extents.push(IsoExtent {
+                    index: read_uint(&mut b, index_size)?,
+                    offset: read_uint(&mut b, offset_size)?,
+                    len: read_uint(&mut b, length_size)?,
+                });
+            }
+
+            items.push(IsoItemLocation {
+                id,
+                construction_method,
+                data_reference_index,
+                base_offset,
+                extents,
+            });
+        }
+
+        Ok(IsoItemLocations {
+            version,
+            offset_size,
+            length_size,
+            base_offset_size,
+            index_size,
+            items,
+        })
+    }
+
+    /// Get the location of the item with id `id`
+    pub fn item(&self, id: u32) -> Option<&IsoItemLocation> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// Get a mutable reference to the location of the item with id `id`
+    pub fn item_mut(&mut self, id: u32) -> Option<&mut IsoItemLocation> {
+        self.items.iter_mut().find(|item| item.id == id)
+    }
+
+    /// Get the size of the contents of the `iloc` box encoding this `IsoItemLocations`
+    pub fn len(&self) -> usize {
+        let id_size = if self.version < 2 { 2 } else { 4 };
+        let method_size = if self.version > 0 { 2 } else { 0 };
+        let extent_size = usize::from(self.index_size + self.offset_size + self.length_size);
+
+        4 + 2
+            + id_size
+            + self
+                .items
+                .iter()
+                .map(|item| {
+                    id_size
+                        + method_size
+                        + 2
+                        + usize::from(self.base_offset_size)
+                        + 2
+                        + item.extents.len() * extent_size
+                })
+                .sum::<usize>()
+    }
+
+    /// Encode this `IsoItemLocations` into the contents of an `iloc` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if one of the values
+    /// doesn't fit in its field or isn't supported by the version.
+    pub fn to_bytes(&self) -> Result<Bytes> {
+        let version = self.version;
+        if version > 2 {
+            return Err(Error::InvalidData);
+        }
+
+        let index_size = if version > 0 { self.index_size } else { 0 };
+        let mut b = BytesMut::with_capacity(self.len());
+        put_full_box(&mut b, version, 0);
+        b.put_u8(self.offset_size << 4 | self.length_size);
+        b.put_u8(self.base_offset_size << 4 | index_size);
+        put_id(&mut b, version, self.items.len())?;
+
+        for item in &self.items {
+            put_id(&mut b, version, item.id as usize)?;
+            match (version, item.construction_method) {
+                (0, 0) => {}
+                (0, _) => return Err(Error::InvalidData),
+                (_, method) if method <= 0xF => b.put_u16(method.into()),
+                _ => return Err(Error::InvalidData),
+            }
+            b.put_u16(item.data_reference_index);
+            put_uint(&mut b, self.base_offset_size, item.base_offset)?;
+
+            let extent_count = u16::try_from(item.extents.len()).map_err(|_| Error::InvalidData)?;
+            b.put_u16(extent_count);
+            for extent in &item.extents {
+                if index_size == 0 && extent.index != 0 {
+                    return Err(Error::InvalidData);
+                }
+                put_uint(&mut b, index_size, extent.index)?;
+                put_uint(&mut b, self.offset_size, extent.offset)?;
+                put_uint(&mut b, self.length_size, extent.len)?;
+            }
+        }
+
+        Ok(b.freeze())
+    }
+}
+
+impl IsoItemReference {
+    /// Parse the contents of a child of type `kind` of the `iref` box,
+    /// whose item ids take 32 bits if `large_ids`, for versions above 0
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the box is truncated.
+    pub fn from_bytes(kind: [u8; 4], b: &Bytes, large_ids: bool) -> Result<IsoItemReference> {
+        let mut b = b.clone();
+        let read_id = |b: &mut Bytes| {
+            if large_ids {
+                read_checked(b, |b| b.get_u32())
+            } else {
+                read_checked(b, |b| b.get_u16()).map(u32::from)
+            }
+        };
+
+        let from_id = read_id(&mut b)?;
+        let count = read_checked(&mut b, |b| b.get_u16())?;
+        let to_ids = (0..count).map(|_| read_id(&mut b)).collect::<Result<_>>()?;
+
+        Ok(IsoItemReference {
+            kind,
+            from_id,
+            to_ids,
+        })
+    }
+
+    /// Encode this `IsoItemReference` into the contents of a child of the `iref`
+    /// box, whose item ids take 32 bits if `large_ids`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if one of the values
+    /// doesn't fit in its field.
+    pub fn to_bytes(&self, large_ids: bool) -> Result<Bytes> {
+        let id_size = if large_ids { 4 } else { 2 };
+        let mut b = BytesMut::with_capacity(2 + id_size * (1 + self.to_ids.len()));
+
+        let put_ref_id = |b: &mut BytesMut, id: u32| {
+            if large_ids {
+                b.put_u32(id);
+            } else {
+                b.put_u16(u16::try_from(id).map_err(|_| Error::InvalidData)?);
+            }
+            Ok(())
+        };
+
+        put_ref_id(&mut b, self.from_id)?;
+        b.put_u16(u16::try_from(self.to_ids.len()).map_err(|_| Error::InvalidData)?);
+        for &id in &self.to_ids {
+            put_ref_id(&mut b, id)?;
+        }
+
+        Ok(b.freeze())
+    }
+}
+
+/// Write an item id or an item count, which takes 16 bits before version 2
+fn put_id(b: &mut BytesMut, version: u8, n: usize) -> Result<()> {
+    if version < 2 {
+        b.put_u16(u16::try_from(n).map_err(|_| Error::InvalidData)?);
+    } else {
+        b.put_u32(u32::try_from(n).map_err(|_| Error::InvalidData)?);
+    }
+    Ok(())
+}
+
+/// Write `n` in `size` bytes, which must be 0, 4 or 8
+fn put_uint(b: &mut BytesMut, size: u8, n: u64) -> Result<()> {
+    match size {
+        0 if n == 0 => {}
+        4 => b.put_u32(u32::try_from(n).map_err(|_| Error::InvalidData)?),
+        8 => b.put_u64(n),
+        _ => return Err(Error::InvalidData),
+    }
+    Ok(())
+}
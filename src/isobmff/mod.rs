@@ -0,0 +1,365 @@
+use alloc::vec::Vec;
+use core::{fmt, mem};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    util::{read_checked, read_u8_array, split_to_checked},
+    Error, Result,
+};
+
+pub use self::{
+    ftyp::IsoFileType,
+    item::{IsoExtent, IsoItemInfo, IsoItemLocation, IsoItemLocations, IsoItemReference},
+    property::{IsoItemProperties, IsoPropertyAssociation, IsoPropertyAssociations},
+};
+
+mod ftyp;
+mod item;
+mod property;
+
+pub const BOX_FTYP: [u8; 4] = [b'f', b't', b'y', b'p'];
+pub const BOX_META: [u8; 4] = [b'm', b'e', b't', b'a'];
+pub const BOX_HDLR: [u8; 4] = [b'h', b'd', b'l', b'r'];
+pub const BOX_PITM: [u8; 4] = [b'p', b'i', b't', b'm'];
+pub const BOX_IINF: [u8; 4] = [b'i', b'i', b'n', b'f'];
+pub const BOX_INFE: [u8; 4] = [b'i', b'n', b'f', b'e'];
+pub const BOX_ILOC: [u8; 4] = [b'i', b'l', b'o', b'c'];
+pub const BOX_IREF: [u8; 4] = [b'i', b'r', b'e', b'f'];
+pub const BOX_IPRP: [u8; 4] = [b'i', b'p', b'r', b'p'];
+pub const BOX_IPCO: [u8; 4] = [b'i', b'p', b'c', b'o'];
+pub const BOX_IPMA: [u8; 4] = [b'i', b'p', b'm', b'a'];
+pub const BOX_IDAT: [u8; 4] = [b'i', b'd', b'a', b't'];
+pub const BOX_MDAT: [u8; 4] = [b'm', b'd', b'a', b't'];
+pub const BOX_MOOV: [u8; 4] = [b'm', b'o', b'o', b'v'];
+pub const BOX_UUID: [u8; 4] = [b'u', b'u', b'i', b'd'];
+pub const BOX_STCO: [u8; 4] = [b's', b't', b'c', b'o'];
+pub const BOX_CO64: [u8; 4] = [b'c', b'o', b'6', b'4'];
+
+// the boxes holding other boxes, after the header returned by `container_header_len`
+const CONTAINERS: [[u8; 4]; 13] = [
+    BOX_META, BOX_IINF, BOX_IREF, BOX_IPRP, BOX_IPCO, BOX_MOOV, *b"trak", *b"edts", *b"mdia",
+    *b"minf", *b"dinf", *b"stbl", *b"grpl",
+];
+
+// the maximum nesting of container boxes
+const MAX_DEPTH: usize = 16;
+
+/// The representation of an ISOBMFF box
+///
+/// ISO base media file format boxes, the building blocks of HEIF, AVIF
+/// and MP4 files, start with a big-endian 32 bit size, which can be
+/// extended to 64 bits, and a 4 byte type.
+#[derive(Clone, PartialEq)]
+pub struct IsoBox {
+    kind: [u8; 4],
+    content: IsoContent,
+    large_size: bool,
+}
+
+/// The contents of an ISOBMFF box
+#[derive(Debug, Clone, PartialEq)]
+pub enum IsoContent {
+    /// A box holding other boxes, like `meta` or `ipco`
+    ///
+    /// `header` holds the fields preceding the children,
+    /// like the version and the flags of a full box.
+    Container {
+        header: Bytes,
+        children: Vec<IsoBox>,
+    },
+    Data(Bytes),
+}
+
+/// Check if the boxes of type `kind` hold other boxes
+#[inline]
+pub fn is_container(kind: [u8; 4]) -> bool {
+    CONTAINERS.contains(&kind)
+}
+
+/// Parse all of the boxes of `b`
+///
+/// # Errors
+///
+/// This method fails if one of the boxes is corrupted or truncated.
+pub fn read_boxes(mut b: Bytes) -> Result<Vec<IsoBox>> {
+    read_boxes_impl(&mut b, 0)
+}
+
+fn read_boxes_impl(b: &mut Bytes, depth: usize) -> Result<Vec<IsoBox>> {
+    if depth > MAX_DEPTH {
+        return Err(Error::LimitExceeded);
+    }
+
+    let mut boxes = Vec::with_capacity(8);
+    while !b.is_empty() {
+        let large_size = b.get(..4) == Some(&[0, 0, 0, 1]);
+        let (kind, contents) = read_box(b)?;
+        let content = IsoContent::from_bytes(kind, contents, depth)?;
+        boxes.push(IsoBox {
+            kind,
+            content,
+            large_size,
+        });
+    }
+
+    Ok(boxes)
+}
+
+/// Split the first box of `b` into its type and its contents
+pub(crate) fn read_box(b: &mut Bytes) -> Result<([u8; 4], Bytes)> {
+    let size = read_checked(b, |b| b.get_u32())?;
+    let kind = read_u8_array(b)?;
+
+    let contents = match size {
+        // the box extends to the end of the file
+        0 => mem::take(b),
+        1 => {
+            let size = read_checked(b, |b| b.get_u64())?;
+            let len = size.checked_sub(16).ok_or(Error::InvalidData)?;
+            split_to_checked(b, usize::try_from(len).map_err(|_| Error::Truncated)?)?
+        }
+        _ => {
+            let len = size.checked_sub(8).ok_or(Error::InvalidData)?;
+            split_to_checked(b, len as usize)?
+        }
+    };
+    Ok((kind, contents))
+}
+
+/// Write the header of a box of type `kind`, with a total size of `len`
+/// and a header of `header_len` bytes, 8 or 16
+pub(crate) fn put_box_header(b: &mut BytesMut, kind: [u8; 4], len: u64, header_len: u64) {
+    if header_len == 16 {
+        b.put_u32(1);
+        b.extend_from_slice(&kind);
+        b.put_u64(len);
+    } else {
+        b.put_u32(len as u32);
+        b.extend_from_slice(&kind);
+    }
+}
+
+/// Read the version and the flags of a full box
+pub(crate) fn read_full_box(b: &mut Bytes) -> Result<(u8, u32)> {
+    let header = read_checked(b, |b| b.get_u32())?;
+    Ok(((header >> 24) as u8, header & 0x00FF_FFFF))
+}
+
+/// Write the version and the flags of a full box
+pub(crate) fn put_full_box(b: &mut BytesMut, version: u8, flags: u32) {
+    b.put_u32(u32::from(version) << 24 | (flags & 0x00FF_FFFF));
+}
+
+/// Read an unsigned integer of `size` bytes, which must be 0, 4 or 8
+pub(crate) fn read_uint(b: &mut Bytes, size: u8) -> Result<u64> {
+    match size {
+        0 => Ok(0),
+        4 => read_checked(b, |b| b.get_u32()).map(u64::from),
+        8 => read_checked(b, |b| b.get_u64()),
+        _ => Err(Error::InvalidData),
+    }
+}
+
+/// Get the size of the fields of the container box `kind` preceding its children
+fn container_header_len(kind: [u8; 4], contents: &[u8]) -> Result<usize> {
+    match kind {
+        // QuickTime `meta` boxes aren't full boxes
+        BOX_META if contents.get(4..8) == Some(&BOX_HDLR) => Ok(0),
+        BOX_META | BOX_IREF => Ok(4),
+        BOX_IINF => match contents.first() {
+            Some(0) => Ok(4 + 2),
+            Some(_) => Ok(4 + 4),
+            None => Err(Error::Truncated),
+        },
+        _ => Ok(0),
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl IsoBox {
+    /// Construct a new ISOBMFF box.
+    #[inline]
+    pub fn new(kind: [u8; 4], content: IsoContent) -> IsoBox {
+        IsoBox {
+            kind,
+            content,
+            large_size: false,
+        }
+    }
+
+    /// Get the type of this `IsoBox`
+    #[inline]
+    pub fn kind(&self) -> [u8; 4] {
+        self.kind
+    }
+
+    /// Get the content of this `IsoBox`
+    #[inline]
+    pub fn content(&self) -> &IsoContent {
+        &self.content
+    }
+
+    /// Get a mutable reference to the content of this `IsoBox`
+    #[inline]
+    pub fn content_mut(&mut self) -> &mut IsoContent {
+        &mut self.content
+    }
+
+    /// Get the children of this `IsoBox`
+    ///
+    /// Returns an empty slice if it isn't a `Container`.
+    pub fn children(&self) -> &[IsoBox] {
+        match &self.content {
+            IsoContent::Container { children, .. } => children,
+            IsoContent::Data(_) => &[],
+        }
+    }
+
+    /// Get a mutable reference to the children of this `IsoBox`
+    ///
+    /// Returns `None` if it isn't a `Container`.
+    pub fn children_mut(&mut self) -> Option<&mut Vec<IsoBox>> {
+        match &mut self.content {
+            IsoContent::Container { children, .. } => Some(children),
+            IsoContent::Data(_) => None,
+        }
+    }
+
+    /// Get the first child of this `IsoBox` with type `kind`
+    pub fn child(&self, kind: [u8; 4]) -> Option<&IsoBox> {
+        self.children().iter().find(|child| child.kind == kind)
+    }
+
+    /// Get a mutable reference to the first child of this `IsoBox` with type `kind`
+    pub fn child_mut(&mut self, kind: [u8; 4]) -> Option<&mut IsoBox> {
+        self.children_mut()?
+            .iter_mut()
+            .find(|child| child.kind == kind)
+    }
+
+    /// Get the data of this `IsoBox` if it isn't a `Container`
+    #[inline]
+    pub fn data(&self) -> Option<&Bytes> {
+        self.content.data()
+    }
+
+    /// Get the size of the header of this `IsoBox`: 16 bytes if its size
+    /// needs 64 bits or if it was parsed with a 64 bit size, 8 bytes otherwise
+    ///
+    /// Keeping the size of the header of the parsed boxes
+    /// avoids moving the data of the following boxes.
+    pub fn header_len(&self) -> u64 {
+        if self.large_size || self.content.len() > u64::from(u32::MAX) - 8 {
+            16
+        } else {
+            8
+        }
+    }
+
+    /// Get the total size of this `IsoBox` once it is encoded.
+    ///
+    /// The size is the sum of:
+    ///
+    /// - The header (8 or 16 bytes), see [`header_len`][IsoBox::header_len].
+    /// - The size of the content.
+    pub fn len(&self) -> u64 {
+        self.header_len() + self.content.len()
+    }
+
+    /// Returns an encoder for this `IsoBox`
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<Self> {
+        ImageEncoder::from(self)
+    }
+}
+
+impl EncodeAt for IsoBox {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match pos {
+            0 => {
+                let mut vec = BytesMut::with_capacity(16);
+                put_box_header(&mut vec, self.kind, self.len(), self.header_len());
+
+                if let IsoContent::Container { header, .. } = &self.content {
+                    vec.extend_from_slice(header);
+                }
+                Some(vec.freeze())
+            }
+            _ => {
+                *pos -= 1;
+
+                match &self.content {
+                    IsoContent::Container { children, .. } => {
+                        for child in children {
+                            if let Some(bytes) = child.encode_at(pos) {
+                                return Some(bytes);
+                            }
+                        }
+
+                        None
+                    }
+                    IsoContent::Data(data) => match pos {
+                        0 => Some(data.clone()),
+                        _ => {
+                            *pos -= 1;
+                            None
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len() as usize
+    }
+}
+
+impl fmt::Debug for IsoBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IsoBox").field("kind", &self.kind).finish()
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl IsoContent {
+    fn from_bytes(kind: [u8; 4], mut contents: Bytes, depth: usize) -> Result<IsoContent> {
+        if !is_container(kind) {
+            return Ok(IsoContent::Data(contents));
+        }
+
+        let header_len = container_header_len(kind, &contents)?;
+        let header = split_to_checked(&mut contents, header_len)?;
+        let children = read_boxes_impl(&mut contents, depth + 1)?;
+        Ok(IsoContent::Container { header, children })
+    }
+
+    /// Get the total size of this `IsoContent` once it is encoded.
+    ///
+    /// If this `IsoContent` is a `Container` the size is the sum of:
+    ///
+    /// - The size of the `header`.
+    /// - The sum of the size of every child.
+    ///
+    /// If this `IsoContent` is `Data` the size is the length of the data.
+    pub fn len(&self) -> u64 {
+        match self {
+            IsoContent::Container { header, children } => {
+                header.len() as u64 + children.iter().map(IsoBox::len).sum::<u64>()
+            }
+            IsoContent::Data(data) => data.len() as u64,
+        }
+    }
+
+    /// Get the `data` of this `IsoContent` if it is `Data`.
+    ///
+    /// Returns `None` if it is a `Container`.
+    pub fn data(&self) -> Option<&Bytes> {
+        match self {
+            IsoContent::Container { .. } => None,
+            IsoContent::Data(data) => Some(data),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{put_full_box, read_full_box};
+use crate::{util::read_checked, Error, Result};
+
+// the `ipma` flag storing the property indexes in 15 bits instead of 7
+const FLAG_LARGE_INDEXES: u32 = 1;
+
+/// The contents of an `ipma` box, associating the items with their properties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoPropertyAssociations {
+    /// The version of the box: `0` for 16 bit item ids, `1` for 32 bit ones
+    pub version: u8,
+    /// The flags of the box: `1` for 15 bit property indexes, `0` for 7 bit ones
+    pub flags: u32,
+    /// The properties of each item
+    pub items: Vec<IsoItemProperties>,
+}
+
+/// The properties of an item, in an [`IsoPropertyAssociations`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoItemProperties {
+    /// The id of the item
+    pub id: u32,
+    /// The properties associated with the item, in order
+    pub properties: Vec<IsoPropertyAssociation>,
+}
+
+/// The association of an item with a property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IsoPropertyAssociation {
+    /// Whether the property must be understood to display the item
+    pub essential: bool,
+    /// The 1-based index of the property in the `ipco` box, `0` meaning no property
+    pub index: u16,
+}
+
+impl IsoPropertyAssociations {
+    /// Parse the contents of an `ipma` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the box is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<IsoPropertyAssociations> {
+        let mut b = b.clone();
+        let (version, flags) = read_full_box(&mut b)?;
+
+        let count = read_checked(&mut b, |b| b.get_u32())?;
+        let mut items = Vec::new();
+        for _ in 0..count {
+            let id = if version < 1 {
+                read_checked(&mut b, |b| b.get_u16())?.into()
+            } else {
+                read_checked(&mut b, |b| b.get_u32())?
+            };
+
+            let association_count = read_checked(&mut b, |b| b.get_u8())?;
+            let mut properties = Vec::with_capacity(association_count.into());
+            for _ in 0..association_count {
+                let property = if flags & FLAG_LARGE_INDEXES != 0 {
+                    let n = read_checked(&mut b, |b| b.get_u16())?;
+                    IsoPropertyAssociation {
+                        essential: n & 0x8000 != 0,
+                        index: n & 0x7FFF,
+                    }
+                } else {
+                    let n = read_checked(&mut b, |b| b.get_u8())?;
+                    IsoPropertyAssociation {
+                        essential: n & 0x80 != 0,
+                        index: (n & 0x7F).into(),
+                    }
+                };
+                properties.push(property);
+            }
+
+            items.push(IsoItemProperties { id, properties });
+        }
+
+        Ok(IsoPropertyAssociations {
+            version,
+            flags,
+            items,
+        })
+    }
+
+    /// Get the properties of the item with id `id`
+    pub fn item(&self, id: u32) -> Option<&IsoItemProperties> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// Encode this `IsoPropertyAssociations` into the contents of an `ipma` box
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if one of the values
+    /// doesn't fit in its field.
+    pub fn to_bytes(&self) -> Result<Bytes> {
+        let large_indexes = self.flags & FLAG_LARGE_INDEXES != 0;
+
+        let mut b = BytesMut::new();
+        put_full_box(&mut b, self.version, self.flags);
+        b.put_u32(u32::try_from(self.items.len()).map_err(|_| Error::InvalidData)?);
+
+        for item in &self.items {
+            if self.version < 1 {
+                b.put_u16(u16::try_from(item.id).map_err(|_| Error::InvalidData)?);
+            } else {
+                b.put_u32(item.id);
+            }
+
+            let count = u8::try_from(item.properties.len()).map_err(|_| Error::InvalidData)?;
+            b.put_u8(count);
+            for property in &item.properties {
+                if large_indexes && property.index <= 0x7FFF {
+                    b.put_u16(u16::from(property.essential) << 15 | property.index);
+                } else if !large_indexes && property.index <= 0x7F {
+                    b.put_u8(u8::from(property.essential) << 7 | property.index as u8);
+                } else {
+                    return Err(Error::InvalidData);
+                }
+            }
+        }
+
+        Ok(b.freeze())
+    }
+}
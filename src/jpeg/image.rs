@@ -73,6 +73,44 @@ impl Jpeg {
         Ok(Jpeg { segments })
     }
 
+    /// Construct a new `Jpeg` holding `segments`
+    ///
+    /// The SOI marker is generated. The EOI marker isn't: the entropy
+    /// of the last scan is written as is, so it has to end with it, just
+    /// like the entropy of the segments parsed by [`Jpeg::from_bytes`].
+    #[inline]
+    pub fn from_segments(segments: Vec<JpegSegment>) -> Jpeg {
+        Jpeg { segments }
+    }
+
+    /// Construct a new `Jpeg` by merging an abbreviated image with the
+    /// tables it relies on.
+    ///
+    /// `tables` is usually an abbreviated table specification, like the
+    /// one stored in the `JPEGTables` TIFF tag. Its quantization, Huffman,
+    /// arithmetic coding and restart interval segments are inserted before
+    /// the frame header of `image`, so that any table redefined by `image`
+    /// still takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `image` doesn't have a frame header.
+    pub fn from_abbreviated(tables: &Jpeg, mut image: Jpeg) -> Result<Jpeg> {
+        let pos = image
+            .segments
+            .iter()
+            .position(|segment| markers::is_frame_header(segment.marker()))
+            .ok_or(Error::InvalidData)?;
+
+        let tables = tables
+            .segments
+            .iter()
+            .filter(|segment| markers::is_table(segment.marker()))
+            .cloned();
+        image.segments.splice(pos..pos, tables);
+        Ok(image)
+    }
+
     /// Get the segments of this `Jpeg`
     #[inline]
     pub fn segments(&self) -> &Vec<JpegSegment> {
@@ -94,3 +94,14 @@ pub(crate) fn has_length(marker: u8) -> bool {
 pub(crate) fn has_entropy(marker: u8) -> bool {
     matches!(marker, SOS)
 }
+
+pub(crate) fn is_frame_header(marker: u8) -> bool {
+    matches!(
+        marker,
+        SOF0..=SOF3 | SOF5..=SOF7 | SOF9..=SOF11 | SOF13..=SOF15
+    )
+}
+
+pub(crate) fn is_table(marker: u8) -> bool {
+    matches!(marker, DQT | DHT | DAC | DRI)
+}
@@ -4,8 +4,13 @@
 //! writing containers from various image formats.
 //!
 //! It currently supports [`Jpeg`][crate::jpeg::Jpeg],
-//! [`Png`][crate::png::Png] and [`RIFF`][crate::riff::RiffChunk]
-//! (with some helper functions for [`WebP`][crate::webp::WebP]).
+//! [`Png`][crate::png::Png], [`Gif`][crate::gif::Gif], [`Tiff`][crate::tiff::Tiff],
+//! [`ISOBMFF`][crate::isobmff::IsoBox] (with [`HEIF`][crate::heif::Heif] images),
+//! [`IFF`][crate::iff::IffChunk] (with [`ILBM`][crate::iff::Ilbm] images)
+//! and [`RIFF`][crate::riff::RiffChunk]
+//! (with some helper functions for [`WebP`][crate::webp::WebP],
+//! [`Wav`][crate::wav::Wav], [`Avi`][crate::avi::Avi] and [`Ani`][crate::ani::Ani]).
+//! The JPEG previews of camera RAW files can be extracted with [`Raw`][crate::raw::Raw].
 //!
 //! ## Reading and writing raw ICCP and EXIF metadata
 //!
@@ -80,13 +85,22 @@ pub use traits::{ImageEXIF, ImageICC};
 pub(crate) const EXIF_DATA_PREFIX: &[u8] = b"Exif\0\0";
 pub(crate) const EXIF_START_PREFIX_LE: &[u8] = b"II\x2A\0";
 pub(crate) const EXIF_START_PREFIX_BE: &[u8] = b"MM\0\x2A";
+pub mod ani;
+pub mod avi;
 mod common;
 mod encoder;
 mod error;
+pub mod gif;
+pub mod heif;
+pub mod iff;
+pub mod isobmff;
 pub mod jpeg;
 pub mod png;
+pub mod raw;
 pub mod riff;
+pub mod tiff;
 mod traits;
 pub(crate) mod util;
 pub mod vp8;
+pub mod wav;
 pub mod webp;
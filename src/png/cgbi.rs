@@ -0,0 +1,32 @@
+use super::{ColorType, PngHeader};
+use crate::{Error, Result};
+
+/// Convert the unfiltered rows of a CgBI image into standard PNG pixels
+///
+/// CgBI images store their pixels in BGR(A) order, with the color samples
+/// premultiplied by the alpha sample.
+pub(super) fn convert_pixels(header: &PngHeader, data: &mut [u8]) -> Result<()> {
+    match (header.color_type, header.bit_depth) {
+        (ColorType::Rgb, 8) => {
+            for pixel in data.chunks_exact_mut(3) {
+                pixel.swap(0, 2);
+            }
+        }
+        (ColorType::Rgba, 8) => {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+
+                let alpha = pixel[3] as u16;
+                if alpha != 0 && alpha != 255 {
+                    for sample in &mut pixel[..3] {
+                        let unpremultiplied = (*sample as u16 * 255 + alpha / 2) / alpha;
+                        *sample = unpremultiplied.min(255) as u8;
+                    }
+                }
+            }
+        }
+        _ => return Err(Error::InvalidData),
+    }
+
+    Ok(())
+}
@@ -3,12 +3,16 @@ use core::fmt;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use crc32fast::Hasher;
 
+use super::PngChunkInfo;
 use crate::{
     encoder::{EncodeAt, ImageEncoder},
     util::{read_checked, read_u8_array, split_to_checked},
     Error, Result,
 };
 
+// the bit of every byte of the type holding the chunk properties
+const PROPERTY_BIT: u8 = 0b0010_0000;
+
 /// The representation of a chunk making up a [`Png`][super::Png]
 #[derive(Clone, PartialEq, Eq)]
 pub struct PngChunk {
@@ -79,6 +83,50 @@ impl PngChunk {
         &self.contents
     }
 
+    /// Check if this `PngChunk` is ancillary
+    ///
+    /// Ancillary chunks aren't necessary to display the image.
+    /// Chunks that aren't ancillary are critical.
+    #[inline]
+    pub fn is_ancillary(&self) -> bool {
+        self.kind[0] & PROPERTY_BIT != 0
+    }
+
+    /// Check if this `PngChunk` is private
+    ///
+    /// Private chunks aren't defined by the PNG specification
+    /// or registered as public chunks.
+    #[inline]
+    pub fn is_private(&self) -> bool {
+        self.kind[1] & PROPERTY_BIT != 0
+    }
+
+    /// Check if the reserved bit of the type of this `PngChunk` is set
+    ///
+    /// The reserved bit must be unset in every chunk conforming
+    /// to the current PNG specification.
+    #[inline]
+    pub fn is_reserved(&self) -> bool {
+        self.kind[2] & PROPERTY_BIT != 0
+    }
+
+    /// Check if this `PngChunk` is safe to copy
+    ///
+    /// Chunks that aren't safe to copy depend on the image data and must be
+    /// removed by editors that modify critical chunks without recognizing them.
+    #[inline]
+    pub fn is_safe_to_copy(&self) -> bool {
+        self.kind[3] & PROPERTY_BIT != 0
+    }
+
+    /// Get the information about the type of this `PngChunk`
+    ///
+    /// Returns `None` if the type isn't a known public chunk type.
+    #[inline]
+    pub fn info(&self) -> Option<&'static PngChunkInfo> {
+        PngChunkInfo::from_kind(self.kind)
+    }
+
     /// Create an [encoder][crate::ImageEncoder] for this `PngChunk`
     #[inline]
     pub fn encoder(self) -> ImageEncoder<Self> {
@@ -126,3 +174,33 @@ fn compute_crc(kind: [u8; 4], contents: &[u8]) -> [u8; 4] {
 
     crc.to_be_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_properties() {
+        let idat = PngChunk::new(*b"IDAT", Bytes::new());
+        assert!(!idat.is_ancillary());
+        assert!(!idat.is_private());
+        assert!(!idat.is_reserved());
+        assert!(!idat.is_safe_to_copy());
+
+        let text = PngChunk::new(*b"tEXt", Bytes::new());
+        assert!(text.is_ancillary());
+        assert!(!text.is_private());
+        assert!(!text.is_reserved());
+        assert!(text.is_safe_to_copy());
+
+        let private = PngChunk::new(*b"prVT", Bytes::new());
+        assert!(private.is_ancillary());
+        assert!(private.is_private());
+        assert!(!private.is_reserved());
+        assert!(!private.is_safe_to_copy());
+
+        let reserved = PngChunk::new(*b"ABcd", Bytes::new());
+        assert!(!reserved.is_ancillary());
+        assert!(reserved.is_reserved());
+    }
+}
@@ -0,0 +1,149 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{read_checked, read_u8_array},
+    Error, Result,
+};
+
+/// The color type of a [`Png`][super::Png]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorType {
+    /// Every pixel is a grayscale sample
+    Grayscale,
+    /// Every pixel is a R, G, B triple
+    Rgb,
+    /// Every pixel is an index into the `PLTE` chunk
+    Indexed,
+    /// Every pixel is a grayscale sample followed by an alpha sample
+    GrayscaleAlpha,
+    /// Every pixel is a R, G, B triple followed by an alpha sample
+    Rgba,
+}
+
+impl ColorType {
+    /// Get the `ColorType` from the value stored in the `IHDR` chunk
+    ///
+    /// Returns `None` if `value` isn't a valid color type.
+    pub fn from_u8(value: u8) -> Option<ColorType> {
+        match value {
+            0 => Some(ColorType::Grayscale),
+            2 => Some(ColorType::Rgb),
+            3 => Some(ColorType::Indexed),
+            4 => Some(ColorType::GrayscaleAlpha),
+            6 => Some(ColorType::Rgba),
+            _ => None,
+        }
+    }
+
+    /// Get the value stored in the `IHDR` chunk for this `ColorType`
+    pub fn to_u8(self) -> u8 {
+        match self {
+            ColorType::Grayscale => 0,
+            ColorType::Rgb => 2,
+            ColorType::Indexed => 3,
+            ColorType::GrayscaleAlpha => 4,
+            ColorType::Rgba => 6,
+        }
+    }
+
+    /// Get the number of samples making up each pixel
+    pub fn channels(self) -> u8 {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+
+    /// Check if `bit_depth` is allowed for this `ColorType`
+    pub fn is_valid_bit_depth(self, bit_depth: u8) -> bool {
+        match self {
+            ColorType::Grayscale => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            ColorType::Indexed => matches!(bit_depth, 1 | 2 | 4 | 8),
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => {
+                matches!(bit_depth, 8 | 16)
+            }
+        }
+    }
+}
+
+/// The contents of the `IHDR` chunk of a [`Png`][super::Png]
+///
+/// The compression and filter methods aren't stored, since the
+/// specification only defines method `0` for both.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PngHeader {
+    /// The width of the image in pixels
+    pub width: u32,
+    /// The height of the image in pixels
+    pub height: u32,
+    /// The number of bits per sample or per palette index
+    pub bit_depth: u8,
+    /// The color type of the image
+    pub color_type: ColorType,
+    /// Whether the image is interlaced using the Adam7 method
+    pub interlaced: bool,
+}
+
+impl PngHeader {
+    /// Parse the contents of an `IHDR` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated or if it contains
+    /// invalid or unsupported values.
+    pub fn from_bytes(b: &Bytes) -> Result<PngHeader> {
+        let mut b = b.clone();
+
+        let width = read_checked(&mut b, |b| b.get_u32())?;
+        let height = read_checked(&mut b, |b| b.get_u32())?;
+        let [bit_depth, color_type, compression, filter, interlace] = read_u8_array(&mut b)?;
+
+        let color_type = ColorType::from_u8(color_type).ok_or(Error::InvalidData)?;
+        if width == 0
+            || height == 0
+            || !color_type.is_valid_bit_depth(bit_depth)
+            || compression != 0
+            || filter != 0
+            || interlace > 1
+        {
+            return Err(Error::InvalidData);
+        }
+
+        Ok(PngHeader {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlaced: interlace == 1,
+        })
+    }
+
+    /// Encode this `PngHeader` into the contents of an `IHDR` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(13);
+        b.put_u32(self.width);
+        b.put_u32(self.height);
+        b.put_u8(self.bit_depth);
+        b.put_u8(self.color_type.to_u8());
+        // compression method
+        b.put_u8(0);
+        // filter method
+        b.put_u8(0);
+        b.put_u8(self.interlaced as u8);
+        b.freeze()
+    }
+
+    /// Get the number of bits making up each pixel
+    #[inline]
+    pub fn bits_per_pixel(&self) -> u8 {
+        self.bit_depth * self.color_type.channels()
+    }
+
+    /// Get the length in bytes of an unfiltered row `width` pixels wide
+    pub(crate) fn row_len(&self, width: u32) -> Option<usize> {
+        let bits = (width as u64).checked_mul(self.bits_per_pixel() as u64)?;
+        ((bits + 7) / 8).try_into().ok()
+    }
+}
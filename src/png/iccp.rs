@@ -0,0 +1,169 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use crate::{util::inflate_zlib, Error, Result};
+
+// the maximum length of the profile name
+const MAX_NAME_LEN: usize = 79;
+
+/// The contents of the `iCCP` chunk of a [`Png`][super::Png]
+///
+/// The chunk holds the name of the ICC profile and the profile itself,
+/// compressed as a zlib stream.
+///
+/// See <http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.iCCP>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PngIccp {
+    name: Bytes,
+    compressed: Bytes,
+}
+
+impl PngIccp {
+    /// Construct a new `PngIccp` by compressing `profile` with the given
+    /// zlib compression `level`.
+    ///
+    /// `name` is the Latin-1 encoded profile name and `level` goes from
+    /// `0` (no compression) to `10` (best compression).
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `name` is empty, longer than 79 bytes or
+    /// contains a NUL byte.
+    pub fn new(name: Bytes, profile: &[u8], level: u8) -> Result<PngIccp> {
+        let compressed = compress_to_vec_zlib(profile, level);
+        PngIccp::from_compressed(name, Bytes::from(compressed))
+    }
+
+    /// Construct a new `PngIccp` from an already compressed profile
+    ///
+    /// `compressed` must be a zlib stream. It is stored as is.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `name` is empty, longer than 79 bytes or
+    /// contains a NUL byte.
+    pub fn from_compressed(name: Bytes, compressed: Bytes) -> Result<PngIccp> {
+        if name.is_empty() || name.len() > MAX_NAME_LEN || name.contains(&0) {
+            return Err(Error::InvalidData);
+        }
+
+        Ok(PngIccp { name, compressed })
+    }
+
+    /// Parse the contents of an `iCCP` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the profile name isn't NUL terminated, if it
+    /// is invalid or if the compression method isn't supported.
+    pub fn from_bytes(b: &Bytes) -> Result<PngIccp> {
+        let nul = b.iter().position(|&b| b == 0).ok_or(Error::Truncated)?;
+        let name = b.slice(..nul);
+
+        // match on the compression method
+        match b.get(nul + 1) {
+            Some(0) => {}
+            Some(_) => return Err(Error::InvalidData),
+            None => return Err(Error::Truncated),
+        }
+
+        PngIccp::from_compressed(name, b.slice(nul + 2..))
+    }
+
+    /// Get the Latin-1 encoded name of the profile
+    #[inline]
+    pub fn name(&self) -> &Bytes {
+        &self.name
+    }
+
+    /// Set the Latin-1 encoded name of the profile
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `name` is empty, longer than 79 bytes or
+    /// contains a NUL byte.
+    pub fn set_name(&mut self, name: Bytes) -> Result<()> {
+        *self = PngIccp::from_compressed(name, self.compressed.clone())?;
+        Ok(())
+    }
+
+    /// Get the compressed profile
+    #[inline]
+    pub fn compressed_profile(&self) -> &Bytes {
+        &self.compressed
+    }
+
+    /// Decompress the profile
+    ///
+    /// At most `limit` bytes are decompressed.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the profile is corrupted or if it is
+    /// bigger than `limit`.
+    pub fn profile(&self, limit: usize) -> Result<Bytes> {
+        inflate_zlib(&self.compressed, limit).map(Bytes::from)
+    }
+
+    /// Encode this `PngIccp` into the contents of an `iCCP` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut contents = BytesMut::with_capacity(self.name.len() + 2 + self.compressed.len());
+        // profile name written as a C string
+        contents.extend_from_slice(&self.name);
+        contents.put_u8(0);
+        // compression method
+        contents.put_u8(0);
+        // compressed profile
+        contents.extend_from_slice(&self.compressed);
+        contents.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iccp_roundtrip() {
+        let iccp = PngIccp::new(Bytes::from_static(b"sRGB"), b"profile", 6).unwrap();
+        let parsed = PngIccp::from_bytes(&iccp.to_bytes()).unwrap();
+
+        assert_eq!(parsed, iccp);
+        assert_eq!(parsed.name(), &b"sRGB"[..]);
+        assert_eq!(parsed.profile(7).unwrap(), &b"profile"[..]);
+        assert_eq!(parsed.profile(6), Err(Error::LimitExceeded));
+    }
+
+    #[test]
+    fn iccp_malformed() {
+        assert_eq!(
+            PngIccp::from_bytes(&Bytes::from_static(b"no terminator")),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            PngIccp::from_bytes(&Bytes::from_static(b"name\0")),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            PngIccp::from_bytes(&Bytes::from_static(b"name\0\x01data")),
+            Err(Error::InvalidData)
+        );
+        assert_eq!(
+            PngIccp::from_bytes(&Bytes::from_static(b"\0\0data")),
+            Err(Error::InvalidData)
+        );
+    }
+
+    #[test]
+    fn iccp_bad_name() {
+        let long = Bytes::from(alloc::vec![b'a'; 80]);
+        assert_eq!(
+            PngIccp::from_compressed(long, Bytes::new()),
+            Err(Error::InvalidData)
+        );
+        assert_eq!(
+            PngIccp::from_compressed(Bytes::from_static(b"a\0b"), Bytes::new()),
+            Err(Error::InvalidData)
+        );
+    }
+}
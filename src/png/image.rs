@@ -1,19 +1,34 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
+use core::cmp;
 
-use bytes::{Buf, BufMut, Bytes, BytesMut};
-use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
+use bytes::{Bytes, BytesMut};
+use miniz_oxide::deflate::compress_to_vec_zlib;
 
-use super::PngChunk;
+use super::{cgbi, scanlines, PngChunk, PngHeader, PngIccp, PngPass};
 use crate::{
     encoder::{EncodeAt, ImageEncoder},
-    util::read_u8_array,
+    util::{inflate_raw, inflate_zlib, read_u8_array},
     Error, ImageEXIF, ImageICC, Result,
 };
 
 // the 8 byte signature
 pub(crate) const SIGNATURE: &[u8] = &[0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
 
+// the maximum length of a chunk as defined by the specification
+const MAX_CHUNK_SIZE: usize = (1 << 31) - 1;
+// the zlib compression level used when the image data has to be compressed again
+const IMAGE_DATA_COMPRESSION_LEVEL: u8 = 6;
+// the zlib compression level used by `ImageICC::set_icc_profile`
+const ICC_COMPRESSION_LEVEL: u8 = 10;
+// the maximum size of the ICC Profile decompressed by `ImageICC::icc_profile`
+const ICC_MAX_SIZE: usize = 16 * 1024 * 1024;
+// the profile name used by `ImageICC::set_icc_profile` if there was no previous profile
+const ICC_DEFAULT_NAME: &[u8] = b"icc";
+
+pub const CHUNK_CGBI: [u8; 4] = [b'C', b'g', b'B', b'I'];
 pub const CHUNK_ICCP: [u8; 4] = [b'i', b'C', b'C', b'P'];
+pub const CHUNK_IDAT: [u8; 4] = [b'I', b'D', b'A', b'T'];
+pub const CHUNK_IHDR: [u8; 4] = [b'I', b'H', b'D', b'R'];
 pub const CHUNK_EXIF: [u8; 4] = [b'e', b'X', b'I', b'f'];
 pub const CHUNK_IEND: [u8; 4] = [b'I', b'E', b'N', b'D'];
 
@@ -54,6 +69,34 @@ impl Png {
         Ok(Png { chunks })
     }
 
+    /// Construct a new `Png` from its header and the compressed image data
+    ///
+    /// `image_data` is the zlib stream of the filtered scanlines. It is split
+    /// into `IDAT` chunks of `chunk_size` bytes, without being copied.
+    /// The `IHDR` and `IEND` chunks are generated.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `chunk_size` is `0`.
+    pub fn from_image_data(
+        header: &PngHeader,
+        image_data: Bytes,
+        chunk_size: usize,
+    ) -> Result<Png> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidData);
+        }
+
+        let mut png = Png {
+            chunks: vec![
+                PngChunk::new(CHUNK_IHDR, header.to_bytes()),
+                PngChunk::new(CHUNK_IEND, Bytes::new()),
+            ],
+        };
+        png.replace_image_data(image_data, chunk_size);
+        Ok(png)
+    }
+
     /// Get the chunks of this `Png`
     #[inline]
     pub fn chunks(&self) -> &Vec<PngChunk> {
@@ -81,6 +124,278 @@ impl Png {
         self.chunks_mut().retain(|chunk| chunk.kind() != kind);
     }
 
+    /// Get the parsed `IHDR` chunk of this `Png`
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `IHDR` chunk is missing or invalid.
+    pub fn header(&self) -> Result<PngHeader> {
+        let ihdr = self.chunk_by_type(CHUNK_IHDR).ok_or(Error::InvalidData)?;
+        PngHeader::from_bytes(ihdr.contents())
+    }
+
+    /// Decompress the image data of this `Png` into filtered scanlines
+    ///
+    /// Returns a single [`PngPass`] for non-interlaced images, or
+    /// 7 passes for Adam7 interlaced images. Every row starts
+    /// with its filter type byte.
+    ///
+    /// At most `limit` bytes are decompressed.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `IHDR` chunk is invalid, if the image data
+    /// is corrupted or truncated or if the decompressed image data would be
+    /// bigger than `limit`.
+    pub fn filtered_scanlines(&self, limit: usize) -> Result<Vec<PngPass>> {
+        let header = self.header()?;
+        if scanlines::filtered_len(&header)? > limit {
+            return Err(Error::LimitExceeded);
+        }
+
+        let data = if self.is_cgbi() {
+            inflate_raw(&self.image_data(), limit)?
+        } else {
+            inflate_zlib(&self.image_data(), limit)?
+        };
+        scanlines::split_passes(&header, Bytes::from(data))
+    }
+
+    /// Decompress the image data of this `Png` and undo the filtering
+    /// of every scanline, giving the raw rows of the image.
+    ///
+    /// See [`filtered_scanlines`][Png::filtered_scanlines] for the
+    /// meaning of `limit` and of the returned passes.
+    ///
+    /// # Errors
+    ///
+    /// This method fails for the same reasons as
+    /// [`filtered_scanlines`][Png::filtered_scanlines] or if
+    /// a row uses an unknown filter type.
+    pub fn unfiltered_scanlines(&self, limit: usize) -> Result<Vec<PngPass>> {
+        let header = self.header()?;
+        self.filtered_scanlines(limit)?
+            .iter()
+            .map(|pass| scanlines::unfilter(&header, pass))
+            .collect()
+    }
+
+    /// Get the parsed `iCCP` chunk of this `Png`
+    ///
+    /// Returns `Ok(None)` if this `Png` doesn't have an `iCCP` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `iCCP` chunk is malformed.
+    pub fn iccp(&self) -> Result<Option<PngIccp>> {
+        self.chunk_by_type(CHUNK_ICCP)
+            .map(|chunk| PngIccp::from_bytes(chunk.contents()))
+            .transpose()
+    }
+
+    /// Overwrites the pre-existing `iCCP` chunk of this `Png`.
+    ///
+    /// Removes any pre-existing `iCCP` chunk from this `Png`.
+    /// Adds a new `iCCP` chunk right after `IHDR` if `iccp` is `Some`.
+    pub fn set_iccp(&mut self, iccp: Option<PngIccp>) {
+        self.remove_chunks_by_type(CHUNK_ICCP);
+
+        if let Some(iccp) = iccp {
+            let pos = self
+                .chunks
+                .iter()
+                .position(|chunk| chunk.kind() == CHUNK_IHDR)
+                .map_or(0, |pos| pos + 1);
+
+            let chunk = PngChunk::new(CHUNK_ICCP, iccp.to_bytes());
+            self.chunks.insert(pos, chunk);
+        }
+    }
+
+    /// Check if this `Png` is an Apple CgBI image
+    ///
+    /// CgBI images are produced by the iOS build tools. They start with
+    /// a `CgBI` chunk, their image data is a raw deflate stream without the
+    /// zlib header and checksum, and their pixels are stored in BGRA order
+    /// with premultiplied alpha. Most decoders can't read them.
+    pub fn is_cgbi(&self) -> bool {
+        self.chunks
+            .first()
+            .map_or(false, |chunk| chunk.kind() == CHUNK_CGBI)
+    }
+
+    /// Convert this Apple CgBI `Png` into a standard `Png`
+    ///
+    /// The pixels are converted to RGBA order without premultiplied alpha,
+    /// the image data is compressed into a zlib stream and the `CgBI` chunk
+    /// is removed. The original filter type of every row is kept.
+    /// At most `limit` bytes of image data are decompressed.
+    ///
+    /// Does nothing if this `Png` isn't a CgBI image.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the image isn't 8 bit RGB or RGBA, if the
+    /// image data is corrupted or if it is bigger than `limit`.
+    pub fn normalize_cgbi(&mut self, limit: usize) -> Result<()> {
+        if !self.is_cgbi() {
+            return Ok(());
+        }
+
+        let header = self.header()?;
+        let passes = self.filtered_scanlines(limit)?;
+
+        let len = passes.iter().map(|pass| pass.data().len()).sum();
+        let mut data = Vec::with_capacity(len);
+        for pass in passes {
+            let filters = pass.rows().map(|row| row[0]).collect::<Vec<_>>();
+
+            let mut pass = scanlines::unfilter(&header, &pass)?;
+            let mut pixels = Vec::from(pass.data().as_ref());
+            cgbi::convert_pixels(&header, &mut pixels)?;
+            pass.data = Bytes::from(pixels);
+
+            let pass = scanlines::filter(&header, &pass, &filters)?;
+            data.extend_from_slice(pass.data());
+        }
+
+        let chunk_size = self
+            .chunks_by_type(CHUNK_IDAT)
+            .map(|chunk| chunk.contents().len())
+            .max()
+            .unwrap_or(MAX_CHUNK_SIZE)
+            .max(1);
+        let compressed = compress_to_vec_zlib(&data, IMAGE_DATA_COMPRESSION_LEVEL);
+
+        self.remove_chunks_by_type(CHUNK_CGBI);
+        self.replace_image_data(Bytes::from(compressed), chunk_size);
+        Ok(())
+    }
+
+    /// Get the compressed image data of this `Png`
+    ///
+    /// The image data is the zlib stream obtained by concatenating
+    /// the contents of every `IDAT` chunk.
+    pub fn image_data(&self) -> Bytes {
+        let idats = self
+            .chunks_by_type(CHUNK_IDAT)
+            .map(|chunk| chunk.contents())
+            .collect::<Vec<_>>();
+        match idats.as_slice() {
+            [] => return Bytes::new(),
+            [idat] => return (*idat).clone(),
+            _ => {}
+        }
+
+        let len = idats.iter().map(|idat| idat.len()).sum::<usize>();
+        let mut data = BytesMut::with_capacity(len);
+        for idat in idats {
+            data.extend_from_slice(idat);
+        }
+
+        data.freeze()
+    }
+
+    /// Merge or split the `IDAT` chunks of this `Png` so that every
+    /// chunk, except for the last one, holds `chunk_size` bytes.
+    ///
+    /// The compressed image data isn't modified, it is only
+    /// redistributed between the new `IDAT` chunks.
+    /// `chunk_size` is capped to the maximum chunk length allowed by the
+    /// PNG specification.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `chunk_size` is `0`.
+    pub fn rechunk_image_data(&mut self, chunk_size: usize) -> Result<()> {
+        if chunk_size == 0 {
+            return Err(Error::InvalidData);
+        }
+
+        let data = self.image_data();
+        self.replace_image_data(data, chunk_size);
+        Ok(())
+    }
+
+    /// Decompress the image data of this `Png` and compress it again
+    /// with the given zlib compression `level`.
+    ///
+    /// The filtered scanlines are left untouched, so the operation is lossless.
+    /// The new `IDAT` chunks keep the size of the biggest pre-existing one.
+    /// `level` goes from `0` (no compression) to `10` (best compression).
+    /// At most `limit` bytes of image data are decompressed.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if this `Png` is an Apple CgBI image, which should
+    /// be converted with [`normalize_cgbi`][Png::normalize_cgbi] first,
+    /// if the image data can't be decompressed or if it is bigger than `limit`.
+    pub fn recompress_image_data(&mut self, level: u8, limit: usize) -> Result<()> {
+        if self.is_cgbi() {
+            return Err(Error::InvalidData);
+        }
+
+        let chunk_size = match self
+            .chunks_by_type(CHUNK_IDAT)
+            .map(|chunk| chunk.contents().len())
+            .max()
+        {
+            Some(chunk_size) => cmp::max(chunk_size, 1),
+            None => return Ok(()),
+        };
+
+        let data = self.image_data();
+        let raw = inflate_zlib(&data, limit)?;
+        let compressed = compress_to_vec_zlib(&raw, level);
+
+        self.replace_image_data(Bytes::from(compressed), chunk_size);
+        Ok(())
+    }
+
+    /// Replace the `IDAT` chunks of this `Png` with new ones holding `data`,
+    /// split into chunks of `chunk_size` bytes.
+    fn replace_image_data(&mut self, mut data: Bytes, chunk_size: usize) {
+        let chunk_size = cmp::min(chunk_size, MAX_CHUNK_SIZE);
+
+        let pos = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.kind() == CHUNK_IDAT)
+            .or_else(|| {
+                self.chunks
+                    .iter()
+                    .position(|chunk| chunk.kind() == CHUNK_IEND)
+            })
+            .unwrap_or(self.chunks.len());
+        self.remove_chunks_by_type(CHUNK_IDAT);
+
+        let mut idats = Vec::with_capacity(data.len() / chunk_size + 1);
+        while !data.is_empty() {
+            let len = cmp::min(data.len(), chunk_size);
+            idats.push(PngChunk::new(CHUNK_IDAT, data.split_to(len)));
+        }
+
+        self.chunks.splice(pos..pos, idats);
+    }
+
+    /// Remove the unrecognized ancillary chunks that aren't safe to copy.
+    ///
+    /// The PNG specification requires editors that modify critical chunks,
+    /// like `IHDR`, `PLTE` or `IDAT`, to drop every unrecognized chunk that
+    /// isn't safe to copy, since it may depend on the modified image data.
+    /// The public chunk types of the [`PngChunkInfo`] registry, like `iCCP`
+    /// or `tRNS`, are recognized and kept. `known` should contain the types
+    /// of the other chunks, usually private ones, that the caller recognizes
+    /// and has already updated to match the modified image.
+    pub fn remove_unsafe_to_copy_chunks(&mut self, known: &[[u8; 4]]) {
+        self.chunks.retain(|chunk| {
+            !chunk.is_ancillary()
+                || chunk.is_safe_to_copy()
+                || chunk.info().is_some()
+                || known.contains(&chunk.kind())
+        });
+    }
+
     /// Get the total size of the `Png` once it is encoded.
     ///
     /// The size is the sum of:
@@ -125,34 +440,20 @@ impl EncodeAt for Png {
 // http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.iCCP
 impl ImageICC for Png {
     fn icc_profile(&self) -> Option<Bytes> {
-        let mut contents = self.chunk_by_type(CHUNK_ICCP)?.contents().clone();
-
-        // skip nul-terminated profile name
-        while contents.get_u8() != 0 {}
-
-        // match on the compression method
-        match contents.get_u8() {
-            0 => decompress_to_vec_zlib(&contents).ok().map(Bytes::from),
-            _ => None,
-        }
+        self.iccp().ok()??.profile(ICC_MAX_SIZE).ok()
     }
 
     fn set_icc_profile(&mut self, profile: Option<Bytes>) {
-        self.remove_chunks_by_type(CHUNK_ICCP);
+        let iccp = profile.map(|profile| {
+            // keep the name of the pre-existing profile
+            let name = match self.iccp() {
+                Ok(Some(iccp)) => iccp.name().clone(),
+                _ => Bytes::from_static(ICC_DEFAULT_NAME),
+            };
 
-        if let Some(profile) = profile {
-            let mut contents = BytesMut::with_capacity(profile.len());
-            // profile name written as a C string
-            contents.extend_from_slice(b"icc\0");
-            // compression method
-            contents.put_u8(0);
-            // compressed profile
-            let compressed = compress_to_vec_zlib(&profile, 10);
-            contents.extend_from_slice(&compressed);
-
-            let chunk = PngChunk::new(CHUNK_ICCP, contents.freeze());
-            self.chunks.insert(1, chunk);
-        }
+            PngIccp::new(name, &profile, ICC_COMPRESSION_LEVEL).expect("the profile name is valid")
+        });
+        self.set_iccp(iccp);
     }
 }
 
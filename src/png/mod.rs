@@ -1,7 +1,19 @@
-pub use self::{chunk::PngChunk, image::Png};
+pub use self::{
+    chunk::PngChunk,
+    header::{ColorType, PngHeader},
+    iccp::PngIccp,
+    image::Png,
+    registry::{PngChunkInfo, PngChunkOrdering},
+    scanlines::PngPass,
+};
 
+mod cgbi;
 mod chunk;
+mod header;
+mod iccp;
 mod image;
+mod registry;
+mod scanlines;
 
 pub(crate) fn is_png(buf: &[u8]) -> bool {
     buf.starts_with(image::SIGNATURE)
@@ -0,0 +1,138 @@
+/// The position a chunk type must have relative to the other chunks of a
+/// [`Png`][super::Png]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PngChunkOrdering {
+    /// The chunk must be the first one
+    First,
+    /// The chunk must be the last one
+    Last,
+    /// The chunk must come before `PLTE` and `IDAT`
+    BeforePlteAndIdat,
+    /// The chunk must come after `PLTE` and before `IDAT`
+    AfterPlteBeforeIdat,
+    /// The chunk must come before `IDAT`
+    BeforeIdat,
+    /// The chunk must come after `IDAT`
+    AfterIdat,
+    /// Every chunk of this type must be consecutive
+    Consecutive,
+    /// The chunk can be placed anywhere between `IHDR` and `IEND`
+    Unconstrained,
+}
+
+/// Information about a public chunk type defined by the PNG specification
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PngChunkInfo {
+    /// The type of the chunk
+    pub kind: [u8; 4],
+    /// A short description of the chunk
+    pub description: &'static str,
+    /// Whether the chunk can appear more than once
+    pub multiple: bool,
+    /// The position the chunk must have
+    pub ordering: PngChunkOrdering,
+}
+
+impl PngChunkInfo {
+    /// Get the information about every known public chunk type
+    #[inline]
+    pub fn all() -> &'static [PngChunkInfo] {
+        REGISTRY
+    }
+
+    /// Get the information about the chunk type `kind`
+    ///
+    /// Returns `None` if `kind` isn't a known public chunk type.
+    pub fn from_kind(kind: [u8; 4]) -> Option<&'static PngChunkInfo> {
+        REGISTRY.iter().find(|info| info.kind == kind)
+    }
+}
+
+const fn info(
+    kind: &[u8; 4],
+    description: &'static str,
+    multiple: bool,
+    ordering: PngChunkOrdering,
+) -> PngChunkInfo {
+    PngChunkInfo {
+        kind: *kind,
+        description,
+        multiple,
+        ordering,
+    }
+}
+
+// https://www.w3.org/TR/png-3/#5ChunkOrdering
+static REGISTRY: &[PngChunkInfo] = {
+    use PngChunkOrdering::*;
+
+    &[
+        // critical chunks
+        info(b"IHDR", "Image header", false, First),
+        info(b"PLTE", "Palette", false, BeforeIdat),
+        info(b"IDAT", "Image data", true, Consecutive),
+        info(b"IEND", "Image trailer", false, Last),
+        // transparency information
+        info(b"tRNS", "Transparency", false, AfterPlteBeforeIdat),
+        // color space information
+        info(
+            b"cHRM",
+            "Primary chromaticities and white point",
+            false,
+            BeforePlteAndIdat,
+        ),
+        info(b"gAMA", "Image gamma", false, BeforePlteAndIdat),
+        info(b"iCCP", "Embedded ICC profile", false, BeforePlteAndIdat),
+        info(b"sBIT", "Significant bits", false, BeforePlteAndIdat),
+        info(
+            b"sRGB",
+            "Standard RGB color space",
+            false,
+            BeforePlteAndIdat,
+        ),
+        info(
+            b"cICP",
+            "Coding-independent code points",
+            false,
+            BeforePlteAndIdat,
+        ),
+        info(
+            b"mDCV",
+            "Mastering display color volume",
+            false,
+            BeforePlteAndIdat,
+        ),
+        info(
+            b"cLLI",
+            "Content light level information",
+            false,
+            BeforePlteAndIdat,
+        ),
+        // textual information
+        info(b"tEXt", "Textual data", true, Unconstrained),
+        info(b"zTXt", "Compressed textual data", true, Unconstrained),
+        info(b"iTXt", "International textual data", true, Unconstrained),
+        // miscellaneous information
+        info(b"bKGD", "Background color", false, AfterPlteBeforeIdat),
+        info(b"hIST", "Image histogram", false, AfterPlteBeforeIdat),
+        info(b"pHYs", "Physical pixel dimensions", false, BeforeIdat),
+        info(b"sPLT", "Suggested palette", true, BeforeIdat),
+        info(
+            b"eXIf",
+            "Exchangeable image file format profile",
+            false,
+            Unconstrained,
+        ),
+        // time information
+        info(
+            b"tIME",
+            "Image last-modification time",
+            false,
+            Unconstrained,
+        ),
+        // animation information
+        info(b"acTL", "Animation control", false, BeforeIdat),
+        info(b"fcTL", "Frame control", true, Unconstrained),
+        info(b"fdAT", "Frame data", true, AfterIdat),
+    ]
+};
@@ -0,0 +1,306 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use super::PngHeader;
+use crate::{Error, Result};
+
+// (x start, y start, x step, y step) of each Adam7 pass
+const ADAM7: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// A pass of the decompressed image data of a [`Png`][super::Png]
+///
+/// Non-interlaced images are made of a single pass covering the whole image,
+/// while Adam7 interlaced images are made of 7 passes, some of which may be
+/// empty for very small images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PngPass {
+    width: u32,
+    height: u32,
+    row_len: usize,
+    filtered: bool,
+    pub(super) data: Bytes,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl PngPass {
+    /// Get the width of this `PngPass` in pixels
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of this `PngPass` in pixels
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Check if the rows of this `PngPass` are still filtered
+    ///
+    /// Filtered rows start with the filter type byte.
+    #[inline]
+    pub fn is_filtered(&self) -> bool {
+        self.filtered
+    }
+
+    /// Get the data of every row of this `PngPass`
+    #[inline]
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Get the length of each row, including the filter type byte
+    /// if this `PngPass` is filtered.
+    #[inline]
+    pub fn row_len(&self) -> usize {
+        self.row_len + self.filtered as usize
+    }
+
+    /// Get the rows of this `PngPass`
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        // `chunks_exact` panics with a size of 0, which happens for empty passes
+        self.data.chunks_exact(self.row_len().max(1))
+    }
+}
+
+/// Get the size of each pass making up the image described by `header`
+pub(super) fn passes(header: &PngHeader) -> Vec<(u32, u32)> {
+    if header.interlaced {
+        ADAM7
+            .iter()
+            .map(|&(x, y, dx, dy)| {
+                let width = header.width.saturating_sub(x).saturating_add(dx - 1) / dx;
+                let height = header.height.saturating_sub(y).saturating_add(dy - 1) / dy;
+                if width == 0 || height == 0 {
+                    (0, 0)
+                } else {
+                    (width, height)
+                }
+            })
+            .collect()
+    } else {
+        alloc::vec![(header.width, header.height)]
+    }
+}
+
+/// Get the expected size of the decompressed image data for `header`
+pub(super) fn filtered_len(header: &PngHeader) -> Result<usize> {
+    passes(header)
+        .into_iter()
+        .filter(|&(width, _)| width != 0)
+        .try_fold(0usize, |len, (width, height)| {
+            let row_len = header.row_len(width)?.checked_add(1)?;
+            len.checked_add(row_len.checked_mul(height as usize)?)
+        })
+        .ok_or(Error::LimitExceeded)
+}
+
+/// Split the decompressed image data into filtered passes
+pub(super) fn split_passes(header: &PngHeader, mut data: Bytes) -> Result<Vec<PngPass>> {
+    if data.len() < filtered_len(header)? {
+        return Err(Error::Truncated);
+    }
+
+    let passes = passes(header)
+        .into_iter()
+        .map(|(width, height)| {
+            let row_len = if width == 0 {
+                0
+            } else {
+                header.row_len(width).ok_or(Error::LimitExceeded)?
+            };
+            let len = if width == 0 {
+                0
+            } else {
+                (row_len + 1) * height as usize
+            };
+
+            Ok(PngPass {
+                width,
+                height,
+                row_len,
+                filtered: true,
+                data: data.split_to(len),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(passes)
+}
+
+/// Undo the filtering of every row of `pass`
+pub(super) fn unfilter(header: &PngHeader, pass: &PngPass) -> Result<PngPass> {
+    // the filters operate on whole bytes, with a minimum distance of 1 byte
+    let bpp = ((header.bits_per_pixel() as usize) / 8).max(1);
+    let row_len = pass.row_len;
+
+    let mut out = Vec::with_capacity(row_len * pass.height as usize);
+    let mut prev_start = None;
+    for row in pass.rows() {
+        let (filter, row) = (row[0], &row[1..]);
+        let start = out.len();
+        out.extend_from_slice(row);
+
+        for i in 0..row_len {
+            let a = if i >= bpp { out[start + i - bpp] } else { 0 };
+            let b = prev_start.map_or(0, |prev: usize| out[prev + i]);
+            let c = match prev_start {
+                Some(prev) if i >= bpp => out[prev + i - bpp],
+                _ => 0,
+            };
+
+            let predictor = predict(filter, a, b, c)?;
+            out[start + i] = out[start + i].wrapping_add(predictor);
+        }
+
+        prev_start = Some(start);
+    }
+
+    Ok(PngPass {
+        width: pass.width,
+        height: pass.height,
+        row_len,
+        filtered: false,
+        data: Bytes::from(out),
+    })
+}
+
+/// Filter every row of the unfiltered `pass`, using the filter type
+/// at the same index in `filters`
+pub(super) fn filter(header: &PngHeader, pass: &PngPass, filters: &[u8]) -> Result<PngPass> {
+    let bpp = ((header.bits_per_pixel() as usize) / 8).max(1);
+    let row_len = pass.row_len;
+
+    let mut out = Vec::with_capacity((row_len + 1) * pass.height as usize);
+    let mut prev = None;
+    for (row, &filter) in pass.rows().zip(filters) {
+        out.push(filter);
+
+        for (i, &x) in row.iter().enumerate() {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = prev.map_or(0, |prev: &[u8]| prev[i]);
+            let c = match prev {
+                Some(prev) if i >= bpp => prev[i - bpp],
+                _ => 0,
+            };
+
+            out.push(x.wrapping_sub(predict(filter, a, b, c)?));
+        }
+
+        prev = Some(row);
+    }
+
+    Ok(PngPass {
+        width: pass.width,
+        height: pass.height,
+        row_len,
+        filtered: true,
+        data: Bytes::from(out),
+    })
+}
+
+/// Get the value predicted by `filter` from the left (`a`), above (`b`)
+/// and upper left (`c`) bytes
+fn predict(filter: u8, a: u8, b: u8, c: u8) -> Result<u8> {
+    match filter {
+        0 => Ok(0),
+        1 => Ok(a),
+        2 => Ok(b),
+        3 => Ok(((a as u16 + b as u16) / 2) as u8),
+        4 => Ok(paeth(a, b, c)),
+        _ => Err(Error::InvalidData),
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::png::ColorType;
+
+    fn header(width: u32, height: u32, interlaced: bool) -> PngHeader {
+        PngHeader {
+            width,
+            height,
+            bit_depth: 8,
+            color_type: ColorType::Rgb,
+            interlaced,
+        }
+    }
+
+    #[test]
+    fn unfilter_every_filter() {
+        let header = header(5, 4, false);
+        let raw = (0..60u32).map(|i| (i * 37 % 251) as u8).collect::<Vec<_>>();
+
+        let raw = PngPass {
+            width: 5,
+            height: 4,
+            row_len: 15,
+            filtered: false,
+            data: Bytes::from(raw),
+        };
+
+        for kind in 0..5 {
+            let filtered = filter(&header, &raw, &[kind; 4]).unwrap();
+            assert!(filtered.rows().all(|row| row[0] == kind));
+            let passes = split_passes(&header, filtered.data().clone()).unwrap();
+            assert_eq!(passes.len(), 1);
+
+            let unfiltered = unfilter(&header, &passes[0]).unwrap();
+            assert!(!unfiltered.is_filtered());
+            assert_eq!(unfiltered, raw);
+            assert_eq!(unfiltered.rows().count(), 4);
+        }
+    }
+
+    #[test]
+    fn unfilter_bad_filter() {
+        let header = header(1, 1, false);
+        let passes = split_passes(&header, Bytes::from(vec![5, 0, 0, 0])).unwrap();
+        assert_eq!(unfilter(&header, &passes[0]), Err(Error::InvalidData));
+    }
+
+    #[test]
+    fn adam7_passes() {
+        let sizes = passes(&header(8, 8, true));
+        assert_eq!(
+            sizes,
+            [(1, 1), (1, 1), (2, 1), (2, 2), (4, 2), (4, 4), (8, 4)]
+        );
+
+        let sizes = passes(&header(1, 1, true));
+        assert_eq!(
+            sizes,
+            [(1, 1), (0, 0), (0, 0), (0, 0), (0, 0), (0, 0), (0, 0)]
+        );
+
+        let sizes = passes(&header(3, 3, true));
+        let pixels = sizes.iter().map(|(w, h)| w * h).sum::<u32>();
+        assert_eq!(pixels, 9);
+    }
+}
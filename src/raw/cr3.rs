@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use super::{Raw, RawPreviewSource};
+use crate::{
+    isobmff::{self, BOX_MOOV, BOX_UUID},
+    tiff::{Tiff, TiffEntry, TiffValue, TAG_EXIF_IFD, TAG_GPS_IFD, TYPE_LONG},
+    ImageEXIF, Result,
+};
+
+const BRAND_CR3: &[u8] = b"crx ";
+
+// the uuid of the box holding the metadata and the thumbnail, inside `moov`
+const UUID_CANON: [u8; 16] = [
+    0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0, 0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a, 0x48,
+];
+// the uuid of the box holding the preview
+const UUID_PREVIEW: [u8; 16] = [
+    0xea, 0xf4, 0x2b, 0x5e, 0x1c, 0x98, 0x4b, 0x88, 0xb9, 0xfb, 0xb7, 0xdc, 0x40, 0x6e, 0x4d, 0x16,
+];
+// the preview box is preceded by 8 unknown bytes
+const PREVIEW_BOX_OFFSET: usize = 8;
+
+// version and flags (4 bytes) + width (2 bytes) + height (2 bytes),
+// followed by the size of the JPEG
+const THMB_SIZE_OFFSET: usize = 8;
+const THMB_JPEG_OFFSET: usize = 16;
+// version and flags (4 bytes) + unknown (2 bytes) + width (2 bytes)
+// + height (2 bytes) + unknown (2 bytes), followed by the size of the JPEG
+const PRVW_SIZE_OFFSET: usize = 12;
+const PRVW_JPEG_OFFSET: usize = 16;
+
+pub(super) fn is_cr3(buf: &[u8]) -> bool {
+    buf.len() > 12 && &buf[4..8] == b"ftyp" && &buf[8..12] == BRAND_CR3
+}
+
+/// Split the ISOBMFF boxes of `b` into their type and contents
+fn boxes(mut b: Bytes) -> Result<Vec<([u8; 4], Bytes)>> {
+    let mut boxes = Vec::new();
+    while !b.is_empty() {
+        boxes.push(isobmff::read_box(&mut b)?);
+    }
+
+    Ok(boxes)
+}
+
+/// Get the contents of the `uuid` box `contents` if its uuid is `uuid`
+fn uuid_contents(contents: &Bytes, uuid: &[u8; 16]) -> Option<Bytes> {
+    if contents.starts_with(uuid) {
+        Some(contents.slice(uuid.len()..))
+    } else {
+        None
+    }
+}
+
+/// Get the JPEG of the `THMB` or `PRVW` box `contents`
+fn jpeg(contents: &Bytes, size_offset: usize, jpeg_offset: usize) -> Option<Bytes> {
+    let size = contents.get(size_offset..size_offset + 4)?;
+    let size = u32::from_be_bytes(size.try_into().unwrap()) as usize;
+
+    let end = jpeg_offset.checked_add(size)?;
+    if end > contents.len() {
+        return None;
+    }
+
+    Some(contents.slice(jpeg_offset..end))
+}
+
+pub(super) fn read(b: &Bytes) -> Result<Raw> {
+    let mut previews = Vec::new();
+    let mut cmt = [None, None, None, None];
+
+    for (kind, contents) in boxes(b.clone())? {
+        match kind {
+            BOX_MOOV => {
+                for (kind, contents) in boxes(contents)? {
+                    let canon = match uuid_contents(&contents, &UUID_CANON) {
+                        Some(canon) if kind == BOX_UUID => canon,
+                        _ => continue,
+                    };
+
+                    for (kind, contents) in boxes(canon)? {
+                        match &kind {
+                            b"THMB" => {
+                                let thumbnail = jpeg(&contents, THMB_SIZE_OFFSET, THMB_JPEG_OFFSET);
+                                if let Some(thumbnail) = thumbnail {
+                                    previews.push((RawPreviewSource::Cr3Thumbnail, thumbnail));
+                                }
+                            }
+                            [b'C', b'M', b'T', n @ b'1'..=b'4'] => {
+                                cmt[usize::from(n - b'1')] = Some(contents);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            BOX_UUID => {
+                let preview = match uuid_contents(&contents, &UUID_PREVIEW) {
+                    Some(preview) if preview.len() >= PREVIEW_BOX_OFFSET => preview,
+                    _ => continue,
+                };
+
+                for (kind, contents) in boxes(preview.slice(PREVIEW_BOX_OFFSET..))? {
+                    if &kind == b"PRVW" {
+                        if let Some(preview) = jpeg(&contents, PRVW_SIZE_OFFSET, PRVW_JPEG_OFFSET) {
+                            previews.push((RawPreviewSource::Cr3Preview, preview));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let [ifd0, exif, _makernote, gps] = cmt;
+    let exif = ifd0.and_then(|ifd0| build_exif(ifd0, exif, gps));
+    Ok(Raw::new(previews, exif))
+}
+
+/// Build the EXIF metadata from the TIFF files holding IFD0 (`CMT1`),
+/// the EXIF IFD (`CMT2`) and the GPS IFD (`CMT4`)
+fn build_exif(ifd0: Bytes, exif: Option<Bytes>, gps: Option<Bytes>) -> Option<Bytes> {
+    let ifd0 = Tiff::from_bytes(ifd0).ok()?;
+    let byte_order = ifd0.byte_order();
+    let mut tiff = Tiff::new(byte_order, ifd0.variant());
+    tiff.ifds_mut()
+        .push(ifd0.ifds().first().cloned().unwrap_or_default());
+
+    for (tag, b) in [(TAG_EXIF_IFD, exif), (TAG_GPS_IFD, gps)] {
+        let sub_tiff = match b.and_then(|b| Tiff::from_bytes(b).ok()) {
+            Some(sub_tiff) => sub_tiff,
+            None => continue,
+        };
+        let mut ifd = match sub_tiff.ifds().first() {
+            Some(ifd) => ifd.clone(),
+            None => continue,
+        };
+        if sub_tiff.byte_order() != byte_order {
+            ifd.swap_byte_order();
+        }
+
+        let entry = TiffEntry::new(tag, TYPE_LONG, TiffValue::Ifds(Vec::from([ifd])));
+        tiff.ifds_mut()[0].set_entry(entry);
+    }
+
+    tiff.exif()
+}
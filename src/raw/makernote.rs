@@ -0,0 +1,83 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use crate::tiff::{self, TiffByteOrder, TAG_JPEG_INTERCHANGE_FORMAT};
+
+// Nikon: the header is followed by a TIFF file, which the offsets are relative to
+const NIKON_HEADER: &[u8] = b"Nikon\0\x02";
+const NIKON_TIFF_OFFSET: usize = 10;
+const NIKON_TAG_PREVIEW_IFD: u16 = 0x0011;
+
+// Olympus: the header is followed by the byte order and an IFD,
+// the offsets are relative to the start of the MakerNote
+const OLYMPUS_HEADERS: [(&[u8], usize); 2] = [(b"OLYMPUS\0", 12), (b"OM SYSTEM\0\0\0", 16)];
+const OLYMPUS_TAG_CAMERA_SETTINGS: u16 = 0x2020;
+const OLYMPUS_TAG_PREVIEW_VALID: u16 = 0x0100;
+const OLYMPUS_TAG_PREVIEW_START: u16 = 0x0101;
+const OLYMPUS_TAG_PREVIEW_LENGTH: u16 = 0x0102;
+
+/// Find the previews pointed to by `makernote`
+///
+/// Unknown or corrupted MakerNotes don't have any preview.
+pub(super) fn previews(makernote: &Bytes) -> Vec<Bytes> {
+    if makernote.starts_with(NIKON_HEADER) {
+        return nikon_preview(makernote).into_iter().collect();
+    }
+
+    OLYMPUS_HEADERS
+        .iter()
+        .find(|(header, _)| makernote.starts_with(header))
+        .and_then(|&(header, ifd_offset)| {
+            olympus_preview(makernote, header.len(), ifd_offset as u64)
+        })
+        .into_iter()
+        .collect()
+}
+
+fn byte_order(b: &[u8]) -> Option<TiffByteOrder> {
+    match b.get(..2)? {
+        b"II" => Some(TiffByteOrder::LittleEndian),
+        b"MM" => Some(TiffByteOrder::BigEndian),
+        _ => None,
+    }
+}
+
+fn nikon_preview(makernote: &Bytes) -> Option<Bytes> {
+    if makernote.len() < NIKON_TIFF_OFFSET + 8 {
+        return None;
+    }
+
+    let b = makernote.slice(NIKON_TIFF_OFFSET..);
+    let byte_order = byte_order(&b)?;
+    let ifd0_offset = byte_order.read_uint(b.get(4..8)?);
+
+    let ifd0 = tiff::read_ifd(&b, byte_order, ifd0_offset).ok()?;
+    let preview_offset = ifd0.value_u64(NIKON_TAG_PREVIEW_IFD, byte_order)?;
+    let preview = tiff::read_ifd(&b, byte_order, preview_offset).ok()?;
+    preview
+        .entry(TAG_JPEG_INTERCHANGE_FORMAT)?
+        .data()?
+        .first()
+        .cloned()
+}
+
+fn olympus_preview(makernote: &Bytes, header_len: usize, ifd_offset: u64) -> Option<Bytes> {
+    let byte_order = byte_order(makernote.get(header_len..)?)?;
+
+    let ifd = tiff::read_ifd(makernote, byte_order, ifd_offset).ok()?;
+    let settings_offset = ifd.value_u64(OLYMPUS_TAG_CAMERA_SETTINGS, byte_order)?;
+    let settings = tiff::read_ifd(makernote, byte_order, settings_offset).ok()?;
+    if settings.value_u64(OLYMPUS_TAG_PREVIEW_VALID, byte_order) == Some(0) {
+        return None;
+    }
+
+    let start = settings.value_u64(OLYMPUS_TAG_PREVIEW_START, byte_order)? as usize;
+    let len = settings.value_u64(OLYMPUS_TAG_PREVIEW_LENGTH, byte_order)? as usize;
+    let end = start.checked_add(len)?;
+    if end > makernote.len() {
+        return None;
+    }
+
+    Some(makernote.slice(start..end))
+}
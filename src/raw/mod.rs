@@ -0,0 +1,228 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use crate::{
+    jpeg::{markers, Jpeg},
+    tiff::{Tiff, TiffIfd, TAG_JPEG_INTERCHANGE_FORMAT, TAG_MAKER_NOTE},
+    ImageEXIF, Result,
+};
+
+mod cr3;
+mod makernote;
+
+// the magic numbers replacing 42 in the TIFF header of Olympus ORF files
+const MAGIC_ORF: u16 = 0x4F52;
+const MAGIC_ORF_SP350: u16 = 0x5352;
+// the magic number replacing 42 in the TIFF header of Panasonic RW2 files
+const MAGIC_RW2: u16 = 0x0055;
+
+// the IFD0 tag holding the whole JPEG preview in Panasonic RW2 files
+const TAG_RW2_JPG_FROM_RAW: u16 = 0x002E;
+
+/// The location of a [`RawPreview`] in the RAW file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RawPreviewSource {
+    /// The image data of an IFD or of one of its SubIFDs
+    Ifd,
+    /// The preview pointed to by the MakerNote of a Nikon or Olympus camera
+    MakerNote,
+    /// The `PRVW` box of a CR3 file
+    Cr3Preview,
+    /// The `THMB` box of a CR3 file
+    Cr3Thumbnail,
+}
+
+/// A JPEG preview embedded in a camera RAW file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPreview {
+    source: RawPreviewSource,
+    width: u16,
+    height: u16,
+    len: usize,
+    jpeg: Jpeg,
+}
+
+/// The JPEG previews and the EXIF metadata of a camera RAW file
+///
+/// TIFF based formats, like DNG, CR2, NEF, ARW, ORF and RW2, and CR3
+/// are supported. The previews are sliced from the input [`Bytes`],
+/// without decoding the RAW image data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raw {
+    previews: Vec<RawPreview>,
+    exif: Option<Bytes>,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl RawPreview {
+    /// Parse the preview stored in `b`
+    ///
+    /// Returns `None` if `b` isn't a JPEG, or if it is a lossless one,
+    /// which is how some formats store the RAW image data itself.
+    fn from_bytes(source: RawPreviewSource, b: Bytes) -> Option<RawPreview> {
+        let len = b.len();
+        let jpeg = Jpeg::from_bytes(b).ok()?;
+
+        let frame = jpeg
+            .segments()
+            .iter()
+            .find(|segment| markers::is_frame_header(segment.marker()))?;
+        if matches!(
+            frame.marker(),
+            markers::SOF3 | markers::SOF7 | markers::SOF11 | markers::SOF15
+        ) {
+            return None;
+        }
+
+        // precision (1 byte) + height (2 bytes) + width (2 bytes)
+        let contents = frame.contents();
+        if contents.len() < 5 {
+            return None;
+        }
+
+        Some(RawPreview {
+            source,
+            height: u16::from_be_bytes([contents[1], contents[2]]),
+            width: u16::from_be_bytes([contents[3], contents[4]]),
+            len,
+            jpeg,
+        })
+    }
+
+    /// Get the location of this `RawPreview` in the RAW file
+    #[inline]
+    pub fn source(&self) -> RawPreviewSource {
+        self.source
+    }
+
+    /// Get the width of this `RawPreview`, in pixels
+    #[inline]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Get the height of this `RawPreview`, in pixels
+    #[inline]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Get the size of the JPEG in the RAW file
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Get the `Jpeg` of this `RawPreview`
+    #[inline]
+    pub fn jpeg(&self) -> &Jpeg {
+        &self.jpeg
+    }
+
+    /// Get the `Jpeg` of this `RawPreview`, consuming it
+    #[inline]
+    pub fn into_jpeg(self) -> Jpeg {
+        self.jpeg
+    }
+}
+
+impl Raw {
+    /// Find the previews and the EXIF metadata of the RAW file `b`
+    ///
+    /// Previews which aren't valid JPEGs are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// the structure of the file is corrupted or truncated.
+    pub fn from_bytes(b: Bytes) -> Result<Raw> {
+        if cr3::is_cr3(&b) {
+            return cr3::read(&b);
+        }
+
+        let tiff = Tiff::from_bytes_with_magic(b, &[MAGIC_ORF, MAGIC_ORF_SP350, MAGIC_RW2])?;
+
+        let mut previews = Vec::new();
+        for ifd in tiff.ifds() {
+            ifd_previews(ifd, &mut previews);
+        }
+
+        let makernote = tiff
+            .ifds()
+            .first()
+            .and_then(TiffIfd::exif_ifd)
+            .and_then(|exif| exif.entry(TAG_MAKER_NOTE)?.raw());
+        if let Some(makernote) = makernote {
+            for preview in makernote::previews(makernote) {
+                previews.push((RawPreviewSource::MakerNote, preview));
+            }
+        }
+
+        Ok(Raw::new(previews, tiff.exif()))
+    }
+
+    fn new(previews: Vec<(RawPreviewSource, Bytes)>, exif: Option<Bytes>) -> Raw {
+        let mut previews = previews
+            .into_iter()
+            .filter_map(|(source, b)| RawPreview::from_bytes(source, b))
+            .collect::<Vec<_>>();
+        previews.sort_by_key(|preview| {
+            let area = u32::from(preview.width) * u32::from(preview.height);
+            core::cmp::Reverse((area, preview.len))
+        });
+
+        Raw { previews, exif }
+    }
+
+    /// Get the previews, ranked by size with the largest one first
+    #[inline]
+    pub fn previews(&self) -> &Vec<RawPreview> {
+        &self.previews
+    }
+
+    /// Get the largest preview
+    #[inline]
+    pub fn largest_preview(&self) -> Option<&RawPreview> {
+        self.previews.first()
+    }
+
+    /// Get the smallest preview, usually the thumbnail
+    #[inline]
+    pub fn smallest_preview(&self) -> Option<&RawPreview> {
+        self.previews.last()
+    }
+
+    /// Get the EXIF metadata of the camera, which can be copied onto
+    /// a preview with [`ImageEXIF::set_exif`]
+    ///
+    /// The MakerNote is copied as is, so any offset it holds which
+    /// is relative to the RAW file is invalid.
+    #[inline]
+    pub fn exif(&self) -> Option<&Bytes> {
+        self.exif.as_ref()
+    }
+}
+
+/// Collect the previews stored in `ifd` and in its SubIFDs
+fn ifd_previews(ifd: &TiffIfd, previews: &mut Vec<(RawPreviewSource, Bytes)>) {
+    let thumbnail = ifd
+        .entry(TAG_JPEG_INTERCHANGE_FORMAT)
+        .and_then(|entry| entry.data()?.first());
+    // a single strip holding a whole JPEG
+    let strip = ifd
+        .strips()
+        .filter(|strips| strips.len() == 1)
+        .map(|strips| &strips[0]);
+    let rw2 = ifd
+        .entry(TAG_RW2_JPG_FROM_RAW)
+        .and_then(|entry| entry.raw());
+
+    for preview in [thumbnail, strip, rw2].into_iter().flatten() {
+        previews.push((RawPreviewSource::Ifd, preview.clone()));
+    }
+
+    for sub_ifd in ifd.sub_ifds() {
+        ifd_previews(sub_ifd, previews);
+    }
+}
@@ -0,0 +1,188 @@
+use alloc::vec::Vec;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::{RiffChunk, RiffContent, RiffPreset};
+use crate::{
+    util::{read_u8_array, trim_nul},
+    Error, Result,
+};
+
+const CHUNK_LIST: [u8; 4] = [b'L', b'I', b'S', b'T'];
+pub const LIST_INFO: [u8; 4] = [b'I', b'N', b'F', b'O'];
+
+/// The title
+pub const INFO_INAM: [u8; 4] = [b'I', b'N', b'A', b'M'];
+/// The artist
+pub const INFO_IART: [u8; 4] = [b'I', b'A', b'R', b'T'];
+/// The copyright notice
+pub const INFO_ICOP: [u8; 4] = [b'I', b'C', b'O', b'P'];
+/// A comment
+pub const INFO_ICMT: [u8; 4] = [b'I', b'C', b'M', b'T'];
+/// The software used to create the file
+pub const INFO_ISFT: [u8; 4] = [b'I', b'S', b'F', b'T'];
+/// The creation date, like `2024-01-31`
+pub const INFO_ICRD: [u8; 4] = [b'I', b'C', b'R', b'D'];
+
+/// The contents of a `LIST` chunk with a form type of `INFO`
+///
+/// Every entry is a subchunk holding a NUL-terminated string, like the
+/// title in `INAM`. The strings are stored without their NUL terminator,
+/// which is added back when encoded. The order of the entries is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiffInfo {
+    entries: Vec<([u8; 4], Bytes)>,
+}
+
+impl RiffInfo {
+    /// Construct a new empty `RiffInfo`
+    #[inline]
+    pub fn new() -> RiffInfo {
+        RiffInfo::default()
+    }
+
+    /// Parse a `LIST` chunk with a form type of `INFO`
+    ///
+    /// The chunk can either be a `List` or hold the unparsed list as
+    /// `Data`, like when parsed with [`RiffPreset::WebP`].
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `chunk` isn't an `INFO`
+    /// list or if one of its entries is a `List`, and if the list is truncated.
+    pub fn from_chunk(chunk: &RiffChunk) -> Result<RiffInfo> {
+        if chunk.id() != CHUNK_LIST {
+            return Err(Error::InvalidData);
+        }
+
+        let subchunks = match chunk.content() {
+            RiffContent::List {
+                kind: Some(LIST_INFO),
+                subchunks,
+            } => subchunks.clone(),
+            RiffContent::Data(data) => {
+                let mut data = data.clone();
+                if read_u8_array(&mut data)? != LIST_INFO {
+                    return Err(Error::InvalidData);
+                }
+
+                let mut subchunks = Vec::new();
+                while !data.is_empty() {
+                    subchunks.push(RiffChunk::from_bytes_impl(
+                        &mut data,
+                        false,
+                        &RiffPreset::WebP,
+                    )?);
+                }
+                subchunks
+            }
+            RiffContent::List { .. } => return Err(Error::InvalidData),
+        };
+
+        let entries = subchunks
+            .iter()
+            .map(|subchunk| {
+                let data = subchunk.content().data().ok_or(Error::InvalidData)?;
+                Ok((subchunk.id(), trim_nul(data)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RiffInfo { entries })
+    }
+
+    /// Encode this `RiffInfo` into a `LIST` chunk with a form type of `INFO`
+    pub fn to_chunk(&self) -> RiffChunk {
+        let subchunks = self
+            .entries
+            .iter()
+            .map(|(id, value)| {
+                let mut b = BytesMut::with_capacity(value.len() + 1);
+                b.extend_from_slice(value);
+                b.put_u8(0);
+                RiffChunk::new(*id, RiffContent::Data(b.freeze()))
+            })
+            .collect();
+
+        RiffChunk::new(
+            CHUNK_LIST,
+            RiffContent::List {
+                kind: Some(LIST_INFO),
+                subchunks,
+            },
+        )
+    }
+
+    /// Get the entries of this `RiffInfo`
+    #[inline]
+    pub fn entries(&self) -> &[([u8; 4], Bytes)] {
+        &self.entries
+    }
+
+    /// Check if this `RiffInfo` doesn't have any entry
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the value of the first entry with an id of `id`
+    pub fn get(&self, id: [u8; 4]) -> Option<&Bytes> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| *entry == id)
+            .map(|(_, value)| value)
+    }
+
+    /// Set the value of the entry with an id of `id`,
+    /// or remove it if `value` is `None`.
+    ///
+    /// The entry keeps its position if it already exists,
+    /// otherwise it is added at the end.
+    pub fn set(&mut self, id: [u8; 4], value: Option<Bytes>) {
+        let pos = self.entries.iter().position(|(entry, _)| *entry == id);
+        self.entries.retain(|(entry, _)| *entry != id);
+
+        if let Some(value) = value {
+            let pos = pos.unwrap_or(self.entries.len());
+            self.entries.insert(pos, (id, value));
+        }
+    }
+}
+
+/// Check if `chunk` is a `LIST` chunk with a form type of `INFO`,
+/// either parsed or held as `Data`
+pub(super) fn is_info(chunk: &RiffChunk) -> bool {
+    chunk.id() == CHUNK_LIST
+        && match chunk.content() {
+            RiffContent::List { kind, .. } => *kind == Some(LIST_INFO),
+            RiffContent::Data(data) => data.starts_with(&LIST_INFO),
+        }
+}
+
+/// Set the `LIST` chunk with a form type of `INFO` among `subchunks`,
+/// or remove it if `info` is `None`
+///
+/// An existing `INFO` list is replaced in place,
+/// otherwise it is added after the other subchunks.
+pub(super) fn set_info(subchunks: &mut Vec<RiffChunk>, info: Option<&RiffInfo>) {
+    let pos = subchunks.iter().position(is_info);
+    subchunks.retain(|chunk| !is_info(chunk));
+
+    if let Some(info) = info {
+        let pos = pos.unwrap_or(subchunks.len());
+        subchunks.insert(pos, info.to_chunk());
+    }
+}
+
+/// Implement `set_info` on a wrapper of a RIFF file, stored in its `$field` field
+macro_rules! impl_set_info {
+    ($field:ident) => {
+        /// Set the `LIST` chunk with a form type of `INFO`, or remove it if `info` is `None`.
+        ///
+        /// See [`RiffChunk::set_info`][crate::riff::RiffChunk::set_info].
+        #[inline]
+        pub fn set_info(&mut self, info: Option<&crate::riff::RiffInfo>) {
+            self.$field.set_root_info(info);
+        }
+    };
+}
+
+pub(crate) use impl_set_info;
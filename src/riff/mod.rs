@@ -0,0 +1,612 @@
+use alloc::{borrow::Cow, vec::Vec};
+use core::fmt;
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    util::{read_u8_array, split_to_checked},
+    Error, Result,
+};
+
+pub(crate) use self::info::impl_set_info;
+pub(crate) use self::variant::{EncodeContext, ParseContext};
+pub use self::{
+    info::{RiffInfo, INFO_IART, INFO_ICMT, INFO_ICOP, INFO_ICRD, INFO_INAM, INFO_ISFT, LIST_INFO},
+    path::{RiffChunks, RiffPath, RiffPathSegment},
+    schema::{RiffPreset, RiffSchema},
+    variant::{RiffVariant, CHUNK_DS64},
+};
+
+mod info;
+mod path;
+mod schema;
+mod variant;
+
+/// The representation of a RIFF chunk
+#[derive(Clone, PartialEq)]
+pub struct RiffChunk {
+    id: [u8; 4],
+    content: RiffContent,
+}
+
+/// The contents of a RIFF chunk
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiffContent {
+    List {
+        kind: Option<[u8; 4]>,
+        subchunks: Vec<RiffChunk>,
+    },
+    Data(Bytes),
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl RiffChunk {
+    /// Construct a new RIFF chunk.
+    #[inline]
+    pub fn new(id: [u8; 4], content: RiffContent) -> RiffChunk {
+        RiffChunk { id, content }
+    }
+
+    /// Create a new `RiffChunk` image from a Reader.
+    ///
+    /// The [`RiffVariant`] is picked from the id of the root chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or one
+    /// of the chunks is corrupted or truncated.
+    #[inline]
+    pub fn from_bytes(b: Bytes) -> Result<RiffChunk> {
+        RiffChunk::from_bytes_with_schema(b, &RiffPreset::Generic)
+    }
+
+    /// Create a new `RiffChunk` from a Reader, using `schema`
+    /// to find out which chunks contain subchunks.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or one
+    /// of the chunks is corrupted or truncated.
+    #[inline]
+    pub fn from_bytes_with_schema<S>(mut b: Bytes, schema: &S) -> Result<RiffChunk>
+    where
+        S: RiffSchema + ?Sized,
+    {
+        RiffChunk::from_bytes_impl(&mut b, true, schema)
+    }
+
+    pub(crate) fn from_bytes_impl<S>(
+        b: &mut Bytes,
+        check_riff_id: bool,
+        schema: &S,
+    ) -> Result<RiffChunk>
+    where
+        S: RiffSchema + ?Sized,
+    {
+        let mut ctx = ParseContext::default();
+        RiffChunk::parse(b, check_riff_id, schema, &mut ctx)
+    }
+
+    pub(crate) fn parse<S>(
+        b: &mut Bytes,
+        is_root: bool,
+        schema: &S,
+        ctx: &mut ParseContext,
+    ) -> Result<RiffChunk>
+    where
+        S: RiffSchema + ?Sized,
+    {
+        let id = read_u8_array(b)?;
+        if is_root {
+            let variant = RiffVariant::from_id(id).ok_or(Error::WrongSignature)?;
+            *ctx = ParseContext::new(variant);
+        }
+
+        let content = RiffContent::from_bytes(b, id, is_root, schema, ctx)?;
+        Ok(RiffChunk::new(id, content))
+    }
+
+    /// Get the id of this `RiffChunk`
+    #[inline]
+    pub fn id(&self) -> [u8; 4] {
+        self.id
+    }
+
+    /// Get the [`RiffVariant`] identified by the id of this `RiffChunk`
+    ///
+    /// Returns `None` if this `RiffChunk` can't be the root of a RIFF file.
+    #[inline]
+    pub fn variant(&self) -> Option<RiffVariant> {
+        RiffVariant::from_id(self.id)
+    }
+
+    /// Check if this `RiffChunk` or any of its subchunks is too big
+    /// to have its size stored in 32 bits.
+    ///
+    /// Files with such chunks can only be encoded correctly as `RF64`, see
+    /// [`promote_to_rf64`][RiffChunk::promote_to_rf64]. `RIFF` and `RIFX`
+    /// files with such chunks are rejected by [`try_encoder`][RiffChunk::try_encoder].
+    pub fn requires_rf64(&self) -> bool {
+        !variant::fits_u32(self.content.len())
+            || self.content.list().map_or(false, |(_, subchunks)| {
+                subchunks.iter().any(RiffChunk::requires_rf64)
+            })
+    }
+
+    /// Convert this `RIFF` file into an `RF64` file.
+    ///
+    /// A `ds64` chunk, with room for the size of every chunk that is too big
+    /// to have its size stored in 32 bits, is added as the first subchunk.
+    /// The sizes in the `ds64` chunk are computed while encoding, except for
+    /// the sample count which is left to `0`.
+    ///
+    /// Does nothing if this `RiffChunk` already is `RF64` or `BW64`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if this `RiffChunk`
+    /// isn't a `RIFF` list.
+    pub fn promote_to_rf64(&mut self) -> Result<()> {
+        match self.variant() {
+            Some(RiffVariant::Rf64 | RiffVariant::Bw64) => return Ok(()),
+            Some(RiffVariant::Riff) => {}
+            Some(RiffVariant::Rifx) | None => return Err(Error::InvalidData),
+        }
+
+        let ds64 = variant::empty_ds64(&self.content);
+        let subchunks = path::subchunks_mut(self).ok_or(Error::InvalidData)?;
+        subchunks.insert(0, RiffChunk::new(CHUNK_DS64, RiffContent::Data(ds64)));
+        self.id = RiffVariant::Rf64.id();
+        Ok(())
+    }
+
+    /// Get the content of this `RiffChunk`
+    #[inline]
+    pub fn content(&self) -> &RiffContent {
+        &self.content
+    }
+
+    /// Get a mutable reference to the content of this `RiffChunk`
+    #[inline]
+    pub fn content_mut(&mut self) -> &mut RiffContent {
+        &mut self.content
+    }
+
+    /// Get the form type of this `RiffChunk` if it is a `List`
+    fn kind(&self) -> Option<[u8; 4]> {
+        self.content.list().and_then(|(kind, _)| kind)
+    }
+
+    /// Get the subchunks of this `RiffChunk`, which is the root
+    /// list of a wrapper like [`WebP`][crate::webp::WebP]
+    ///
+    /// Returns an empty list if it holds data.
+    pub(crate) fn root_subchunks(&self) -> &Vec<RiffChunk> {
+        static NO_SUBCHUNKS: Vec<RiffChunk> = Vec::new();
+
+        match &self.content {
+            RiffContent::List { subchunks, .. } => subchunks,
+            RiffContent::Data(_) => &NO_SUBCHUNKS,
+        }
+    }
+
+    /// Get a mutable reference to the subchunks of this `RiffChunk`,
+    /// which is the root list of a wrapper like [`WebP`][crate::webp::WebP]
+    ///
+    /// If it holds data, it is replaced by an empty list first.
+    #[inline]
+    pub(crate) fn root_subchunks_mut(&mut self) -> &mut Vec<RiffChunk> {
+        root_subchunks_mut(&mut self.content)
+    }
+
+    /// Set the `LIST` chunk with a form type of `INFO` among the subchunks
+    /// of this `RiffChunk`, which is the root list of a wrapper like
+    /// [`WebP`][crate::webp::WebP]
+    #[inline]
+    pub(crate) fn set_root_info(&mut self, info: Option<&RiffInfo>) {
+        info::set_info(self.root_subchunks_mut(), info);
+    }
+
+    /// Get a depth-first iterator over this `RiffChunk` and all of its
+    /// subchunks, each one with its [`RiffPath`].
+    ///
+    /// Every chunk is yielded before its subchunks.
+    #[inline]
+    pub fn iter(&self) -> RiffChunks<'_> {
+        RiffChunks::new(self)
+    }
+
+    /// Call `f` on this `RiffChunk` and on all of its subchunks,
+    /// depth-first, with the [`RiffPath`] of each one.
+    ///
+    /// Every chunk is visited before its subchunks, so `f` can modify the
+    /// subchunks of a `List` before they are visited. The path of a
+    /// chunk is computed before `f` is called on it. The `ANMF` frames
+    /// of a WebP are encoded again if their subchunks were modified.
+    pub fn walk_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&RiffPath, &mut RiffChunk),
+    {
+        let root = RiffPath::root(self);
+        path::walk_mut(self, None, &root, &mut f);
+    }
+
+    /// Get the chunk found at `path`
+    ///
+    /// See [`RiffPath`] for the syntax. The first segment must match this `RiffChunk`.
+    /// The chunks inside of the `ANMF` frames of a WebP are parsed, and
+    /// returned as owned values.
+    ///
+    /// Returns `None` if `path` is invalid or if no chunk is found at `path`.
+    pub fn chunk_by_path(&self, path: &str) -> Option<Cow<'_, RiffChunk>> {
+        let path = path.parse::<RiffPath>().ok()?;
+        let segments = path::below_root(self, &path)?;
+        path::find(self, None, segments)
+    }
+
+    /// Get the chunk found at `path`, without looking inside of `ANMF` frames
+    pub(crate) fn tree_chunk_by_path(&self, path: &str) -> Option<&RiffChunk> {
+        match self.chunk_by_path(path)? {
+            Cow::Borrowed(chunk) => Some(chunk),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    /// Get a mutable reference to the chunk found at `path`
+    ///
+    /// The chunks inside of the `ANMF` frames of a WebP can't be borrowed,
+    /// use [`walk_mut`][RiffChunk::walk_mut] or the other path based methods
+    /// to edit them.
+    ///
+    /// Returns `None` if `path` is invalid or if no chunk is found at `path`.
+    pub fn chunk_by_path_mut(&mut self, path: &str) -> Option<&mut RiffChunk> {
+        let path = path.parse::<RiffPath>().ok()?;
+        let segments = path::below_root(self, &path)?;
+        path::find_mut(self, segments)
+    }
+
+    /// Insert `chunk` in the `List` found at `path`, at position `index`
+    /// of its subchunks.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `path` is invalid,
+    /// if no `List` is found at `path` or if `index` is greater than the
+    /// number of subchunks.
+    pub fn insert_chunk_by_path(
+        &mut self,
+        path: &str,
+        index: usize,
+        chunk: RiffChunk,
+    ) -> Result<()> {
+        let path = path.parse::<RiffPath>()?;
+        let segments = path::below_root(self, &path).ok_or(Error::InvalidData)?;
+        path::edit(self, None, segments, |subchunks| {
+            if index > subchunks.len() {
+                return Err(Error::InvalidData);
+            }
+
+            subchunks.insert(index, chunk);
+            Ok(())
+        })
+    }
+
+    /// Replace the chunk found at `path` with `chunk`, returning the old one
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `path` is invalid
+    /// or if no chunk is found at `path`.
+    pub fn replace_chunk_by_path(&mut self, path: &str, chunk: RiffChunk) -> Result<RiffChunk> {
+        let path = path.parse::<RiffPath>()?;
+        let segments = path::below_root(self, &path).ok_or(Error::InvalidData)?;
+        let (last, parent) = match segments.split_last() {
+            Some(split) => split,
+            None => return Ok(core::mem::replace(self, chunk)),
+        };
+
+        path::edit(self, None, parent, |subchunks| {
+            let pos = path::position(subchunks, last).ok_or(Error::InvalidData)?;
+            Ok(core::mem::replace(&mut subchunks[pos], chunk))
+        })
+    }
+
+    /// Remove the chunk found at `path`, returning it
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `path` is invalid,
+    /// if no chunk is found at `path` or if `path` points to this `RiffChunk`.
+    pub fn remove_chunk_by_path(&mut self, path: &str) -> Result<RiffChunk> {
+        let path = path.parse::<RiffPath>()?;
+        let segments = path::below_root(self, &path).ok_or(Error::InvalidData)?;
+        let (last, parent) = segments.split_last().ok_or(Error::InvalidData)?;
+
+        path::edit(self, None, parent, |subchunks| {
+            let pos = path::position(subchunks, last).ok_or(Error::InvalidData)?;
+            Ok(subchunks.remove(pos))
+        })
+    }
+
+    /// Get the parsed `LIST` chunk with a form type of `INFO`
+    /// found among the subchunks of this `RiffChunk`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `INFO` list is malformed or truncated.
+    pub fn info(&self) -> Result<Option<RiffInfo>> {
+        let subchunks = match self.content.list() {
+            Some((_, subchunks)) => subchunks,
+            None => return Ok(None),
+        };
+
+        subchunks
+            .iter()
+            .find(|chunk| info::is_info(chunk))
+            .map(RiffInfo::from_chunk)
+            .transpose()
+    }
+
+    /// Set the `LIST` chunk with a form type of `INFO` among the subchunks
+    /// of this `RiffChunk`, or remove it if `info` is `None`.
+    ///
+    /// An existing `INFO` list is replaced in place,
+    /// otherwise it is added after the other subchunks.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`]
+    /// if this `RiffChunk` isn't a `List`.
+    pub fn set_info(&mut self, info: Option<&RiffInfo>) -> Result<()> {
+        let subchunks = path::subchunks_mut(self).ok_or(Error::InvalidData)?;
+        info::set_info(subchunks, info);
+        Ok(())
+    }
+
+    /// Get the total size of this `RiffChunk` once it is encoded.
+    ///
+    /// The size is the sum of:
+    ///
+    /// - The chunk id (4 bytes).
+    /// - The size field (4 bytes).
+    /// - The size of the content + a single padding byte if the size is odd.
+    pub fn len(&self) -> u64 {
+        let mut len = 4 + 4 + self.content.len();
+
+        // RIFF chunks with an uneven number of bytes have an extra 0x00 padding byte
+        len += len % 2;
+
+        if self.variant().map_or(false, RiffVariant::has_ds64) {
+            len += variant::ds64_growth(&self.content);
+        }
+
+        len
+    }
+
+    /// Returns an encoder for this `RiffChunk`
+    ///
+    /// A `RIFF` or `RIFX` file with chunks bigger than 4 GiB is encoded
+    /// with [`u32::MAX`] as their size, which readers can't make sense of.
+    /// Use [`try_encoder`][RiffChunk::try_encoder] to get an error instead.
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<Self> {
+        ImageEncoder::from(self)
+    }
+
+    /// Returns an encoder for this `RiffChunk`, if all of its sizes can be encoded
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LimitExceeded`] if this `RiffChunk`
+    /// isn't an `RF64` or `BW64` file and [`requires_rf64`][RiffChunk::requires_rf64].
+    /// Call [`promote_to_rf64`][RiffChunk::promote_to_rf64] before encoding it.
+    pub fn try_encoder(self) -> Result<ImageEncoder<Self>> {
+        if !self.variant().map_or(false, RiffVariant::has_ds64) && self.requires_rf64() {
+            return Err(Error::LimitExceeded);
+        }
+
+        Ok(self.encoder())
+    }
+}
+
+fn root_subchunks_mut(content: &mut RiffContent) -> &mut Vec<RiffChunk> {
+    match content {
+        RiffContent::List { subchunks, .. } => subchunks,
+        content => {
+            *content = RiffContent::List {
+                kind: None,
+                subchunks: Vec::new(),
+            };
+            root_subchunks_mut(content)
+        }
+    }
+}
+
+impl RiffChunk {
+    pub(crate) fn encode_at_impl(
+        &self,
+        pos: &mut usize,
+        ctx: EncodeContext,
+        is_root: bool,
+    ) -> Option<Bytes> {
+        match pos {
+            0 => {
+                let mut vec = BytesMut::with_capacity(8);
+                vec.extend_from_slice(&self.id);
+                ctx.put_size(&mut vec, self.content.len(), is_root);
+
+                Some(vec.freeze())
+            }
+            _ => {
+                *pos -= 1;
+                self.content.encode_at_impl(pos, ctx, is_root)
+            }
+        }
+    }
+}
+
+impl EncodeAt for RiffChunk {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        self.encode_at_impl(pos, EncodeContext::new(self), true)
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.len()).unwrap_or(usize::MAX)
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl RiffContent {
+    fn from_bytes<S>(
+        b: &mut Bytes,
+        id: [u8; 4],
+        is_root: bool,
+        schema: &S,
+        ctx: &mut ParseContext,
+    ) -> Result<RiffContent>
+    where
+        S: RiffSchema + ?Sized,
+    {
+        let len = ctx.read_size(b, id, is_root)?;
+        let len = usize::try_from(len).map_err(|_| Error::Truncated)?;
+        let mut content = split_to_checked(b, len)?;
+
+        if schema.has_subchunks(id) {
+            let kind = if schema.has_kind(id) {
+                Some(read_u8_array(&mut content)?)
+            } else {
+                None
+            };
+
+            let mut subchunks = Vec::with_capacity(8);
+            while !content.is_empty() {
+                let subchunk = RiffChunk::parse(&mut content, false, schema, ctx)?;
+                ctx.visit(&subchunk)?;
+                subchunks.push(subchunk);
+            }
+
+            Ok(RiffContent::List { kind, subchunks })
+        } else {
+            // RIFF chunks with an uneven number of bytes have an extra 0x00 padding byte
+            if len % 2 != 0 {
+                ctx.skip_padding(b)?;
+            }
+
+            Ok(RiffContent::Data(content))
+        }
+    }
+
+    /// Get the total size of this `RiffContent` once it is encoded.
+    ///
+    /// If this `RiffContent` is a `List` the size is the sum of:
+    ///
+    /// - The kind (4 bytes) if this `List` has a kind.
+    /// - The sum of the size of every `subchunk`.
+    ///
+    /// If this `RiffContent` is `Data` the size is the length of the data.
+    pub fn len(&self) -> u64 {
+        match self {
+            RiffContent::List { kind, subchunks } => {
+                let mut len = 0;
+
+                if kind.is_some() {
+                    len += 4;
+                }
+
+                len += subchunks.iter().map(|subchunk| subchunk.len()).sum::<u64>();
+                len
+            }
+            RiffContent::Data(data) => data.len() as u64,
+        }
+    }
+
+    /// Get `kind` and `subchunks` of this `RiffContent` if it is a `List`.
+    ///
+    /// Returns `None` if it is `Data`.
+    pub fn list(&self) -> Option<(Option<[u8; 4]>, &Vec<RiffChunk>)> {
+        match self {
+            RiffContent::List { kind, subchunks } => Some((*kind, subchunks)),
+            RiffContent::Data(_) => None,
+        }
+    }
+
+    /// Get the `data` of this `RiffContent` if it is `Data`.
+    ///
+    /// Returns `None` if it is a `List`.
+    pub fn data(&self) -> Option<&Bytes> {
+        match self {
+            RiffContent::List { .. } => None,
+            RiffContent::Data(data) => Some(data),
+        }
+    }
+
+    /// Returns an encoder for this `RiffContent`
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<Self> {
+        ImageEncoder::from(self)
+    }
+}
+
+impl RiffContent {
+    fn encode_at_impl(&self, pos: &mut usize, ctx: EncodeContext, is_root: bool) -> Option<Bytes> {
+        match self {
+            RiffContent::List { kind, subchunks } => {
+                if let Some(kind) = kind {
+                    if *pos == 0 {
+                        return Some(Bytes::copy_from_slice(kind.as_ref()));
+                    }
+
+                    *pos -= 1;
+                };
+
+                let mut ds64 = is_root && ctx.has_ds64();
+                for chunk in subchunks {
+                    // the ds64 chunk is built from the tree being encoded, in a single piece
+                    if ds64 && chunk.id == CHUNK_DS64 {
+                        ds64 = false;
+                        if let Some(current) = chunk.content.data() {
+                            if *pos == 0 {
+                                return Some(variant::encode_ds64(self, current));
+                            }
+
+                            *pos -= 1;
+                            continue;
+                        }
+                    }
+
+                    let bytes = chunk.encode_at_impl(pos, ctx, false);
+                    if bytes.is_some() {
+                        return bytes;
+                    }
+                }
+
+                None
+            }
+            RiffContent::Data(data) => match pos {
+                0 => Some(data.clone()),
+                1 if data.len() % 2 == 1 => Some(Bytes::from_static(&[0x00])),
+                _ => {
+                    *pos -= 1 + data.len() % 2;
+                    None
+                }
+            },
+        }
+    }
+}
+
+impl EncodeAt for RiffContent {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        self.encode_at_impl(pos, EncodeContext::default(), false)
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.len()).unwrap_or(usize::MAX)
+    }
+}
+
+impl fmt::Debug for RiffChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RiffChunk").field("id", &self.id).finish()
+    }
+}
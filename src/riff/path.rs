@@ -0,0 +1,528 @@
+use alloc::{borrow::Cow, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use super::{RiffChunk, RiffContent};
+use crate::{
+    webp::{WebPFrame, CHUNK_ANMF},
+    Error, Result,
+};
+
+const KIND_WEBP: [u8; 4] = [b'W', b'E', b'B', b'P'];
+
+/// The location of a [`RiffChunk`] inside of a RIFF tree
+///
+/// A path is made of segments separated by `/`, starting with the root
+/// chunk. Every segment is a 4 character chunk id, optionally followed by
+/// the form type of the list in parentheses and by the 0-based index
+/// of the chunk among the siblings with the same id and form type in
+/// square brackets. For example `RIFF(AVI )/LIST(hdrl)/avih` or
+/// `RIFF(WEBP)/ANMF[2]/VP8L`.
+///
+/// The form type of the root chunk can also be written as its own segment,
+/// like `WEBP` in `RIFF/WEBP/VP8L`. The subchunks of the `ANMF` frames
+/// of a WebP are found after their frame header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RiffPath {
+    segments: Vec<RiffPathSegment>,
+}
+
+/// A segment of a [`RiffPath`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RiffPathSegment {
+    /// The id of the chunk
+    pub id: [u8; 4],
+    /// The form type of the list, or `None` to match any chunk with `id`
+    pub kind: Option<[u8; 4]>,
+    /// The index of the chunk among the siblings matching `id` and `kind`
+    pub index: usize,
+}
+
+impl RiffPath {
+    /// Construct a new `RiffPath` from its `segments`
+    #[inline]
+    pub fn new(segments: Vec<RiffPathSegment>) -> RiffPath {
+        RiffPath { segments }
+    }
+
+    /// Get the segments of this `RiffPath`
+    #[inline]
+    pub fn segments(&self) -> &[RiffPathSegment] {
+        &self.segments
+    }
+
+    /// Get the path of the root chunk `chunk`
+    pub(super) fn root(chunk: &RiffChunk) -> RiffPath {
+        RiffPath::new(alloc::vec![RiffPathSegment {
+            id: chunk.id(),
+            kind: chunk.kind(),
+            index: 0,
+        }])
+    }
+
+    /// Get the path of the chunk at `pos` in `siblings`, a list found at this path
+    fn join(&self, siblings: &[RiffChunk], pos: usize) -> RiffPath {
+        let chunk = &siblings[pos];
+        let segment = RiffPathSegment {
+            id: chunk.id(),
+            kind: chunk.kind(),
+            index: siblings[..pos]
+                .iter()
+                .filter(|sibling| sibling.id() == chunk.id() && sibling.kind() == chunk.kind())
+                .count(),
+        };
+
+        let mut segments = Vec::with_capacity(self.segments.len() + 1);
+        segments.extend_from_slice(&self.segments);
+        segments.push(segment);
+        RiffPath::new(segments)
+    }
+}
+
+impl RiffPathSegment {
+    fn matches(&self, chunk: &RiffChunk) -> bool {
+        self.id == chunk.id() && self.kind.map_or(true, |kind| chunk.kind() == Some(kind))
+    }
+}
+
+impl FromStr for RiffPath {
+    type Err = Error;
+
+    /// Parse a `RiffPath`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if the syntax is invalid.
+    fn from_str(s: &str) -> Result<RiffPath> {
+        s.split('/')
+            .map(RiffPathSegment::from_str)
+            .collect::<Result<Vec<_>>>()
+            .map(RiffPath::new)
+    }
+}
+
+impl FromStr for RiffPathSegment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RiffPathSegment> {
+        // ids are made of bytes, written as Latin-1 characters
+        let b = s
+            .chars()
+            .map(|c| u8::try_from(c).map_err(|_| Error::InvalidData))
+            .collect::<Result<Vec<u8>>>()?;
+
+        let (id, mut rest) = split_id(&b)?;
+
+        let mut kind = None;
+        if let Some(b'(') = rest.first() {
+            let (k, r) = split_id(&rest[1..])?;
+            rest = r.strip_prefix(b")").ok_or(Error::InvalidData)?;
+            kind = Some(k);
+        }
+
+        let mut index = 0;
+        if let Some(b'[') = rest.first() {
+            let end = rest
+                .iter()
+                .position(|&c| c == b']')
+                .ok_or(Error::InvalidData)?;
+            let digits = core::str::from_utf8(&rest[1..end]).map_err(|_| Error::InvalidData)?;
+            index = digits.parse().map_err(|_| Error::InvalidData)?;
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            return Err(Error::InvalidData);
+        }
+
+        Ok(RiffPathSegment { id, kind, index })
+    }
+}
+
+fn split_id(b: &[u8]) -> Result<([u8; 4], &[u8])> {
+    let id = b.get(..4).ok_or(Error::InvalidData)?;
+    Ok((id.try_into().unwrap(), &b[4..]))
+}
+
+impl fmt::Display for RiffPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i != 0 {
+                f.write_str("/")?;
+            }
+            fmt::Display::fmt(segment, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RiffPathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_id(f, self.id)?;
+        if let Some(kind) = self.kind {
+            f.write_str("(")?;
+            write_id(f, kind)?;
+            f.write_str(")")?;
+        }
+        if self.index != 0 {
+            write!(f, "[{}]", self.index)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_id(f: &mut fmt::Formatter<'_>, id: [u8; 4]) -> fmt::Result {
+    id.iter()
+        .try_for_each(|&b| fmt::Write::write_char(f, char::from(b)))
+}
+
+/// A depth-first iterator over a RIFF tree
+///
+/// Created by [`RiffChunk::iter`]. Every chunk is yielded
+/// together with its [`RiffPath`], before its subchunks.
+///
+/// The chunks inside of the `ANMF` frames of a WebP are parsed
+/// while iterating, and are yielded as owned values.
+#[derive(Debug, Clone)]
+pub struct RiffChunks<'a> {
+    root: Option<&'a RiffChunk>,
+    stack: Vec<Level<'a>>,
+}
+
+/// The subchunks of a list or a frame being iterated over
+#[derive(Debug, Clone)]
+struct Level<'a> {
+    path: RiffPath,
+    kind: Option<[u8; 4]>,
+    subchunks: Cow<'a, [RiffChunk]>,
+    pos: usize,
+}
+
+impl<'a> RiffChunks<'a> {
+    pub(super) fn new(root: &'a RiffChunk) -> RiffChunks<'a> {
+        RiffChunks {
+            root: Some(root),
+            stack: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        path: &RiffPath,
+        parent_kind: Option<[u8; 4]>,
+        chunk: &RiffChunk,
+        subchunks: Option<Cow<'a, [RiffChunk]>>,
+    ) {
+        let (kind, subchunks) = match frame(parent_kind, chunk) {
+            Some(frame) => (None, Cow::Owned(frame.chunks().clone())),
+            None => match subchunks {
+                Some(subchunks) => (chunk.kind(), subchunks),
+                None => return,
+            },
+        };
+        self.stack.push(Level {
+            path: path.clone(),
+            kind,
+            subchunks,
+            pos: 0,
+        });
+    }
+}
+
+impl<'a> Iterator for RiffChunks<'a> {
+    type Item = (RiffPath, Cow<'a, RiffChunk>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            let path = RiffPath::root(root);
+            let subchunks = root
+                .content()
+                .list()
+                .map(|(_, subchunks)| Cow::Borrowed(subchunks.as_slice()));
+            self.push(&path, None, root, subchunks);
+            return Some((path, Cow::Borrowed(root)));
+        }
+
+        loop {
+            let level = self.stack.last_mut()?;
+            if level.pos == level.subchunks.len() {
+                self.stack.pop();
+                continue;
+            }
+
+            let path = level.path.join(&level.subchunks, level.pos);
+            let (chunk, subchunks) = match &level.subchunks {
+                Cow::Borrowed(siblings) => {
+                    let siblings: &'a [RiffChunk] = siblings;
+                    let chunk = &siblings[level.pos];
+                    let subchunks = chunk
+                        .content()
+                        .list()
+                        .map(|(_, subchunks)| Cow::Borrowed(subchunks.as_slice()));
+                    (Cow::Borrowed(chunk), subchunks)
+                }
+                Cow::Owned(siblings) => {
+                    let chunk = siblings[level.pos].clone();
+                    let subchunks = chunk
+                        .content()
+                        .list()
+                        .map(|(_, subchunks)| Cow::Owned(subchunks.clone()));
+                    (Cow::Owned(chunk), subchunks)
+                }
+            };
+            let kind = level.kind;
+            level.pos += 1;
+
+            self.push(&path, kind, &chunk, subchunks);
+            return Some((path, chunk));
+        }
+    }
+}
+
+/// Parse the `ANMF` frame of a WebP, found among the subchunks of
+/// a list with a form type of `parent_kind`
+///
+/// Returns `None` if `chunk` isn't a frame.
+fn frame(parent_kind: Option<[u8; 4]>, chunk: &RiffChunk) -> Option<WebPFrame> {
+    if parent_kind != Some(KIND_WEBP) || chunk.id() != CHUNK_ANMF {
+        return None;
+    }
+
+    WebPFrame::from_bytes(chunk.content().data()?).ok()
+}
+
+pub(super) fn walk_mut<F>(
+    chunk: &mut RiffChunk,
+    parent_kind: Option<[u8; 4]>,
+    path: &RiffPath,
+    f: &mut F,
+) where
+    F: FnMut(&RiffPath, &mut RiffChunk),
+{
+    f(path, chunk);
+
+    // frames are only written back if they were modified
+    if let Some(mut frame) = frame(parent_kind, chunk) {
+        let original = frame.clone();
+        walk_list_mut(frame.chunks_mut(), None, path, f);
+        if frame != original {
+            // the fields of a parsed frame can always be encoded again
+            if let Ok(encoded) = frame.to_chunk() {
+                *chunk = encoded;
+            }
+        }
+        return;
+    }
+
+    let kind = chunk.kind();
+    if let RiffContent::List { subchunks, .. } = chunk.content_mut() {
+        walk_list_mut(subchunks, kind, path, f);
+    }
+}
+
+fn walk_list_mut<F>(subchunks: &mut [RiffChunk], kind: Option<[u8; 4]>, path: &RiffPath, f: &mut F)
+where
+    F: FnMut(&RiffPath, &mut RiffChunk),
+{
+    for pos in 0..subchunks.len() {
+        let path = path.join(subchunks, pos);
+        walk_mut(&mut subchunks[pos], kind, &path, f);
+    }
+}
+
+/// Get the segments of `path` following the one matching `root`
+///
+/// The form type of the root chunk can also be written as its own
+/// segment, like `WEBP` in `RIFF/WEBP/VP8L`.
+///
+/// Returns `None` if the first segment doesn't match `root`.
+pub(super) fn below_root<'p>(
+    root: &RiffChunk,
+    path: &'p RiffPath,
+) -> Option<&'p [RiffPathSegment]> {
+    let (first, mut segments) = path.segments.split_first()?;
+    if !first.matches(root) || first.index != 0 {
+        return None;
+    }
+
+    if let (None, Some(kind), Some(next)) = (first.kind, root.kind(), segments.first()) {
+        if next.id == kind && next.kind.is_none() && next.index == 0 {
+            segments = &segments[1..];
+        }
+    }
+    Some(segments)
+}
+
+/// Get the position of the chunk matching `segment` in `subchunks`
+pub(super) fn position(subchunks: &[RiffChunk], segment: &RiffPathSegment) -> Option<usize> {
+    subchunks
+        .iter()
+        .enumerate()
+        .filter(|(_, subchunk)| segment.matches(subchunk))
+        .nth(segment.index)
+        .map(|(pos, _)| pos)
+}
+
+/// Find the chunk found by following `segments` from `chunk`, found among
+/// the subchunks of a list with a form type of `parent_kind`
+///
+/// The chunks inside of `ANMF` frames are parsed, and returned as owned values.
+pub(super) fn find<'a>(
+    chunk: &'a RiffChunk,
+    parent_kind: Option<[u8; 4]>,
+    segments: &[RiffPathSegment],
+) -> Option<Cow<'a, RiffChunk>> {
+    let (segment, segments) = match segments.split_first() {
+        Some(split) => split,
+        None => return Some(Cow::Borrowed(chunk)),
+    };
+
+    if let Some(frame) = frame(parent_kind, chunk) {
+        let pos = position(frame.chunks(), segment)?;
+        let found = find(&frame.chunks()[pos], None, segments)?;
+        return Some(Cow::Owned(found.into_owned()));
+    }
+
+    let (_, subchunks) = chunk.content().list()?;
+    let pos = position(subchunks, segment)?;
+    find(&subchunks[pos], chunk.kind(), segments)
+}
+
+/// Find the chunk found by following `segments` from `chunk`,
+/// without looking inside of `ANMF` frames
+pub(super) fn find_mut<'a>(
+    chunk: &'a mut RiffChunk,
+    segments: &[RiffPathSegment],
+) -> Option<&'a mut RiffChunk> {
+    match segments.split_first() {
+        Some((segment, segments)) => {
+            let subchunks = subchunks_mut(chunk)?;
+            let pos = position(subchunks, segment)?;
+            find_mut(&mut subchunks[pos], segments)
+        }
+        None => Some(chunk),
+    }
+}
+
+/// Call `f` on the subchunks of the chunk found by following `segments`
+/// from `chunk`, found among the subchunks of a list with a form type
+/// of `parent_kind`
+///
+/// The `ANMF` frames walked through are parsed, and written back if `f` succeeds.
+///
+/// # Errors
+///
+/// This method fails with [`Error::InvalidData`] if no list is found,
+/// or with the error returned by `f`.
+pub(super) fn edit<F, R>(
+    chunk: &mut RiffChunk,
+    parent_kind: Option<[u8; 4]>,
+    segments: &[RiffPathSegment],
+    f: F,
+) -> Result<R>
+where
+    F: FnOnce(&mut Vec<RiffChunk>) -> Result<R>,
+{
+    if let Some(mut frame) = frame(parent_kind, chunk) {
+        let result = edit_list(frame.chunks_mut(), None, segments, f)?;
+        *chunk = frame.to_chunk()?;
+        return Ok(result);
+    }
+
+    let kind = chunk.kind();
+    let subchunks = subchunks_mut(chunk).ok_or(Error::InvalidData)?;
+    edit_list(subchunks, kind, segments, f)
+}
+
+fn edit_list<F, R>(
+    subchunks: &mut Vec<RiffChunk>,
+    kind: Option<[u8; 4]>,
+    segments: &[RiffPathSegment],
+    f: F,
+) -> Result<R>
+where
+    F: FnOnce(&mut Vec<RiffChunk>) -> Result<R>,
+{
+    match segments.split_first() {
+        Some((segment, segments)) => {
+            let pos = position(subchunks, segment).ok_or(Error::InvalidData)?;
+            edit(&mut subchunks[pos], kind, segments, f)
+        }
+        None => f(subchunks),
+    }
+}
+
+pub(super) fn subchunks_mut(chunk: &mut RiffChunk) -> Option<&mut Vec<RiffChunk>> {
+    match chunk.content_mut() {
+        RiffContent::List { subchunks, .. } => Some(subchunks),
+        RiffContent::Data(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn path_roundtrip() {
+        for path in [
+            "RIFF(WEBP)/ANMF[2]",
+            "RIFF(AVI )/LIST(hdrl)/avih",
+            "LIST/ICON[1]",
+        ] {
+            let parsed = path.parse::<RiffPath>().unwrap();
+            assert_eq!(parsed.to_string(), path);
+        }
+    }
+
+    #[test]
+    fn path_root_kind() {
+        let vp8l = RiffChunk::new(*b"VP8L", RiffContent::Data(Bytes::from_static(b"data")));
+        let root = RiffChunk::new(
+            *b"RIFF",
+            RiffContent::List {
+                kind: Some(KIND_WEBP),
+                subchunks: alloc::vec![vp8l.clone()],
+            },
+        );
+
+        for path in ["RIFF/WEBP/VP8L", "RIFF(WEBP)/VP8L"] {
+            assert_eq!(root.chunk_by_path(path).as_deref(), Some(&vp8l), "{path}");
+        }
+        let path = "RIFF/WEBP/VP8L".parse::<RiffPath>().unwrap();
+        assert_eq!(path.to_string(), "RIFF/WEBP/VP8L");
+    }
+
+    #[test]
+    fn path_segments() {
+        let path = "RIFF(AVI )/LIST(hdrl)[1]".parse::<RiffPath>().unwrap();
+        assert_eq!(
+            path.segments()[1],
+            RiffPathSegment {
+                id: *b"LIST",
+                kind: Some(*b"hdrl"),
+                index: 1
+            }
+        );
+    }
+
+    #[test]
+    fn path_invalid() {
+        for path in [
+            "",
+            "RIF",
+            "RIFF/",
+            "LIST(hdr)",
+            "LIST(hdrl",
+            "ANMF[x]",
+            "ANMF[1]x",
+        ] {
+            assert_eq!(path.parse::<RiffPath>(), Err(Error::InvalidData), "{path}");
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::RiffVariant;
+
+/// Describes which chunks of a RIFF based format contain subchunks
+///
+/// The chunks with an id for which [`has_subchunks`][RiffSchema::has_subchunks]
+/// returns `true` are parsed as a [`RiffContent::List`][super::RiffContent::List],
+/// every other chunk is parsed as [`RiffContent::Data`][super::RiffContent::Data].
+pub trait RiffSchema {
+    /// Check if the chunks with `id` contain subchunks
+    fn has_subchunks(&self, id: [u8; 4]) -> bool;
+
+    /// Check if the chunks with `id` start with a 4 byte form type,
+    /// followed by the subchunks
+    ///
+    /// Only called if [`has_subchunks`][RiffSchema::has_subchunks] returned `true`.
+    fn has_kind(&self, id: [u8; 4]) -> bool;
+}
+
+/// The [`RiffSchema`] of some well known RIFF based formats
+///
+/// The root chunk of every [`RiffVariant`] always contains subchunks
+/// and has a form type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RiffPreset {
+    /// `LIST` chunks with a form type and `seqt` chunks without one,
+    /// as parsed by [`RiffChunk::from_bytes`][super::RiffChunk::from_bytes]
+    #[default]
+    Generic,
+    /// WebP images, where only the root chunk contains subchunks
+    WebP,
+    /// WAVE audio files
+    Wav,
+    /// AVI videos
+    Avi,
+    /// Windows animated cursors
+    Ani,
+}
+
+impl RiffSchema for RiffPreset {
+    fn has_subchunks(&self, id: [u8; 4]) -> bool {
+        if RiffVariant::from_id(id).is_some() {
+            return true;
+        }
+
+        match self {
+            RiffPreset::Generic => matches!(&id, b"LIST" | b"seqt"),
+            RiffPreset::WebP => false,
+            RiffPreset::Wav | RiffPreset::Avi | RiffPreset::Ani => &id == b"LIST",
+        }
+    }
+
+    fn has_kind(&self, id: [u8; 4]) -> bool {
+        RiffVariant::from_id(id).is_some() || &id == b"LIST"
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{RiffChunk, RiffContent};
+use crate::{
+    util::{read_checked, read_u8_array},
+    Error, Result,
+};
+
+pub const CHUNK_DS64: [u8; 4] = [b'd', b's', b'6', b'4'];
+const CHUNK_DATA: [u8; 4] = [b'd', b'a', b't', b'a'];
+
+// the size written in place of sizes that don't fit in 32 bits
+const SIZE_PLACEHOLDER: u32 = u32::MAX;
+// riff size (8 bytes) + data size (8 bytes) + sample count (8 bytes) + table length (4 bytes)
+const DS64_HEADER_SIZE: usize = 8 + 8 + 8 + 4;
+// chunk id (4 bytes) + chunk size (8 bytes)
+const DS64_ENTRY_SIZE: usize = 4 + 8;
+
+/// The variants of the RIFF container, identified by the id of the root chunk
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RiffVariant {
+    /// `RIFF`, with little-endian 32-bit sizes
+    Riff,
+    /// `RIFX`, with big-endian 32-bit sizes
+    Rifx,
+    /// `RF64`, with the 64-bit sizes stored in the `ds64` chunk
+    Rf64,
+    /// `BW64`, the ITU-R BS.2088 name for `RF64`
+    Bw64,
+}
+
+impl RiffVariant {
+    /// Get the `RiffVariant` with `id` as the id of the root chunk
+    ///
+    /// Returns `None` if `id` doesn't identify a known variant.
+    pub fn from_id(id: [u8; 4]) -> Option<RiffVariant> {
+        match &id {
+            b"RIFF" => Some(RiffVariant::Riff),
+            b"RIFX" => Some(RiffVariant::Rifx),
+            b"RF64" => Some(RiffVariant::Rf64),
+            b"BW64" => Some(RiffVariant::Bw64),
+            _ => None,
+        }
+    }
+
+    /// Get the id of the root chunk of this `RiffVariant`
+    pub fn id(self) -> [u8; 4] {
+        match self {
+            RiffVariant::Riff => *b"RIFF",
+            RiffVariant::Rifx => *b"RIFX",
+            RiffVariant::Rf64 => *b"RF64",
+            RiffVariant::Bw64 => *b"BW64",
+        }
+    }
+
+    /// Check if the sizes are stored in big-endian byte order
+    #[inline]
+    pub fn is_big_endian(self) -> bool {
+        self == RiffVariant::Rifx
+    }
+
+    /// Check if sizes that don't fit in 32 bits are stored in the `ds64` chunk
+    #[inline]
+    pub fn has_ds64(self) -> bool {
+        matches!(self, RiffVariant::Rf64 | RiffVariant::Bw64)
+    }
+}
+
+/// The state needed to parse a RIFF tree
+#[derive(Debug, Default)]
+pub(crate) struct ParseContext {
+    big_endian: bool,
+    rf64: bool,
+    iff: bool,
+    ds64: Option<Ds64>,
+}
+
+#[derive(Debug)]
+struct Ds64 {
+    data_size: u64,
+    table: Vec<([u8; 4], u64)>,
+}
+
+impl ParseContext {
+    pub(super) fn new(variant: RiffVariant) -> ParseContext {
+        ParseContext {
+            big_endian: variant.is_big_endian(),
+            rf64: variant.has_ds64(),
+            iff: false,
+            ds64: None,
+        }
+    }
+
+    /// Get the context for parsing an EA IFF 85 tree, with big-endian sizes
+    pub(crate) fn iff() -> ParseContext {
+        ParseContext {
+            big_endian: true,
+            rf64: false,
+            iff: true,
+            ds64: None,
+        }
+    }
+
+    /// Read the size of the chunk with `id`, looking up
+    /// the real size in the `ds64` chunk if needed.
+    pub(super) fn read_size(&mut self, b: &mut Bytes, id: [u8; 4], is_root: bool) -> Result<u64> {
+        let size = if self.big_endian {
+            read_checked(b, |b| b.get_u32())?
+        } else {
+            read_checked(b, |b| b.get_u32_le())?
+        };
+        if !self.rf64 || size != SIZE_PLACEHOLDER {
+            return Ok(size.into());
+        }
+
+        // the root chunk is read before the ds64 chunk, and always spans the whole file
+        if is_root {
+            return Ok(b.len() as u64);
+        }
+
+        let ds64 = self.ds64.as_mut().ok_or(Error::InvalidData)?;
+        if id == CHUNK_DATA {
+            return Ok(ds64.data_size);
+        }
+
+        let pos = ds64
+            .table
+            .iter()
+            .position(|(entry, _)| *entry == id)
+            .ok_or(Error::InvalidData)?;
+        Ok(ds64.table.remove(pos).1)
+    }
+
+    /// Skip the padding byte following a chunk with an uneven number of bytes
+    pub(super) fn skip_padding(&self, b: &mut Bytes) -> Result<()> {
+        // the padding of the last chunk is often missing from IFF files
+        if self.iff && b.is_empty() {
+            return Ok(());
+        }
+
+        read_checked(b, |b| b.get_u8())?;
+        Ok(())
+    }
+
+    /// Take note of the sizes stored in `chunk` if it is the `ds64` chunk
+    pub(super) fn visit(&mut self, chunk: &RiffChunk) -> Result<()> {
+        if !self.rf64 || self.ds64.is_some() || chunk.id() != CHUNK_DS64 {
+            return Ok(());
+        }
+
+        let mut b = chunk.content().data().ok_or(Error::InvalidData)?.clone();
+        let _riff_size = read_checked(&mut b, |b| b.get_u64_le())?;
+        let data_size = read_checked(&mut b, |b| b.get_u64_le())?;
+        let _sample_count = read_checked(&mut b, |b| b.get_u64_le())?;
+        let table_len = read_checked(&mut b, |b| b.get_u32_le())?;
+
+        let mut table = Vec::with_capacity((table_len as usize).min(b.len() / DS64_ENTRY_SIZE));
+        for _ in 0..table_len {
+            let id = read_u8_array(&mut b)?;
+            let size = read_checked(&mut b, |b| b.get_u64_le())?;
+            table.push((id, size));
+        }
+
+        self.ds64 = Some(Ds64 { data_size, table });
+        Ok(())
+    }
+}
+
+/// The state needed to encode a RIFF tree
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct EncodeContext {
+    big_endian: bool,
+    rf64: bool,
+}
+
+impl EncodeContext {
+    /// Get the context for encoding `chunk` as the root of a tree
+    pub(super) fn new(chunk: &RiffChunk) -> EncodeContext {
+        match RiffVariant::from_id(chunk.id()) {
+            Some(variant) => EncodeContext {
+                big_endian: variant.is_big_endian(),
+                rf64: variant.has_ds64(),
+            },
+            None => EncodeContext::default(),
+        }
+    }
+
+    /// Get the context for encoding an EA IFF 85 tree, with big-endian sizes
+    pub(crate) fn iff() -> EncodeContext {
+        EncodeContext {
+            big_endian: true,
+            rf64: false,
+        }
+    }
+
+    /// Check if the sizes that don't fit in 32 bits are stored in the `ds64` chunk
+    #[inline]
+    pub(super) fn has_ds64(&self) -> bool {
+        self.rf64
+    }
+
+    /// Write the size field of a chunk with a content of `len` bytes
+    pub(super) fn put_size(&self, b: &mut BytesMut, len: u64, is_root: bool) {
+        let size = if self.rf64 && is_root {
+            SIZE_PLACEHOLDER
+        } else {
+            u32::try_from(len).unwrap_or(SIZE_PLACEHOLDER)
+        };
+
+        if self.big_endian {
+            b.put_u32(size);
+        } else {
+            b.put_u32_le(size);
+        }
+    }
+}
+
+/// Check if `len` can be stored in a 32-bit size field
+#[inline]
+pub(super) fn fits_u32(len: u64) -> bool {
+    len < u64::from(SIZE_PLACEHOLDER)
+}
+
+/// Get the contents of the `ds64` chunk of `root`, the first of its subchunks
+fn find_ds64(root: &RiffContent) -> Option<&Bytes> {
+    let (_, subchunks) = root.list()?;
+    subchunks
+        .iter()
+        .find(|chunk| chunk.id() == CHUNK_DS64)?
+        .content()
+        .data()
+}
+
+/// Get the length of the contents of the `ds64` chunk of `root` once it is
+/// encoded, which grows from `current` to fit an entry for every oversized chunk
+fn ds64_len(root: &RiffContent, current: &Bytes) -> usize {
+    let mut table = Vec::new();
+    if let Some((_, subchunks)) = root.list() {
+        oversized_chunks(subchunks, &mut table);
+    }
+
+    current
+        .len()
+        .max(DS64_HEADER_SIZE + table.len() * DS64_ENTRY_SIZE)
+}
+
+/// Get the number of bytes the `ds64` chunk of `root` grows by once it is encoded
+pub(super) fn ds64_growth(root: &RiffContent) -> u64 {
+    let current = match find_ds64(root) {
+        Some(current) => current,
+        None => return 0,
+    };
+
+    // the chunk is padded to an even length
+    let padded = |len: usize| (len + len % 2) as u64;
+    padded(ds64_len(root, current)) - padded(current.len())
+}
+
+/// Encode the `ds64` chunk of `root`, holding its current sizes, with
+/// `current` as its old contents
+///
+/// The table grows to fit an entry for every oversized chunk, and any
+/// space left after it is filled with zeros.
+pub(super) fn encode_ds64(root: &RiffContent, current: &Bytes) -> Bytes {
+    let len = ds64_len(root, current);
+    let mut b = BytesMut::with_capacity(8 + len + len % 2);
+    b.extend_from_slice(&CHUNK_DS64);
+    b.put_u32_le(len as u32);
+    if current.len() < DS64_HEADER_SIZE {
+        // not a valid ds64 chunk, which is kept unchanged
+        b.extend_from_slice(current);
+        b.resize(8 + len + len % 2, 0);
+        return b.freeze();
+    }
+
+    let subchunks = root.list().map_or(&[][..], |(_, subchunks)| subchunks);
+    let data_size = subchunks
+        .iter()
+        .find(|chunk| chunk.id() == CHUNK_DATA)
+        .map_or(0, |chunk| chunk.content().len());
+    // the sample count can't be computed from the tree, so it is kept
+    let sample_count = current.slice(16..24).get_u64_le();
+
+    let mut table = Vec::new();
+    oversized_chunks(subchunks, &mut table);
+
+    b.put_u64_le(root.len() + ds64_growth(root));
+    b.put_u64_le(data_size);
+    b.put_u64_le(sample_count);
+    b.put_u32_le(table.len() as u32);
+    for (id, size) in table {
+        b.extend_from_slice(&id);
+        b.put_u64_le(size);
+    }
+    b.resize(8 + len + len % 2, 0);
+    b.freeze()
+}
+
+/// Build empty contents for a `ds64` chunk with enough space for `root`
+pub(super) fn empty_ds64(root: &RiffContent) -> Bytes {
+    let mut table = Vec::new();
+    if let Some((_, subchunks)) = root.list() {
+        oversized_chunks(subchunks, &mut table);
+    }
+
+    Bytes::from(alloc::vec![0; DS64_HEADER_SIZE + table.len() * DS64_ENTRY_SIZE])
+}
+
+/// Collect, depth-first, the chunks that need an entry in the `ds64` table
+fn oversized_chunks(chunks: &[RiffChunk], table: &mut Vec<([u8; 4], u64)>) {
+    for chunk in chunks {
+        let len = chunk.content().len();
+        if chunk.id() != CHUNK_DATA && !fits_u32(len) {
+            table.push((chunk.id(), len));
+        }
+
+        if let Some((_, subchunks)) = chunk.content().list() {
+            oversized_chunks(subchunks, table);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use alloc::vec::Vec;
+
+use bytes::{Bytes, BytesMut};
+
+use super::TiffByteOrder;
+
+pub const TYPE_BYTE: u16 = 1;
+pub const TYPE_ASCII: u16 = 2;
+pub const TYPE_SHORT: u16 = 3;
+pub const TYPE_LONG: u16 = 4;
+pub const TYPE_RATIONAL: u16 = 5;
+pub const TYPE_SBYTE: u16 = 6;
+pub const TYPE_UNDEFINED: u16 = 7;
+pub const TYPE_SSHORT: u16 = 8;
+pub const TYPE_SLONG: u16 = 9;
+pub const TYPE_SRATIONAL: u16 = 10;
+pub const TYPE_FLOAT: u16 = 11;
+pub const TYPE_DOUBLE: u16 = 12;
+pub const TYPE_IFD: u16 = 13;
+pub const TYPE_LONG8: u16 = 16;
+pub const TYPE_SLONG8: u16 = 17;
+pub const TYPE_IFD8: u16 = 18;
+
+pub const TAG_NEW_SUBFILE_TYPE: u16 = 254;
+pub const TAG_IMAGE_WIDTH: u16 = 256;
+pub const TAG_IMAGE_LENGTH: u16 = 257;
+pub const TAG_COMPRESSION: u16 = 259;
+pub const TAG_IMAGE_DESCRIPTION: u16 = 270;
+pub const TAG_MAKE: u16 = 271;
+pub const TAG_MODEL: u16 = 272;
+pub const TAG_STRIP_OFFSETS: u16 = 273;
+pub const TAG_ORIENTATION: u16 = 274;
+pub const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+pub const TAG_SOFTWARE: u16 = 305;
+pub const TAG_DATE_TIME: u16 = 306;
+pub const TAG_ARTIST: u16 = 315;
+pub const TAG_TILE_OFFSETS: u16 = 324;
+pub const TAG_TILE_BYTE_COUNTS: u16 = 325;
+pub const TAG_SUB_IFDS: u16 = 330;
+pub const TAG_JPEG_INTERCHANGE_FORMAT: u16 = 513;
+pub const TAG_JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 514;
+pub const TAG_XMP: u16 = 700;
+pub const TAG_COPYRIGHT: u16 = 33432;
+pub const TAG_IPTC: u16 = 33723;
+pub const TAG_EXIF_IFD: u16 = 34665;
+pub const TAG_ICC_PROFILE: u16 = 34675;
+pub const TAG_GPS_IFD: u16 = 34853;
+pub const TAG_MAKER_NOTE: u16 = 37500;
+pub const TAG_INTEROP_IFD: u16 = 40965;
+
+/// The tags pointing to sub-IFDs
+pub(super) const POINTER_TAGS: [u16; 4] =
+    [TAG_SUB_IFDS, TAG_EXIF_IFD, TAG_GPS_IFD, TAG_INTEROP_IFD];
+
+/// The tags pointing to image data, paired with the tags holding its length
+pub(super) const DATA_TAGS: [(u16, u16); 3] = [
+    (TAG_STRIP_OFFSETS, TAG_STRIP_BYTE_COUNTS),
+    (TAG_TILE_OFFSETS, TAG_TILE_BYTE_COUNTS),
+    (
+        TAG_JPEG_INTERCHANGE_FORMAT,
+        TAG_JPEG_INTERCHANGE_FORMAT_LENGTH,
+    ),
+];
+
+/// Get the size in bytes of a single value of the field type `kind`
+///
+/// Returns `None` if `kind` isn't a known field type.
+pub fn type_size(kind: u16) -> Option<usize> {
+    match kind {
+        TYPE_BYTE | TYPE_ASCII | TYPE_SBYTE | TYPE_UNDEFINED => Some(1),
+        TYPE_SHORT | TYPE_SSHORT => Some(2),
+        TYPE_LONG | TYPE_SLONG | TYPE_FLOAT | TYPE_IFD => Some(4),
+        TYPE_RATIONAL | TYPE_SRATIONAL | TYPE_DOUBLE | TYPE_LONG8 | TYPE_SLONG8 | TYPE_IFD8 => {
+            Some(8)
+        }
+        _ => None,
+    }
+}
+
+/// An Image File Directory of a [`Tiff`][super::Tiff]
+///
+/// The entries are sorted by tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TiffIfd {
+    entries: Vec<TiffEntry>,
+}
+
+/// An entry of a [`TiffIfd`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TiffEntry {
+    tag: u16,
+    kind: u16,
+    value: TiffValue,
+}
+
+/// The value of a [`TiffEntry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TiffValue {
+    /// The raw values, stored in the byte order of the [`Tiff`][super::Tiff]
+    ///
+    /// Offsets stored in raw values aren't updated when the file is encoded.
+    Raw(Bytes),
+    /// The IFDs pointed to by the entry, like the EXIF IFD or the SubIFDs
+    Ifds(Vec<TiffIfd>),
+    /// The image data pointed to by the entry, like strips or tiles
+    ///
+    /// The lengths are stored in the paired byte counts entry, which has
+    /// to be kept in sync.
+    Data(Vec<Bytes>),
+}
+
+impl TiffIfd {
+    /// Construct a new empty `TiffIfd`
+    #[inline]
+    pub fn new() -> TiffIfd {
+        TiffIfd::default()
+    }
+
+    /// Construct a `TiffIfd` from `entries`, sorting them by tag
+    pub fn from_entries(mut entries: Vec<TiffEntry>) -> TiffIfd {
+        entries.sort_by_key(TiffEntry::tag);
+        TiffIfd { entries }
+    }
+
+    /// Get the entries of this `TiffIfd`
+    #[inline]
+    pub fn entries(&self) -> &Vec<TiffEntry> {
+        &self.entries
+    }
+
+    /// Get a mutable reference to the entries of this `TiffIfd`
+    ///
+    /// The entries must be kept sorted by tag.
+    #[inline]
+    pub fn entries_mut(&mut self) -> &mut Vec<TiffEntry> {
+        &mut self.entries
+    }
+
+    /// Get the entry with a tag of `tag`
+    pub fn entry(&self, tag: u16) -> Option<&TiffEntry> {
+        self.entries.iter().find(|entry| entry.tag() == tag)
+    }
+
+    /// Get a mutable reference to the entry with a tag of `tag`
+    pub fn entry_mut(&mut self, tag: u16) -> Option<&mut TiffEntry> {
+        self.entries.iter_mut().find(|entry| entry.tag() == tag)
+    }
+
+    /// Replace the entry with the same tag as `entry`, or insert it
+    /// keeping the entries sorted.
+    pub fn set_entry(&mut self, entry: TiffEntry) {
+        match self
+            .entries
+            .binary_search_by_key(&entry.tag(), TiffEntry::tag)
+        {
+            Ok(pos) => self.entries[pos] = entry,
+            Err(pos) => self.entries.insert(pos, entry),
+        }
+    }
+
+    /// Remove the entry with a tag of `tag`, returning it
+    pub fn remove_entry(&mut self, tag: u16) -> Option<TiffEntry> {
+        let pos = self.entries.iter().position(|entry| entry.tag() == tag)?;
+        Some(self.entries.remove(pos))
+    }
+
+    /// Get the IFDs pointed to by the SubIFDs entry
+    pub fn sub_ifds(&self) -> &[TiffIfd] {
+        self.entry(TAG_SUB_IFDS)
+            .and_then(TiffEntry::ifds)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the IFD pointed to by the EXIF IFD entry
+    pub fn exif_ifd(&self) -> Option<&TiffIfd> {
+        self.entry(TAG_EXIF_IFD)?.ifds()?.first()
+    }
+
+    /// Get the IFD pointed to by the GPS IFD entry
+    pub fn gps_ifd(&self) -> Option<&TiffIfd> {
+        self.entry(TAG_GPS_IFD)?.ifds()?.first()
+    }
+
+    /// Get the image data of the strips
+    pub fn strips(&self) -> Option<&Vec<Bytes>> {
+        self.entry(TAG_STRIP_OFFSETS)?.data()
+    }
+
+    /// Get the image data of the tiles
+    pub fn tiles(&self) -> Option<&Vec<Bytes>> {
+        self.entry(TAG_TILE_OFFSETS)?.data()
+    }
+
+    /// Get the first value of the unsigned integer entry with a tag of `tag`
+    pub fn value_u64(&self, tag: u16, byte_order: TiffByteOrder) -> Option<u64> {
+        self.entry(tag)?.values_u64(byte_order)?.first().copied()
+    }
+
+    /// Convert the raw values to the other byte order
+    pub(crate) fn swap_byte_order(&mut self) {
+        for entry in &mut self.entries {
+            entry.swap_byte_order();
+        }
+    }
+}
+
+impl TiffEntry {
+    /// Construct a new `TiffEntry`
+    ///
+    /// `kind` is the field type of the values, like [`TYPE_SHORT`].
+    #[inline]
+    pub fn new(tag: u16, kind: u16, value: TiffValue) -> TiffEntry {
+        TiffEntry { tag, kind, value }
+    }
+
+    /// Get the tag of this `TiffEntry`
+    #[inline]
+    pub fn tag(&self) -> u16 {
+        self.tag
+    }
+
+    /// Get the field type of this `TiffEntry`, like [`TYPE_SHORT`]
+    #[inline]
+    pub fn kind(&self) -> u16 {
+        self.kind
+    }
+
+    /// Get the value of this `TiffEntry`
+    #[inline]
+    pub fn value(&self) -> &TiffValue {
+        &self.value
+    }
+
+    /// Get a mutable reference to the value of this `TiffEntry`
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut TiffValue {
+        &mut self.value
+    }
+
+    /// Get the number of values of this `TiffEntry`
+    pub fn count(&self) -> usize {
+        match &self.value {
+            TiffValue::Raw(raw) => raw.len() / type_size(self.kind).unwrap_or(1),
+            TiffValue::Ifds(ifds) => ifds.len(),
+            TiffValue::Data(data) => data.len(),
+        }
+    }
+
+    /// Get the raw values if the value of this `TiffEntry` is `Raw`
+    pub fn raw(&self) -> Option<&Bytes> {
+        match &self.value {
+            TiffValue::Raw(raw) => Some(raw),
+            _ => None,
+        }
+    }
+
+    /// Get the IFDs if the value of this `TiffEntry` is `Ifds`
+    pub fn ifds(&self) -> Option<&Vec<TiffIfd>> {
+        match &self.value {
+            TiffValue::Ifds(ifds) => Some(ifds),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the IFDs if the value of this `TiffEntry` is `Ifds`
+    pub fn ifds_mut(&mut self) -> Option<&mut Vec<TiffIfd>> {
+        match &mut self.value {
+            TiffValue::Ifds(ifds) => Some(ifds),
+            _ => None,
+        }
+    }
+
+    /// Get the image data if the value of this `TiffEntry` is `Data`
+    pub fn data(&self) -> Option<&Vec<Bytes>> {
+        match &self.value {
+            TiffValue::Data(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Get the raw values as unsigned integers, read in `byte_order`
+    ///
+    /// Returns `None` if the value isn't `Raw` or if the field type
+    /// isn't `BYTE`, `SHORT`, `LONG`, `LONG8`, `IFD` or `IFD8`.
+    pub fn values_u64(&self, byte_order: TiffByteOrder) -> Option<Vec<u64>> {
+        let raw = self.raw()?;
+        let size = match self.kind {
+            TYPE_BYTE | TYPE_SHORT | TYPE_LONG | TYPE_LONG8 | TYPE_IFD | TYPE_IFD8 => {
+                type_size(self.kind)?
+            }
+            _ => return None,
+        };
+
+        Some(
+            raw.chunks_exact(size)
+                .map(|value| byte_order.read_uint(value))
+                .collect(),
+        )
+    }
+
+    /// Convert the raw values to the other byte order
+    fn swap_byte_order(&mut self) {
+        // rationals are made of two 32 bit values
+        let size = match self.kind {
+            TYPE_RATIONAL | TYPE_SRATIONAL => 4,
+            kind => type_size(kind).unwrap_or(1),
+        };
+
+        match &mut self.value {
+            TiffValue::Raw(raw) if size > 1 => {
+                let mut swapped = BytesMut::from(&raw[..]);
+                for value in swapped.chunks_exact_mut(size) {
+                    value.reverse();
+                }
+                *raw = swapped.freeze();
+            }
+            TiffValue::Raw(_) | TiffValue::Data(_) => {}
+            TiffValue::Ifds(ifds) => ifds.iter_mut().for_each(TiffIfd::swap_byte_order),
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use alloc::vec::Vec;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::{
+    encoder::{EncodeAt, ImageEncoder},
+    Error, ImageEXIF, ImageICC, Result, EXIF_DATA_PREFIX,
+};
+
+pub use self::ifd::{
+    type_size, TiffEntry, TiffIfd, TiffValue, TAG_ARTIST, TAG_COMPRESSION, TAG_COPYRIGHT,
+    TAG_DATE_TIME, TAG_EXIF_IFD, TAG_GPS_IFD, TAG_ICC_PROFILE, TAG_IMAGE_DESCRIPTION,
+    TAG_IMAGE_LENGTH, TAG_IMAGE_WIDTH, TAG_INTEROP_IFD, TAG_IPTC, TAG_JPEG_INTERCHANGE_FORMAT,
+    TAG_JPEG_INTERCHANGE_FORMAT_LENGTH, TAG_MAKE, TAG_MAKER_NOTE, TAG_MODEL, TAG_NEW_SUBFILE_TYPE,
+    TAG_ORIENTATION, TAG_SOFTWARE, TAG_STRIP_BYTE_COUNTS, TAG_STRIP_OFFSETS, TAG_SUB_IFDS,
+    TAG_TILE_BYTE_COUNTS, TAG_TILE_OFFSETS, TAG_XMP, TYPE_ASCII, TYPE_BYTE, TYPE_DOUBLE,
+    TYPE_FLOAT, TYPE_IFD, TYPE_IFD8, TYPE_LONG, TYPE_LONG8, TYPE_RATIONAL, TYPE_SBYTE, TYPE_SHORT,
+    TYPE_SLONG, TYPE_SLONG8, TYPE_SRATIONAL, TYPE_SSHORT, TYPE_UNDEFINED,
+};
+use self::{read::Reader, write::Writer};
+
+mod ifd;
+mod read;
+mod write;
+
+pub const MAGIC_CLASSIC: u16 = 42;
+pub const MAGIC_BIG: u16 = 43;
+
+// the IFD0 entries making up the EXIF metadata
+const EXIF_TAGS: [u16; 10] = [
+    TAG_IMAGE_DESCRIPTION,
+    TAG_MAKE,
+    TAG_MODEL,
+    TAG_ORIENTATION,
+    TAG_SOFTWARE,
+    TAG_DATE_TIME,
+    TAG_ARTIST,
+    TAG_COPYRIGHT,
+    TAG_EXIF_IFD,
+    TAG_GPS_IFD,
+];
+
+pub(crate) fn is_tiff(buf: &[u8]) -> bool {
+    buf.len() > 4
+        && matches!(
+            &buf[..4],
+            b"II\x2A\0" | b"MM\0\x2A" | b"II\x2B\0" | b"MM\0\x2B"
+        )
+}
+
+/// The byte order of a [`Tiff`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TiffByteOrder {
+    /// `II`, least significant byte first
+    LittleEndian,
+    /// `MM`, most significant byte first
+    BigEndian,
+}
+
+/// The variants of the TIFF format
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TiffVariant {
+    /// The classic TIFF, with 32 bit offsets
+    Classic,
+    /// BigTIFF, with 64 bit offsets
+    Big,
+}
+
+/// The representation of a TIFF file
+///
+/// The IFDs pointed to by the SubIFDs, EXIF, GPS and Interoperability
+/// entries are parsed, as are the strips, tiles and JPEG thumbnails.
+/// When the file is encoded the IFDs are written first, followed by
+/// the image data, and the offsets pointing to them are updated.
+///
+/// Entries of an unknown field type are dropped, since their values
+/// can't be moved. Files where the offsets of the image data aren't
+/// paired with their byte counts are rejected for the same reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tiff {
+    byte_order: TiffByteOrder,
+    variant: TiffVariant,
+    ifds: Vec<TiffIfd>,
+}
+
+impl TiffByteOrder {
+    /// Read the unsigned integer stored in `b`, of up to 8 bytes
+    pub(crate) fn read_uint(self, b: &[u8]) -> u64 {
+        let mut buf = [0; 8];
+        match self {
+            TiffByteOrder::LittleEndian => {
+                buf[..b.len()].copy_from_slice(b);
+                u64::from_le_bytes(buf)
+            }
+            TiffByteOrder::BigEndian => {
+                buf[8 - b.len()..].copy_from_slice(b);
+                u64::from_be_bytes(buf)
+            }
+        }
+    }
+
+    /// Write `n` as an unsigned integer of `len` bytes
+    pub(crate) fn put_uint(self, b: &mut BytesMut, n: u64, len: usize) {
+        match self {
+            TiffByteOrder::LittleEndian => b.put_uint_le(n, len),
+            TiffByteOrder::BigEndian => b.put_uint(n, len),
+        }
+    }
+}
+
+impl TiffVariant {
+    /// Get the size of the offsets
+    #[inline]
+    pub fn offset_len(self) -> u64 {
+        match self {
+            TiffVariant::Classic => 4,
+            TiffVariant::Big => 8,
+        }
+    }
+
+    /// Get the size of the entry counts of the IFDs
+    #[inline]
+    pub fn count_len(self) -> u64 {
+        match self {
+            TiffVariant::Classic => 2,
+            TiffVariant::Big => 8,
+        }
+    }
+
+    fn header_len(self) -> u64 {
+        match self {
+            // byte order (2 bytes) + magic (2 bytes) + offset (4 bytes)
+            TiffVariant::Classic => 8,
+            // byte order (2 bytes) + magic (2 bytes) + offset size (2 bytes)
+            // + reserved (2 bytes) + offset (8 bytes)
+            TiffVariant::Big => 16,
+        }
+    }
+
+    fn put_header(self, b: &mut BytesMut, byte_order: TiffByteOrder, first_ifd: u64) {
+        b.extend_from_slice(match byte_order {
+            TiffByteOrder::LittleEndian => b"II",
+            TiffByteOrder::BigEndian => b"MM",
+        });
+
+        match self {
+            TiffVariant::Classic => {
+                byte_order.put_uint(b, u64::from(MAGIC_CLASSIC), 2);
+                byte_order.put_uint(b, first_ifd, 4);
+            }
+            TiffVariant::Big => {
+                byte_order.put_uint(b, u64::from(MAGIC_BIG), 2);
+                byte_order.put_uint(b, 8, 2);
+                byte_order.put_uint(b, 0, 2);
+                byte_order.put_uint(b, first_ifd, 8);
+            }
+        }
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Tiff {
+    /// Construct a new `Tiff` without any IFD
+    #[inline]
+    pub fn new(byte_order: TiffByteOrder, variant: TiffVariant) -> Tiff {
+        Tiff {
+            byte_order,
+            variant,
+            ifds: Vec::new(),
+        }
+    }
+
+    /// Create a `Tiff` from `Bytes`
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    pub fn from_bytes(b: Bytes) -> Result<Tiff> {
+        Tiff::from_bytes_with_magic(b, &[])
+    }
+
+    /// Create a `Tiff` from `Bytes`, also accepting a classic TIFF header
+    /// holding one of `magic`, like the ones of some RAW formats
+    pub(crate) fn from_bytes_with_magic(b: Bytes, magic: &[u16]) -> Result<Tiff> {
+        if b.len() < 8 {
+            return Err(Error::Truncated);
+        }
+
+        let byte_order = match &b[..2] {
+            b"II" => TiffByteOrder::LittleEndian,
+            b"MM" => TiffByteOrder::BigEndian,
+            _ => return Err(Error::WrongSignature),
+        };
+        let (variant, first_ifd) = match byte_order.read_uint(&b[2..4]) as u16 {
+            MAGIC_CLASSIC => (TiffVariant::Classic, byte_order.read_uint(&b[4..8])),
+            MAGIC_BIG => {
+                if b.len() < 16 {
+                    return Err(Error::Truncated);
+                }
+                if byte_order.read_uint(&b[4..6]) != 8 || byte_order.read_uint(&b[6..8]) != 0 {
+                    return Err(Error::InvalidData);
+                }
+
+                (TiffVariant::Big, byte_order.read_uint(&b[8..16]))
+            }
+            n if magic.contains(&n) => (TiffVariant::Classic, byte_order.read_uint(&b[4..8])),
+            _ => return Err(Error::WrongSignature),
+        };
+
+        let ifds = Reader::new(&b, byte_order, variant).read_chain(first_ifd)?;
+        Ok(Tiff {
+            byte_order,
+            variant,
+            ifds,
+        })
+    }
+
+    /// Get the byte order of this `Tiff`
+    #[inline]
+    pub fn byte_order(&self) -> TiffByteOrder {
+        self.byte_order
+    }
+
+    /// Get the variant of this `Tiff`
+    #[inline]
+    pub fn variant(&self) -> TiffVariant {
+        self.variant
+    }
+
+    /// Set the variant of this `Tiff`
+    ///
+    /// A classic TIFF can't hold offsets bigger than 4 GiB, so it is
+    /// encoded as a BigTIFF when it doesn't fit in them.
+    #[inline]
+    pub fn set_variant(&mut self, variant: TiffVariant) {
+        self.variant = variant;
+    }
+
+    /// Get the chain of IFDs of this `Tiff`
+    ///
+    /// The first IFD, called IFD0, usually holds the main image.
+    #[inline]
+    pub fn ifds(&self) -> &Vec<TiffIfd> {
+        &self.ifds
+    }
+
+    /// Get a mutable reference to the chain of IFDs of this `Tiff`
+    #[inline]
+    pub fn ifds_mut(&mut self) -> &mut Vec<TiffIfd> {
+        &mut self.ifds
+    }
+
+    /// Get the raw value of the IFD0 entry with a tag of `tag`
+    fn raw_entry(&self, tag: u16) -> Option<Bytes> {
+        self.ifds.first()?.entry(tag)?.raw().cloned()
+    }
+
+    /// Replace the IFD0 entry with a tag of `tag` by the raw `value`,
+    /// or remove it if `value` is `None`
+    fn set_raw_entry(&mut self, tag: u16, kind: u16, value: Option<Bytes>) {
+        match value {
+            Some(value) => {
+                if self.ifds.is_empty() {
+                    self.ifds.push(TiffIfd::new());
+                }
+
+                self.ifds[0].set_entry(TiffEntry::new(tag, kind, TiffValue::Raw(value)));
+            }
+            None => {
+                if let Some(ifd) = self.ifds.first_mut() {
+                    ifd.remove_entry(tag);
+                }
+            }
+        }
+    }
+
+    /// Get the XMP packet, stored in IFD0
+    #[inline]
+    pub fn xmp(&self) -> Option<Bytes> {
+        self.raw_entry(TAG_XMP)
+    }
+
+    /// Set the XMP packet, or remove it if `xmp` is `None`
+    #[inline]
+    pub fn set_xmp(&mut self, xmp: Option<Bytes>) {
+        self.set_raw_entry(TAG_XMP, TYPE_BYTE, xmp);
+    }
+
+    /// Get the IPTC-NAA metadata, stored in IFD0
+    #[inline]
+    pub fn iptc(&self) -> Option<Bytes> {
+        self.raw_entry(TAG_IPTC)
+    }
+
+    /// Set the IPTC-NAA metadata, or remove it if `iptc` is `None`
+    #[inline]
+    pub fn set_iptc(&mut self, iptc: Option<Bytes>) {
+        self.set_raw_entry(TAG_IPTC, TYPE_UNDEFINED, iptc);
+    }
+
+    /// Get the variant this `Tiff` is encoded as
+    ///
+    /// This is a BigTIFF if a classic TIFF would be bigger than 4 GiB.
+    pub fn encoded_variant(&self) -> TiffVariant {
+        match self.variant {
+            TiffVariant::Classic if self.len_as(TiffVariant::Classic) > u64::from(u32::MAX) => {
+                TiffVariant::Big
+            }
+            variant => variant,
+        }
+    }
+
+    /// Get the total size of the `Tiff` once it is encoded as `variant`
+    fn len_as(&self, variant: TiffVariant) -> u64 {
+        Writer::new(self.byte_order, variant).structure_len(&self.ifds)
+            + write::data_len(&self.ifds)
+    }
+
+    /// Get the total size of the `Tiff` once it is encoded.
+    pub fn len(&self) -> u64 {
+        self.len_as(self.encoded_variant())
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Tiff`
+    #[inline]
+    pub fn encoder(self) -> ImageEncoder<Self> {
+        ImageEncoder::from(self)
+    }
+}
+
+/// Read the classic IFD at `offset` of `b`, without following the next IFDs
+pub(crate) fn read_ifd(b: &Bytes, byte_order: TiffByteOrder, offset: u64) -> Result<TiffIfd> {
+    Reader::new(b, byte_order, TiffVariant::Classic).read_single(offset)
+}
+
+impl EncodeAt for Tiff {
+    fn encode_at(&self, pos: &mut usize) -> Option<Bytes> {
+        match pos {
+            0 => Some(Writer::new(self.byte_order, self.encoded_variant()).write(&self.ifds)),
+            _ => {
+                *pos -= 1;
+                write::data_at(&self.ifds, pos)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len() as usize
+    }
+}
+
+impl ImageICC for Tiff {
+    fn icc_profile(&self) -> Option<Bytes> {
+        self.raw_entry(TAG_ICC_PROFILE)
+    }
+
+    fn set_icc_profile(&mut self, profile: Option<Bytes>) {
+        self.set_raw_entry(TAG_ICC_PROFILE, TYPE_UNDEFINED, profile);
+    }
+}
+
+/// The EXIF metadata is encoded as a classic TIFF file with a single IFD,
+/// holding the EXIF and GPS IFDs and the descriptive IFD0 entries, like
+/// the camera make and model.
+///
+/// Invalid EXIF metadata passed to `set_exif` is ignored, leaving
+/// the pre-existing metadata unchanged.
+impl ImageEXIF for Tiff {
+    fn exif(&self) -> Option<Bytes> {
+        let entries = self
+            .ifds
+            .first()?
+            .entries()
+            .iter()
+            .filter(|entry| EXIF_TAGS.contains(&entry.tag()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return None;
+        }
+
+        let mut exif = Tiff::new(self.byte_order, TiffVariant::Classic);
+        exif.ifds.push(TiffIfd::from_entries(entries));
+        Some(exif.encoder().bytes())
+    }
+
+    fn set_exif(&mut self, exif: Option<Bytes>) {
+        // the new entries are parsed first, so the IFD is left unchanged if they're invalid
+        let new_ifd = match exif {
+            Some(exif) => {
+                let exif = if exif.starts_with(EXIF_DATA_PREFIX) {
+                    exif.slice(EXIF_DATA_PREFIX.len()..)
+                } else {
+                    exif
+                };
+                let exif = match Tiff::from_bytes(exif) {
+                    Ok(exif) => exif,
+                    Err(_) => return,
+                };
+                let mut ifd = match exif.ifds.into_iter().next() {
+                    Some(ifd) => ifd,
+                    None => return,
+                };
+                if exif.byte_order != self.byte_order {
+                    ifd.swap_byte_order();
+                }
+                Some(ifd)
+            }
+            None => None,
+        };
+
+        if let Some(ifd) = self.ifds.first_mut() {
+            ifd.entries_mut()
+                .retain(|entry| !EXIF_TAGS.contains(&entry.tag()));
+        }
+
+        let mut new_ifd = match new_ifd {
+            Some(ifd) => ifd,
+            None => return,
+        };
+        if self.ifds.is_empty() {
+            self.ifds.push(TiffIfd::new());
+        }
+        for entry in new_ifd.entries_mut().drain(..) {
+            if EXIF_TAGS.contains(&entry.tag()) {
+                self.ifds[0].set_entry(entry);
+            }
+        }
+    }
+}
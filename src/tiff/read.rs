@@ -0,0 +1,170 @@
+use alloc::vec::Vec;
+
+use bytes::Bytes;
+
+use super::{
+    ifd::{
+        type_size, DATA_TAGS, POINTER_TAGS, TYPE_IFD, TYPE_IFD8, TYPE_LONG, TYPE_LONG8, TYPE_SHORT,
+    },
+    TiffByteOrder, TiffEntry, TiffIfd, TiffValue, TiffVariant,
+};
+use crate::{Error, Result};
+
+// the maximum nesting of sub-IFDs
+const MAX_DEPTH: usize = 16;
+
+/// Parses the IFDs of a TIFF file, keeping track of the ones already
+/// read in order to detect loops
+pub(super) struct Reader<'a> {
+    file: &'a Bytes,
+    byte_order: TiffByteOrder,
+    variant: TiffVariant,
+    visited: Vec<u64>,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(file: &'a Bytes, byte_order: TiffByteOrder, variant: TiffVariant) -> Self {
+        Self {
+            file,
+            byte_order,
+            variant,
+            visited: Vec::new(),
+        }
+    }
+
+    /// Read the chain of IFDs starting at `offset`
+    pub(super) fn read_chain(&mut self, mut offset: u64) -> Result<Vec<TiffIfd>> {
+        let mut ifds = Vec::new();
+        while offset != 0 {
+            let (ifd, next) = self.read_ifd(offset, 0)?;
+            ifds.push(ifd);
+            offset = next;
+        }
+
+        Ok(ifds)
+    }
+
+    /// Read the IFD at `offset`, without following the next IFDs
+    pub(super) fn read_single(&mut self, offset: u64) -> Result<TiffIfd> {
+        self.read_ifd(offset, 0).map(|(ifd, _)| ifd)
+    }
+
+    fn slice(&self, offset: u64, len: u64) -> Result<Bytes> {
+        let end = offset.checked_add(len).ok_or(Error::Truncated)?;
+        if end > self.file.len() as u64 {
+            return Err(Error::Truncated);
+        }
+
+        Ok(self.file.slice(offset as usize..end as usize))
+    }
+
+    fn read_uint(&self, offset: u64, len: u64) -> Result<u64> {
+        let b = self.slice(offset, len)?;
+        Ok(self.byte_order.read_uint(&b))
+    }
+
+    /// Read the IFD at `offset`, returning it with the offset of the next one
+    fn read_ifd(&mut self, offset: u64, depth: usize) -> Result<(TiffIfd, u64)> {
+        if depth > MAX_DEPTH {
+            return Err(Error::LimitExceeded);
+        }
+        if self.visited.contains(&offset) {
+            return Err(Error::InvalidData);
+        }
+        self.visited.push(offset);
+
+        let count_len = self.variant.count_len();
+        let field_len = self.variant.offset_len();
+        // tag (2 bytes) + field type (2 bytes) + count + value or offset
+        let entry_len = 2 + 2 + field_len + field_len;
+
+        let count = self.read_uint(offset, count_len)?;
+        let table_len = count.checked_mul(entry_len).ok_or(Error::Truncated)?;
+        let table = self.slice(offset + count_len, table_len)?;
+        let next = self.read_uint(offset + count_len + table_len, field_len)?;
+
+        let mut raw_entries = Vec::with_capacity(count as usize);
+        for i in 0..count as usize {
+            let entry = table.slice(i * entry_len as usize..(i + 1) * entry_len as usize);
+            let tag = self.byte_order.read_uint(&entry[0..2]) as u16;
+            let kind = self.byte_order.read_uint(&entry[2..4]) as u16;
+            let count = self.byte_order.read_uint(&entry[4..4 + field_len as usize]);
+            let field = entry.slice(4 + field_len as usize..);
+
+            // entries of an unknown field type can't be relocated
+            let size = match type_size(kind) {
+                Some(size) => size as u64,
+                None => continue,
+            };
+            let len = size.checked_mul(count).ok_or(Error::InvalidData)?;
+
+            let raw = if len <= field_len {
+                field.slice(..len as usize)
+            } else {
+                self.slice(self.byte_order.read_uint(&field), len)?
+            };
+            raw_entries.push(TiffEntry::new(tag, kind, TiffValue::Raw(raw)));
+        }
+
+        let mut entries = Vec::with_capacity(raw_entries.len());
+        for entry in &raw_entries {
+            entries.push(self.resolve_entry(entry, &raw_entries, depth)?);
+        }
+
+        Ok((TiffIfd::from_entries(entries), next))
+    }
+
+    /// Resolve the sub-IFDs and the image data pointed to by `entry`
+    fn resolve_entry(
+        &mut self,
+        entry: &TiffEntry,
+        entries: &[TiffEntry],
+        depth: usize,
+    ) -> Result<TiffEntry> {
+        let tag = entry.tag();
+        let lengths_tag = DATA_TAGS
+            .iter()
+            .find(|(offsets_tag, _)| *offsets_tag == tag)
+            .map(|(_, lengths_tag)| *lengths_tag);
+        // the offsets of the image data may also be SHORTs
+        let is_pointer = match entry.kind() {
+            TYPE_LONG | TYPE_LONG8 | TYPE_IFD | TYPE_IFD8 => true,
+            TYPE_SHORT => lengths_tag.is_some(),
+            _ => false,
+        };
+        let offsets = match entry.values_u64(self.byte_order) {
+            Some(offsets) if is_pointer && !offsets.contains(&0) => offsets,
+            _ => return Ok(entry.clone()),
+        };
+
+        if POINTER_TAGS.contains(&tag) {
+            let ifds = offsets
+                .iter()
+                .map(|&offset| self.read_ifd(offset, depth + 1).map(|(ifd, _)| ifd))
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(TiffEntry::new(tag, entry.kind(), TiffValue::Ifds(ifds)));
+        }
+
+        let lengths_tag = match lengths_tag {
+            Some(lengths_tag) => lengths_tag,
+            None => return Ok(entry.clone()),
+        };
+        // without the lengths the image data can't be moved, and the
+        // offsets would point to the wrong place once the file is encoded
+        let lengths = entries
+            .iter()
+            .find(|entry| entry.tag() == lengths_tag)
+            .and_then(|lengths| lengths.values_u64(self.byte_order))
+            .ok_or(Error::InvalidData)?;
+        if lengths.len() != offsets.len() {
+            return Err(Error::InvalidData);
+        }
+
+        let data = offsets
+            .iter()
+            .zip(&lengths)
+            .map(|(&offset, &len)| self.slice(offset, len))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TiffEntry::new(tag, entry.kind(), TiffValue::Data(data)))
+    }
+}
@@ -0,0 +1,224 @@
+use alloc::vec::Vec;
+
+use bytes::{Bytes, BytesMut};
+
+use super::{
+    ifd::{TYPE_IFD, TYPE_IFD8, TYPE_LONG, TYPE_LONG8},
+    TiffByteOrder, TiffEntry, TiffIfd, TiffValue, TiffVariant,
+};
+
+/// Lays out the IFDs of a TIFF file
+///
+/// Every IFD is followed by the values that don't fit in its entries,
+/// and then by its sub-IFDs. The image data comes after all of the IFDs,
+/// in the order given by [`data_at`].
+pub(super) struct Writer {
+    byte_order: TiffByteOrder,
+    variant: TiffVariant,
+}
+
+impl Writer {
+    pub(super) fn new(byte_order: TiffByteOrder, variant: TiffVariant) -> Self {
+        Self {
+            byte_order,
+            variant,
+        }
+    }
+
+    /// Get the size of the table of an IFD with `count` entries
+    fn table_len(&self, count: usize) -> u64 {
+        let field_len = self.variant.offset_len();
+        // count + entries (tag, field type, count, value or offset) + next offset
+        self.variant.count_len() + count as u64 * (2 + 2 + field_len + field_len) + field_len
+    }
+
+    /// Get the size of the value of `entry`, or `None` if it fits in the entry
+    fn value_len(&self, entry: &TiffEntry) -> Option<u64> {
+        let len = match entry.value() {
+            TiffValue::Raw(raw) => raw.len() as u64,
+            TiffValue::Ifds(_) | TiffValue::Data(_) => {
+                entry.count() as u64 * self.variant.offset_len()
+            }
+        };
+
+        // values start on a word boundary
+        (len > self.variant.offset_len()).then(|| len + len % 2)
+    }
+
+    /// Get the total size of `ifd`, including its values and sub-IFDs
+    pub(super) fn ifd_len(&self, ifd: &TiffIfd) -> u64 {
+        let mut len = self.table_len(ifd.entries().len());
+        for entry in ifd.entries() {
+            len += self.value_len(entry).unwrap_or(0);
+            if let TiffValue::Ifds(ifds) = entry.value() {
+                len += ifds.iter().map(|ifd| self.ifd_len(ifd)).sum::<u64>();
+            }
+        }
+        len
+    }
+
+    /// Get the total size of the header and of `ifds`
+    pub(super) fn structure_len(&self, ifds: &[TiffIfd]) -> u64 {
+        self.variant.header_len() + ifds.iter().map(|ifd| self.ifd_len(ifd)).sum::<u64>()
+    }
+
+    /// Write the header and `ifds`
+    pub(super) fn write(&self, ifds: &[TiffIfd]) -> Bytes {
+        let len = self.structure_len(ifds);
+        let mut out = BytesMut::with_capacity(len as usize);
+
+        let first = if ifds.is_empty() {
+            0
+        } else {
+            self.variant.header_len()
+        };
+        self.variant.put_header(&mut out, self.byte_order, first);
+
+        let mut data_offset = len;
+        for (i, ifd) in ifds.iter().enumerate() {
+            let offset = out.len() as u64;
+            let next = if i + 1 < ifds.len() {
+                offset + self.ifd_len(ifd)
+            } else {
+                0
+            };
+            self.write_ifd(&mut out, ifd, next, &mut data_offset);
+        }
+
+        debug_assert_eq!(out.len() as u64, len);
+        out.freeze()
+    }
+
+    fn put_offset(&self, out: &mut BytesMut, offset: u64) {
+        self.byte_order
+            .put_uint(out, offset, self.variant.offset_len() as usize);
+    }
+
+    /// Write `ifd` at the end of `out`, followed by its values and sub-IFDs
+    fn write_ifd(&self, out: &mut BytesMut, ifd: &TiffIfd, next: u64, data_offset: &mut u64) {
+        let field_len = self.variant.offset_len() as usize;
+        let offset = out.len() as u64;
+
+        // the values that don't fit in the entries, then the sub-IFDs
+        let mut values_offset = offset + self.table_len(ifd.entries().len());
+        let mut ifds_offset = values_offset
+            + ifd
+                .entries()
+                .iter()
+                .filter_map(|entry| self.value_len(entry))
+                .sum::<u64>();
+
+        let mut values = Vec::with_capacity(ifd.entries().len());
+        for entry in ifd.entries() {
+            let value = match entry.value() {
+                TiffValue::Raw(raw) => raw.clone(),
+                TiffValue::Ifds(ifds) => {
+                    let mut b = BytesMut::with_capacity(ifds.len() * field_len);
+                    for ifd in ifds {
+                        self.put_offset(&mut b, ifds_offset);
+                        ifds_offset += self.ifd_len(ifd);
+                    }
+                    b.freeze()
+                }
+                TiffValue::Data(data) => {
+                    let mut b = BytesMut::with_capacity(data.len() * field_len);
+                    for data in data {
+                        self.put_offset(&mut b, *data_offset);
+                        *data_offset += data.len() as u64;
+                    }
+                    b.freeze()
+                }
+            };
+            values.push(value);
+        }
+
+        self.byte_order.put_uint(
+            out,
+            ifd.entries().len() as u64,
+            self.variant.count_len() as usize,
+        );
+        for (entry, value) in ifd.entries().iter().zip(&values) {
+            self.byte_order.put_uint(out, u64::from(entry.tag()), 2);
+            self.byte_order
+                .put_uint(out, u64::from(self.kind(entry)), 2);
+            self.byte_order
+                .put_uint(out, entry.count() as u64, field_len);
+
+            match self.value_len(entry) {
+                Some(len) => {
+                    self.put_offset(out, values_offset);
+                    values_offset += len;
+                }
+                None => {
+                    out.extend_from_slice(value);
+                    out.resize(out.len() + field_len - value.len(), 0);
+                }
+            }
+        }
+        self.put_offset(out, next);
+
+        for (entry, value) in ifd.entries().iter().zip(&values) {
+            if self.value_len(entry).is_some() {
+                out.extend_from_slice(value);
+                if value.len() % 2 != 0 {
+                    out.extend_from_slice(&[0]);
+                }
+            }
+        }
+
+        for entry in ifd.entries() {
+            if let TiffValue::Ifds(ifds) = entry.value() {
+                for ifd in ifds {
+                    self.write_ifd(out, ifd, 0, data_offset);
+                }
+            }
+        }
+    }
+
+    /// Get the field type of `entry`, using offsets of the right size
+    fn kind(&self, entry: &TiffEntry) -> u16 {
+        let is_ifd = matches!(entry.kind(), TYPE_IFD | TYPE_IFD8);
+        match (entry.value(), self.variant, is_ifd) {
+            (TiffValue::Raw(_), _, _) => entry.kind(),
+            (TiffValue::Ifds(_), TiffVariant::Classic, true) => TYPE_IFD,
+            (TiffValue::Ifds(_), TiffVariant::Big, true) => TYPE_IFD8,
+            (_, TiffVariant::Classic, _) => TYPE_LONG,
+            (_, TiffVariant::Big, _) => TYPE_LONG8,
+        }
+    }
+}
+
+/// Get the `pos`th piece of image data of `ifds`, the IFDs themselves
+/// first and then their sub-IFDs
+///
+/// `pos` is decremented by the number of pieces found if it isn't reached.
+pub(super) fn data_at(ifds: &[TiffIfd], pos: &mut usize) -> Option<Bytes> {
+    for ifd in ifds {
+        for data in ifd.entries().iter().filter_map(TiffEntry::data) {
+            match data.get(*pos) {
+                Some(data) => return Some(data.clone()),
+                None => *pos -= data.len(),
+            }
+        }
+
+        for sub_ifds in ifd.entries().iter().filter_map(TiffEntry::ifds) {
+            if let Some(data) = data_at(sub_ifds, pos) {
+                return Some(data);
+            }
+        }
+    }
+
+    None
+}
+
+/// Get the total size of the image data of `ifds` and their sub-IFDs
+pub(super) fn data_len(ifds: &[TiffIfd]) -> u64 {
+    ifds.iter()
+        .flat_map(TiffIfd::entries)
+        .map(|entry| match entry.value() {
+            TiffValue::Raw(_) => 0,
+            TiffValue::Ifds(ifds) => data_len(ifds),
+            TiffValue::Data(data) => data.iter().map(|data| data.len() as u64).sum(),
+        })
+        .sum()
+}
@@ -1,6 +1,10 @@
+use alloc::vec::Vec;
 use core::mem;
 
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use miniz_oxide::inflate::{
+    decompress_to_vec_with_limit, decompress_to_vec_zlib_with_limit, DecompressError, TINFLStatus,
+};
 
 use crate::{Error, Result};
 
@@ -13,6 +17,30 @@ pub fn u24_to_le_bytes(n: u32) -> [u8; 3] {
     b[0..3].try_into().unwrap()
 }
 
+/// Encode `n` as a 24-bit integer, failing with [`Error::InvalidData`] if it doesn't fit
+pub fn try_u24_to_le_bytes(n: u32) -> Result<[u8; 3]> {
+    if n > 0xFF_FFFF {
+        return Err(Error::InvalidData);
+    }
+
+    Ok(u24_to_le_bytes(n))
+}
+
+/// Get the string stored in `b`, up to the first NUL byte
+pub fn trim_nul(b: &Bytes) -> Bytes {
+    match b.iter().position(|&b| b == 0) {
+        Some(nul) => b.slice(..nul),
+        None => b.clone(),
+    }
+}
+
+/// Write `s` into a field of `len` bytes, truncating it or padding it with NUL bytes
+pub fn put_fixed(buf: &mut BytesMut, s: &[u8], len: usize) {
+    let s = &s[..s.len().min(len)];
+    buf.extend_from_slice(s);
+    buf.put_bytes(0, len - s.len());
+}
+
 /// Check that the type returned by the function `f` isn't bigger than
 /// the remaining length of `Bytes`. This avoids panicking if the input
 /// is corrupted or truncated.
@@ -59,6 +87,72 @@ pub fn split_to_checked(buf: &mut Bytes, at: usize) -> Result<Bytes> {
     Ok(buf.split_to(at))
 }
 
+/// Decompress the zlib stream `data`, producing at most `limit` bytes.
+///
+/// # Errors
+///
+/// Returns [`Error::LimitExceeded`][crate::Error::LimitExceeded] if the
+/// decompressed data is bigger than `limit` or
+/// [`Error::Inflate`][crate::Error::Inflate] if `data` is corrupted.
+pub fn inflate_zlib(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    decompress_to_vec_zlib_with_limit(data, limit).map_err(inflate_error)
+}
+
+/// Decompress the raw deflate stream `data`, producing at most `limit` bytes.
+///
+/// See [`inflate_zlib`][self::inflate_zlib] for the errors.
+pub fn inflate_raw(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    decompress_to_vec_with_limit(data, limit).map_err(inflate_error)
+}
+
+fn inflate_error(err: DecompressError) -> Error {
+    match err.status {
+        TINFLStatus::HasMoreOutput => Error::LimitExceeded,
+        _ => Error::Inflate,
+    }
+}
+
+/// Implement the methods giving access to the subchunks of the root list
+/// of `$name`, a [`RiffChunk`][crate::riff::RiffChunk] stored in its `$field` field
+macro_rules! impl_subchunks {
+    ($name:literal, $field:ident) => {
+        #[doc = concat!("Get the chunks of this `", $name, "`.")]
+        #[inline]
+        pub fn chunks(&self) -> &alloc::vec::Vec<crate::riff::RiffChunk> {
+            self.$field.root_subchunks()
+        }
+
+        #[doc = concat!("Get a mutable reference to the chunks of this `", $name, "`.")]
+        #[inline]
+        pub fn chunks_mut(&mut self) -> &mut alloc::vec::Vec<crate::riff::RiffChunk> {
+            self.$field.root_subchunks_mut()
+        }
+
+        /// Check if there's a chunk with an id of `id`.
+        #[inline]
+        pub fn has_chunk(&self, id: [u8; 4]) -> bool {
+            self.chunk_by_id(id).is_some()
+        }
+
+        /// Get the first chunk with an id of `id`.
+        pub fn chunk_by_id(&self, id: [u8; 4]) -> Option<&crate::riff::RiffChunk> {
+            self.chunks().iter().find(|chunk| chunk.id() == id)
+        }
+
+        /// Get every chunk with an id of `id`.
+        pub fn chunks_by_id(&self, id: [u8; 4]) -> impl Iterator<Item = &crate::riff::RiffChunk> {
+            self.chunks().iter().filter(move |chunk| chunk.id() == id)
+        }
+
+        /// Remove every chunk with an id of `id`
+        pub fn remove_chunks_by_id(&mut self, id: [u8; 4]) {
+            self.chunks_mut().retain(|chunk| chunk.id() != id);
+        }
+    };
+}
+
+pub(crate) use impl_subchunks;
+
 #[cfg(test)]
 mod tests {
     use bytes::Buf;
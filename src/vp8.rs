@@ -1,4 +1,9 @@
-use crate::util::u24_from_le_bytes;
+use crate::{util::u24_from_le_bytes, Error, Result};
+
+// the start code of a VP8 key frame
+const VP8_START_CODE: [u8; 3] = [0x9d, 0x01, 0x2a];
+// the signature of a VP8L bitstream
+const VP8L_SIGNATURE: u8 = 0x2f;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum VP8Kind {
@@ -10,22 +15,273 @@ pub enum VP8Kind {
     VP8X,
 }
 
-// the first 10 bytes are necessary
-pub(crate) fn size_from_vp8_header(b: &[u8]) -> (u16, u16) {
-    let tag = u24_from_le_bytes(b[0..3].try_into().unwrap());
+/// The type of a VP8 frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VP8FrameType {
+    /// A frame that can be decoded on its own, the only type allowed in WebP
+    KeyFrame,
+    /// A frame that depends on the previous frames
+    InterFrame,
+}
+
+/// The header of a VP8 (lossy) bitstream
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VP8Header {
+    /// The type of the frame
+    pub frame_type: VP8FrameType,
+    /// The version of the bitstream, selecting the reconstruction and loop filters
+    pub version: u8,
+    /// Whether the frame is meant to be displayed
+    pub show_frame: bool,
+    /// The size of the first data partition in bytes
+    pub first_partition_size: u32,
+    /// The fields only present in key frames
+    ///
+    /// `None` if the frame is an inter frame.
+    pub key_frame: Option<VP8KeyFrameHeader>,
+}
+
+/// The part of the header of a VP8 bitstream only present in key frames
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VP8KeyFrameHeader {
+    /// The width of the frame in pixels
+    pub width: u16,
+    /// The horizontal upscaling factor
+    pub horizontal_scale: u8,
+    /// The height of the frame in pixels
+    pub height: u16,
+    /// The vertical upscaling factor
+    pub vertical_scale: u8,
+    /// The color space, `0` being YUV as defined by the specification
+    pub color_space: u8,
+    /// Whether the reconstructed pixel values don't need clamping
+    pub clamping_type: u8,
+}
+
+impl VP8Header {
+    /// Parse the header at the start of the VP8 bitstream `b`
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `b` is truncated or if the start code
+    /// of a key frame doesn't match.
+    pub fn from_bytes(b: &[u8]) -> Result<VP8Header> {
+        let tag = b.get(..3).ok_or(Error::Truncated)?;
+        let tag = u24_from_le_bytes(tag.try_into().unwrap());
+
+        let frame_type = if tag & 1 == 0 {
+            VP8FrameType::KeyFrame
+        } else {
+            VP8FrameType::InterFrame
+        };
+        let version = ((tag >> 1) & 0b111) as u8;
+        let show_frame = (tag >> 4) & 1 == 1;
+        let first_partition_size = tag >> 5;
+
+        let key_frame = match frame_type {
+            VP8FrameType::KeyFrame => Some(VP8KeyFrameHeader::from_bytes(&b[3..])?),
+            VP8FrameType::InterFrame => None,
+        };
+
+        Ok(VP8Header {
+            frame_type,
+            version,
+            show_frame,
+            first_partition_size,
+            key_frame,
+        })
+    }
+}
+
+impl VP8KeyFrameHeader {
+    fn from_bytes(b: &[u8]) -> Result<VP8KeyFrameHeader> {
+        let b = b.get(..9).ok_or(Error::Truncated)?;
+        if b[0..3] != VP8_START_CODE {
+            return Err(Error::WrongSignature);
+        }
 
-    let keyframe = tag & 1 == 0;
+        let width = u16::from_le_bytes(b[3..5].try_into().unwrap());
+        let height = u16::from_le_bytes(b[5..7].try_into().unwrap());
 
-    if keyframe {
-        if b[3..6] != [0x9d, 0x01, 0x2a] {
-            panic!("invalid frame magic bytes");
+        // the first two fields of the first partition are boolean encoded
+        let mut decoder = BoolDecoder::new(&b[7..9]);
+        let color_space = decoder.read_bit() as u8;
+        let clamping_type = decoder.read_bit() as u8;
+
+        Ok(VP8KeyFrameHeader {
+            width: width & 0x3FFF,
+            horizontal_scale: (width >> 14) as u8,
+            height: height & 0x3FFF,
+            vertical_scale: (height >> 14) as u8,
+            color_space,
+            clamping_type,
+        })
+    }
+}
+
+/// A minimal boolean entropy decoder, only able to read
+/// the first bits of a partition encoded with a probability of 1/2
+struct BoolDecoder {
+    value: u16,
+    range: u16,
+}
+
+impl BoolDecoder {
+    fn new(b: &[u8]) -> BoolDecoder {
+        BoolDecoder {
+            value: u16::from_be_bytes([b[0], b[1]]),
+            range: 255,
         }
+    }
+
+    fn read_bit(&mut self) -> bool {
+        let split = 1 + (((self.range - 1) * 128) >> 8);
+        let big_split = split << 8;
+
+        let bit = if self.value >= big_split {
+            self.range -= split;
+            self.value -= big_split;
+            true
+        } else {
+            self.range = split;
+            false
+        };
+
+        // reading at most 2 bits never needs to shift new bytes in
+        while self.range < 128 {
+            self.value <<= 1;
+            self.range <<= 1;
+        }
+
+        bit
+    }
+}
+
+/// The header of a VP8L (lossless) bitstream
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VP8LHeader {
+    /// The width of the image in pixels
+    pub width: u32,
+    /// The height of the image in pixels
+    pub height: u32,
+    /// A hint telling whether the image uses the alpha channel
+    pub alpha_is_used: bool,
+    /// The version of the bitstream, always `0`
+    pub version: u8,
+}
+
+impl VP8LHeader {
+    /// Parse the header at the start of the VP8L bitstream `b`
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the signature doesn't match, if `b` is
+    /// truncated or if the version isn't supported.
+    pub fn from_bytes(b: &[u8]) -> Result<VP8LHeader> {
+        let (&signature, b) = b.split_first().ok_or(Error::Truncated)?;
+        if signature != VP8L_SIGNATURE {
+            return Err(Error::WrongSignature);
+        }
+
+        let bits = b.get(..4).ok_or(Error::Truncated)?;
+        let bits = u32::from_le_bytes(bits.try_into().unwrap());
+
+        let version = (bits >> 29) as u8;
+        if version != 0 {
+            return Err(Error::InvalidData);
+        }
+
+        Ok(VP8LHeader {
+            width: (bits & 0x3FFF) + 1,
+            height: ((bits >> 14) & 0x3FFF) + 1,
+            alpha_is_used: (bits >> 28) & 1 == 1,
+            version,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vp8_header() {
+        // key frame, version 1, shown, first partition of 0x1232 bytes, 717x512 scaled 2x vertically
+        let b = [
+            0x52, 0x46, 0x02, 0x9d, 0x01, 0x2a, 0xcd, 0x02, 0x00, 0x82, 0x00, 0x00,
+        ];
+        let header = VP8Header::from_bytes(&b).unwrap();
+        assert_eq!(
+            header,
+            VP8Header {
+                frame_type: VP8FrameType::KeyFrame,
+                version: 1,
+                show_frame: true,
+                first_partition_size: 0x1232,
+                key_frame: Some(VP8KeyFrameHeader {
+                    width: 717,
+                    horizontal_scale: 0,
+                    height: 512,
+                    vertical_scale: 2,
+                    color_space: 0,
+                    clamping_type: 0,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn vp8_header_color_space() {
+        // color space 1 and clamping type 1
+        let b = [
+            0x10, 0x00, 0x00, 0x9d, 0x01, 0x2a, 0x01, 0x00, 0x01, 0x00, 0xc0, 0x00,
+        ];
+        let key_frame = VP8Header::from_bytes(&b).unwrap().key_frame.unwrap();
+        assert_eq!(key_frame.color_space, 1);
+        assert_eq!(key_frame.clamping_type, 1);
+    }
+
+    #[test]
+    fn vp8_header_interframe() {
+        let header = VP8Header::from_bytes(&[0x01, 0x00, 0x00]).unwrap();
+        assert_eq!(header.frame_type, VP8FrameType::InterFrame);
+        assert_eq!(header.key_frame, None);
+    }
+
+    #[test]
+    fn vp8_header_malformed() {
+        assert_eq!(VP8Header::from_bytes(&[0x00, 0x00]), Err(Error::Truncated));
+        assert_eq!(
+            VP8Header::from_bytes(&[0x00, 0x00, 0x00, 0x9d, 0x01, 0x2a, 0x01]),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            VP8Header::from_bytes(&[0x00, 0x00, 0x00, 0x9d, 0x01, 0x2b, 0, 0, 0, 0, 0, 0]),
+            Err(Error::WrongSignature)
+        );
+    }
 
-        let width = u16::from_le_bytes(b[6..8].try_into().unwrap());
-        let height = u16::from_le_bytes(b[8..10].try_into().unwrap());
+    #[test]
+    fn vp8l_header() {
+        let header = VP8LHeader::from_bytes(&[0x2f, 0x01, 0x80, 0x00, 0x10, 0xff]).unwrap();
+        assert_eq!(
+            header,
+            VP8LHeader {
+                width: 2,
+                height: 3,
+                alpha_is_used: true,
+                version: 0,
+            }
+        );
 
-        (width & 0x3FFF, height & 0x3FFF)
-    } else {
-        panic!("expected keyframe")
+        assert_eq!(
+            VP8LHeader::from_bytes(&[0x2e, 0, 0, 0, 0]),
+            Err(Error::WrongSignature)
+        );
+        assert_eq!(VP8LHeader::from_bytes(&[0x2f, 0, 0]), Err(Error::Truncated));
+        assert_eq!(
+            VP8LHeader::from_bytes(&[0x2f, 0, 0, 0, 0x20]),
+            Err(Error::InvalidData)
+        );
     }
 }
@@ -0,0 +1,139 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    riff::{RiffChunk, RiffContent},
+    util::{read_checked, read_u8_array, trim_nul},
+    Result,
+};
+
+pub const CHUNK_LABL: [u8; 4] = [b'l', b'a', b'b', b'l'];
+pub const CHUNK_NOTE: [u8; 4] = [b'n', b'o', b't', b'e'];
+pub const CHUNK_LTXT: [u8; 4] = [b'l', b't', b'x', b't'];
+
+/// An entry of the `LIST` chunk with a form type of `adtl` of a [`Wav`][super::Wav]
+///
+/// Every entry refers to a [`WavCuePoint`][super::WavCuePoint] by its id.
+/// The text is stored without its NUL terminator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WavAssociatedData {
+    /// The title of a cue point, stored in a `labl` chunk
+    Label {
+        /// The id of the cue point
+        cue_id: u32,
+        /// The title
+        text: Bytes,
+    },
+    /// A comment about a cue point, stored in a `note` chunk
+    Note {
+        /// The id of the cue point
+        cue_id: u32,
+        /// The comment
+        text: Bytes,
+    },
+    /// A region starting at a cue point, stored in an `ltxt` chunk
+    LabeledText {
+        /// The id of the cue point
+        cue_id: u32,
+        /// The length of the region, in frames
+        sample_length: u32,
+        /// The purpose of the region, like `rgn `
+        purpose: [u8; 4],
+        /// The country code of the text
+        country: u16,
+        /// The language code of the text
+        language: u16,
+        /// The dialect code of the text
+        dialect: u16,
+        /// The code page of the text
+        code_page: u16,
+        /// The text
+        text: Bytes,
+    },
+    /// Any other chunk, kept as is
+    Other(RiffChunk),
+}
+
+impl WavAssociatedData {
+    /// Parse the subchunks of an `adtl` list
+    ///
+    /// # Errors
+    ///
+    /// This method fails if a `labl`, `note` or `ltxt` chunk is truncated.
+    pub fn from_chunks(chunks: &[RiffChunk]) -> Result<Vec<WavAssociatedData>> {
+        chunks.iter().map(WavAssociatedData::from_chunk).collect()
+    }
+
+    fn from_chunk(chunk: &RiffChunk) -> Result<WavAssociatedData> {
+        let mut b = match (chunk.id(), chunk.content().data()) {
+            (CHUNK_LABL | CHUNK_NOTE | CHUNK_LTXT, Some(data)) => data.clone(),
+            _ => return Ok(WavAssociatedData::Other(chunk.clone())),
+        };
+
+        let cue_id = read_checked(&mut b, |b| b.get_u32_le())?;
+        Ok(match chunk.id() {
+            CHUNK_LABL => WavAssociatedData::Label {
+                cue_id,
+                text: trim_nul(&b),
+            },
+            CHUNK_NOTE => WavAssociatedData::Note {
+                cue_id,
+                text: trim_nul(&b),
+            },
+            _ => WavAssociatedData::LabeledText {
+                cue_id,
+                sample_length: read_checked(&mut b, |b| b.get_u32_le())?,
+                purpose: read_u8_array(&mut b)?,
+                country: read_checked(&mut b, |b| b.get_u16_le())?,
+                language: read_checked(&mut b, |b| b.get_u16_le())?,
+                dialect: read_checked(&mut b, |b| b.get_u16_le())?,
+                code_page: read_checked(&mut b, |b| b.get_u16_le())?,
+                text: trim_nul(&b),
+            },
+        })
+    }
+
+    /// Encode this `WavAssociatedData` into a subchunk of an `adtl` list
+    pub fn to_chunk(&self) -> RiffChunk {
+        let (id, b) = match self {
+            WavAssociatedData::Label { cue_id, text } => (CHUNK_LABL, text_bytes(*cue_id, text)),
+            WavAssociatedData::Note { cue_id, text } => (CHUNK_NOTE, text_bytes(*cue_id, text)),
+            WavAssociatedData::LabeledText {
+                cue_id,
+                sample_length,
+                purpose,
+                country,
+                language,
+                dialect,
+                code_page,
+                text,
+            } => {
+                let mut b = BytesMut::with_capacity(20 + text.len() + 1);
+                b.put_u32_le(*cue_id);
+                b.put_u32_le(*sample_length);
+                b.extend_from_slice(purpose);
+                b.put_u16_le(*country);
+                b.put_u16_le(*language);
+                b.put_u16_le(*dialect);
+                b.put_u16_le(*code_page);
+                if !text.is_empty() {
+                    b.extend_from_slice(text);
+                    b.put_u8(0);
+                }
+                (CHUNK_LTXT, b.freeze())
+            }
+            WavAssociatedData::Other(chunk) => return chunk.clone(),
+        };
+
+        RiffChunk::new(id, RiffContent::Data(b))
+    }
+}
+
+fn text_bytes(cue_id: u32, text: &[u8]) -> Bytes {
+    let mut b = BytesMut::with_capacity(4 + text.len() + 1);
+    b.put_u32_le(cue_id);
+    b.extend_from_slice(text);
+    b.put_u8(0);
+    b.freeze()
+}
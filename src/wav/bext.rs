@@ -0,0 +1,150 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{put_fixed, read_checked, read_u8_array, split_to_checked, trim_nul},
+    Result,
+};
+
+const DESCRIPTION_LEN: usize = 256;
+const ORIGINATOR_LEN: usize = 32;
+const ORIGINATOR_REFERENCE_LEN: usize = 32;
+const ORIGINATION_DATE_LEN: usize = 10;
+const ORIGINATION_TIME_LEN: usize = 8;
+const UMID_LEN: usize = 64;
+const RESERVED_LEN: usize = 180;
+// the size of the fixed fields, preceding the coding history
+const FIXED_LEN: usize = DESCRIPTION_LEN
+    + ORIGINATOR_LEN
+    + ORIGINATOR_REFERENCE_LEN
+    + ORIGINATION_DATE_LEN
+    + ORIGINATION_TIME_LEN
+    + 8
+    + 2
+    + UMID_LEN
+    + 5 * 2
+    + RESERVED_LEN;
+
+/// The contents of the `bext` chunk of a Broadcast Wave Format [`Wav`][super::Wav]
+///
+/// The text fields are ASCII strings. They are truncated to the
+/// size of their field when encoded.
+///
+/// See EBU Tech 3285
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WavBext {
+    /// The description of the sound sequence, up to 256 characters
+    pub description: Bytes,
+    /// The name of the originator, up to 32 characters
+    pub originator: Bytes,
+    /// The reference of the originator, up to 32 characters
+    pub originator_reference: Bytes,
+    /// The creation date, formatted as `yyyy-mm-dd`
+    pub origination_date: Bytes,
+    /// The creation time, formatted as `hh:mm:ss`
+    pub origination_time: Bytes,
+    /// The number of samples since midnight of the first sample
+    pub time_reference: u64,
+    /// The version of the `bext` chunk
+    pub version: u16,
+    /// The SMPTE UMID of the sound sequence
+    pub umid: [u8; UMID_LEN],
+    /// The integrated loudness, in LUFS multiplied by 100
+    pub loudness_value: i16,
+    /// The loudness range, in LU multiplied by 100
+    pub loudness_range: i16,
+    /// The maximum true peak level, in dBTP multiplied by 100
+    pub max_true_peak_level: i16,
+    /// The maximum momentary loudness, in LUFS multiplied by 100
+    pub max_momentary_loudness: i16,
+    /// The maximum short term loudness, in LUFS multiplied by 100
+    pub max_short_term_loudness: i16,
+    /// The coding history, made of CR LF terminated lines
+    pub coding_history: Bytes,
+}
+
+impl WavBext {
+    /// Parse the contents of a `bext` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<WavBext> {
+        let mut b = b.clone();
+        let mut string = |len| split_to_checked(&mut b, len).map(|s| trim_nul(&s));
+
+        let description = string(DESCRIPTION_LEN)?;
+        let originator = string(ORIGINATOR_LEN)?;
+        let originator_reference = string(ORIGINATOR_REFERENCE_LEN)?;
+        let origination_date = string(ORIGINATION_DATE_LEN)?;
+        let origination_time = string(ORIGINATION_TIME_LEN)?;
+
+        let time_reference = read_checked(&mut b, |b| b.get_u64_le())?;
+        let version = read_checked(&mut b, |b| b.get_u16_le())?;
+        let umid = read_u8_array(&mut b)?;
+        let loudness_value = read_checked(&mut b, |b| b.get_i16_le())?;
+        let loudness_range = read_checked(&mut b, |b| b.get_i16_le())?;
+        let max_true_peak_level = read_checked(&mut b, |b| b.get_i16_le())?;
+        let max_momentary_loudness = read_checked(&mut b, |b| b.get_i16_le())?;
+        let max_short_term_loudness = read_checked(&mut b, |b| b.get_i16_le())?;
+        split_to_checked(&mut b, RESERVED_LEN)?;
+
+        Ok(WavBext {
+            description,
+            originator,
+            originator_reference,
+            origination_date,
+            origination_time,
+            time_reference,
+            version,
+            umid,
+            loudness_value,
+            loudness_range,
+            max_true_peak_level,
+            max_momentary_loudness,
+            max_short_term_loudness,
+            coding_history: trim_nul(&b),
+        })
+    }
+
+    /// Encode this `WavBext` into the contents of a `bext` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(FIXED_LEN + self.coding_history.len());
+        put_fixed(&mut b, &self.description, DESCRIPTION_LEN);
+        put_fixed(&mut b, &self.originator, ORIGINATOR_LEN);
+        put_fixed(&mut b, &self.originator_reference, ORIGINATOR_REFERENCE_LEN);
+        put_fixed(&mut b, &self.origination_date, ORIGINATION_DATE_LEN);
+        put_fixed(&mut b, &self.origination_time, ORIGINATION_TIME_LEN);
+        b.put_u64_le(self.time_reference);
+        b.put_u16_le(self.version);
+        b.extend_from_slice(&self.umid);
+        b.put_i16_le(self.loudness_value);
+        b.put_i16_le(self.loudness_range);
+        b.put_i16_le(self.max_true_peak_level);
+        b.put_i16_le(self.max_momentary_loudness);
+        b.put_i16_le(self.max_short_term_loudness);
+        b.put_bytes(0, RESERVED_LEN);
+        b.extend_from_slice(&self.coding_history);
+        b.freeze()
+    }
+}
+
+impl Default for WavBext {
+    fn default() -> WavBext {
+        WavBext {
+            description: Bytes::new(),
+            originator: Bytes::new(),
+            originator_reference: Bytes::new(),
+            origination_date: Bytes::new(),
+            origination_time: Bytes::new(),
+            time_reference: 0,
+            version: 2,
+            umid: [0; UMID_LEN],
+            loudness_value: 0,
+            loudness_range: 0,
+            max_true_peak_level: 0,
+            max_momentary_loudness: 0,
+            max_short_term_loudness: 0,
+            coding_history: Bytes::new(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::CHUNK_CUE;
+use crate::{
+    riff::{RiffChunk, RiffContent},
+    util::{read_checked, read_u8_array},
+    Result,
+};
+
+const CUE_POINT_SIZE: usize = 24;
+
+/// A cue point of a [`Wav`][super::Wav], stored in the `cue ` chunk
+///
+/// Labels and notes can be attached to a cue point through
+/// its `id`, see [`WavAssociatedData`][super::WavAssociatedData].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WavCuePoint {
+    /// The unique identifier of the cue point
+    pub id: u32,
+    /// The position of the cue point in the playlist, in frames
+    pub position: u32,
+    /// The id of the chunk holding the cue point, usually `data`
+    pub data_chunk_id: [u8; 4],
+    /// The position of the chunk holding the cue point, `0` for `data`
+    pub chunk_start: u32,
+    /// The position of the block holding the cue point, `0` for uncompressed data
+    pub block_start: u32,
+    /// The position of the cue point in the block, in frames
+    pub sample_offset: u32,
+}
+
+impl WavCuePoint {
+    /// Construct a new `WavCuePoint` at frame `position` of the `data` chunk
+    pub fn new(id: u32, position: u32) -> WavCuePoint {
+        WavCuePoint {
+            id,
+            position,
+            data_chunk_id: super::CHUNK_DATA,
+            chunk_start: 0,
+            block_start: 0,
+            sample_offset: position,
+        }
+    }
+
+    /// Parse the contents of a `cue ` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<Vec<WavCuePoint>> {
+        let mut b = b.clone();
+
+        let count = read_checked(&mut b, |b| b.get_u32_le())?;
+        let mut points = Vec::with_capacity((count as usize).min(b.len() / CUE_POINT_SIZE));
+        for _ in 0..count {
+            points.push(WavCuePoint {
+                id: read_checked(&mut b, |b| b.get_u32_le())?,
+                position: read_checked(&mut b, |b| b.get_u32_le())?,
+                data_chunk_id: read_u8_array(&mut b)?,
+                chunk_start: read_checked(&mut b, |b| b.get_u32_le())?,
+                block_start: read_checked(&mut b, |b| b.get_u32_le())?,
+                sample_offset: read_checked(&mut b, |b| b.get_u32_le())?,
+            });
+        }
+
+        Ok(points)
+    }
+}
+
+/// Encode `points` into a `cue ` chunk
+pub fn cue_chunk(points: &[WavCuePoint]) -> RiffChunk {
+    let mut b = BytesMut::with_capacity(4 + points.len() * CUE_POINT_SIZE);
+    b.put_u32_le(points.len() as u32);
+    for point in points {
+        b.put_u32_le(point.id);
+        b.put_u32_le(point.position);
+        b.extend_from_slice(&point.data_chunk_id);
+        b.put_u32_le(point.chunk_start);
+        b.put_u32_le(point.block_start);
+        b.put_u32_le(point.sample_offset);
+    }
+
+    RiffChunk::new(CHUNK_CUE, RiffContent::Data(b.freeze()))
+}
@@ -0,0 +1,208 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{read_checked, read_u8_array, split_to_checked},
+    Error, Result,
+};
+
+pub const FORMAT_PCM: u16 = 0x0001;
+pub const FORMAT_IEEE_FLOAT: u16 = 0x0003;
+pub const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+// the sub formats of WAVE_FORMAT_EXTENSIBLE end with this GUID suffix,
+// after the 2 bytes holding the format code
+const SUB_FORMAT_SUFFIX: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+
+/// The encoding of the samples of a [`Wav`][super::Wav]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    /// Integer PCM samples
+    Pcm,
+    /// IEEE 754 floating point samples
+    Float,
+    /// Any other format code, like a compressed format
+    Other(u16),
+}
+
+/// The contents of the `fmt ` chunk of a [`Wav`][super::Wav]
+///
+/// See <https://learn.microsoft.com/en-us/windows/win32/api/mmreg/ns-mmreg-waveformatex>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WavFormat {
+    /// The format code, like `1` for PCM or `0xFFFE` for `WAVE_FORMAT_EXTENSIBLE`
+    pub format_tag: u16,
+    /// The number of channels
+    pub channels: u16,
+    /// The number of frames per second
+    pub sample_rate: u32,
+    /// The average number of bytes per second
+    pub byte_rate: u32,
+    /// The size of a frame, in bytes
+    pub block_align: u16,
+    /// The number of bits of every sample
+    pub bits_per_sample: u16,
+    /// The `WAVE_FORMAT_EXTENSIBLE` fields
+    pub extensible: Option<WavExtensible>,
+    /// The remaining format specific bytes, after the `cbSize` field
+    pub extra: Bytes,
+}
+
+/// The `WAVE_FORMAT_EXTENSIBLE` fields of a [`WavFormat`]
+///
+/// See <https://learn.microsoft.com/en-us/windows/win32/api/mmreg/ns-mmreg-waveformatextensible>
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WavExtensible {
+    /// The number of bits of precision of every sample
+    pub valid_bits_per_sample: u16,
+    /// The speaker positions of the channels
+    pub channel_mask: u32,
+    /// The GUID of the format of the samples
+    pub sub_format: [u8; 16],
+}
+
+impl WavFormat {
+    /// Parse the contents of a `fmt ` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<WavFormat> {
+        let mut b = b.clone();
+
+        let format_tag = read_checked(&mut b, |b| b.get_u16_le())?;
+        let channels = read_checked(&mut b, |b| b.get_u16_le())?;
+        let sample_rate = read_checked(&mut b, |b| b.get_u32_le())?;
+        let byte_rate = read_checked(&mut b, |b| b.get_u32_le())?;
+        let block_align = read_checked(&mut b, |b| b.get_u16_le())?;
+        // PCMWAVEFORMAT, used by old PCM files, ends here
+        let bits_per_sample = if b.is_empty() {
+            0
+        } else {
+            read_checked(&mut b, |b| b.get_u16_le())?
+        };
+
+        let mut extensible = None;
+        let mut extra = Bytes::new();
+        if !b.is_empty() {
+            let size = read_checked(&mut b, |b| b.get_u16_le())?;
+            let mut cb = split_to_checked(&mut b, size.into())?;
+
+            if format_tag == FORMAT_EXTENSIBLE {
+                extensible = Some(WavExtensible {
+                    valid_bits_per_sample: read_checked(&mut cb, |b| b.get_u16_le())?,
+                    channel_mask: read_checked(&mut cb, |b| b.get_u32_le())?,
+                    sub_format: read_u8_array(&mut cb)?,
+                });
+            }
+            extra = cb;
+        }
+
+        Ok(WavFormat {
+            format_tag,
+            channels,
+            sample_rate,
+            byte_rate,
+            block_align,
+            bits_per_sample,
+            extensible,
+            extra,
+        })
+    }
+
+    /// Get the encoding of the samples, looking into the
+    /// sub format of `WAVE_FORMAT_EXTENSIBLE`
+    pub fn sample_format(&self) -> WavSampleFormat {
+        let tag = match (self.format_tag, &self.extensible) {
+            (FORMAT_EXTENSIBLE, Some(extensible))
+                if extensible.sub_format[2..] == SUB_FORMAT_SUFFIX =>
+            {
+                u16::from_le_bytes([extensible.sub_format[0], extensible.sub_format[1]])
+            }
+            (tag, _) => tag,
+        };
+
+        match tag {
+            FORMAT_PCM => WavSampleFormat::Pcm,
+            FORMAT_IEEE_FLOAT => WavSampleFormat::Float,
+            tag => WavSampleFormat::Other(tag),
+        }
+    }
+
+    /// Encode this `WavFormat` into the contents of a `fmt ` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if the format specific
+    /// bytes don't fit in the `cbSize` field.
+    pub fn to_bytes(&self) -> Result<Bytes> {
+        let cb_size = self.extra.len() + if self.extensible.is_some() { 22 } else { 0 };
+        let cb_size = u16::try_from(cb_size).map_err(|_| Error::InvalidData)?;
+
+        let mut b = BytesMut::with_capacity(18 + usize::from(cb_size));
+        b.put_u16_le(self.format_tag);
+        b.put_u16_le(self.channels);
+        b.put_u32_le(self.sample_rate);
+        b.put_u32_le(self.byte_rate);
+        b.put_u16_le(self.block_align);
+        b.put_u16_le(self.bits_per_sample);
+
+        // plain PCM doesn't have the cbSize field
+        if self.format_tag != FORMAT_PCM || cb_size != 0 {
+            b.put_u16_le(cb_size);
+        }
+        if let Some(extensible) = &self.extensible {
+            b.put_u16_le(extensible.valid_bits_per_sample);
+            b.put_u32_le(extensible.channel_mask);
+            b.extend_from_slice(&extensible.sub_format);
+        }
+        b.extend_from_slice(&self.extra);
+
+        Ok(b.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_extensible() {
+        let mut sub_format = [0; 16];
+        sub_format[0] = 0x03;
+        sub_format[2..].copy_from_slice(&SUB_FORMAT_SUFFIX);
+
+        let format = WavFormat {
+            format_tag: FORMAT_EXTENSIBLE,
+            channels: 2,
+            sample_rate: 48000,
+            byte_rate: 48000 * 8,
+            block_align: 8,
+            bits_per_sample: 32,
+            extensible: Some(WavExtensible {
+                valid_bits_per_sample: 32,
+                channel_mask: 0b11,
+                sub_format,
+            }),
+            extra: Bytes::new(),
+        };
+
+        let b = format.to_bytes().unwrap();
+        assert_eq!(b.len(), 40);
+        let parsed = WavFormat::from_bytes(&b).unwrap();
+        assert_eq!(parsed, format);
+        assert_eq!(parsed.sample_format(), WavSampleFormat::Float);
+    }
+
+    #[test]
+    fn format_pcmwaveformat() {
+        let b = Bytes::from_static(&[1, 0, 1, 0, 0x44, 0xac, 0, 0, 0x88, 0x58, 1, 0, 2, 0]);
+        let format = WavFormat::from_bytes(&b).unwrap();
+        assert_eq!(format.sample_format(), WavSampleFormat::Pcm);
+        assert_eq!(format.sample_rate, 44100);
+        assert_eq!(format.bits_per_sample, 0);
+
+        assert_eq!(WavFormat::from_bytes(&b.slice(..10)), Err(Error::Truncated));
+    }
+}
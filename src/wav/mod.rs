@@ -0,0 +1,368 @@
+use alloc::vec::Vec;
+use core::{ops::Range, time::Duration};
+
+use bytes::Bytes;
+
+use crate::{
+    encoder::ImageEncoder,
+    riff::{impl_set_info, RiffChunk, RiffContent, RiffInfo, RiffPreset, RiffVariant},
+    util::impl_subchunks,
+    Error, Result,
+};
+
+pub use crate::riff::CHUNK_DS64;
+
+pub use self::{
+    adtl::WavAssociatedData,
+    bext::WavBext,
+    cue::{cue_chunk, WavCuePoint},
+    format::{WavExtensible, WavFormat, WavSampleFormat},
+    smpl::{WavSampleLoop, WavSampler},
+};
+
+mod adtl;
+mod bext;
+mod cue;
+mod format;
+mod smpl;
+
+pub const CHUNK_BEXT: [u8; 4] = [b'b', b'e', b'x', b't'];
+pub const CHUNK_CUE: [u8; 4] = [b'c', b'u', b'e', b' '];
+pub const CHUNK_DATA: [u8; 4] = [b'd', b'a', b't', b'a'];
+pub const CHUNK_FMT: [u8; 4] = [b'f', b'm', b't', b' '];
+pub const CHUNK_IXML: [u8; 4] = [b'i', b'X', b'M', b'L'];
+pub const CHUNK_LIST: [u8; 4] = [b'L', b'I', b'S', b'T'];
+pub const CHUNK_SMPL: [u8; 4] = [b's', b'm', b'p', b'l'];
+
+pub const LIST_ADTL: [u8; 4] = [b'a', b'd', b't', b'l'];
+
+// id (4 bytes) + size (4 bytes) + form type (4 bytes)
+const HEADER_SIZE: u64 = 12;
+
+/// The representation of a WAV audio file
+///
+/// Only the chunks being read or written are parsed, the audio samples
+/// in the `data` chunk are never copied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wav {
+    riff: RiffChunk,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Wav {
+    /// Construct a new `Wav` from a [`RiffChunk`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns a [`Error::WrongSignature`] if the [`RiffChunk`]
+    /// isn't a little-endian `RIFF`, `RF64` or `BW64` chunk, if its content
+    /// isn't a `List` or if the list's kind isn't "WAVE".
+    pub fn new(riff: RiffChunk) -> Result<Wav> {
+        let little_endian = matches!(riff.variant(), Some(variant) if !variant.is_big_endian());
+        match riff.content().list() {
+            Some((Some(kind), _)) if little_endian && kind == *b"WAVE" => Ok(Wav { riff }),
+            _ => Err(Error::WrongSignature),
+        }
+    }
+
+    /// Create a new `Wav` from a Reader.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the file signature doesn't match or if
+    /// it is corrupted or truncated.
+    #[inline]
+    pub fn from_bytes(b: Bytes) -> Result<Wav> {
+        let riff = RiffChunk::from_bytes_with_schema(b, &RiffPreset::Wav)?;
+        Wav::new(riff)
+    }
+
+    /// Get the [`RiffVariant`] of this `Wav`
+    #[inline]
+    pub fn variant(&self) -> RiffVariant {
+        self.riff.variant().expect("checked by Wav::new")
+    }
+
+    /// Get the parsed `fmt ` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `fmt ` chunk is missing, malformed or truncated.
+    pub fn format(&self) -> Result<WavFormat> {
+        let data = self
+            .chunk_by_id(CHUNK_FMT)
+            .and_then(|chunk| chunk.content().data())
+            .ok_or(Error::InvalidData)?;
+        WavFormat::from_bytes(data)
+    }
+
+    /// Replace the `fmt ` chunk with `format`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `format` can't be encoded, see [`WavFormat::to_bytes`].
+    pub fn set_format(&mut self, format: &WavFormat) -> Result<()> {
+        // keep the layout of the current chunk, like a 14 byte PCMWAVEFORMAT,
+        // if nothing changed
+        if self.format().as_ref() == Ok(format) {
+            return Ok(());
+        }
+
+        let data = format.to_bytes()?;
+        self.set_chunk(CHUNK_FMT, Some(RiffContent::Data(data)));
+        Ok(())
+    }
+
+    /// Get the audio samples, stored in the `data` chunk.
+    pub fn data(&self) -> Option<&Bytes> {
+        self.chunk_by_id(CHUNK_DATA)?.content().data()
+    }
+
+    /// Get the position of the audio samples in the encoded file.
+    ///
+    /// Returns `None` if this `Wav` doesn't have a `data` chunk.
+    pub fn data_range(&self) -> Option<Range<u64>> {
+        let pos = self
+            .chunks()
+            .iter()
+            .position(|chunk| chunk.id() == CHUNK_DATA)?;
+        let data = self.chunks()[pos].content().data()?;
+
+        let start = HEADER_SIZE
+            + self.chunks()[..pos]
+                .iter()
+                .map(|chunk| chunk.len())
+                .sum::<u64>()
+            + 8;
+        Some(start..start + data.len() as u64)
+    }
+
+    /// Get the number of frames, made of one sample per channel.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `fmt ` chunk is invalid, if its block
+    /// alignment is `0` or if this `Wav` doesn't have a `data` chunk.
+    pub fn frame_count(&self) -> Result<u64> {
+        let format = self.format()?;
+        let data = self.data().ok_or(Error::InvalidData)?;
+        if format.block_align == 0 {
+            return Err(Error::InvalidData);
+        }
+
+        Ok(data.len() as u64 / u64::from(format.block_align))
+    }
+
+    /// Get the duration of the audio.
+    ///
+    /// # Errors
+    ///
+    /// This method fails for the same reasons as [`frame_count`][Wav::frame_count]
+    /// or if the sample rate is `0`.
+    pub fn duration(&self) -> Result<Duration> {
+        let frame_count = self.frame_count()?;
+        let sample_rate = u64::from(self.format()?.sample_rate);
+        if sample_rate == 0 {
+            return Err(Error::InvalidData);
+        }
+
+        let secs = frame_count / sample_rate;
+        let nanos = (frame_count % sample_rate) * 1_000_000_000 / sample_rate;
+        Ok(Duration::new(secs, nanos as u32))
+    }
+
+    /// Get the parsed `bext` chunk of a Broadcast Wave Format file.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `bext` chunk is malformed or truncated.
+    pub fn bext(&self) -> Result<Option<WavBext>> {
+        self.chunk_by_id(CHUNK_BEXT)
+            .and_then(|chunk| chunk.content().data())
+            .map(WavBext::from_bytes)
+            .transpose()
+    }
+
+    /// Set the `bext` chunk, or remove it if `bext` is `None`.
+    ///
+    /// A new `bext` chunk is added before the `fmt ` chunk.
+    pub fn set_bext(&mut self, bext: Option<&WavBext>) {
+        let content = bext.map(|bext| RiffContent::Data(bext.to_bytes()));
+        self.set_chunk(CHUNK_BEXT, content);
+    }
+
+    /// Get the raw iXML document, stored in the `iXML` chunk.
+    pub fn ixml(&self) -> Option<Bytes> {
+        Some(self.chunk_by_id(CHUNK_IXML)?.content().data()?.clone())
+    }
+
+    /// Set the raw iXML document, or remove it if `ixml` is `None`.
+    pub fn set_ixml(&mut self, ixml: Option<Bytes>) {
+        self.set_chunk(CHUNK_IXML, ixml.map(RiffContent::Data));
+    }
+
+    /// Get the cue points, stored in the `cue ` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `cue ` chunk is malformed or truncated.
+    pub fn cue_points(&self) -> Result<Vec<WavCuePoint>> {
+        match self
+            .chunk_by_id(CHUNK_CUE)
+            .and_then(|chunk| chunk.content().data())
+        {
+            Some(data) => WavCuePoint::from_bytes(data),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Set the cue points, or remove the `cue ` chunk if `points` is empty.
+    pub fn set_cue_points(&mut self, points: &[WavCuePoint]) {
+        let content = if points.is_empty() {
+            None
+        } else {
+            Some(cue_chunk(points).content().clone())
+        };
+        self.set_chunk(CHUNK_CUE, content);
+    }
+
+    /// Get the labels, notes and regions attached to the cue points,
+    /// stored in the `LIST` chunk with a form type of `adtl`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if one of the entries is malformed or truncated.
+    pub fn associated_data(&self) -> Result<Vec<WavAssociatedData>> {
+        match self.adtl_position() {
+            Some(pos) => {
+                let (_, subchunks) = self.chunks()[pos].content().list().unwrap();
+                WavAssociatedData::from_chunks(subchunks)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Set the labels, notes and regions attached to the cue points,
+    /// or remove the `adtl` list if `entries` is empty.
+    pub fn set_associated_data(&mut self, entries: &[WavAssociatedData]) {
+        let pos = self.adtl_position();
+        let list = RiffChunk::new(
+            CHUNK_LIST,
+            RiffContent::List {
+                kind: Some(LIST_ADTL),
+                subchunks: entries.iter().map(WavAssociatedData::to_chunk).collect(),
+            },
+        );
+
+        match (pos, entries.is_empty()) {
+            (Some(pos), true) => {
+                self.chunks_mut().remove(pos);
+            }
+            (Some(pos), false) => self.chunks_mut()[pos] = list,
+            (None, true) => {}
+            (None, false) => self.chunks_mut().push(list),
+        }
+    }
+
+    fn adtl_position(&self) -> Option<usize> {
+        self.chunks().iter().position(|chunk| {
+            chunk.id() == CHUNK_LIST && matches!(chunk.content().list(), Some((Some(LIST_ADTL), _)))
+        })
+    }
+
+    /// Get the parsed `smpl` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `smpl` chunk is malformed or truncated.
+    pub fn sampler(&self) -> Result<Option<WavSampler>> {
+        self.chunk_by_id(CHUNK_SMPL)
+            .and_then(|chunk| chunk.content().data())
+            .map(WavSampler::from_bytes)
+            .transpose()
+    }
+
+    /// Set the `smpl` chunk, or remove it if `sampler` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `sampler` can't be encoded, see [`WavSampler::to_bytes`].
+    pub fn set_sampler(&mut self, sampler: Option<&WavSampler>) -> Result<()> {
+        let content = sampler
+            .map(|sampler| sampler.to_bytes().map(RiffContent::Data))
+            .transpose()?;
+        self.set_chunk(CHUNK_SMPL, content);
+        Ok(())
+    }
+
+    /// Replace the chunks with an id of `id` with a single chunk holding `content`,
+    /// or remove them if `content` is `None`.
+    ///
+    /// The new chunk takes the place of the first one being replaced. Otherwise
+    /// `bext` and `fmt ` are added at the start, after `ds64`, and any
+    /// other chunk is added at the end.
+    fn set_chunk(&mut self, id: [u8; 4], content: Option<RiffContent>) {
+        let pos = self.chunks().iter().position(|chunk| chunk.id() == id);
+        self.remove_chunks_by_id(id);
+
+        if let Some(content) = content {
+            let pos = pos.unwrap_or_else(|| {
+                if id == CHUNK_BEXT || id == CHUNK_FMT {
+                    self.chunks()
+                        .iter()
+                        .position(|chunk| chunk.id() != CHUNK_DS64)
+                        .unwrap_or(self.chunks().len())
+                } else {
+                    self.chunks().len()
+                }
+            });
+
+            let chunk = RiffChunk::new(id, content);
+            self.chunks_mut().insert(pos, chunk);
+        }
+    }
+
+    /// Get the parsed `LIST` chunk with a form type of `INFO`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `INFO` list is malformed or truncated.
+    #[inline]
+    pub fn info(&self) -> Result<Option<RiffInfo>> {
+        self.riff.info()
+    }
+
+    impl_set_info!(riff);
+
+    impl_subchunks!("Wav", riff);
+
+    /// Get the total size of the `Wav` once it is encoded.
+    ///
+    /// Internally calls [`RiffChunk::len`][crate::riff::RiffChunk::len] on the
+    /// inner `RiffChunk`
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.riff.len()
+    }
+
+    /// Create an [encoder][crate::ImageEncoder] for this `Wav`
+    ///
+    /// Internally calls [`RiffChunk::try_encoder`][crate::riff::RiffChunk::try_encoder]
+    /// on the inner `RiffChunk`
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LimitExceeded`] if this `Wav` is bigger
+    /// than 4 GiB and isn't an `RF64` or `BW64` file, see
+    /// [`RiffChunk::promote_to_rf64`][crate::riff::RiffChunk::promote_to_rf64].
+    #[inline]
+    pub fn encoder(self) -> Result<ImageEncoder<RiffChunk>> {
+        self.riff.try_encoder()
+    }
+
+    /// Get the inner [`RiffChunk`] of this `Wav`
+    #[inline]
+    pub fn into_inner(self) -> RiffChunk {
+        self.riff
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    util::{read_checked, split_to_checked},
+    Error, Result,
+};
+
+const SAMPLER_SIZE: usize = 36;
+const SAMPLE_LOOP_SIZE: usize = 24;
+
+/// The contents of the `smpl` chunk of a [`Wav`][super::Wav]
+///
+/// Describes how a sampler plays the sound, like its MIDI
+/// note and its loops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WavSampler {
+    /// The MMA manufacturer code of the intended sampler, or `0`
+    pub manufacturer: u32,
+    /// The product code of the intended sampler, or `0`
+    pub product: u32,
+    /// The duration of a frame, in nanoseconds
+    pub sample_period: u32,
+    /// The MIDI note played at the original pitch
+    pub midi_unity_note: u32,
+    /// The fraction of a semitone above `midi_unity_note`
+    pub midi_pitch_fraction: u32,
+    /// The SMPTE time format, like `25` or `30`, or `0`
+    pub smpte_format: u32,
+    /// The SMPTE time offset of the first frame
+    pub smpte_offset: u32,
+    /// The loops
+    pub loops: Vec<WavSampleLoop>,
+    /// The sampler specific data
+    pub sampler_data: Bytes,
+}
+
+/// A loop of a [`WavSampler`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WavSampleLoop {
+    /// The id of the cue point of the loop
+    pub cue_point_id: u32,
+    /// The kind of loop, `0` for forward, `1` for alternating, `2` for backward
+    pub kind: u32,
+    /// The first frame of the loop
+    pub start: u32,
+    /// The last frame of the loop
+    pub end: u32,
+    /// The fraction of a frame at which the loop ends
+    pub fraction: u32,
+    /// The number of times the loop is played, `0` meaning infinitely
+    pub play_count: u32,
+}
+
+impl WavSampler {
+    /// Parse the contents of a `smpl` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<WavSampler> {
+        let mut b = b.clone();
+        let read_u32 = |b: &mut Bytes| read_checked(b, |b| b.get_u32_le());
+
+        let manufacturer = read_u32(&mut b)?;
+        let product = read_u32(&mut b)?;
+        let sample_period = read_u32(&mut b)?;
+        let midi_unity_note = read_u32(&mut b)?;
+        let midi_pitch_fraction = read_u32(&mut b)?;
+        let smpte_format = read_u32(&mut b)?;
+        let smpte_offset = read_u32(&mut b)?;
+        let loop_count = read_u32(&mut b)?;
+        let sampler_data_len = read_u32(&mut b)?;
+
+        let mut loops = Vec::with_capacity((loop_count as usize).min(b.len() / SAMPLE_LOOP_SIZE));
+        for _ in 0..loop_count {
+            loops.push(WavSampleLoop {
+                cue_point_id: read_u32(&mut b)?,
+                kind: read_u32(&mut b)?,
+                start: read_u32(&mut b)?,
+                end: read_u32(&mut b)?,
+                fraction: read_u32(&mut b)?,
+                play_count: read_u32(&mut b)?,
+            });
+        }
+        let sampler_data = split_to_checked(&mut b, sampler_data_len as usize)?;
+
+        Ok(WavSampler {
+            manufacturer,
+            product,
+            sample_period,
+            midi_unity_note,
+            midi_pitch_fraction,
+            smpte_format,
+            smpte_offset,
+            loops,
+            sampler_data,
+        })
+    }
+
+    /// Encode this `WavSampler` into the contents of a `smpl` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if there are
+    /// too many loops or too much sampler specific data.
+    pub fn to_bytes(&self) -> Result<Bytes> {
+        let loop_count = u32::try_from(self.loops.len()).map_err(|_| Error::InvalidData)?;
+        let sampler_data_len =
+            u32::try_from(self.sampler_data.len()).map_err(|_| Error::InvalidData)?;
+
+        let mut b = BytesMut::with_capacity(
+            SAMPLER_SIZE + self.loops.len() * SAMPLE_LOOP_SIZE + self.sampler_data.len(),
+        );
+        b.put_u32_le(self.manufacturer);
+        b.put_u32_le(self.product);
+        b.put_u32_le(self.sample_period);
+        b.put_u32_le(self.midi_unity_note);
+        b.put_u32_le(self.midi_pitch_fraction);
+        b.put_u32_le(self.smpte_format);
+        b.put_u32_le(self.smpte_offset);
+        b.put_u32_le(loop_count);
+        b.put_u32_le(sampler_data_len);
+        for sample_loop in &self.loops {
+            b.put_u32_le(sample_loop.cue_point_id);
+            b.put_u32_le(sample_loop.kind);
+            b.put_u32_le(sample_loop.start);
+            b.put_u32_le(sample_loop.end);
+            b.put_u32_le(sample_loop.fraction);
+            b.put_u32_le(sample_loop.play_count);
+        }
+        b.extend_from_slice(&self.sampler_data);
+
+        Ok(b.freeze())
+    }
+}
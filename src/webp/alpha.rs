@@ -0,0 +1,121 @@
+use bytes::Bytes;
+
+use crate::{Error, Result};
+
+/// The compression method of the alpha channel of a [`WebP`][super::WebP]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlphaCompression {
+    /// The alpha values are stored as raw bytes
+    None,
+    /// The alpha values are compressed as the green channel of a headerless VP8L bitstream
+    Lossless,
+}
+
+/// The filtering method applied to the alpha channel of a [`WebP`][super::WebP]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlphaFiltering {
+    /// No filtering
+    None,
+    /// Every value is predicted from the one on its left
+    Horizontal,
+    /// Every value is predicted from the one above it
+    Vertical,
+    /// Every value is predicted from the ones on its left, above it and above-left
+    Gradient,
+}
+
+/// The preprocessing applied to the alpha channel of a [`WebP`][super::WebP]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlphaPreprocessing {
+    /// No preprocessing
+    None,
+    /// The alpha values were quantized to a smaller number of levels
+    LevelReduction,
+}
+
+/// The contents of the `ALPH` chunk of a [`WebP`][super::WebP]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebPAlpha {
+    /// How the alpha values are compressed
+    pub compression: AlphaCompression,
+    /// How the alpha values are filtered
+    pub filtering: AlphaFiltering,
+    /// How the alpha values were preprocessed
+    pub preprocessing: AlphaPreprocessing,
+    data: Bytes,
+}
+
+impl WebPAlpha {
+    /// Parse the contents of an `ALPH` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is empty or if the header
+    /// contains an invalid value.
+    pub fn from_bytes(b: &Bytes) -> Result<WebPAlpha> {
+        let header = *b.first().ok_or(Error::Truncated)?;
+
+        let compression = match header & 0b11 {
+            0 => AlphaCompression::None,
+            1 => AlphaCompression::Lossless,
+            _ => return Err(Error::InvalidData),
+        };
+        let filtering = match (header >> 2) & 0b11 {
+            0 => AlphaFiltering::None,
+            1 => AlphaFiltering::Horizontal,
+            2 => AlphaFiltering::Vertical,
+            _ => AlphaFiltering::Gradient,
+        };
+        let preprocessing = match (header >> 4) & 0b11 {
+            0 => AlphaPreprocessing::None,
+            1 => AlphaPreprocessing::LevelReduction,
+            _ => return Err(Error::InvalidData),
+        };
+
+        Ok(WebPAlpha {
+            compression,
+            filtering,
+            preprocessing,
+            data: b.slice(1..),
+        })
+    }
+
+    /// Get the alpha bitstream following the header byte
+    #[inline]
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpha_header() {
+        let alpha = WebPAlpha::from_bytes(&Bytes::from_static(&[0b0001_1101, 0xaa])).unwrap();
+        assert_eq!(alpha.compression, AlphaCompression::Lossless);
+        assert_eq!(alpha.filtering, AlphaFiltering::Gradient);
+        assert_eq!(alpha.preprocessing, AlphaPreprocessing::LevelReduction);
+        assert_eq!(alpha.data(), &[0xaa][..]);
+
+        let alpha = WebPAlpha::from_bytes(&Bytes::from_static(&[0b0000_1000])).unwrap();
+        assert_eq!(alpha.compression, AlphaCompression::None);
+        assert_eq!(alpha.filtering, AlphaFiltering::Vertical);
+        assert_eq!(alpha.preprocessing, AlphaPreprocessing::None);
+        assert!(alpha.data().is_empty());
+    }
+
+    #[test]
+    fn alpha_header_malformed() {
+        assert_eq!(WebPAlpha::from_bytes(&Bytes::new()), Err(Error::Truncated));
+        assert_eq!(
+            WebPAlpha::from_bytes(&Bytes::from_static(&[0b0000_0010])),
+            Err(Error::InvalidData)
+        );
+        assert_eq!(
+            WebPAlpha::from_bytes(&Bytes::from_static(&[0b0010_0000])),
+            Err(Error::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{WebP, CHUNK_ALPH, CHUNK_ANMF, CHUNK_VP8, CHUNK_VP8L};
+use crate::{
+    riff::{RiffChunk, RiffContent, RiffPreset},
+    util::{read_checked, read_u8_array, try_u24_to_le_bytes, u24_from_le_bytes},
+    vp8::VP8LHeader,
+    Error, Result,
+};
+
+const FLAG_NO_BLEND: u8 = 0b0000_0010;
+const FLAG_DISPOSE: u8 = 0b0000_0001;
+
+/// The global parameters of an animated [`WebP`], stored in the `ANIM` chunk
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WebPAnimation {
+    /// The background color of the canvas, in `[blue, green, red, alpha]` order
+    pub background_color: [u8; 4],
+    /// The number of times the animation is played, `0` meaning infinitely
+    pub loop_count: u16,
+}
+
+impl WebPAnimation {
+    /// Parse the contents of an `ANIM` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<WebPAnimation> {
+        let mut b = b.clone();
+
+        let background_color = read_u8_array(&mut b)?;
+        let loop_count = read_checked(&mut b, |b| b.get_u16_le())?;
+
+        Ok(WebPAnimation {
+            background_color,
+            loop_count,
+        })
+    }
+
+    /// Encode this `WebPAnimation` into the contents of an `ANIM` chunk
+    pub fn to_bytes(&self) -> Bytes {
+        let mut b = BytesMut::with_capacity(6);
+        b.extend_from_slice(&self.background_color);
+        b.put_u16_le(self.loop_count);
+        b.freeze()
+    }
+}
+
+/// A frame of an animated [`WebP`], stored in an `ANMF` chunk
+///
+/// The frame holds its own image data in the `ALPH`, `VP8` or `VP8L` chunks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebPFrame {
+    /// The horizontal position of the frame on the canvas, which must be even
+    pub x: u32,
+    /// The vertical position of the frame on the canvas, which must be even
+    pub y: u32,
+    /// The width of the frame
+    pub width: u32,
+    /// The height of the frame
+    pub height: u32,
+    /// How long the frame is shown, in milliseconds
+    pub duration: u32,
+    /// Whether the frame is alpha-blended with the previous canvas.
+    /// If `false` the frame overwrites the covered area.
+    pub blend: bool,
+    /// Whether the area covered by the frame is disposed to the background
+    /// color before rendering the next frame
+    pub dispose: bool,
+    chunks: Vec<RiffChunk>,
+}
+
+impl WebPFrame {
+    /// Construct a new `WebPFrame` from the image data of the static `webp`
+    ///
+    /// The frame is as big as the image, is placed at `x` and `y`, it
+    /// is alpha-blended and isn't disposed.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `webp` is animated or if its bitstream header is invalid.
+    pub fn from_webp(webp: &WebP, x: u32, y: u32, duration: u32) -> Result<WebPFrame> {
+        if webp.has_chunk(CHUNK_ANMF) {
+            return Err(Error::InvalidData);
+        }
+
+        let (width, height) = webp.bitstream_dimensions()?;
+        let chunks = webp
+            .chunks()
+            .iter()
+            .filter(|chunk| is_frame_data(chunk.id()))
+            .cloned()
+            .collect();
+
+        Ok(WebPFrame {
+            x,
+            y,
+            width,
+            height,
+            duration,
+            blend: true,
+            dispose: false,
+            chunks,
+        })
+    }
+
+    /// Parse the contents of an `ANMF` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the chunk is corrupted or truncated.
+    pub fn from_bytes(b: &Bytes) -> Result<WebPFrame> {
+        let mut b = b.clone();
+
+        let x = u24_from_le_bytes(read_u8_array(&mut b)?) * 2;
+        let y = u24_from_le_bytes(read_u8_array(&mut b)?) * 2;
+        let width = u24_from_le_bytes(read_u8_array(&mut b)?) + 1;
+        let height = u24_from_le_bytes(read_u8_array(&mut b)?) + 1;
+        let duration = u24_from_le_bytes(read_u8_array(&mut b)?);
+        let flags = read_checked(&mut b, |b| b.get_u8())?;
+
+        let mut chunks = Vec::with_capacity(2);
+        while !b.is_empty() {
+            chunks.push(RiffChunk::from_bytes_impl(
+                &mut b,
+                false,
+                &RiffPreset::WebP,
+            )?);
+        }
+
+        Ok(WebPFrame {
+            x,
+            y,
+            width,
+            height,
+            duration,
+            blend: flags & FLAG_NO_BLEND == 0,
+            dispose: flags & FLAG_DISPOSE != 0,
+            chunks,
+        })
+    }
+
+    /// Encode this `WebPFrame` into an `ANMF` chunk
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if `x` or `y` is odd, or
+    /// if a field doesn't fit in the 24 bits of the `ANMF` chunk, which holds
+    /// up to 33554430 for `x` and `y`, 1 to 16777216 for `width` and
+    /// `height` and up to 16777215 for `duration`.
+    pub fn to_chunk(&self) -> Result<RiffChunk> {
+        if self.x % 2 != 0 || self.y % 2 != 0 {
+            return Err(Error::InvalidData);
+        }
+
+        let len = 16
+            + self
+                .chunks
+                .iter()
+                .map(|chunk| chunk.len() as usize)
+                .sum::<usize>();
+        let mut b = BytesMut::with_capacity(len);
+
+        let minus_one = |n: u32| n.checked_sub(1).ok_or(Error::InvalidData);
+        b.extend_from_slice(&try_u24_to_le_bytes(self.x / 2)?);
+        b.extend_from_slice(&try_u24_to_le_bytes(self.y / 2)?);
+        b.extend_from_slice(&try_u24_to_le_bytes(minus_one(self.width)?)?);
+        b.extend_from_slice(&try_u24_to_le_bytes(minus_one(self.height)?)?);
+        b.extend_from_slice(&try_u24_to_le_bytes(self.duration)?);
+
+        let mut flags = 0;
+        if !self.blend {
+            flags |= FLAG_NO_BLEND;
+        }
+        if self.dispose {
+            flags |= FLAG_DISPOSE;
+        }
+        b.put_u8(flags);
+
+        for chunk in &self.chunks {
+            for bytes in chunk.clone().encoder() {
+                b.extend_from_slice(&bytes);
+            }
+        }
+
+        Ok(RiffChunk::new(CHUNK_ANMF, RiffContent::Data(b.freeze())))
+    }
+
+    /// Get the chunks holding the image data of this `WebPFrame`
+    #[inline]
+    pub fn chunks(&self) -> &Vec<RiffChunk> {
+        &self.chunks
+    }
+
+    /// Get a mutable reference to the chunks holding the image data of this `WebPFrame`
+    #[inline]
+    pub fn chunks_mut(&mut self) -> &mut Vec<RiffChunk> {
+        &mut self.chunks
+    }
+
+    /// Check if the image data of this `WebPFrame` contains transparency
+    pub fn has_alpha(&self) -> bool {
+        self.chunks.iter().any(|chunk| match chunk.id() {
+            CHUNK_ALPH => true,
+            CHUNK_VP8L => chunk
+                .content()
+                .data()
+                .and_then(|data| VP8LHeader::from_bytes(data).ok())
+                .map_or(false, |header| header.alpha_is_used),
+            _ => false,
+        })
+    }
+
+    /// Convert this `WebPFrame` into a static `WebP`
+    ///
+    /// The image data of the frame is taken as is, without being composited
+    /// over the previous frames of the animation.
+    pub fn to_webp(&self) -> WebP {
+        let chunks = self
+            .chunks
+            .iter()
+            .filter(|chunk| is_frame_data(chunk.id()))
+            .cloned()
+            .collect();
+
+        let mut webp = WebP::from_chunks(chunks);
+        webp.update_vp8x();
+        webp
+    }
+}
+
+fn is_frame_data(id: [u8; 4]) -> bool {
+    matches!(id, CHUNK_ALPH | CHUNK_VP8 | CHUNK_VP8L)
+}
@@ -1,5 +1,12 @@
-use super::{WebP, CHUNK_EXIF, CHUNK_ICCP};
+use super::{WebP, CHUNK_ANIM, CHUNK_ANMF, CHUNK_EXIF, CHUNK_ICCP, CHUNK_XMP};
 
+const FLAG_ICC: u8 = 0b0010_0000;
+const FLAG_ALPHA: u8 = 0b0001_0000;
+const FLAG_EXIF: u8 = 0b0000_1000;
+const FLAG_XMP: u8 = 0b0000_0100;
+const FLAG_ANIMATION: u8 = 0b0000_0010;
+
+/// The feature flags stored in the `VP8X` chunk of a [`WebP`]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 pub struct WebPFlags(pub(super) [u8; 4]);
 
@@ -7,11 +14,68 @@ impl WebPFlags {
     pub(super) fn from_webp(webp: &WebP) -> WebPFlags {
         let mut flags = WebPFlags::default();
         if webp.has_chunk(CHUNK_ICCP) {
-            flags.0[0] |= 0b0010_0000;
+            flags.0[0] |= FLAG_ICC;
+        }
+        if webp.has_alpha() {
+            flags.0[0] |= FLAG_ALPHA;
         }
         if webp.has_chunk(CHUNK_EXIF) {
-            flags.0[0] |= 0b0000_1000;
+            flags.0[0] |= FLAG_EXIF;
+        }
+        if webp.has_chunk(CHUNK_XMP) {
+            flags.0[0] |= FLAG_XMP;
+        }
+        if webp.has_chunk(CHUNK_ANIM) || webp.has_chunk(CHUNK_ANMF) {
+            flags.0[0] |= FLAG_ANIMATION;
         }
         flags
     }
+
+    /// Get the feature bits, ignoring the reserved ones
+    pub(super) fn features(self) -> u8 {
+        self.0[0] & (FLAG_ICC | FLAG_ALPHA | FLAG_EXIF | FLAG_XMP | FLAG_ANIMATION)
+    }
+
+    /// Check if the image has an ICC Profile
+    #[inline]
+    pub fn icc(&self) -> bool {
+        self.0[0] & FLAG_ICC != 0
+    }
+
+    /// Check if any of the frames of the image contain transparency
+    #[inline]
+    pub fn alpha(&self) -> bool {
+        self.0[0] & FLAG_ALPHA != 0
+    }
+
+    /// Check if the image has EXIF metadata
+    #[inline]
+    pub fn exif(&self) -> bool {
+        self.0[0] & FLAG_EXIF != 0
+    }
+
+    /// Check if the image has XMP metadata
+    #[inline]
+    pub fn xmp(&self) -> bool {
+        self.0[0] & FLAG_XMP != 0
+    }
+
+    /// Check if the image is animated
+    #[inline]
+    pub fn animation(&self) -> bool {
+        self.0[0] & FLAG_ANIMATION != 0
+    }
+
+    /// Get the raw flags, as stored in the first 4 bytes of the `VP8X` chunk
+    #[inline]
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.0
+    }
+}
+
+impl From<[u8; 4]> for WebPFlags {
+    #[inline]
+    fn from(flags: [u8; 4]) -> WebPFlags {
+        WebPFlags(flags)
+    }
 }
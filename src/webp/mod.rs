@@ -1,18 +1,27 @@
 use alloc::vec::Vec;
 
 use bytes::{Bytes, BytesMut};
-use flags::WebPFlags;
 
 use crate::{
     encoder::ImageEncoder,
-    riff::{RiffChunk, RiffContent},
-    util::{u24_from_le_bytes, u24_to_le_bytes},
-    vp8::{size_from_vp8_header, VP8Kind},
+    riff::{impl_set_info, RiffChunk, RiffContent, RiffInfo, RiffPreset},
+    util::{impl_subchunks, u24_from_le_bytes, u24_to_le_bytes},
+    vp8::{VP8Header, VP8Kind, VP8LHeader},
     Error, ImageEXIF, ImageICC, Result, EXIF_DATA_PREFIX, EXIF_START_PREFIX_BE,
     EXIF_START_PREFIX_LE,
 };
 
+pub use self::{
+    alpha::{AlphaCompression, AlphaFiltering, AlphaPreprocessing, WebPAlpha},
+    anim::{WebPAnimation, WebPFrame},
+    flags::WebPFlags,
+    validate::WebPViolation,
+};
+
+mod alpha;
+mod anim;
 mod flags;
+mod validate;
 
 pub const CHUNK_ALPH: [u8; 4] = [b'A', b'L', b'P', b'H'];
 pub const CHUNK_ANIM: [u8; 4] = [b'A', b'N', b'I', b'M'];
@@ -41,15 +50,27 @@ impl WebP {
     /// # Errors
     ///
     /// This method returns a [`Error::WrongSignature`]
-    /// if the content of the [`RiffChunk`] isn't a `List` or
-    /// if the list's kind isn't "WEBP".
+    /// if the [`RiffChunk`] isn't a little-endian `RIFF` chunk, if its
+    /// content isn't a `List` or if the list's kind isn't "WEBP".
     pub fn new(riff: RiffChunk) -> Result<WebP> {
         match riff.content().list() {
-            Some((kind, _)) if kind == Some(*b"WEBP") => Ok(WebP { riff }),
+            Some((kind, _)) if riff.id() == *b"RIFF" && kind == Some(*b"WEBP") => Ok(WebP { riff }),
             _ => Err(Error::WrongSignature),
         }
     }
 
+    /// Construct a new `WebP` image holding `chunks`.
+    fn from_chunks(chunks: Vec<RiffChunk>) -> WebP {
+        let riff = RiffChunk::new(
+            *b"RIFF",
+            RiffContent::List {
+                kind: Some(*b"WEBP"),
+                subchunks: chunks,
+            },
+        );
+        WebP { riff }
+    }
+
     /// Create a new `WebP` image from a Reader.
     ///
     /// # Errors
@@ -58,10 +79,56 @@ impl WebP {
     /// it is corrupted or truncated.
     #[inline]
     pub fn from_bytes(b: Bytes) -> Result<WebP> {
-        let riff = RiffChunk::from_bytes(b)?;
+        let riff = RiffChunk::from_bytes_with_schema(b, &RiffPreset::WebP)?;
         WebP::new(riff)
     }
 
+    /// Construct a new `WebP` image from a bare `VP8` or `VP8L` bitstream.
+    ///
+    /// The kind of the bitstream is detected from its header. `alpha` holds
+    /// the contents of an `ALPH` chunk, header byte included, and can only be
+    /// used with a `VP8` bitstream. `icc` and `exif` are added as `ICCP` and
+    /// `EXIF` chunks. The `VP8X` chunk is generated if any of them is set.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `bitstream` isn't a `VP8` key frame or a `VP8L`
+    /// image, if `alpha` is invalid or if `alpha` is set for a `VP8L` bitstream.
+    pub fn from_bitstream(
+        bitstream: Bytes,
+        alpha: Option<Bytes>,
+        icc: Option<Bytes>,
+        exif: Option<Bytes>,
+    ) -> Result<WebP> {
+        let id = if VP8LHeader::from_bytes(&bitstream).is_ok() {
+            if alpha.is_some() {
+                return Err(Error::InvalidData);
+            }
+
+            CHUNK_VP8L
+        } else {
+            let header = VP8Header::from_bytes(&bitstream)?;
+            if header.key_frame.is_none() {
+                return Err(Error::InvalidData);
+            }
+
+            CHUNK_VP8
+        };
+
+        let mut chunks = Vec::with_capacity(5);
+        if let Some(alpha) = alpha {
+            WebPAlpha::from_bytes(&alpha)?;
+            chunks.push(RiffChunk::new(CHUNK_ALPH, RiffContent::Data(alpha)));
+        }
+        chunks.push(RiffChunk::new(id, RiffContent::Data(bitstream)));
+
+        let mut webp = WebP::from_chunks(chunks);
+        webp.set_icc_profile(icc);
+        // also generates the VP8X chunk if it's needed
+        webp.set_exif(exif);
+        Ok(webp)
+    }
+
     /// Get the `VP8Kind` of this `WebP`.
     pub fn kind(&self) -> VP8Kind {
         if self.has_chunk(CHUNK_VP8X) {
@@ -73,134 +140,390 @@ impl WebP {
         }
     }
 
+    /// Get the feature flags stored in the `VP8X` chunk.
+    ///
+    /// Returns `None` if this `WebP` doesn't have a `VP8X` chunk.
+    pub fn flags(&self) -> Option<WebPFlags> {
+        let data = self.chunk_by_id(CHUNK_VP8X)?.content().data()?;
+        let flags: [u8; 4] = data.get(0..4)?.try_into().unwrap();
+        Some(WebPFlags::from(flags))
+    }
+
+    /// Check if the image data of this `WebP` contains transparency.
+    ///
+    /// This is the case if this `WebP` has an `ALPH` chunk, if the
+    /// `VP8L` bitstream header says that alpha is used, or if any of
+    /// the frames of the animation contains transparency.
+    pub fn has_alpha(&self) -> bool {
+        if self.has_chunk(CHUNK_ALPH) {
+            return true;
+        }
+
+        if let Some(header) = self.vp8l_header() {
+            return header.alpha_is_used;
+        }
+
+        self.frames()
+            .map_or(false, |frames| frames.iter().any(|frame| frame.has_alpha()))
+    }
+
     fn infer_kind(&self) -> VP8Kind {
-        if self.has_chunk(CHUNK_ICCP) | self.has_chunk(CHUNK_EXIF) {
+        let extended = [
+            CHUNK_ICCP, CHUNK_EXIF, CHUNK_XMP, CHUNK_ALPH, CHUNK_ANIM, CHUNK_ANMF,
+        ]
+        .into_iter()
+        .any(|id| self.has_chunk(id));
+
+        if extended {
             VP8Kind::VP8X
+        } else if self.has_chunk(CHUNK_VP8L) {
+            VP8Kind::VP8L
         } else {
-            // TODO: VP8L
             VP8Kind::VP8
         }
     }
 
-    fn convert_into_infered_kind(&mut self) {
-        let current_kind = self.kind();
-        let correct_kind = self.infer_kind();
-
-        if current_kind == correct_kind {
-            if correct_kind == VP8Kind::VP8X {
-                // TODO: update flags in the VP8X chunk
-            }
-        } else if correct_kind == VP8Kind::VP8 {
+    /// Update the `VP8X` chunk to match the other chunks of this `WebP`.
+    ///
+    /// If this `WebP` uses any of the features of the extended file format
+    /// (ICC Profile, alpha channel, EXIF or XMP metadata, animation), the
+    /// `VP8X` chunk is added if it was missing and its flags and canvas size
+    /// are updated. Otherwise the `VP8X` chunk is removed, turning this
+    /// `WebP` into the simple file format.
+    ///
+    /// This is called automatically by the methods of this `WebP` that
+    /// modify its chunks, but has to be called manually after modifying the
+    /// chunks through [`chunks_mut`][WebP::chunks_mut].
+    pub fn update_vp8x(&mut self) {
+        if self.infer_kind() != VP8Kind::VP8X {
             self.remove_chunks_by_id(CHUNK_VP8X);
-        } else if correct_kind == VP8Kind::VP8X {
-            // TODO VP8L
-
-            let pos = self
-                .chunks()
-                .iter()
-                .position(|chunk| chunk.id() == CHUNK_ICCP)
-                .unwrap_or(0);
-
-            let (width, height) = self.dimensions().unwrap();
+            return;
+        }
 
-            let flags = WebPFlags::from_webp(self);
-            let mut content = BytesMut::with_capacity(10);
+        let flags = WebPFlags::from_webp(self);
+        let (width, height) = if flags.animation() {
+            // the canvas of an animation is independent from the size of the frames
+            self.try_dimensions()
+        } else {
+            self.bitstream_dimensions()
+                .or_else(|_| self.try_dimensions())
+        }
+        .unwrap_or((1, 1));
 
-            content.extend_from_slice(&flags.0);
+        self.write_vp8x(flags, width, height);
+    }
 
-            let buf = u24_to_le_bytes(width - 1);
-            content.extend_from_slice(&buf);
-            let buf = u24_to_le_bytes(height - 1);
-            content.extend_from_slice(&buf);
+    /// Check the chunks of this `WebP` against the rules of the WebP
+    /// container specification.
+    ///
+    /// Every chunk must appear in the canonical order (`VP8X`, `ICCP`,
+    /// `ANIM`, `ALPH`, image data, `EXIF`, `XMP`) at most once, `ALPH` must
+    /// be followed by `VP8`, frames require `ANIM` and the
+    /// `VP8X` flags must match the other chunks. Unknown chunks are ignored.
+    ///
+    /// Returns every violation found, or an empty `Vec` if this `WebP` is valid.
+    pub fn validate(&self) -> Vec<WebPViolation> {
+        validate::validate(self)
+    }
 
-            let chunk = RiffChunk::new(CHUNK_VP8X, RiffContent::Data(content.freeze()));
-            self.chunks_mut().insert(pos, chunk);
-        }
+    /// Reorder the chunks of this `WebP` into the canonical layout
+    /// and update the `VP8X` chunk.
+    ///
+    /// Unknown chunks are moved together with the chunk preceding them,
+    /// or after the image data if they follow `ALPH`. Duplicated chunks
+    /// aren't removed.
+    pub fn normalize(&mut self) {
+        validate::normalize(self);
     }
 
     /// Get the width and height of this `WebP`.
     ///
     /// If this `WebP` has a `VP8X` chunk the dimension is the canvas size.
     ///
-    /// Otherwise the dimension is read from the VP8 bitstream header.
+    /// Otherwise the dimension is read from the VP8 or VP8L bitstream header.
+    ///
+    /// Returns `None` if the dimension can't be read, see
+    /// [`try_dimensions`][WebP::try_dimensions] for the reason.
+    #[inline]
     pub fn dimensions(&self) -> Option<(u32, u32)> {
+        self.try_dimensions().ok()
+    }
+
+    /// Get the width and height of this `WebP`, like [`dimensions`][WebP::dimensions].
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `VP8X` chunk or the bitstream header are
+    /// malformed or truncated, or if this `WebP` doesn't contain an image.
+    pub fn try_dimensions(&self) -> Result<(u32, u32)> {
         if let Some(vp8x) = self.chunk_by_id(CHUNK_VP8X) {
-            if let Some(data) = vp8x.content().data() {
-                if let Some(range) = data.get(2..8) {
-                    let width = u24_from_le_bytes(range[0..3].try_into().unwrap()) + 1;
-                    let height = u24_from_le_bytes(range[3..6].try_into().unwrap()) + 1;
-                    return Some((width, height));
-                }
-            }
+            let data = vp8x.content().data().ok_or(Error::InvalidData)?;
+            let range = data.get(4..10).ok_or(Error::Truncated)?;
+
+            let width = u24_from_le_bytes(range[0..3].try_into().unwrap()) + 1;
+            let height = u24_from_le_bytes(range[3..6].try_into().unwrap()) + 1;
+            return Ok((width, height));
         }
 
+        self.bitstream_dimensions()
+    }
+
+    /// Get the width and height read from the VP8 or VP8L bitstream header.
+    fn bitstream_dimensions(&self) -> Result<(u32, u32)> {
         if let Some(vp8) = self.chunk_by_id(CHUNK_VP8) {
-            if let Some(data) = vp8.content().data() {
-                let (width, height) = size_from_vp8_header(data);
-                return Some((width as u32, height as u32));
-            }
+            let data = vp8.content().data().ok_or(Error::InvalidData)?;
+            let header = VP8Header::from_bytes(data)?;
+            let key_frame = header.key_frame.ok_or(Error::InvalidData)?;
+
+            return Ok((key_frame.width as u32, key_frame.height as u32));
         }
 
         if let Some(vp8l) = self.chunk_by_id(CHUNK_VP8L) {
-            if let Some(data) = vp8l.content().data() {
-                if let Some(range) = data.get(1..5) {
-                    let size = u32::from_le_bytes(range.try_into().unwrap());
-                    let width = (size & 0x3FFF) + 1;
-                    let height = ((size >> 14) & 0x3FFF) + 1;
+            let data = vp8l.content().data().ok_or(Error::InvalidData)?;
+            let header = VP8LHeader::from_bytes(data)?;
 
-                    return Some((width, height));
-                }
-            }
+            return Ok((header.width, header.height));
         }
 
-        None
+        Err(Error::InvalidData)
     }
 
-    /// Get the chunks of this `WebP`.
-    pub fn chunks(&self) -> &Vec<RiffChunk> {
-        match self.riff.content() {
-            RiffContent::List { subchunks, .. } => subchunks,
-            _ => unreachable!(),
+    /// Get the parsed header of the `VP8` bitstream.
+    ///
+    /// Returns `None` if this `WebP` doesn't have a `VP8` chunk
+    /// or if its header is invalid.
+    pub fn vp8_header(&self) -> Option<VP8Header> {
+        let data = self.chunk_by_id(CHUNK_VP8)?.content().data()?;
+        VP8Header::from_bytes(data).ok()
+    }
+
+    /// Get the parsed header of the `VP8L` bitstream.
+    ///
+    /// Returns `None` if this `WebP` doesn't have a `VP8L` chunk
+    /// or if its header is invalid.
+    pub fn vp8l_header(&self) -> Option<VP8LHeader> {
+        let data = self.chunk_by_id(CHUNK_VP8L)?.content().data()?;
+        VP8LHeader::from_bytes(data).ok()
+    }
+
+    /// Construct a new animated `WebP` from `frames`.
+    ///
+    /// The canvas is made big enough to contain every frame.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if one of the frames
+    /// can't be encoded, see [`WebPFrame::to_chunk`], or if the canvas
+    /// is wider or taller than 16777216 pixels.
+    pub fn from_frames(animation: WebPAnimation, frames: Vec<WebPFrame>) -> Result<WebP> {
+        let mut webp = WebP::from_chunks(Vec::new());
+
+        let canvas_size = |frame_size: fn(&WebPFrame) -> Option<u32>| {
+            frames
+                .iter()
+                .map(frame_size)
+                .try_fold(1, |max, size| match size {
+                    Some(size) if size <= 0x100_0000 => Ok(max.max(size)),
+                    _ => Err(Error::InvalidData),
+                })
+        };
+        let width = canvas_size(|frame| frame.x.checked_add(frame.width))?;
+        let height = canvas_size(|frame| frame.y.checked_add(frame.height))?;
+        webp.set_canvas_size(width, height);
+
+        webp.set_animation(Some(animation));
+        webp.set_frames(frames)?;
+        Ok(webp)
+    }
+
+    /// Set the canvas size stored in the `VP8X` chunk, adding it if it's missing.
+    fn set_canvas_size(&mut self, width: u32, height: u32) {
+        let flags = self.flags().unwrap_or_default();
+        self.write_vp8x(flags, width, height);
+    }
+
+    /// Overwrite the contents of the `VP8X` chunk, adding it if it's missing.
+    fn write_vp8x(&mut self, flags: WebPFlags, width: u32, height: u32) {
+        let mut content = BytesMut::with_capacity(10);
+        content.extend_from_slice(&flags.0);
+        content.extend_from_slice(&u24_to_le_bytes(width.saturating_sub(1)));
+        content.extend_from_slice(&u24_to_le_bytes(height.saturating_sub(1)));
+        let content = RiffContent::Data(content.freeze());
+
+        match self
+            .chunks_mut()
+            .iter_mut()
+            .find(|chunk| chunk.id() == CHUNK_VP8X)
+        {
+            Some(vp8x) => *vp8x.content_mut() = content,
+            None => {
+                let chunk = RiffChunk::new(CHUNK_VP8X, content);
+                self.chunks_mut().insert(0, chunk);
+            }
         }
     }
 
-    /// Get a mutable reference to the chunks of this `WebP`.
-    pub fn chunks_mut(&mut self) -> &mut Vec<RiffChunk> {
-        match self.riff.content_mut() {
-            RiffContent::List {
-                ref mut subchunks, ..
-            } => subchunks,
-            _ => unreachable!(),
+    /// Get the parsed `ALPH` chunk of this `WebP`.
+    ///
+    /// Returns `Ok(None)` if this `WebP` doesn't have an `ALPH` chunk.
+    /// Lossless and animated images store their alpha channel
+    /// differently, see [`has_alpha`][WebP::has_alpha].
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `ALPH` chunk is malformed.
+    pub fn alpha(&self) -> Result<Option<WebPAlpha>> {
+        self.chunk_by_id(CHUNK_ALPH)
+            .and_then(|chunk| chunk.content().data())
+            .map(WebPAlpha::from_bytes)
+            .transpose()
+    }
+
+    /// Remove the `ALPH` chunks of this `WebP` and of its frames.
+    ///
+    /// The alpha flag of the `VP8X` chunk is cleared unless the image still
+    /// has transparency stored in a `VP8L` bitstream, which can't be
+    /// removed without re-encoding the image.
+    pub fn remove_alpha(&mut self) {
+        self.remove_chunks_by_id(CHUNK_ALPH);
+
+        if let Ok(mut frames) = self.frames() {
+            if frames
+                .iter()
+                .any(|frame| frame.chunks().iter().any(|chunk| chunk.id() == CHUNK_ALPH))
+            {
+                for frame in &mut frames {
+                    frame.chunks_mut().retain(|chunk| chunk.id() != CHUNK_ALPH);
+                }
+                // frames parsed from this image can always be encoded again
+                let _ = self.set_frames(frames);
+            }
         }
+
+        self.update_vp8x();
     }
 
-    /// Check if there's a chunk with an id of `id`.
-    #[inline]
-    pub fn has_chunk(&self, id: [u8; 4]) -> bool {
-        self.chunk_by_id(id).is_some()
+    /// Get the parsed `ANIM` chunk of this `WebP`.
+    ///
+    /// Returns `Ok(None)` if this `WebP` isn't animated.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `ANIM` chunk is truncated.
+    pub fn animation(&self) -> Result<Option<WebPAnimation>> {
+        self.chunk_by_id(CHUNK_ANIM)
+            .and_then(|chunk| chunk.content().data())
+            .map(WebPAnimation::from_bytes)
+            .transpose()
+    }
+
+    /// Overwrites the pre-existing `ANIM` chunk of this `WebP`.
+    ///
+    /// If `animation` is `None` the `ANIM` chunk is removed,
+    /// leaving the frames untouched.
+    pub fn set_animation(&mut self, animation: Option<WebPAnimation>) {
+        match animation {
+            Some(animation) => {
+                let content = RiffContent::Data(animation.to_bytes());
+                match self
+                    .chunks_mut()
+                    .iter_mut()
+                    .find(|chunk| chunk.id() == CHUNK_ANIM)
+                {
+                    Some(anim) => *anim.content_mut() = content,
+                    None => {
+                        // the ANIM chunk goes after VP8X and ICCP
+                        let pos = self
+                            .chunks()
+                            .iter()
+                            .rposition(|chunk| chunk.id() == CHUNK_VP8X || chunk.id() == CHUNK_ICCP)
+                            .map_or(0, |pos| pos + 1);
+                        let chunk = RiffChunk::new(CHUNK_ANIM, content);
+                        self.chunks_mut().insert(pos, chunk);
+                    }
+                }
+            }
+            None => self.remove_chunks_by_id(CHUNK_ANIM),
+        }
+
+        self.update_vp8x();
     }
 
-    /// Get the first chunk with an id of `id`.
-    pub fn chunk_by_id(&self, id: [u8; 4]) -> Option<&RiffChunk> {
-        self.chunks().iter().find(|chunk| chunk.id() == id)
+    /// Get the parsed `ANMF` chunks of this `WebP`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if any of the `ANMF` chunks is corrupted or truncated.
+    pub fn frames(&self) -> Result<Vec<WebPFrame>> {
+        self.chunks_by_id(CHUNK_ANMF)
+            .filter_map(|chunk| chunk.content().data())
+            .map(WebPFrame::from_bytes)
+            .collect()
     }
 
-    /// Get every chunk with an id of `id`.
-    pub fn chunks_by_id(&self, id: [u8; 4]) -> impl Iterator<Item = &RiffChunk> {
-        self.chunks().iter().filter(move |chunk| chunk.id() == id)
+    /// Overwrites the pre-existing `ANMF` chunks of this `WebP`.
+    ///
+    /// The new frames are placed where the first pre-existing frame was,
+    /// or after the `ANIM` chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidData`] if one of the frames
+    /// can't be encoded, see [`WebPFrame::to_chunk`]. The chunks of this
+    /// `WebP` are left unchanged.
+    pub fn set_frames(&mut self, frames: Vec<WebPFrame>) -> Result<()> {
+        let chunks = frames
+            .iter()
+            .map(WebPFrame::to_chunk)
+            .collect::<Result<Vec<_>>>()?;
+
+        let pos = self
+            .chunks()
+            .iter()
+            .position(|chunk| chunk.id() == CHUNK_ANMF)
+            .or_else(|| {
+                self.chunks()
+                    .iter()
+                    .position(|chunk| chunk.id() == CHUNK_ANIM)
+                    .map(|pos| pos + 1)
+            })
+            .unwrap_or_else(|| {
+                // before the metadata at the end of the file
+                self.chunks()
+                    .iter()
+                    .position(|chunk| chunk.id() == CHUNK_EXIF || chunk.id() == CHUNK_XMP)
+                    .unwrap_or(self.chunks().len())
+            });
+        self.remove_chunks_by_id(CHUNK_ANMF);
+
+        self.chunks_mut().splice(pos..pos, chunks);
+
+        self.update_vp8x();
+        Ok(())
     }
 
-    /// Remove every chunk with an id of `id`
-    pub fn remove_chunks_by_id(&mut self, id: [u8; 4]) {
-        self.chunks_mut().retain(|chunk| chunk.id() != id);
+    /// Get the parsed `LIST` chunk with a form type of `INFO`.
+    ///
+    /// Some writers add the `INFO` list after the image data.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `INFO` list is malformed or truncated.
+    #[inline]
+    pub fn info(&self) -> Result<Option<RiffInfo>> {
+        self.riff.info()
     }
 
+    impl_set_info!(riff);
+
+    impl_subchunks!("WebP", riff);
+
     /// Get the total size of the `WebP` once it is encoded.
     ///
     /// Internally calls [`RiffChunk::len`][crate::riff::RiffChunk::len] on the
     /// inner `RiffChunk`
     #[inline]
-    pub fn len(&self) -> u32 {
+    pub fn len(&self) -> u64 {
         self.riff.len()
     }
 
@@ -227,25 +550,19 @@ impl ImageICC for WebP {
         self.remove_chunks_by_id(CHUNK_ICCP);
 
         if let Some(profile) = profile {
-            let kind = self.infer_kind();
-            let pos = match kind {
-                VP8Kind::VP8 => self
-                    .chunks()
-                    .iter()
-                    .position(|chunk| chunk.id() == CHUNK_VP8),
-                VP8Kind::VP8L | VP8Kind::VP8X => self
-                    .chunks()
-                    .iter()
-                    .position(|chunk| chunk.id() == CHUNK_VP8L || chunk.id() == CHUNK_VP8X)
-                    .map(|pos| pos + 1),
-            }
-            .unwrap_or(0);
+            // the ICC Profile goes right after the VP8X chunk, which is
+            // added at the start of the file if it is missing
+            let pos = self
+                .chunks()
+                .iter()
+                .position(|chunk| chunk.id() == CHUNK_VP8X)
+                .map_or(0, |pos| pos + 1);
 
             let chunk = RiffChunk::new(CHUNK_ICCP, RiffContent::Data(profile));
             self.chunks_mut().insert(pos, chunk);
         }
 
-        self.convert_into_infered_kind();
+        self.update_vp8x();
     }
 }
 
@@ -272,10 +589,17 @@ impl ImageEXIF for WebP {
         self.remove_chunks_by_id(CHUNK_EXIF);
 
         if let Some(exif) = exif {
+            // the EXIF metadata goes before the XMP metadata
+            let pos = self
+                .chunks()
+                .iter()
+                .position(|chunk| chunk.id() == CHUNK_XMP)
+                .unwrap_or(self.chunks().len());
+
             let chunk = RiffChunk::new(CHUNK_EXIF, RiffContent::Data(exif));
-            self.chunks_mut().push(chunk);
+            self.chunks_mut().insert(pos, chunk);
         }
 
-        self.convert_into_infered_kind();
+        self.update_vp8x();
     }
 }
@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+
+use super::{
+    WebP, WebPFlags, CHUNK_ALPH, CHUNK_ANIM, CHUNK_ANMF, CHUNK_EXIF, CHUNK_ICCP, CHUNK_VP8,
+    CHUNK_VP8L, CHUNK_VP8X, CHUNK_XMP,
+};
+
+/// A violation of the WebP container specification
+///
+/// The `index` is the position of the offending chunk in
+/// [`WebP::chunks`][super::WebP::chunks].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WebPViolation {
+    /// The chunk can only appear once, but it was already found before
+    Duplicate { index: usize, id: [u8; 4] },
+    /// The chunk isn't in the position required by the specification
+    Misplaced { index: usize, id: [u8; 4] },
+    /// The chunk requires the extended file format, but there's no `VP8X` chunk
+    MissingVP8X { index: usize, id: [u8; 4] },
+    /// The `ALPH` chunk isn't followed by a `VP8` chunk,
+    /// ignoring unknown chunks
+    AlphaWithoutVP8 { index: usize },
+    /// The `ANMF` chunk isn't preceded by an `ANIM` chunk
+    FrameWithoutAnimation { index: usize },
+    /// The still image chunk is part of an animated image
+    StillImageInAnimation { index: usize, id: [u8; 4] },
+    /// The flags of the `VP8X` chunk don't match the other chunks
+    IncorrectFlags { index: usize },
+    /// There's no `VP8`, `VP8L` or `ANMF` chunk
+    MissingImage,
+}
+
+/// Get the position of `id` in the canonical chunk order
+///
+/// Returns `None` for chunks not defined by the specification.
+fn rank(id: [u8; 4]) -> Option<u8> {
+    match id {
+        CHUNK_VP8X => Some(0),
+        CHUNK_ICCP => Some(1),
+        CHUNK_ANIM => Some(2),
+        CHUNK_ALPH => Some(3),
+        CHUNK_VP8 | CHUNK_VP8L | CHUNK_ANMF => Some(4),
+        CHUNK_EXIF => Some(5),
+        CHUNK_XMP => Some(6),
+        _ => None,
+    }
+}
+
+pub(super) fn validate(webp: &WebP) -> Vec<WebPViolation> {
+    let chunks = webp.chunks();
+    let mut violations = Vec::new();
+
+    let has_vp8x = webp.has_chunk(CHUNK_VP8X);
+    let has_anim = webp.has_chunk(CHUNK_ANIM);
+    let mut seen: Vec<[u8; 4]> = Vec::with_capacity(chunks.len());
+    let mut max_rank = 0;
+    let mut animation_found = false;
+    let mut image_found = false;
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let id = chunk.id();
+        let rank = match rank(id) {
+            Some(rank) => rank,
+            // unknown chunks can appear anywhere
+            None => continue,
+        };
+
+        if id != CHUNK_ANMF && seen.contains(&id) {
+            violations.push(WebPViolation::Duplicate { index, id });
+        }
+        seen.push(id);
+
+        if rank < max_rank || (id == CHUNK_VP8X && index != 0) {
+            violations.push(WebPViolation::Misplaced { index, id });
+        }
+        max_rank = max_rank.max(rank);
+
+        if !has_vp8x && !matches!(id, CHUNK_VP8 | CHUNK_VP8L) {
+            violations.push(WebPViolation::MissingVP8X { index, id });
+        }
+
+        match id {
+            CHUNK_ANIM => animation_found = true,
+            CHUNK_ANMF => {
+                image_found = true;
+                if !animation_found {
+                    violations.push(WebPViolation::FrameWithoutAnimation { index });
+                }
+            }
+            CHUNK_ALPH | CHUNK_VP8 | CHUNK_VP8L => {
+                if id != CHUNK_ALPH {
+                    image_found = true;
+                }
+
+                if has_anim {
+                    violations.push(WebPViolation::StillImageInAnimation { index, id });
+                } else if id == CHUNK_ALPH {
+                    let next = chunks[index + 1..]
+                        .iter()
+                        .map(|chunk| chunk.id())
+                        .find(|id| self::rank(*id).is_some());
+                    if next != Some(CHUNK_VP8) {
+                        violations.push(WebPViolation::AlphaWithoutVP8 { index });
+                    }
+                }
+            }
+            CHUNK_VP8X => {
+                let expected = WebPFlags::from_webp(webp);
+                if webp.flags().map(WebPFlags::features) != Some(expected.features()) {
+                    violations.push(WebPViolation::IncorrectFlags { index });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !image_found {
+        violations.push(WebPViolation::MissingImage);
+    }
+
+    violations
+}
+
+pub(super) fn normalize(webp: &mut WebP) {
+    // unknown chunks move together with the known chunk preceding them,
+    // except that they go after the image data instead of splitting it from `ALPH`
+    let alph = rank(CHUNK_ALPH);
+    let mut previous = (0, 0);
+    let keys = webp
+        .chunks()
+        .iter()
+        .map(|chunk| {
+            previous = match rank(chunk.id()) {
+                Some(rank) => (rank, 0),
+                None if Some(previous.0) == alph => (previous.0 + 1, 1),
+                None => (previous.0, 1),
+            };
+            previous
+        })
+        .collect::<Vec<_>>();
+
+    let chunks = webp.chunks_mut();
+    let mut keyed = keys.into_iter().zip(chunks.drain(..)).collect::<Vec<_>>();
+    keyed.sort_by_key(|(key, _)| *key);
+    chunks.extend(keyed.into_iter().map(|(_, chunk)| chunk));
+
+    webp.update_vp8x();
+}
@@ -0,0 +1,80 @@
+use std::fs;
+
+use bytes::{Bytes, BytesMut};
+use img_parts::{
+    jpeg::{markers, Jpeg, JpegSegment},
+    Error,
+};
+
+#[test]
+fn abbreviated_jpeg_merge() {
+    let file = read_jpeg("P1133897.jpg");
+    let is_table = |segment: &&JpegSegment| matches!(segment.marker(), markers::DQT | markers::DHT);
+
+    // an abbreviated table specification: SOI, tables, EOI
+    let mut b = BytesMut::from(&[markers::P, markers::SOI][..]);
+    for segment in file.segments().iter().filter(is_table) {
+        b.extend_from_slice(&segment.clone().encoder().bytes());
+    }
+    b.extend_from_slice(&[markers::P, markers::EOI]);
+    let tables = Jpeg::from_bytes(b.freeze()).expect("parse tables");
+    assert_eq!(tables.segments().len(), 6);
+
+    let image = Jpeg::from_segments(
+        file.segments()
+            .iter()
+            .filter(|segment| !is_table(segment))
+            .cloned()
+            .collect(),
+    );
+    let merged = Jpeg::from_abbreviated(&tables, image).expect("merge");
+
+    let markers = merged
+        .segments()
+        .iter()
+        .map(|segment| segment.marker())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        markers,
+        [
+            markers::APP0,
+            markers::DQT,
+            markers::DQT,
+            markers::DHT,
+            markers::DHT,
+            markers::DHT,
+            markers::DHT,
+            markers::SOF0,
+            markers::SOS
+        ]
+    );
+    assert_eq!(merged.len(), file.len());
+
+    let reparsed = Jpeg::from_bytes(merged.clone().encoder().bytes()).expect("reparse");
+    assert_eq!(reparsed, merged);
+}
+
+#[test]
+fn abbreviated_jpeg_from_segments() {
+    let file = read_jpeg("P1133897.jpg");
+    let jpeg = Jpeg::from_segments(file.segments().clone());
+    assert_eq!(jpeg, file);
+}
+
+#[test]
+fn abbreviated_jpeg_no_frame() {
+    let tables = Jpeg::from_segments(Vec::new());
+    let image = Jpeg::from_segments(vec![JpegSegment::new_with_contents(
+        markers::COM,
+        Bytes::from_static(b"comment"),
+    )]);
+    assert_eq!(
+        Jpeg::from_abbreviated(&tables, image),
+        Err(Error::InvalidData)
+    );
+}
+
+fn read_jpeg(name: &str) -> Jpeg {
+    let file = fs::read(format!("tests/images/{name}")).expect("read file");
+    Jpeg::from_bytes(Bytes::from(file)).expect("parse jpeg")
+}
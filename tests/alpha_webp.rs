@@ -0,0 +1,82 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    vp8::VP8Kind,
+    webp::{
+        AlphaCompression, AlphaFiltering, AlphaPreprocessing, WebP, WebPAnimation, WebPFrame,
+        CHUNK_ALPH, CHUNK_VP8,
+    },
+};
+
+#[test]
+fn alpha_webp_read() {
+    let webp = alpha_webp();
+    assert!(webp.has_alpha());
+    assert!(webp.flags().expect("flags").alpha());
+
+    let alpha = webp.alpha().expect("alpha").expect("has alpha");
+    assert_eq!(alpha.compression, AlphaCompression::Lossless);
+    assert_eq!(alpha.filtering, AlphaFiltering::Horizontal);
+    assert_eq!(alpha.preprocessing, AlphaPreprocessing::None);
+    assert_eq!(alpha.data(), &[0x2a, 0x2a][..]);
+}
+
+#[test]
+fn alpha_webp_remove() {
+    let file = read("P1133897.webp");
+    let mut webp = alpha_webp();
+
+    webp.remove_alpha();
+    assert!(!webp.has_alpha());
+    assert_eq!(webp.alpha(), Ok(None));
+    assert_eq!(webp.kind(), VP8Kind::VP8);
+    assert_eq!(webp.encoder().bytes(), file);
+}
+
+#[test]
+fn alpha_webp_none() {
+    let webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    assert!(!webp.has_alpha());
+    assert_eq!(webp.alpha(), Ok(None));
+}
+
+#[test]
+fn alpha_webp_remove_frames() {
+    let frame = WebPFrame::from_webp(&alpha_webp(), 0, 0, 100).expect("frame");
+    let animation = WebPAnimation {
+        background_color: [0, 0, 0, 0],
+        loop_count: 0,
+    };
+    let mut webp = WebP::from_frames(animation, vec![frame]).expect("from frames");
+    assert!(webp.has_alpha());
+    assert!(webp.flags().expect("flags").alpha());
+
+    webp.remove_alpha();
+    assert!(!webp.has_alpha());
+    assert!(!webp.flags().expect("flags").alpha());
+    let frames = webp.frames().expect("frames");
+    assert_eq!(frames[0].chunks().len(), 1);
+}
+
+// P1133897.webp with an ALPH chunk
+fn alpha_webp() -> WebP {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let alph = RiffChunk::new(
+        CHUNK_ALPH,
+        RiffContent::Data(Bytes::from_static(&[0b0000_0101, 0x2a, 0x2a])),
+    );
+    let pos = webp
+        .chunks()
+        .iter()
+        .position(|chunk| chunk.id() == CHUNK_VP8)
+        .unwrap();
+    webp.chunks_mut().insert(pos, alph);
+    webp.update_vp8x();
+    webp
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
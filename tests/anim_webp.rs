@@ -0,0 +1,135 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    vp8::VP8Kind,
+    webp::{WebP, WebPAnimation, WebPFrame, CHUNK_ANIM, CHUNK_ANMF, CHUNK_VP8L, CHUNK_VP8X},
+    Error,
+};
+
+#[test]
+fn anim_webp_assemble() {
+    let file = read("P1133897.webp");
+    let still = WebP::from_bytes(file.clone()).expect("parse webp");
+
+    let first = WebPFrame::from_webp(&still, 0, 0, 100).expect("frame");
+    let mut second = WebPFrame::from_webp(&vp8l_webp(), 10, 20, 250).expect("frame");
+    second.blend = false;
+    second.dispose = true;
+
+    let animation = WebPAnimation {
+        background_color: [255, 255, 255, 255],
+        loop_count: 3,
+    };
+    let webp =
+        WebP::from_frames(animation, vec![first.clone(), second.clone()]).expect("from frames");
+
+    let ids = webp.chunks().iter().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(ids, [CHUNK_VP8X, CHUNK_ANIM, CHUNK_ANMF, CHUNK_ANMF]);
+    assert_eq!(webp.kind(), VP8Kind::VP8X);
+    assert_eq!(webp.try_dimensions(), Ok((717, 512)));
+    let flags = webp.flags().expect("flags");
+    assert!(flags.animation() && flags.alpha());
+
+    let webp = WebP::from_bytes(webp.encoder().bytes()).expect("parse animation");
+    assert_eq!(webp.animation(), Ok(Some(animation)));
+    assert_eq!(webp.frames(), Ok(vec![first, second]));
+
+    // extract the first frame back as a static image
+    let frame = webp.frames().expect("frames").remove(0).to_webp();
+    assert_eq!(frame.encoder().bytes(), file);
+}
+
+#[test]
+fn anim_webp_edit() {
+    let still = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let frame = WebPFrame::from_webp(&still, 0, 0, 100).expect("frame");
+    let animation = WebPAnimation {
+        background_color: [0, 0, 0, 0],
+        loop_count: 0,
+    };
+    let mut webp = WebP::from_frames(animation, vec![frame.clone(), frame]).expect("from frames");
+
+    let mut animation = webp.animation().expect("anim").expect("animated");
+    animation.loop_count = 1;
+    webp.set_animation(Some(animation));
+
+    let mut frames = webp.frames().expect("frames");
+    for frame in &mut frames {
+        frame.duration *= 2;
+    }
+    webp.set_frames(frames).expect("set frames");
+
+    let webp = WebP::from_bytes(webp.encoder().bytes()).expect("parse animation");
+    assert_eq!(
+        webp.animation()
+            .expect("anim")
+            .expect("animated")
+            .loop_count,
+        1
+    );
+    let frames = webp.frames().expect("frames");
+    assert!(frames.iter().all(|frame| frame.duration == 200));
+    assert!(!webp.flags().expect("flags").alpha());
+}
+
+#[test]
+fn anim_webp_invalid_frames() {
+    let animation = WebPAnimation {
+        background_color: [0, 0, 0, 0],
+        loop_count: 0,
+    };
+    let frame = WebPFrame::from_webp(&vp8l_webp(), 0, 0, 100).expect("frame");
+    let mut webp = WebP::from_frames(animation, vec![frame.clone()]).expect("from frames");
+    let before = webp.clone();
+
+    let edits: [fn(&mut WebPFrame); 6] = [
+        |frame| frame.x = 1,
+        |frame| frame.y = 3,
+        |frame| frame.x = 1 << 25,
+        |frame| frame.width = 0,
+        |frame| frame.height = (1 << 24) + 1,
+        |frame| frame.duration = 1 << 24,
+    ];
+    for edit in edits {
+        let mut invalid = frame.clone();
+        edit(&mut invalid);
+
+        assert_eq!(invalid.to_chunk(), Err(Error::InvalidData));
+        assert_eq!(
+            WebP::from_frames(animation, vec![invalid.clone()]).map(|_| ()),
+            Err(Error::InvalidData)
+        );
+        assert_eq!(webp.set_frames(vec![invalid]), Err(Error::InvalidData));
+        assert_eq!(webp, before);
+    }
+
+    // the largest values that fit in the frame, but not in the canvas
+    let mut largest = frame;
+    largest.x = (1 << 25) - 2;
+    largest.width = 2;
+    largest.duration = (1 << 24) - 1;
+    assert!(largest.to_chunk().is_ok());
+    assert_eq!(
+        WebP::from_frames(animation, vec![largest]).map(|_| ()),
+        Err(Error::InvalidData)
+    );
+}
+
+fn vp8l_webp() -> WebP {
+    // 2x3 VP8L header using alpha, followed by some bitstream bytes
+    let vp8l = Bytes::from_static(&[0x2f, 0x01, 0x80, 0x00, 0x10, 0x12, 0x34]);
+    let riff = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WEBP"),
+            subchunks: vec![RiffChunk::new(CHUNK_VP8L, RiffContent::Data(vp8l))],
+        },
+    );
+    WebP::new(riff).expect("webp")
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
@@ -0,0 +1,85 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    vp8::VP8Kind,
+    webp::{WebP, CHUNK_ALPH, CHUNK_EXIF, CHUNK_ICCP, CHUNK_VP8, CHUNK_VP8L, CHUNK_VP8X},
+    Error, ImageEXIF, ImageICC,
+};
+
+#[test]
+fn bitstream_webp_vp8() {
+    let file = read("P1133897.webp");
+    let vp8 = vp8_bitstream(&file);
+
+    let webp = WebP::from_bitstream(vp8, None, None, None).expect("build webp");
+    assert_eq!(webp.kind(), VP8Kind::VP8);
+    assert_eq!(webp.encoder().bytes(), file);
+}
+
+#[test]
+fn bitstream_webp_metadata() {
+    let vp8 = vp8_bitstream(&read("P1133897.webp"));
+    let icc = read("P1133897_sRGB.icc");
+    let exif = read("P1133897_sRGB.exif");
+    let alpha = Bytes::from_static(&[0b0000_0101, 0x2a, 0x2a]);
+
+    let webp = WebP::from_bitstream(vp8, Some(alpha), Some(icc.clone()), Some(exif.clone()))
+        .expect("build webp");
+    assert_eq!(webp.kind(), VP8Kind::VP8X);
+    assert_eq!(
+        ids(&webp),
+        [CHUNK_VP8X, CHUNK_ICCP, CHUNK_ALPH, CHUNK_VP8, CHUNK_EXIF]
+    );
+    assert_eq!(webp.validate(), vec![]);
+
+    let webp = WebP::from_bytes(webp.encoder().bytes()).expect("reparse webp");
+    let flags = webp.flags().expect("flags");
+    assert!(flags.icc() && flags.alpha() && flags.exif());
+    assert_eq!(webp.icc_profile(), Some(icc));
+    assert_eq!(webp.exif(), Some(exif));
+    assert_eq!(webp.try_dimensions(), Ok((717, 512)));
+}
+
+#[test]
+fn bitstream_webp_vp8l() {
+    let vp8l = Bytes::from(vec![0x2f, 0x01, 0x80, 0x00, 0x10, 0x12, 0x34]);
+
+    let webp = WebP::from_bitstream(vp8l.clone(), None, None, None).expect("build webp");
+    assert_eq!(webp.kind(), VP8Kind::VP8L);
+    assert_eq!(ids(&webp), [CHUNK_VP8L]);
+    assert_eq!(webp.try_dimensions(), Ok((2, 3)));
+
+    // VP8L stores the alpha channel in the bitstream itself
+    let alpha = Bytes::from_static(&[0b0000_0000]);
+    assert_eq!(
+        WebP::from_bitstream(vp8l, Some(alpha), None, None),
+        Err(Error::InvalidData)
+    );
+}
+
+#[test]
+fn bitstream_webp_invalid() {
+    assert!(WebP::from_bitstream(Bytes::from_static(b"garbage"), None, None, None).is_err());
+
+    let vp8 = vp8_bitstream(&read("P1133897.webp"));
+    let alpha = Bytes::from_static(&[0b0000_0011]);
+    assert_eq!(
+        WebP::from_bitstream(vp8, Some(alpha), None, None),
+        Err(Error::InvalidData)
+    );
+}
+
+fn vp8_bitstream(file: &Bytes) -> Bytes {
+    let webp = WebP::from_bytes(file.clone()).expect("parse webp");
+    let vp8 = webp.chunk_by_id(CHUNK_VP8).expect("vp8");
+    vp8.content().data().expect("vp8 data").clone()
+}
+
+fn ids(webp: &WebP) -> Vec<[u8; 4]> {
+    webp.chunks().iter().map(|chunk| chunk.id()).collect()
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
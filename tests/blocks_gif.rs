@@ -0,0 +1,197 @@
+use bytes::Bytes;
+use img_parts::{
+    gif::{Gif, GifApplication, GifBlock, GifGraphicControl, LABEL_COMMENT, VERSION_89A},
+    DynImage, Error, ImageICC,
+};
+
+// a 2x1 animated gif, made of two frames and a comment
+fn animation() -> Bytes {
+    let mut file = Vec::new();
+    file.extend_from_slice(b"GIF89a");
+    // logical screen descriptor, with a global color table of 2 colors
+    file.extend_from_slice(&[2, 0, 1, 0, 0x80, 0, 0]);
+    file.extend_from_slice(&[0, 0, 0, 0xff, 0xff, 0xff]);
+    // NETSCAPE2.0, looping forever
+    file.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+    file.extend_from_slice(b"\x21\xfe\x05hello\x00");
+    for _ in 0..2 {
+        // graphic control extension, 10/100 s delay, color 1 transparent
+        file.extend_from_slice(&[0x21, 0xf9, 4, 0x05, 10, 0, 1, 0]);
+        // image descriptor followed by the LZW data
+        file.extend_from_slice(&[0x2c, 0, 0, 0, 0, 2, 0, 1, 0, 0]);
+        file.extend_from_slice(&[2, 2, 0x44, 0x01, 0]);
+    }
+    file.push(0x3b);
+    file.into()
+}
+
+#[test]
+fn blocks_gif_parse() {
+    let file = animation();
+    let gif = Gif::from_bytes(file.clone()).expect("parse gif");
+
+    assert_eq!(gif.version(), VERSION_89A);
+    assert_eq!((gif.screen().width, gif.screen().height), (2, 1));
+    assert_eq!(gif.global_color_table().map(Bytes::len), Some(6));
+    assert_eq!(gif.blocks().len(), 6);
+    assert_eq!(gif.loop_count(), Some(0));
+    assert_eq!(gif.comments(), [Bytes::from_static(b"hello")]);
+    assert_eq!(gif.xmp(), None);
+
+    let control = gif
+        .blocks()
+        .iter()
+        .filter_map(GifBlock::extension)
+        .find_map(|extension| GifGraphicControl::from_extension(extension).ok())
+        .expect("graphic control");
+    assert_eq!(control.delay, 10);
+    assert_eq!(control.disposal, 1);
+    assert_eq!(control.transparent_color, Some(1));
+
+    let images = gif.images().collect::<Vec<_>>();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].lzw_min_code_size, 2);
+    assert_eq!(images[0].sub_blocks().concat(), [0x44, 0x01][..]);
+
+    // the image data isn't copied
+    let range = file.as_ptr_range();
+    assert!(range.contains(&images[1].data().as_ptr()));
+
+    assert_eq!(gif.len(), file.len());
+    assert_eq!(gif.encoder().bytes(), file);
+}
+
+#[test]
+fn blocks_gif_edit() {
+    let mut gif = Gif::from_bytes(animation()).expect("parse gif");
+
+    gif.set_loop_count(Some(3));
+    gif.set_comments(vec![Bytes::from_static(b"a"), Bytes::from(vec![b'b'; 300])]);
+    gif.set_xmp(Some(Bytes::from_static(b"<x:xmpmeta/>")));
+    gif.set_icc_profile(Some(Bytes::from_static(b"profile")));
+
+    let file = gif.clone().encoder().bytes();
+    assert_eq!(file.len(), gif.len());
+    let gif = Gif::from_bytes(file.clone()).expect("reparse gif");
+    assert_eq!(gif.loop_count(), Some(3));
+    assert_eq!(
+        gif.comments(),
+        [Bytes::from_static(b"a"), Bytes::from(vec![b'b'; 300])]
+    );
+    assert_eq!(gif.xmp(), Some(Bytes::from_static(b"<x:xmpmeta/>")));
+    assert_eq!(gif.icc_profile(), Some(Bytes::from_static(b"profile")));
+
+    // the XMP packet isn't copied
+    let xmp = gif.xmp().expect("xmp");
+    assert!(file.as_ptr_range().contains(&xmp.as_ptr()));
+
+    // metadata goes before the first graphic control extension
+    let labels = gif
+        .blocks()
+        .iter()
+        .map(|block| block.extension().map(|extension| extension.label()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            Some(0xff),
+            Some(LABEL_COMMENT),
+            Some(LABEL_COMMENT),
+            Some(0xff),
+            Some(0xff),
+            Some(0xf9),
+            None,
+            Some(0xf9),
+            None
+        ]
+    );
+    let identifiers = gif
+        .applications()
+        .map(|application| application.identifier)
+        .collect::<Vec<_>>();
+    assert_eq!(identifiers, [*b"NETSCAPE", *b"XMP Data", *b"ICCRGBG1"]);
+
+    let mut gif = gif;
+    gif.set_loop_count(None);
+    gif.set_comments(Vec::new());
+    gif.set_xmp(None);
+    gif.set_icc_profile(None);
+    assert_eq!(gif.loop_count(), None);
+    assert!(gif.comments().is_empty());
+    assert_eq!(gif.applications().count(), 0);
+    assert_eq!(gif.blocks().len(), 4);
+}
+
+#[test]
+fn blocks_gif_animexts() {
+    let application = GifApplication {
+        identifier: *b"ANIMEXTS",
+        auth_code: *b"1.0",
+        data: Bytes::from_static(&[3, 1, 5, 0, 0]),
+    };
+
+    let mut gif = Gif::from_bytes(animation()).expect("parse gif");
+    // replace NETSCAPE2.0 with ANIMEXTS1.0
+    gif.blocks_mut()[0] = GifBlock::Extension(application.to_extension());
+    assert_eq!(gif.loop_count(), Some(5));
+
+    gif.set_loop_count(Some(2));
+    assert_eq!(gif.applications().count(), 1);
+    assert_eq!(
+        gif.applications().next().expect("app").identifier,
+        *b"NETSCAPE"
+    );
+}
+
+#[test]
+fn blocks_gif_87a() {
+    let file = Bytes::from_static(
+        b"GIF87a\x01\0\x01\0\0\0\0\x2c\0\0\0\0\x01\0\x01\0\0\x02\x02\x44\x01\0\x3b",
+    );
+    let mut gif = Gif::from_bytes(file.clone()).expect("parse gif");
+    assert_eq!(gif.global_color_table(), None);
+    assert_eq!(gif.clone().encoder().bytes(), file);
+
+    // extensions require 89a
+    gif.set_comments(vec![Bytes::from_static(b"hi")]);
+    assert_eq!(gif.version(), VERSION_89A);
+
+    gif.set_global_color_table(Some(Bytes::from_static(&[0; 12])))
+        .expect("color table");
+    assert_eq!(gif.screen().flags, 0x81);
+    assert_eq!(
+        gif.set_global_color_table(Some(Bytes::from_static(&[0; 9]))),
+        Err(Error::InvalidData)
+    );
+    assert_eq!(gif.screen().flags, 0x81);
+    let gif = Gif::from_bytes(gif.encoder().bytes()).expect("reparse gif");
+    assert_eq!(gif.global_color_table().map(Bytes::len), Some(12));
+}
+
+#[test]
+fn blocks_gif_invalid() {
+    let file = animation();
+    assert_eq!(
+        Gif::from_bytes(file.slice(..file.len() - 1)),
+        Err(Error::Truncated)
+    );
+    assert_eq!(
+        Gif::from_bytes(Bytes::from_static(b"GIF90a\x01\0\x01\0\0\0\0\x3b")),
+        Err(Error::WrongSignature)
+    );
+    assert_eq!(
+        Gif::from_bytes(Bytes::from_static(b"GIF89a\x01\0\x01\0\0\0\0\x42\x3b")),
+        Err(Error::InvalidData)
+    );
+}
+
+#[test]
+fn blocks_gif_dyn_image() {
+    let file = animation();
+    let dimg = DynImage::from_bytes(file.clone())
+        .expect("parse")
+        .expect("gif");
+    assert!(matches!(dimg, DynImage::Gif(_)));
+    assert_eq!(dimg.len(), file.len());
+    assert_eq!(dimg.encoder().bytes(), file);
+}
@@ -0,0 +1,230 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    heif::{Heif, HeifColor, HeifMirror, HeifNclx, HeifRotation, HeifSize, ITEM_EXIF},
+    isobmff::{
+        self, IsoBox, IsoContent, IsoExtent, IsoFileType, IsoItemInfo, IsoItemLocation,
+        IsoItemLocations, IsoItemProperties, IsoPropertyAssociation, IsoPropertyAssociations,
+        BOX_FTYP, BOX_IDAT, BOX_IINF, BOX_ILOC, BOX_IPRP, BOX_MDAT, BOX_META,
+    },
+    DynImage, Error, ImageEXIF, ImageICC,
+};
+
+mod common;
+
+use common::heif::{heic, iso_box, EXIF, ICC, IMAGE};
+
+#[test]
+fn boxes_heif_parse() {
+    let file = heic();
+    let heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    let kinds = heif.boxes().iter().map(IsoBox::kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [BOX_FTYP, BOX_META, BOX_MDAT]);
+    let file_type = heif.file_type().expect("ftyp");
+    assert_eq!(&file_type.major_brand, b"heic");
+    assert!(file_type.has_brand(*b"mif1"));
+
+    assert_eq!(heif.primary_item_id(), Some(1));
+    let items = heif.items();
+    assert_eq!(items.len(), 2);
+    assert_eq!(&items[0].kind, b"hvc1");
+    assert_eq!(items[1].kind, ITEM_EXIF);
+    assert_eq!(items[1].name, Bytes::from_static(b"exif"));
+
+    // the extents are concatenated
+    assert_eq!(heif.item_data(1), Some(Bytes::from_static(IMAGE)));
+    assert_eq!(heif.item_data(3), None);
+
+    assert_eq!(
+        heif.dimensions(),
+        Some(HeifSize {
+            width: 100,
+            height: 50
+        })
+    );
+    assert_eq!(heif.rotation(), Some(HeifRotation::Rotate90));
+    assert_eq!(heif.rotation().map(HeifRotation::degrees), Some(90));
+    assert_eq!(heif.mirror(), Some(HeifMirror::Horizontal));
+    assert_eq!(
+        heif.nclx(),
+        Some(HeifNclx {
+            colour_primaries: 9,
+            transfer_characteristics: 16,
+            matrix_coefficients: 9,
+            full_range: true,
+        })
+    );
+    assert_eq!(heif.colors().len(), 2);
+    assert_eq!(heif.item_properties(2), Vec::<&IsoBox>::new());
+
+    assert_eq!(heif.icc_profile(), Some(Bytes::from_static(ICC)));
+    let exif = heif.exif().expect("exif");
+    assert_eq!(exif, Bytes::from_static(EXIF));
+    assert!(file.as_ptr_range().contains(&exif.as_ptr()));
+}
+
+#[test]
+fn boxes_heif_encode() {
+    let file = heic();
+    let heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    // the 64 bit size of `mdat` is kept
+    assert_eq!(heif.len(), file.len() as u64);
+    assert_eq!(heif.encoder().bytes(), file);
+}
+
+#[test]
+fn boxes_heif_dyn_image() {
+    let file = heic();
+    let dimg = DynImage::from_bytes(file.clone())
+        .expect("parse")
+        .expect("heif");
+    assert!(matches!(dimg, DynImage::Heif(_)));
+    assert_eq!(dimg.exif(), Some(Bytes::from_static(EXIF)));
+
+    assert_eq!(dimg.len(), file.len());
+    assert_eq!(dimg.encoder().bytes(), file);
+}
+
+#[test]
+fn boxes_heif_invalid() {
+    let file = heic();
+    assert_eq!(
+        Heif::from_bytes(file.slice(..file.len() - 1)).map(|_| ()),
+        Err(Error::Truncated)
+    );
+    assert_eq!(
+        Heif::from_bytes(Bytes::from(iso_box(b"ftyp", b"crx \0\0\0\x01crx "))).map(|_| ()),
+        Err(Error::WrongSignature)
+    );
+
+    let ftyp = iso_box(b"ftyp", b"avif\0\0\0\0");
+    assert_eq!(
+        Heif::from_bytes(ftyp.into()).map(|_| ()),
+        Err(Error::InvalidData)
+    );
+}
+
+#[test]
+fn boxes_heif_isobmff() {
+    let file = heic();
+    let boxes = isobmff::read_boxes(file.clone()).expect("parse boxes");
+    let meta = &boxes[1];
+    assert!(isobmff::is_container(meta.kind()));
+    assert!(matches!(
+        meta.content(),
+        IsoContent::Container { header, .. } if header.as_ref() == [0, 0, 0, 0]
+    ));
+    assert_eq!(
+        meta.child(BOX_IINF).map(|iinf| iinf.children().len()),
+        Some(2)
+    );
+    assert!(meta.child(BOX_IPRP).is_some());
+    assert_eq!(
+        meta.child(BOX_IDAT).and_then(IsoBox::data).map(Bytes::len),
+        Some(4 + 6 + EXIF.len())
+    );
+
+    let iloc = meta.child(BOX_ILOC).and_then(IsoBox::data).expect("iloc");
+    let locations = IsoItemLocations::from_bytes(iloc).expect("parse iloc");
+    assert_eq!(locations.item(1).map(|item| item.extents.len()), Some(2));
+    assert_eq!(
+        locations.item(2),
+        Some(&IsoItemLocation {
+            id: 2,
+            construction_method: 1,
+            data_reference_index: 0,
+            base_offset: 0,
+            extents: vec![IsoExtent {
+                index: 0,
+                offset: 0,
+                len: 0
+            }],
+        })
+    );
+    assert_eq!(locations.len(), iloc.len());
+    assert_eq!(locations.to_bytes().as_ref(), Ok(iloc));
+
+    // the encoded boxes are identical
+    let mut encoded = BytesMut::new();
+    for b in boxes {
+        encoded.put(b.encoder().bytes());
+    }
+    assert_eq!(encoded.freeze(), file);
+}
+
+#[test]
+fn boxes_heif_typed() {
+    let file_type = IsoFileType {
+        major_brand: *b"avif",
+        minor_version: 0,
+        compatible_brands: vec![*b"mif1", *b"miaf"],
+    };
+    assert_eq!(
+        IsoFileType::from_bytes(&file_type.to_bytes()),
+        Ok(file_type)
+    );
+
+    let info = IsoItemInfo {
+        id: 0x10000,
+        protection_index: 0,
+        kind: *b"mime",
+        name: Bytes::from_static(b"XMP"),
+        content_type: Some(Bytes::from_static(b"application/rdf+xml")),
+        content_encoding: None,
+        hidden: true,
+    };
+    let b = info.to_bytes();
+    assert_eq!(b[0], 3);
+    assert_eq!(IsoItemInfo::from_bytes(&b), Ok(info));
+
+    let mut associations = IsoPropertyAssociations {
+        version: 1,
+        flags: 1,
+        items: vec![IsoItemProperties {
+            id: 7,
+            properties: vec![IsoPropertyAssociation {
+                essential: true,
+                index: 300,
+            }],
+        }],
+    };
+    let b = associations.to_bytes().expect("encode ipma");
+    assert_eq!(
+        IsoPropertyAssociations::from_bytes(&b).as_ref(),
+        Ok(&associations)
+    );
+    // the index doesn't fit in 7 bits
+    associations.flags = 0;
+    assert_eq!(associations.to_bytes(), Err(Error::InvalidData));
+
+    let mut locations = IsoItemLocations {
+        version: 0,
+        offset_size: 4,
+        length_size: 4,
+        base_offset_size: 0,
+        index_size: 0,
+        items: vec![IsoItemLocation {
+            id: 1,
+            construction_method: 1,
+            data_reference_index: 0,
+            base_offset: 0,
+            extents: vec![IsoExtent {
+                index: 0,
+                offset: 0,
+                len: 1,
+            }],
+        }],
+    };
+    // version 0 doesn't have the construction method
+    assert_eq!(locations.to_bytes(), Err(Error::InvalidData));
+    locations.version = 1;
+    let b = locations.to_bytes().expect("encode iloc");
+    assert_eq!(IsoItemLocations::from_bytes(&b), Ok(locations));
+
+    let colr = Bytes::from_static(b"rICCprofile");
+    assert_eq!(
+        HeifColor::from_bytes(&colr),
+        Ok(HeifColor::RestrictedIcc(Bytes::from_static(b"profile")))
+    );
+}
@@ -0,0 +1,116 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    png::{ColorType, Png, PngChunk, PngHeader},
+    Error,
+};
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_zlib};
+
+#[test]
+fn cgbi_png_detect() {
+    let png = build_cgbi_png();
+    assert!(png.is_cgbi());
+
+    // the raw deflate stream can still be read
+    let passes = png.unfiltered_scanlines(64).expect("scanlines");
+    assert_eq!(
+        passes[0].data(),
+        &[0x40, 0x20, 0x10, 0x80, 0, 0, 255, 255][..]
+    );
+}
+
+#[test]
+fn cgbi_png_normalize() {
+    let mut png = build_cgbi_png();
+    png.normalize_cgbi(64).expect("normalize");
+
+    assert!(!png.is_cgbi());
+    assert!(png.chunk_by_type(*b"CgBI").is_none());
+    assert_eq!(png.chunks()[0].kind(), *b"IHDR");
+
+    // the image data is now a zlib stream
+    decompress_to_vec_zlib(&png.image_data()).expect("inflate zlib");
+
+    let passes = png.filtered_scanlines(64).expect("scanlines");
+    // the original filter type is kept
+    assert_eq!(passes[0].rows().next().unwrap()[0], 1);
+
+    let passes = png.unfiltered_scanlines(64).expect("scanlines");
+    assert_eq!(
+        passes[0].data(),
+        &[0x20, 0x40, 0x80, 0x80, 255, 0, 0, 255][..]
+    );
+
+    let out = png.encoder().bytes();
+    let png = Png::from_bytes(out).expect("parse normalized png");
+    assert!(!png.is_cgbi());
+}
+
+#[test]
+fn cgbi_png_recompress() {
+    let mut png = build_cgbi_png();
+    let before = png.clone();
+
+    // the raw deflate stream can't be recompressed into a zlib stream
+    assert_eq!(png.recompress_image_data(6, 64), Err(Error::InvalidData));
+    assert_eq!(png, before);
+}
+
+#[test]
+fn cgbi_png_normalize_noop() {
+    let header = header();
+    let mut png = build_png(None, header, Bytes::from(compress_to_vec(&[0; 9], 6)));
+    let before = png.clone();
+
+    png.normalize_cgbi(64).expect("normalize");
+    assert_eq!(png, before);
+}
+
+fn header() -> PngHeader {
+    PngHeader {
+        width: 2,
+        height: 1,
+        bit_depth: 8,
+        color_type: ColorType::Rgba,
+        interlaced: false,
+    }
+}
+
+// a 2x1 CgBI image holding a half transparent pixel and an opaque red pixel
+fn build_cgbi_png() -> Png {
+    // premultiplied BGRA
+    let raw: [u8; 8] = [0x40, 0x20, 0x10, 0x80, 0, 0, 255, 255];
+    // filter type Sub
+    let mut filtered = vec![1];
+    filtered.extend_from_slice(&raw[..4]);
+    filtered.extend(
+        raw[4..]
+            .iter()
+            .zip(&raw[..4])
+            .map(|(x, a)| x.wrapping_sub(*a)),
+    );
+
+    let cgbi = Bytes::from_static(&[0x50, 0x00, 0x20, 0x06]);
+    build_png(
+        Some(cgbi),
+        header(),
+        Bytes::from(compress_to_vec(&filtered, 6)),
+    )
+}
+
+fn build_png(cgbi: Option<Bytes>, header: PngHeader, data: Bytes) -> Png {
+    let mut b = BytesMut::new();
+    b.put_slice(&[0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+    let mut chunks = Vec::new();
+    if let Some(cgbi) = cgbi {
+        chunks.push(PngChunk::new(*b"CgBI", cgbi));
+    }
+    chunks.push(PngChunk::new(*b"IHDR", header.to_bytes()));
+    chunks.push(PngChunk::new(*b"IDAT", data));
+    chunks.push(PngChunk::new(*b"IEND", Bytes::new()));
+    for chunk in chunks {
+        b.put(chunk.encoder().bytes());
+    }
+
+    Png::from_bytes(b.freeze()).expect("parse png")
+}
@@ -0,0 +1,220 @@
+//! Hand-built HEIF files
+
+use bytes::{BufMut, Bytes};
+
+pub const ICC: &[u8] = b"an icc profile";
+pub const EXIF: &[u8] = b"MM\0\x2A\0\0\0\x08";
+pub const IMAGE: &[u8] = b"the first half|the second half";
+pub const NOTE: &[u8] = b"a note";
+pub const SAMPLE: &[u8] = b"a sample of the sequence";
+
+pub fn iso_box(kind: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+    let mut b = Vec::new();
+    b.put_u32(8 + contents.len() as u32);
+    b.put_slice(kind);
+    b.put_slice(contents);
+    b
+}
+
+pub fn full_box(kind: &[u8; 4], version: u8, contents: &[u8]) -> Vec<u8> {
+    iso_box(kind, &[&[version, 0, 0, 0], contents].concat())
+}
+
+pub fn hdlr() -> Vec<u8> {
+    full_box(b"hdlr", 0, b"\0\0\0\0pict\0\0\0\0\0\0\0\0\0\0\0\0\0")
+}
+
+// a HEIC image, with the image split in two extents of a 64 bit `mdat` and the EXIF in `idat`
+pub fn heic() -> Bytes {
+    let ftyp = iso_box(b"ftyp", b"heic\0\0\0\0mif1heic");
+
+    let hdlr = hdlr();
+    let pitm = full_box(b"pitm", 0, &[0, 1]);
+    let iinf = full_box(
+        b"iinf",
+        0,
+        &[
+            &[0, 2][..],
+            &full_box(b"infe", 2, b"\0\x01\0\0hvc1\0"),
+            &full_box(b"infe", 2, b"\0\x02\0\0Exifexif\0"),
+        ]
+        .concat(),
+    );
+
+    let ipco = iso_box(
+        b"ipco",
+        &[
+            full_box(b"ispe", 0, &[0, 0, 0, 100, 0, 0, 0, 50]),
+            iso_box(b"colr", &[b"prof", ICC].concat()),
+            iso_box(b"colr", b"nclx\0\x09\0\x10\0\x09\x80"),
+            iso_box(b"irot", &[1]),
+            iso_box(b"imir", &[1]),
+        ]
+        .concat(),
+    );
+    let ipma = full_box(
+        b"ipma",
+        0,
+        &[0, 0, 0, 1, 0, 1, 5, 0x81, 0x02, 0x03, 0x84, 0x85],
+    );
+    let iprp = iso_box(b"iprp", &[ipco, ipma].concat());
+
+    let mut exif = vec![0, 0, 0, 6];
+    exif.extend_from_slice(b"Exif\0\0");
+    exif.extend_from_slice(EXIF);
+    let idat = iso_box(b"idat", &exif);
+
+    // iloc, version 1 with 4 byte offsets and lengths
+    // header (16 bytes), then each item (8 bytes) with its extents (8 bytes each)
+    let iloc_len = 16 + (8 + 2 * 8) + (8 + 8);
+    let meta_len = 12 + hdlr.len() + pitm.len() + iinf.len() + iprp.len() + idat.len() + iloc_len;
+    let data_offset = (ftyp.len() + meta_len + 16) as u32;
+
+    let mut iloc = vec![0x44, 0x00, 0, 2];
+    // item 1: two extents of the file
+    iloc.put_slice(&[0, 1, 0, 0, 0, 0, 0, 2]);
+    iloc.put_u32(data_offset);
+    iloc.put_u32(15);
+    iloc.put_u32(data_offset + 15);
+    iloc.put_u32(15);
+    // item 2: the whole `idat` box
+    iloc.put_slice(&[0, 2, 0, 1, 0, 0, 0, 1]);
+    iloc.put_u32(0);
+    iloc.put_u32(0);
+    let iloc = full_box(b"iloc", 1, &iloc);
+    assert_eq!(iloc.len(), iloc_len);
+
+    let meta = full_box(b"meta", 0, &[hdlr, pitm, iinf, iloc, iprp, idat].concat());
+    assert_eq!(meta.len(), meta_len);
+
+    let mut mdat = vec![0, 0, 0, 1];
+    mdat.put_slice(b"mdat");
+    mdat.put_u64(16 + IMAGE.len() as u64);
+    mdat.put_slice(IMAGE);
+
+    [ftyp, meta, mdat].concat().into()
+}
+
+// a HEIC image, with the EXIF before the image in `mdat` and a text item in `idat`
+pub fn heic_with_note(with_icc: bool) -> Bytes {
+    let ftyp = iso_box(b"ftyp", b"heic\0\0\0\0mif1heic");
+
+    let hdlr = hdlr();
+    let pitm = full_box(b"pitm", 0, &[0, 1]);
+    let iinf = full_box(
+        b"iinf",
+        0,
+        &[
+            &[0, 3][..],
+            &full_box(b"infe", 2, b"\0\x01\0\0hvc1\0"),
+            &full_box(b"infe", 2, b"\0\x02\0\0Exif\0"),
+            &full_box(b"infe", 2, b"\0\x03\0\0mime\0text/plain\0"),
+        ]
+        .concat(),
+    );
+    let iref = full_box(b"iref", 0, &iso_box(b"cdsc", &[0, 2, 0, 1, 0, 1]));
+
+    let colr = if with_icc {
+        iso_box(b"colr", &[b"prof", ICC].concat())
+    } else {
+        iso_box(b"free", b"")
+    };
+    let ipco = iso_box(
+        b"ipco",
+        &[
+            full_box(b"ispe", 0, &[0, 0, 0, 100, 0, 0, 0, 50]),
+            colr,
+            iso_box(b"colr", b"nclx\0\x09\0\x10\0\x09\x80"),
+            iso_box(b"irot", &[1]),
+            iso_box(b"imir", &[1]),
+        ]
+        .concat(),
+    );
+    let associations: &[u8] = if with_icc {
+        &[0x81, 0x02, 0x03, 0x84, 0x85]
+    } else {
+        &[0x81, 0x03, 0x84, 0x85]
+    };
+    let ipma = full_box(
+        b"ipma",
+        0,
+        &[&[0, 0, 0, 1, 0, 1, associations.len() as u8], associations].concat(),
+    );
+    let iprp = iso_box(b"iprp", &[ipco, ipma].concat());
+
+    let idat = iso_box(b"idat", NOTE);
+
+    // iloc, version 1 with 4 byte offsets and lengths
+    let iloc_len = 16 + (8 + 2 * 8) + (8 + 8) + (8 + 8);
+    let meta_len =
+        12 + hdlr.len() + pitm.len() + iinf.len() + iref.len() + iprp.len() + idat.len() + iloc_len;
+    let exif_offset = (ftyp.len() + meta_len + 8) as u32;
+    let image_offset = exif_offset + 4 + EXIF.len() as u32;
+
+    let mut iloc = vec![0x44, 0x00, 0, 3];
+    iloc.put_slice(&[0, 1, 0, 0, 0, 0, 0, 2]);
+    iloc.put_u32(image_offset);
+    iloc.put_u32(15);
+    iloc.put_u32(image_offset + 15);
+    iloc.put_u32(15);
+    iloc.put_slice(&[0, 2, 0, 0, 0, 0, 0, 1]);
+    iloc.put_u32(exif_offset);
+    iloc.put_u32(4 + EXIF.len() as u32);
+    iloc.put_slice(&[0, 3, 0, 1, 0, 0, 0, 1]);
+    iloc.put_u32(0);
+    iloc.put_u32(NOTE.len() as u32);
+    let iloc = full_box(b"iloc", 1, &iloc);
+    assert_eq!(iloc.len(), iloc_len);
+
+    let meta = full_box(
+        b"meta",
+        0,
+        &[hdlr, pitm, iinf, iref, iloc, iprp, idat].concat(),
+    );
+    assert_eq!(meta.len(), meta_len);
+
+    let mdat = iso_box(b"mdat", &[&[0, 0, 0, 0], EXIF, IMAGE].concat());
+
+    [ftyp, meta, mdat].concat().into()
+}
+
+// an AVIF image sequence, with an item and a track sharing the same `mdat`
+pub fn avis() -> Bytes {
+    let ftyp = iso_box(b"ftyp", b"avis\0\0\0\0avifmif1msf1");
+
+    let hdlr = hdlr();
+    let pitm = full_box(b"pitm", 0, &[0, 1]);
+    let iinf = full_box(
+        b"iinf",
+        0,
+        &[&[0, 1][..], &full_box(b"infe", 2, b"\0\x01\0\0av01\0")].concat(),
+    );
+
+    // iloc, version 0 with 4 byte offsets and lengths
+    let iloc_len = 16 + (6 + 8);
+    let meta_len = 12 + hdlr.len() + pitm.len() + iinf.len() + iloc_len;
+    // moov, trak, mdia, minf and stbl, around a stco box with one chunk
+    let moov_len = 5 * 8 + 20;
+    let image_offset = (ftyp.len() + meta_len + moov_len + 8) as u32;
+    let sample_offset = image_offset + IMAGE.len() as u32;
+
+    let mut iloc = vec![0x44, 0x00, 0, 1, 0, 1, 0, 0, 0, 1];
+    iloc.put_u32(image_offset);
+    iloc.put_u32(IMAGE.len() as u32);
+    let iloc = full_box(b"iloc", 0, &iloc);
+    assert_eq!(iloc.len(), iloc_len);
+    let meta = full_box(b"meta", 0, &[hdlr, pitm, iinf, iloc].concat());
+    assert_eq!(meta.len(), meta_len);
+
+    let mut stco = vec![0, 0, 0, 1];
+    stco.put_u32(sample_offset);
+    let mut moov = full_box(b"stco", 0, &stco);
+    for kind in [b"stbl", b"minf", b"mdia", b"trak", b"moov"] {
+        moov = iso_box(kind, &moov);
+    }
+    assert_eq!(moov.len(), moov_len);
+
+    let mdat = iso_box(b"mdat", &[IMAGE, SAMPLE].concat());
+
+    [ftyp, meta, moov, mdat].concat().into()
+}
@@ -0,0 +1,78 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    vp8::VP8Kind,
+    webp::{WebP, CHUNK_ALPH, CHUNK_VP8, CHUNK_XMP},
+    ImageEXIF, ImageICC,
+};
+
+#[test]
+fn flags_webp_exif() {
+    let file = read("P1133897.webp");
+    let exif = read("P1133897_sRGB.exif");
+
+    let mut webp = WebP::from_bytes(file.clone()).expect("parse webp");
+    let dimensions = webp.try_dimensions();
+    webp.set_exif(Some(exif));
+
+    assert_eq!(webp.kind(), VP8Kind::VP8X);
+    let flags = webp.flags().expect("flags");
+    assert!(flags.exif());
+    assert!(!flags.icc() && !flags.alpha() && !flags.xmp() && !flags.animation());
+    assert_eq!(webp.try_dimensions(), dimensions);
+
+    webp.set_exif(None);
+    assert_eq!(webp.kind(), VP8Kind::VP8);
+    assert_eq!(webp.encoder().bytes(), file);
+}
+
+#[test]
+fn flags_webp_canvas() {
+    let webp = WebP::from_bytes(read("P1133897_sRGB.webp")).expect("parse webp");
+    assert_eq!(webp.try_dimensions(), Ok((717, 512)));
+}
+
+#[test]
+fn flags_webp_keep_xmp() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let xmp = RiffChunk::new(CHUNK_XMP, RiffContent::Data(Bytes::from_static(b"<x/>")));
+    webp.chunks_mut().push(xmp);
+    webp.update_vp8x();
+
+    let flags = webp.flags().expect("flags");
+    assert!(flags.xmp());
+
+    webp.set_icc_profile(Some(read("P1133897_sRGB.icc")));
+    webp.set_icc_profile(None);
+
+    // XMP still needs the extended file format
+    assert_eq!(webp.kind(), VP8Kind::VP8X);
+    let flags = webp.flags().expect("flags");
+    assert!(flags.xmp() && !flags.icc());
+}
+
+#[test]
+fn flags_webp_alpha() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let pos = webp
+        .chunks()
+        .iter()
+        .position(|chunk| chunk.id() == CHUNK_VP8)
+        .unwrap();
+    let alph = RiffChunk::new(CHUNK_ALPH, RiffContent::Data(Bytes::from_static(&[0])));
+    webp.chunks_mut().insert(pos, alph);
+    webp.update_vp8x();
+
+    assert_eq!(webp.chunks()[0].id(), *b"VP8X");
+    assert!(webp.flags().expect("flags").alpha());
+
+    webp.remove_chunks_by_id(CHUNK_ALPH);
+    webp.update_vp8x();
+    assert_eq!(webp.kind(), VP8Kind::VP8);
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
@@ -0,0 +1,102 @@
+use bytes::Bytes;
+use img_parts::{
+    ani::{
+        Ani, AniHeader, CHUNK_ANIH, CHUNK_LIST, CHUNK_RATE, CHUNK_SEQ, FLAG_ICON, FLAG_SEQUENCE,
+    },
+    riff::{RiffInfo, INFO_INAM},
+    Error,
+};
+
+// the header of an ICO file holding no image, plus a byte to tell them apart
+fn icon(n: u8) -> Bytes {
+    Bytes::from(vec![0, 0, 1, 0, 0, 0, n])
+}
+
+#[test]
+fn frames_ani_build() {
+    let frames = vec![icon(0), icon(1), icon(2)];
+    let mut ani = Ani::from_frames(frames.clone(), 10);
+    assert_eq!(
+        ani.header(),
+        Ok(AniHeader {
+            frames: 3,
+            steps: 3,
+            width: 0,
+            height: 0,
+            bit_count: 0,
+            planes: 0,
+            display_rate: 10,
+            flags: FLAG_ICON,
+        })
+    );
+
+    ani.set_sequence(Some(&[0, 1, 2, 1]));
+    ani.set_rates(Some(&[10, 5, 5, 20]));
+    let mut info = RiffInfo::new();
+    info.set(INFO_INAM, Some(Bytes::from_static(b"Busy")));
+    ani.set_info(Some(&info));
+
+    let ids = ani
+        .chunks()
+        .iter()
+        .map(|chunk| chunk.id())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        [CHUNK_ANIH, CHUNK_SEQ, CHUNK_RATE, CHUNK_LIST, CHUNK_LIST]
+    );
+
+    let ani = Ani::from_bytes(ani.encoder().bytes()).expect("reparse ani");
+    let header = ani.header().unwrap();
+    assert_eq!(header.steps, 4);
+    assert_eq!(header.flags, FLAG_ICON | FLAG_SEQUENCE);
+    assert_eq!(ani.frames(), frames);
+    assert_eq!(ani.sequence(), Ok(Some(vec![0, 1, 2, 1])));
+    assert_eq!(ani.rates(), Ok(Some(vec![10, 5, 5, 20])));
+    assert_eq!(ani.info(), Ok(Some(info)));
+
+    let mut ani = ani;
+    ani.set_sequence(None);
+    ani.set_frames(vec![icon(3)]);
+    let header = ani.header().unwrap();
+    assert_eq!(
+        (header.frames, header.steps, header.flags),
+        (1, 1, FLAG_ICON)
+    );
+    assert_eq!(ani.sequence(), Ok(None));
+}
+
+#[test]
+fn frames_ani_parse() {
+    let mut file = Vec::new();
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&108u32.to_le_bytes());
+    file.extend_from_slice(b"ACON");
+    file.extend_from_slice(b"anih");
+    file.extend_from_slice(&36u32.to_le_bytes());
+    for field in [36u32, 2, 2, 0, 0, 0, 0, 6, FLAG_ICON] {
+        file.extend_from_slice(&field.to_le_bytes());
+    }
+    file.extend_from_slice(b"rate");
+    file.extend_from_slice(&8u32.to_le_bytes());
+    file.extend_from_slice(&[6, 0, 0, 0, 12, 0, 0, 0]);
+    file.extend_from_slice(b"LIST");
+    file.extend_from_slice(&36u32.to_le_bytes());
+    file.extend_from_slice(b"fram");
+    file.extend_from_slice(b"icon\x07\0\0\0\0\0\x01\0\0\0\x05\0");
+    file.extend_from_slice(b"icon\x07\0\0\0\0\0\x01\0\0\0\x06\0");
+    let file = Bytes::from(file);
+
+    let ani = Ani::from_bytes(file.clone()).expect("parse ani");
+    assert_eq!(ani.header().unwrap().display_rate, 6);
+    assert_eq!(ani.rates(), Ok(Some(vec![6, 12])));
+    assert_eq!(ani.sequence(), Ok(None));
+    assert_eq!(ani.frames(), [icon(5), icon(6)]);
+    assert_eq!(ani.len(), file.len() as u64);
+    assert_eq!(ani.encoder().bytes(), file);
+
+    assert_eq!(
+        Ani::from_bytes(file.slice(..file.len() - 1)),
+        Err(Error::Truncated)
+    );
+}
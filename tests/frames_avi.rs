@@ -0,0 +1,276 @@
+use std::fs;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    avi::{Avi, AviBitmapInfo, AviMainHeader, AviStreamHeader},
+    riff::{RiffChunk, RiffContent},
+    Error,
+};
+
+fn read(name: &str) -> Bytes {
+    fs::read(format!("tests/images/{name}")).unwrap().into()
+}
+
+fn data(id: &[u8; 4], data: Bytes) -> RiffChunk {
+    RiffChunk::new(*id, RiffContent::Data(data))
+}
+
+fn list(id: &[u8; 4], kind: &[u8; 4], subchunks: Vec<RiffChunk>) -> RiffChunk {
+    RiffChunk::new(
+        *id,
+        RiffContent::List {
+            kind: Some(*kind),
+            subchunks,
+        },
+    )
+}
+
+fn main_header() -> AviMainHeader {
+    AviMainHeader {
+        micro_sec_per_frame: 40_000,
+        max_bytes_per_sec: 0,
+        padding_granularity: 0,
+        flags: 0x10,
+        total_frames: 2,
+        initial_frames: 0,
+        streams: 1,
+        suggested_buffer_size: 0,
+        width: 717,
+        height: 512,
+        reserved: [0; 4],
+    }
+}
+
+fn hdrl(indx: Option<Bytes>) -> RiffChunk {
+    let stream_header = AviStreamHeader {
+        kind: *b"vids",
+        handler: *b"MJPG",
+        flags: 0,
+        priority: 0,
+        language: 0,
+        initial_frames: 0,
+        scale: 1,
+        rate: 25,
+        start: 0,
+        length: 2,
+        suggested_buffer_size: 0,
+        quality: u32::MAX,
+        sample_size: 0,
+        frame: [0, 0, 717, 512],
+    };
+    let bitmap_info = AviBitmapInfo {
+        size: 40,
+        width: 717,
+        height: 512,
+        planes: 1,
+        bit_count: 24,
+        compression: *b"MJPG",
+        size_image: 717 * 512 * 3,
+        x_pels_per_meter: 0,
+        y_pels_per_meter: 0,
+        clr_used: 0,
+        clr_important: 0,
+    };
+
+    let mut strl = vec![
+        data(b"strh", stream_header.to_bytes()),
+        data(b"strf", bitmap_info.to_bytes()),
+    ];
+    if let Some(indx) = indx {
+        strl.push(data(b"indx", indx));
+    }
+
+    list(
+        b"LIST",
+        b"hdrl",
+        vec![
+            data(b"avih", main_header().to_bytes()),
+            list(b"LIST", b"strl", strl),
+        ],
+    )
+}
+
+// a file with both frames in the same `movi` list, indexed by `idx1`
+fn avi_idx1(frame: &Bytes) -> Bytes {
+    let hdrl = hdrl(None);
+    let junk = data(b"JUNK", Bytes::from_static(b"odd"));
+    let movi = list(
+        b"LIST",
+        b"movi",
+        vec![
+            data(b"00dc", frame.clone()),
+            data(b"01wb", Bytes::from_static(b"sound")),
+            data(b"00dc", frame.clone()),
+        ],
+    );
+
+    // offsets relative to the form type of the `movi` list
+    let mut idx1 = BytesMut::new();
+    let mut offset = 4;
+    for (id, len) in [(b"00dc", frame.len()), (b"01wb", 5), (b"00dc", frame.len())] {
+        idx1.extend_from_slice(id);
+        idx1.put_u32_le(0x10);
+        idx1.put_u32_le(offset);
+        idx1.put_u32_le(len as u32);
+        offset += 8 + (len as u32 + 1) / 2 * 2;
+    }
+
+    let riff = list(
+        b"RIFF",
+        b"AVI ",
+        vec![hdrl, junk, movi, data(b"idx1", idx1.freeze())],
+    );
+    riff.encoder().bytes()
+}
+
+#[test]
+fn frames_avi_idx1() {
+    let frame = read("P1133897.jpg");
+    let file = avi_idx1(&frame);
+    let avi = Avi::from_bytes(file.clone()).expect("parse avi");
+
+    assert_eq!(avi.main_header(), Ok(main_header()));
+    assert_eq!(avi.total_frames(), Ok(2));
+    let streams = avi.streams().expect("parse streams");
+    assert_eq!(streams.len(), 1);
+    assert_eq!(streams[0].header.handler, *b"MJPG");
+    assert_eq!(streams[0].bitmap_info().unwrap().width, 717);
+
+    let chunks = avi.stream_chunks(0).expect("read idx1");
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.iter().all(|chunk| chunk.keyframe));
+    assert_eq!(avi.stream_chunks(1).unwrap()[0].data, "sound");
+
+    let frames = avi.jpeg_frames(0).expect("parse frames");
+    assert_eq!(frames.len(), 2);
+    for jpeg in frames {
+        // the frames point into the input
+        let contents = jpeg.segments()[0].contents();
+        let range = file.as_ptr_range();
+        assert!(range.contains(&contents.as_ptr()));
+
+        assert_eq!(jpeg.encoder().bytes(), frame);
+    }
+
+    assert_eq!(avi.len(), file.len() as u64);
+    assert_eq!(avi.encoder().expect("encoder").bytes(), file);
+}
+
+#[test]
+fn frames_avi_scan() {
+    let frame = read("P1133897.jpg");
+    let avi = Avi::from_bytes(avi_idx1(&frame)).unwrap();
+
+    let mut riffs = avi.riffs().clone();
+    riffs[0].remove_chunk_by_path("RIFF(AVI )/idx1").unwrap();
+    let avi = Avi::new(riffs).unwrap();
+    assert_eq!(avi.jpeg_frames(0).map(|frames| frames.len()), Ok(2));
+    assert_eq!(avi.stream_chunks(2), Ok(Vec::new()));
+}
+
+// a file with one frame in the `RIFF AVI ` chunk and one in the `RIFF AVIX`
+// chunk, indexed by an OpenDML super index
+#[test]
+fn frames_avi_opendml() {
+    let frame = read("P1133897.jpg");
+
+    let super_index = |ix_offset: u64| {
+        let mut b = BytesMut::new();
+        b.put_u16_le(4);
+        b.put_u8(0);
+        b.put_u8(0x00); // AVI_INDEX_OF_INDEXES
+        b.put_u32_le(1);
+        b.extend_from_slice(b"00dc");
+        b.put_bytes(0, 12);
+        b.put_u64_le(ix_offset);
+        b.put_u32_le(24 + 2 * 8);
+        b.put_u32_le(2);
+        b.freeze()
+    };
+    let standard_index = |offsets: [u32; 2]| {
+        let mut b = BytesMut::new();
+        b.put_u16_le(2);
+        b.put_u8(0);
+        b.put_u8(0x01); // AVI_INDEX_OF_CHUNKS
+        b.put_u32_le(2);
+        b.extend_from_slice(b"00dc");
+        b.put_u64_le(0);
+        b.put_u32_le(0);
+        b.put_u32_le(offsets[0]);
+        b.put_u32_le(frame.len() as u32);
+        b.put_u32_le(offsets[1]);
+        b.put_u32_le(frame.len() as u32 | 0x8000_0000);
+        b.freeze()
+    };
+
+    let build = |ix_offset, offsets| {
+        let riff = list(
+            b"RIFF",
+            b"AVI ",
+            vec![
+                hdrl(Some(super_index(ix_offset))),
+                list(b"LIST", b"movi", vec![data(b"00dc", frame.clone())]),
+            ],
+        );
+        let avix = list(
+            b"RIFF",
+            b"AVIX",
+            vec![list(
+                b"LIST",
+                b"movi",
+                vec![
+                    data(b"00dc", frame.clone()),
+                    data(b"ix00", standard_index(offsets)),
+                ],
+            )],
+        );
+        vec![riff, avix]
+    };
+
+    // compute the offsets from the layout, which doesn't depend on them
+    let riffs = build(0, [0; 2]);
+    let hdrl_len = riffs[0]
+        .chunk_by_path("RIFF(AVI )/LIST(hdrl)")
+        .unwrap()
+        .len();
+    let frame_len = data(b"00dc", frame.clone()).len();
+    let first = 12 + hdrl_len + 12 + 8;
+    let second = riffs[0].len() + 12 + 12 + 8;
+    let ix_offset = riffs[0].len() + 12 + 12 + frame_len;
+
+    let riffs = build(ix_offset, [first as u32, second as u32]);
+    let avi = Avi::new(riffs).expect("avi");
+    let file = avi.clone().encoder().expect("encoder").bytes();
+
+    let avi = Avi::from_bytes(file).expect("parse opendml avi");
+    assert_eq!(avi.riffs().len(), 2);
+    let chunks = avi.stream_chunks(0).expect("read indx");
+    assert_eq!(
+        chunks
+            .iter()
+            .map(|chunk| chunk.keyframe)
+            .collect::<Vec<_>>(),
+        [true, false]
+    );
+    assert_eq!(avi.jpeg_frames(0).map(|frames| frames.len()), Ok(2));
+}
+
+#[test]
+fn frames_avi_signature() {
+    let wav = list(b"RIFF", b"WAVE", Vec::new());
+    assert_eq!(Avi::new(vec![wav]), Err(Error::WrongSignature));
+    assert_eq!(Avi::new(Vec::new()), Err(Error::WrongSignature));
+
+    let avix = list(b"RIFF", b"AVIX", Vec::new());
+    assert_eq!(Avi::new(vec![avix]), Err(Error::WrongSignature));
+
+    // the accessors don't panic once the chunks are removed
+    let mut avi = Avi::from_bytes(avi_idx1(&read("P1133897.jpg"))).unwrap();
+    avi.riffs_mut().clear();
+    assert_eq!(avi.main_header(), Err(Error::InvalidData));
+    assert_eq!(avi.total_frames(), Err(Error::InvalidData));
+    assert_eq!(avi.streams(), Err(Error::InvalidData));
+    assert_eq!(avi.stream_chunks(0), Err(Error::InvalidData));
+    assert_eq!(avi.jpeg_frames(0).map(|_| ()), Err(Error::InvalidData));
+    assert_eq!(avi.len(), 0);
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    png::{Png, PngChunk, PngIccp},
+    Error, ImageICC,
+};
+
+#[test]
+fn iccp_png_default_name() {
+    let mut png = read_png("P1133897.png");
+    let icc = read_icc("P1133897_sRGB.icc");
+
+    png.set_icc_profile(Some(icc.clone()));
+    assert_eq!(png.chunks()[1].kind(), *b"iCCP");
+
+    let iccp = png.iccp().expect("iccp").expect("has iccp");
+    assert_eq!(iccp.name(), &b"icc"[..]);
+    assert_eq!(iccp.profile(icc.len()).expect("profile"), icc);
+
+    let png = Png::from_bytes(png.encoder().bytes()).expect("parse png");
+    assert_eq!(png.icc_profile(), Some(icc));
+}
+
+#[test]
+fn iccp_png_keep_name() {
+    let mut png = read_png("P1133897.png");
+    let srgb = read_icc("P1133897_sRGB.icc");
+    let adobe = read_icc("P1133897_AdobeRGB.icc");
+
+    let iccp = PngIccp::new(Bytes::from_static(b"Print profile"), &srgb, 0).expect("iccp");
+    png.set_iccp(Some(iccp));
+    png.set_icc_profile(Some(adobe.clone()));
+
+    let iccp = png.iccp().expect("iccp").expect("has iccp");
+    assert_eq!(iccp.name(), &b"Print profile"[..]);
+    assert_eq!(png.icc_profile(), Some(adobe));
+}
+
+#[test]
+fn iccp_png_precompressed() {
+    let mut png = read_png("P1133897.png");
+    let icc = read_icc("P1133897_sRGB.icc");
+
+    let compressed = PngIccp::new(Bytes::from_static(b"sRGB"), &icc, 9)
+        .expect("iccp")
+        .compressed_profile()
+        .clone();
+    let iccp =
+        PngIccp::from_compressed(Bytes::from_static(b"sRGB"), compressed.clone()).expect("iccp");
+    png.set_iccp(Some(iccp));
+
+    let iccp = png.iccp().expect("iccp").expect("has iccp");
+    assert_eq!(iccp.compressed_profile(), &compressed);
+    assert_eq!(iccp.profile(1024), Err(Error::LimitExceeded));
+    assert_eq!(png.icc_profile(), Some(icc));
+
+    png.set_iccp(None);
+    assert_eq!(png.iccp(), Ok(None));
+    assert_eq!(png.icc_profile(), None);
+}
+
+#[test]
+fn iccp_png_malformed() {
+    let mut png = read_png("P1133897.png");
+    png.chunks_mut().insert(
+        1,
+        PngChunk::new(*b"iCCP", Bytes::from_static(b"no nul terminator")),
+    );
+
+    assert_eq!(png.iccp(), Err(Error::Truncated));
+    assert_eq!(png.icc_profile(), None);
+}
+
+fn read_png(input: &str) -> Png {
+    let file = Bytes::from(fs::read(format!("tests/images/{input}")).expect("read png"));
+    Png::from_bytes(file).expect("parse png")
+}
+
+fn read_icc(icc: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{icc}")).expect("read icc"))
+}
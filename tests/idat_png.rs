@@ -0,0 +1,79 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{png::Png, Error};
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+const IDAT: [u8; 4] = *b"IDAT";
+
+#[test]
+fn rechunk_png_merge() {
+    let mut png = read_png("P1133897.png");
+    let data = png.image_data();
+
+    png.rechunk_image_data(usize::MAX).expect("rechunk");
+    assert_eq!(png.chunks_by_type(IDAT).count(), 1);
+    assert_eq!(png.image_data(), data);
+    assert_eq!(png.chunks()[5].kind(), IDAT);
+
+    reparse(png);
+}
+
+#[test]
+fn rechunk_png_split() {
+    let mut png = read_png("P1133897.png");
+    let data = png.image_data();
+
+    png.rechunk_image_data(8192).expect("rechunk");
+    let idats = png.chunks_by_type(IDAT).collect::<Vec<_>>();
+    assert_eq!(idats.len(), (data.len() + 8191) / 8192);
+    assert!(idats[..idats.len() - 1]
+        .iter()
+        .all(|chunk| chunk.contents().len() == 8192));
+    assert_eq!(png.image_data(), data);
+
+    reparse(png);
+}
+
+#[test]
+fn rechunk_png_empty_chunks() {
+    let mut png = read_png("P1133897.png");
+    let before = png.clone();
+
+    assert_eq!(png.rechunk_image_data(0), Err(Error::InvalidData));
+    assert_eq!(png, before);
+}
+
+#[test]
+fn recompress_png() {
+    let mut png = read_png("P1133897.png");
+    let raw = decompress_to_vec_zlib(&png.image_data()).expect("inflate");
+
+    assert_eq!(
+        png.clone().recompress_image_data(1, raw.len() - 1),
+        Err(Error::LimitExceeded)
+    );
+    png.recompress_image_data(1, raw.len()).expect("recompress");
+    assert!(png
+        .chunks_by_type(IDAT)
+        .all(|chunk| chunk.contents().len() <= 32768));
+    assert_eq!(
+        decompress_to_vec_zlib(&png.image_data()).expect("inflate"),
+        raw
+    );
+
+    reparse(png);
+}
+
+fn read_png(input: &str) -> Png {
+    let file = Bytes::from(fs::read(format!("tests/images/{input}")).expect("read png"));
+    Png::from_bytes(file).expect("parse png")
+}
+
+fn reparse(png: Png) {
+    let len = png.len();
+    let out = png.encoder().bytes();
+    assert_eq!(out.len(), len);
+
+    Png::from_bytes(out).expect("parse rechunked png");
+}
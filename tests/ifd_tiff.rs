@@ -0,0 +1,325 @@
+use std::fs;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    tiff::{
+        Tiff, TiffByteOrder, TiffEntry, TiffIfd, TiffValue, TiffVariant, TAG_EXIF_IFD, TAG_MAKE,
+        TAG_MODEL, TAG_STRIP_BYTE_COUNTS, TAG_STRIP_OFFSETS, TAG_SUB_IFDS, TYPE_ASCII, TYPE_LONG,
+        TYPE_LONG8, TYPE_SHORT,
+    },
+    DynImage, Error, ImageEXIF, ImageICC,
+};
+
+// a little-endian classic TIFF, with two strips and a SubIFD holding a single strip
+fn classic() -> Bytes {
+    let mut b = BytesMut::new();
+    b.put_slice(b"II\x2A\0");
+    b.put_u32_le(8);
+
+    // IFD0 at 8: 5 entries
+    b.put_u16_le(5);
+    entry(&mut b, 256, TYPE_SHORT, 1, 4); // width
+    entry(&mut b, 271, TYPE_ASCII, 4, u32::from_le_bytes(*b"Cam\0")); // make
+    entry(&mut b, 273, TYPE_LONG, 2, 100); // strip offsets
+    entry(&mut b, 279, TYPE_SHORT, 2, u32::from_le_bytes([3, 0, 5, 0])); // strip byte counts
+    entry(&mut b, 330, TYPE_LONG, 1, 120); // SubIFDs
+    b.put_u32_le(0);
+    assert_eq!(b.len(), 74);
+    b.resize(100, 0);
+
+    // strip offsets at 100, the strips at 108 and 111
+    b.put_u32_le(108);
+    b.put_u32_le(111);
+    b.put_slice(b"abc");
+    b.put_slice(b"defgh");
+    b.put_u8(0);
+    assert_eq!(b.len(), 117);
+    b.resize(120, 0);
+
+    // SubIFD at 120
+    b.put_u16_le(2);
+    entry(&mut b, 273, TYPE_LONG, 1, 150);
+    entry(&mut b, 279, TYPE_LONG, 1, 2);
+    b.put_u32_le(0);
+    b.resize(150, 0);
+    b.put_slice(b"ij");
+
+    b.freeze()
+}
+
+fn entry(b: &mut BytesMut, tag: u16, kind: u16, count: u32, value: u32) {
+    b.put_u16_le(tag);
+    b.put_u16_le(kind);
+    b.put_u32_le(count);
+    b.put_u32_le(value);
+}
+
+fn read(name: &str) -> Bytes {
+    fs::read(format!("tests/images/{name}"))
+        .expect("read")
+        .into()
+}
+
+#[test]
+fn ifd_tiff_parse() {
+    let file = classic();
+    let tiff = Tiff::from_bytes(file.clone()).expect("parse tiff");
+    assert_eq!(tiff.byte_order(), TiffByteOrder::LittleEndian);
+    assert_eq!(tiff.variant(), TiffVariant::Classic);
+    assert_eq!(tiff.ifds().len(), 1);
+
+    let ifd0 = &tiff.ifds()[0];
+    assert_eq!(
+        ifd0.entry(TAG_MAKE).and_then(TiffEntry::raw),
+        Some(&Bytes::from_static(b"Cam\0"))
+    );
+    assert_eq!(
+        ifd0.strips(),
+        Some(&vec![
+            Bytes::from_static(b"abc"),
+            Bytes::from_static(b"defgh")
+        ])
+    );
+    assert_eq!(ifd0.sub_ifds().len(), 1);
+    assert_eq!(
+        ifd0.sub_ifds()[0].strips(),
+        Some(&vec![Bytes::from_static(b"ij")])
+    );
+
+    // the image data isn't copied
+    let strip = &ifd0.strips().unwrap()[1];
+    assert!(file.as_ptr_range().contains(&strip.as_ptr()));
+}
+
+#[test]
+fn ifd_tiff_encode() {
+    let tiff = Tiff::from_bytes(classic()).expect("parse tiff");
+
+    let file = tiff.clone().encoder().bytes();
+    assert_eq!(file.len() as u64, tiff.len());
+    assert_eq!(Tiff::from_bytes(file.clone()).expect("reparse"), tiff);
+
+    // the image data is relocated after the IFDs
+    assert!(file.ends_with(b"abcdefghij"));
+}
+
+#[test]
+fn ifd_tiff_short_offsets() {
+    let mut b = BytesMut::new();
+    b.put_slice(b"II\x2A\0");
+    b.put_u32_le(8);
+
+    // IFD0 at 8: 3 entries, the strip offsets are SHORTs
+    b.put_u16_le(3);
+    entry(&mut b, 256, TYPE_SHORT, 1, 4); // width
+    entry(
+        &mut b,
+        273,
+        TYPE_SHORT,
+        2,
+        u32::from_le_bytes([50, 0, 53, 0]),
+    ); // strip offsets
+    entry(&mut b, 279, TYPE_SHORT, 2, u32::from_le_bytes([3, 0, 5, 0])); // strip byte counts
+    b.put_u32_le(0);
+    b.resize(50, 0);
+    b.put_slice(b"abcdefgh");
+
+    let mut tiff = Tiff::from_bytes(b.freeze()).expect("parse tiff");
+    let strips = vec![Bytes::from_static(b"abc"), Bytes::from_static(b"defgh")];
+    assert_eq!(tiff.ifds()[0].strips(), Some(&strips));
+
+    let icc = read("P1133897_sRGB.icc");
+    tiff.set_icc_profile(Some(icc.clone()));
+    let file = tiff.encoder().bytes();
+    assert!(file.ends_with(b"abcdefgh"));
+
+    let tiff = Tiff::from_bytes(file).expect("reparse");
+    assert_eq!(tiff.icc_profile(), Some(icc));
+    assert_eq!(tiff.ifds()[0].strips(), Some(&strips));
+}
+
+#[test]
+fn ifd_tiff_metadata() {
+    let mut tiff = Tiff::from_bytes(classic()).expect("parse tiff");
+    let icc = read("P1133897_sRGB.icc");
+    let exif = read("P1133897_sRGB.exif");
+
+    tiff.set_icc_profile(Some(icc.clone()));
+    tiff.set_xmp(Some(Bytes::from_static(b"<x:xmpmeta/>")));
+    tiff.set_iptc(Some(Bytes::from_static(b"\x1c\x02\x00\x00\x02\x00\x04")));
+    tiff.set_exif(Some(exif));
+
+    let file = tiff.clone().encoder().bytes();
+    assert_eq!(file.len() as u64, tiff.len());
+    let tiff = Tiff::from_bytes(file).expect("reparse");
+
+    assert_eq!(tiff.icc_profile(), Some(icc));
+    assert_eq!(tiff.xmp(), Some(Bytes::from_static(b"<x:xmpmeta/>")));
+    assert_eq!(
+        tiff.iptc(),
+        Some(Bytes::from_static(b"\x1c\x02\x00\x00\x02\x00\x04"))
+    );
+    let ifd0 = &tiff.ifds()[0];
+    assert_eq!(
+        ifd0.entry(TAG_MAKE).and_then(TiffEntry::raw),
+        Some(&Bytes::from_static(b"Panasonic\0"))
+    );
+    assert!(ifd0.exif_ifd().is_some());
+    assert_eq!(
+        ifd0.strips(),
+        Some(&vec![
+            Bytes::from_static(b"abc"),
+            Bytes::from_static(b"defgh")
+        ])
+    );
+
+    let exif = Tiff::from_bytes(tiff.exif().expect("exif")).expect("parse exif");
+    assert_eq!(exif.ifds().len(), 1);
+    assert_eq!(exif.ifds()[0].exif_ifd(), ifd0.exif_ifd());
+    assert!(exif.ifds()[0].entry(TAG_SUB_IFDS).is_none());
+
+    // invalid metadata leaves the current one untouched
+    let mut tiff = tiff;
+    let before = tiff.clone();
+    tiff.set_exif(Some(Bytes::from_static(b"Exif\0\0II*\0\xff\xff")));
+    assert_eq!(tiff, before);
+
+    tiff.set_icc_profile(None);
+    tiff.set_exif(None);
+    assert_eq!(tiff.icc_profile(), None);
+    assert_eq!(tiff.exif(), None);
+    assert!(tiff.ifds()[0].entry(TAG_EXIF_IFD).is_none());
+}
+
+#[test]
+fn ifd_tiff_exif_byte_order() {
+    let exif = read("P1133897_sRGB.exif");
+    let mut tiff = Tiff::new(TiffByteOrder::BigEndian, TiffVariant::Big);
+    tiff.set_exif(Some(exif.clone()));
+
+    let tiff = Tiff::from_bytes(tiff.encoder().bytes()).expect("parse bigtiff");
+    assert_eq!(tiff.byte_order(), TiffByteOrder::BigEndian);
+    assert_eq!(tiff.variant(), TiffVariant::Big);
+
+    let le = Tiff::from_bytes(exif).expect("parse exif");
+    let le = le.ifds()[0].exif_ifd().expect("exif ifd");
+    let be = tiff.ifds()[0].exif_ifd().expect("exif ifd");
+    assert_eq!(le.entries().len(), be.entries().len());
+    for (le, be) in le.entries().iter().zip(be.entries()) {
+        assert_eq!(le.tag(), be.tag());
+        assert_eq!(
+            le.values_u64(TiffByteOrder::LittleEndian),
+            be.values_u64(TiffByteOrder::BigEndian)
+        );
+    }
+}
+
+#[test]
+fn ifd_tiff_big() {
+    let order = TiffByteOrder::BigEndian;
+    let mut ifd = TiffIfd::new();
+    ifd.set_entry(TiffEntry::new(
+        TAG_STRIP_BYTE_COUNTS,
+        TYPE_SHORT,
+        TiffValue::Raw(Bytes::from_static(&[0, 2, 0, 3])),
+    ));
+    ifd.set_entry(TiffEntry::new(
+        TAG_STRIP_OFFSETS,
+        TYPE_LONG8,
+        TiffValue::Data(vec![Bytes::from_static(b"xy"), Bytes::from_static(b"zzz")]),
+    ));
+    ifd.set_entry(TiffEntry::new(
+        TAG_MODEL,
+        TYPE_ASCII,
+        TiffValue::Raw(Bytes::from_static(b"a model\0")),
+    ));
+    assert_eq!(ifd.entries()[0].tag(), TAG_MODEL);
+
+    let mut tiff = Tiff::new(order, TiffVariant::Big);
+    tiff.ifds_mut().push(ifd);
+    tiff.ifds_mut().push(TiffIfd::new());
+
+    let file = tiff.clone().encoder().bytes();
+    assert_eq!(&file[..16], b"MM\0\x2B\0\x08\0\0\0\0\0\0\0\0\0\x10");
+    let parsed = Tiff::from_bytes(file).expect("parse bigtiff");
+    assert_eq!(parsed, tiff);
+
+    assert_eq!(
+        parsed.ifds()[0].value_u64(TAG_STRIP_BYTE_COUNTS, order),
+        Some(2)
+    );
+}
+
+#[test]
+fn ifd_tiff_big_fallback() {
+    // 4097 strips of 1 MiB, sharing the same buffer
+    let strip = Bytes::from(vec![0; 1 << 20]);
+    let counts = (0..4097).flat_map(|_| (1u32 << 20).to_le_bytes());
+    let mut ifd = TiffIfd::new();
+    ifd.set_entry(TiffEntry::new(
+        TAG_STRIP_OFFSETS,
+        TYPE_LONG,
+        TiffValue::Data(vec![strip; 4097]),
+    ));
+    ifd.set_entry(TiffEntry::new(
+        TAG_STRIP_BYTE_COUNTS,
+        TYPE_LONG,
+        TiffValue::Raw(counts.collect()),
+    ));
+
+    // the offsets don't fit in a classic TIFF
+    let mut tiff = Tiff::new(TiffByteOrder::LittleEndian, TiffVariant::Classic);
+    tiff.ifds_mut().push(ifd);
+    assert_eq!(tiff.variant(), TiffVariant::Classic);
+    assert_eq!(tiff.encoded_variant(), TiffVariant::Big);
+
+    let len = tiff.len();
+    assert!(len > u64::from(u32::MAX));
+    let header = tiff.encoder().next().expect("header");
+    assert_eq!(&header[..4], b"II\x2B\0");
+
+    // the IFD at 16 starts with the strip offsets
+    let at = |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+    assert_eq!(header[26..28], TYPE_LONG8.to_le_bytes());
+    let offsets = at(36) as usize;
+    assert_eq!(at(offsets + 8 * 4096), len - (1 << 20));
+}
+
+#[test]
+fn ifd_tiff_invalid() {
+    let file = classic();
+    assert_eq!(
+        Tiff::from_bytes(file.slice(..151)).map(|_| ()),
+        Err(Error::Truncated)
+    );
+    assert_eq!(
+        Tiff::from_bytes(Bytes::from_static(b"IM\x2A\0\x08\0\0\0")).map(|_| ()),
+        Err(Error::WrongSignature)
+    );
+
+    // the strips can't be moved without their byte counts
+    let mut orphan = file.to_vec();
+    orphan[46..48].copy_from_slice(&280u16.to_le_bytes());
+    assert_eq!(
+        Tiff::from_bytes(orphan.into()).map(|_| ()),
+        Err(Error::InvalidData)
+    );
+
+    // the IFD points to itself
+    let mut looping = file.to_vec();
+    looping[70..74].copy_from_slice(&8u32.to_le_bytes());
+    assert_eq!(
+        Tiff::from_bytes(looping.into()).map(|_| ()),
+        Err(Error::InvalidData)
+    );
+}
+
+#[test]
+fn ifd_tiff_dyn_image() {
+    let file = classic();
+    let dimg = DynImage::from_bytes(file).expect("parse").expect("tiff");
+    assert!(matches!(dimg, DynImage::Tiff(_)));
+
+    let len = dimg.len();
+    assert_eq!(dimg.encoder().bytes().len(), len);
+}
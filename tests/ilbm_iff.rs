@@ -0,0 +1,171 @@
+use bytes::Bytes;
+use img_parts::{
+    iff::{
+        IffChunk, Ilbm, IlbmCompression, IlbmHeader, IlbmMasking, CHUNK_ANNO, CHUNK_BMHD,
+        CHUNK_BODY, CHUNK_CMAP, CHUNK_NAME,
+    },
+    riff::{RiffChunk, RiffContent},
+    Error,
+};
+
+fn ilbm() -> Bytes {
+    let mut file = Vec::new();
+    file.extend_from_slice(b"FORM");
+    file.extend_from_slice(&(4 + 28 + 14 + 12 + 12 + 12u32).to_be_bytes());
+    file.extend_from_slice(b"ILBM");
+    file.extend_from_slice(b"BMHD\0\0\0\x14");
+    file.extend_from_slice(&[
+        0, 16, 0, 2, // width and height
+        0, 0, 0, 0, // position
+        1, 2, 1, 0, // planes, masking, compression, padding
+        0, 1, // transparent color
+        10, 11, // aspect
+        1, 64, 0, 200, // page size
+    ]);
+    file.extend_from_slice(b"CMAP\0\0\0\x06");
+    file.extend_from_slice(&[0, 0, 0, 255, 255, 255]);
+    file.extend_from_slice(b"NAME\0\0\0\x03abc\0"); // with padding
+    file.extend_from_slice(b"CAMG\0\0\0\x04\0\0\x80\x04");
+    file.extend_from_slice(b"BODY\0\0\0\x04\x01\xff\x01\x00");
+    Bytes::from(file)
+}
+
+#[test]
+fn ilbm_iff_parse() {
+    let file = ilbm();
+    let ilbm = Ilbm::from_bytes(file.clone()).expect("parse ilbm");
+
+    assert_eq!(
+        ilbm.header(),
+        Ok(IlbmHeader {
+            width: 16,
+            height: 2,
+            x: 0,
+            y: 0,
+            planes: 1,
+            masking: IlbmMasking::TransparentColor,
+            compression: IlbmCompression::ByteRun1,
+            transparent_color: 1,
+            x_aspect: 10,
+            y_aspect: 11,
+            page_width: 320,
+            page_height: 200,
+        })
+    );
+    assert_eq!(ilbm.header().unwrap().to_bytes(), file.slice(20..40));
+    assert_eq!(ilbm.palette(), Some(vec![[0, 0, 0], [255, 255, 255]]));
+    assert_eq!(ilbm.name(), Some(Bytes::from_static(b"abc")));
+    assert_eq!(ilbm.camg(), Ok(Some(0x8004)));
+    assert_eq!(ilbm.author(), None);
+    assert_eq!(ilbm.body().map(|body| body.len()), Some(4));
+
+    assert_eq!(ilbm.len(), file.len() as u64);
+    assert_eq!(ilbm.encoder().expect("encoder").bytes(), file);
+}
+
+#[test]
+fn ilbm_iff_edit() {
+    let mut ilbm = Ilbm::from_bytes(ilbm()).unwrap();
+    ilbm.set_name(None);
+    ilbm.set_camg(None);
+    ilbm.set_author(Some(Bytes::from_static(b"Someone")));
+    ilbm.set_copyright(Some(Bytes::from_static(b"1987")));
+    ilbm.set_annotations(vec![
+        Bytes::from_static(b"first"),
+        Bytes::from_static(b"second"),
+    ]);
+    ilbm.set_palette(Some(&[[1, 2, 3]]));
+
+    let ilbm = Ilbm::from_bytes(ilbm.encoder().unwrap().bytes()).expect("reparse ilbm");
+    let ids = ilbm
+        .chunks()
+        .iter()
+        .map(|chunk| chunk.id())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        [CHUNK_BMHD, CHUNK_CMAP, *b"AUTH", *b"(c) ", CHUNK_ANNO, CHUNK_ANNO, CHUNK_BODY]
+    );
+    assert_eq!(ilbm.author(), Some(Bytes::from_static(b"Someone")));
+    assert_eq!(ilbm.copyright(), Some(Bytes::from_static(b"1987")));
+    assert_eq!(ilbm.annotations(), ["first", "second"]);
+    assert_eq!(ilbm.palette(), Some(vec![[1, 2, 3]]));
+    assert!(!ilbm.has_chunk(CHUNK_NAME));
+}
+
+#[test]
+fn ilbm_iff_groups() {
+    let prop = RiffChunk::new(
+        *b"PROP",
+        RiffContent::List {
+            kind: Some(*b"ILBM"),
+            subchunks: vec![RiffChunk::new(
+                CHUNK_CMAP,
+                RiffContent::Data(Bytes::from_static(&[1, 2, 3])),
+            )],
+        },
+    );
+    let form = RiffChunk::from(IffChunk::from_bytes(ilbm()).unwrap());
+    let list = RiffChunk::new(
+        *b"LIST",
+        RiffContent::List {
+            kind: Some(*b"ILBM"),
+            subchunks: vec![prop, form.clone(), form],
+        },
+    );
+    let cat = IffChunk::new(
+        *b"CAT ",
+        RiffContent::List {
+            kind: Some(*b"    "),
+            subchunks: vec![list],
+        },
+    );
+
+    let file = cat.clone().encoder().unwrap().bytes();
+    assert_eq!(&file[4..8], (file.len() as u32 - 8).to_be_bytes());
+    assert_eq!(&file[16..20], (file.len() as u32 - 20).to_be_bytes());
+    let parsed = IffChunk::from_bytes(file).expect("parse cat");
+    assert_eq!(parsed, cat);
+
+    let (_, lists) = parsed.content().list().unwrap();
+    let (kind, subchunks) = lists[0].content().list().unwrap();
+    assert_eq!(kind, Some(*b"ILBM"));
+    assert_eq!(subchunks[0].id(), *b"PROP");
+    assert!(Ilbm::new(subchunks[1].clone().into()).is_ok());
+    assert_eq!(
+        Ilbm::new(subchunks[0].clone().into()),
+        Err(Error::WrongSignature)
+    );
+}
+
+#[test]
+fn ilbm_iff_signature() {
+    assert_eq!(
+        IffChunk::from_bytes(Bytes::from_static(b"RIFF\0\0\0\x04WAVE")),
+        Err(Error::WrongSignature)
+    );
+
+    // the padding of the last chunk is often missing
+    let mut file = Vec::new();
+    file.extend_from_slice(b"FORM\0\0\0\x0fTEST");
+    file.extend_from_slice(b"abcd\0\0\0\x03xyz");
+    let form = IffChunk::from_bytes(Bytes::from(file)).expect("parse form");
+    assert_eq!(form.len(), 24);
+
+    assert_eq!(
+        IffChunk::from_bytes(Bytes::from_static(b"FORM\0\0\0\x10TEST")),
+        Err(Error::Truncated)
+    );
+}
+
+#[test]
+fn ilbm_iff_oversized() {
+    // 257 chunks sharing the same 16 MiB buffer, more than 4 GiB in total
+    let block = Bytes::from(vec![0; 16 * 1024 * 1024]);
+    let mut ilbm = Ilbm::from_bytes(ilbm()).unwrap();
+    ilbm.chunks_mut()
+        .extend((0..257).map(|_| RiffChunk::new(*b"BLCK", RiffContent::Data(block.clone()))));
+
+    assert!(ilbm.len() > u64::from(u32::MAX));
+    assert_eq!(ilbm.encoder().err(), Some(Error::LimitExceeded));
+}
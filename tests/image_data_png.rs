@@ -0,0 +1,63 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    png::{ColorType, Png, PngHeader},
+    Error,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+const IDAT: [u8; 4] = *b"IDAT";
+const IEND: [u8; 4] = *b"IEND";
+const IHDR: [u8; 4] = *b"IHDR";
+
+#[test]
+fn image_data_png_fixture() {
+    let file = read_png("P1133897.png");
+    let header = file.header().expect("header");
+
+    let png = Png::from_image_data(&header, file.image_data(), 8192).expect("png");
+    assert_eq!(png.header(), Ok(header));
+    assert_eq!(png.chunks().first().map(|chunk| chunk.kind()), Some(IHDR));
+    assert_eq!(png.chunks().last().map(|chunk| chunk.kind()), Some(IEND));
+    assert_eq!(png.image_data(), file.image_data());
+
+    let reparsed = Png::from_bytes(png.clone().encoder().bytes()).expect("reparse png");
+    assert_eq!(reparsed, png);
+}
+
+#[test]
+fn image_data_png_split() {
+    let header = PngHeader {
+        width: 4,
+        height: 4,
+        bit_depth: 8,
+        color_type: ColorType::Grayscale,
+        interlaced: false,
+    };
+    // every row starts with the filter type
+    let raw = (0..4u8)
+        .flat_map(|row| [0, row, row, row, row])
+        .collect::<Vec<_>>();
+    let compressed = Bytes::from(compress_to_vec_zlib(&raw, 6));
+
+    assert_eq!(
+        Png::from_image_data(&header, compressed.clone(), 0),
+        Err(Error::InvalidData)
+    );
+
+    let png = Png::from_image_data(&header, compressed.clone(), 4).expect("png");
+    let idats = png.chunks_by_type(IDAT).collect::<Vec<_>>();
+    assert_eq!(idats.len(), (compressed.len() + 3) / 4);
+    assert_eq!(png.image_data(), compressed);
+    assert_eq!(png.chunks().len(), idats.len() + 2);
+
+    let reparsed = Png::from_bytes(png.encoder().bytes()).expect("reparse png");
+    let passes = reparsed.unfiltered_scanlines(1024).expect("scanlines");
+    assert_eq!(passes[0].rows().nth(2), Some(&[2, 2, 2, 2][..]));
+}
+
+fn read_png(name: &str) -> Png {
+    let file = fs::read(format!("tests/images/{name}")).expect("read file");
+    Png::from_bytes(Bytes::from(file)).expect("parse png")
+}
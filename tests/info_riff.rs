@@ -0,0 +1,92 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent, RiffInfo, INFO_IART, INFO_ICMT, INFO_INAM, INFO_ISFT},
+    webp::WebP,
+    Error,
+};
+
+fn read(name: &str) -> Bytes {
+    fs::read(format!("tests/images/{name}")).unwrap().into()
+}
+
+#[test]
+fn info_riff_parse() {
+    let file = Bytes::from_static(&[
+        b'R', b'I', b'F', b'F', 40, 0, 0, 0, // root
+        b'T', b'E', b'S', b'T', // form type
+        b'L', b'I', b'S', b'T', 28, 0, 0, 0, b'I', b'N', b'F', b'O', // info list
+        b'I', b'N', b'A', b'M', 3, 0, 0, 0, b'H', b'i', 0, 0, // name with padding
+        b'I', b'S', b'F', b'T', 4, 0, 0, 0, b'x', b'y', b'z', 0, // software
+    ]);
+
+    let riff = RiffChunk::from_bytes(file.clone()).expect("parse riff");
+    let info = riff.info().expect("parse info").expect("info list");
+    assert_eq!(info.get(INFO_INAM), Some(&Bytes::from_static(b"Hi")));
+    assert_eq!(info.get(INFO_ISFT), Some(&Bytes::from_static(b"xyz")));
+    assert_eq!(info.get(INFO_IART), None);
+
+    let mut riff = riff;
+    riff.set_info(Some(&info)).unwrap();
+    assert_eq!(riff.encoder().bytes(), file);
+}
+
+#[test]
+fn info_riff_edit() {
+    let mut riff = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"TEST"),
+            subchunks: vec![RiffChunk::new(
+                *b"abcd",
+                RiffContent::Data(Bytes::from_static(b"x")),
+            )],
+        },
+    );
+    assert_eq!(riff.info(), Ok(None));
+
+    let mut info = RiffInfo::new();
+    info.set(INFO_INAM, Some(Bytes::from_static(b"Title")));
+    info.set(INFO_ICMT, Some(Bytes::from_static(b"Comment")));
+    info.set(INFO_INAM, Some(Bytes::from_static(b"Other title")));
+    assert_eq!(info.entries()[0].0, INFO_INAM);
+    riff.set_info(Some(&info)).unwrap();
+
+    let b = riff.clone().encoder().bytes();
+    assert_eq!(b.len() as u64, riff.len());
+    // the size of the root chunk covers the odd-sized chunks and their padding
+    assert_eq!(
+        u32::from_le_bytes(b[4..8].try_into().unwrap()) as usize,
+        b.len() - 8
+    );
+
+    let mut riff = RiffChunk::from_bytes(b).expect("reparse riff");
+    assert_eq!(riff.info(), Ok(Some(info)));
+
+    riff.set_info(None).unwrap();
+    assert_eq!(riff.info(), Ok(None));
+    assert_eq!(riff.chunk_by_path("RIFF(TEST)/abcd").map(|_| ()), Some(()));
+
+    let mut data = RiffChunk::new(*b"abcd", RiffContent::Data(Bytes::new()));
+    assert_eq!(data.set_info(None), Err(Error::InvalidData));
+}
+
+#[test]
+fn info_riff_webp() {
+    let file = read("P1133897.webp");
+    let mut webp = WebP::from_bytes(file.clone()).unwrap();
+    assert_eq!(webp.info(), Ok(None));
+
+    let mut info = RiffInfo::new();
+    info.set(INFO_ISFT, Some(Bytes::from_static(b"img-parts")));
+    webp.set_info(Some(&info));
+
+    // WebP files are parsed without looking into the subchunks of LIST chunks
+    let mut webp = WebP::from_bytes(webp.encoder().bytes()).expect("reparse webp");
+    assert_eq!(webp.info(), Ok(Some(info)));
+    assert!(webp.validate().is_empty());
+
+    webp.set_info(None);
+    assert_eq!(webp.encoder().bytes(), file);
+}
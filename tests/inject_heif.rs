@@ -0,0 +1,243 @@
+use bytes::Bytes;
+use img_parts::{
+    heif::{Heif, BOX_COLR, BOX_IMIR, BOX_IROT, BOX_ISPE, ITEM_EXIF, ITEM_MIME},
+    isobmff::{
+        self, IsoBox, IsoContent, IsoItemLocations, IsoItemReference, BOX_IDAT, BOX_ILOC, BOX_IREF,
+        BOX_MDAT, BOX_META, BOX_MOOV,
+    },
+    DynImage, ImageEXIF, ImageICC,
+};
+
+mod common;
+
+use common::heif::{avis, heic_with_note, EXIF, ICC, IMAGE, NOTE, SAMPLE};
+
+fn meta_child(heif: &Heif, kind: [u8; 4]) -> Option<&IsoBox> {
+    let meta = heif.boxes().iter().find(|b| b.kind() == BOX_META)?;
+    meta.child(kind)
+}
+
+fn references(heif: &Heif) -> Vec<IsoItemReference> {
+    let iref = meta_child(heif, BOX_IREF).expect("iref");
+    iref.children()
+        .iter()
+        .map(|b| IsoItemReference::from_bytes(b.kind(), b.data().unwrap(), false).unwrap())
+        .collect()
+}
+
+/// Encode `heif`, checking that the image isn't copied, and parse it again
+fn reencode(heif: Heif, file: &Bytes) -> Heif {
+    let len = heif.len();
+    let pieces = heif.encoder().collect::<Vec<_>>();
+    assert!(pieces.iter().any(|piece| {
+        file.as_ptr_range().contains(&piece.as_ptr())
+            && piece.windows(IMAGE.len()).any(|window| window == IMAGE)
+    }));
+
+    let out = pieces.concat();
+    assert_eq!(out.len() as u64, len);
+    Heif::from_bytes(out.into()).expect("parse heif")
+}
+
+#[test]
+fn inject_heif_noop() {
+    let file = heic_with_note(true);
+    let heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    assert_eq!(heif.exif(), Some(Bytes::from_static(EXIF)));
+
+    assert_eq!(heif.len(), file.len() as u64);
+    assert_eq!(heif.encoder().bytes(), file);
+}
+
+#[test]
+fn inject_heif_exif() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    let exif = Bytes::from_static(b"MM\0\x2A\0\0\0\x08\0\0the new exif");
+    heif.set_exif(Some(exif.clone()));
+    let heif = reencode(heif, &file);
+
+    assert_eq!(heif.exif(), Some(exif));
+    assert_eq!(heif.items().len(), 3);
+    assert_eq!(heif.item_data(1), Some(Bytes::from_static(IMAGE)));
+    assert_eq!(heif.item_data(3), Some(Bytes::from_static(NOTE)));
+
+    // the image stays in the original `mdat`, the EXIF goes to a new one at the end
+    let kinds = heif.boxes().iter().map(IsoBox::kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [*b"ftyp", BOX_META, BOX_MDAT, BOX_MDAT]);
+    let iloc = meta_child(&heif, BOX_ILOC).and_then(IsoBox::data).unwrap();
+    let iloc = IsoItemLocations::from_bytes(iloc).expect("parse iloc");
+    let image = &iloc.item(1).unwrap().extents;
+    let exif = &iloc.item(2).unwrap().extents;
+    assert_eq!(image[1].offset, image[0].offset + 15);
+    assert_eq!(exif[0].offset + exif[0].len, heif.len());
+    assert_eq!(iloc.item(3).unwrap().construction_method, 1);
+}
+
+#[test]
+fn inject_heif_exif_remove() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    heif.set_exif(None);
+    let heif = reencode(heif, &file);
+
+    assert_eq!(heif.exif(), None);
+    let kinds = heif
+        .items()
+        .iter()
+        .map(|item| item.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [*b"hvc1", ITEM_MIME]);
+    assert_eq!(references(&heif), []);
+    assert_eq!(heif.item_data(1), Some(Bytes::from_static(IMAGE)));
+    assert_eq!(heif.item_data(3), Some(Bytes::from_static(NOTE)));
+}
+
+#[test]
+fn inject_heif_exif_add() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    heif.set_exif(None);
+    heif.set_exif(Some(Bytes::from_static(EXIF)));
+    let heif = reencode(heif, &file);
+
+    assert_eq!(heif.exif(), Some(Bytes::from_static(EXIF)));
+    let exif = heif.items().into_iter().find(|item| item.kind == ITEM_EXIF);
+    assert_eq!(exif.map(|item| item.id), Some(4));
+    assert_eq!(
+        references(&heif),
+        [IsoItemReference {
+            kind: *b"cdsc",
+            from_id: 4,
+            to_ids: vec![1],
+        }]
+    );
+}
+
+#[test]
+fn inject_heif_xmp() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    assert_eq!(heif.xmp(), None);
+
+    let xmp = Bytes::from_static(b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>");
+    heif.set_xmp(Some(xmp.clone()));
+    let mut heif = reencode(heif, &file);
+    assert_eq!(heif.xmp(), Some(xmp));
+    assert_eq!(heif.items().len(), 4);
+    assert_eq!(references(&heif).len(), 2);
+    assert_eq!(heif.exif(), Some(Bytes::from_static(EXIF)));
+
+    heif.set_xmp(None);
+    let file = heif.encoder().bytes();
+    let heif = Heif::from_bytes(file).expect("parse heif");
+    assert_eq!(heif.xmp(), None);
+    assert_eq!(heif.items().len(), 3);
+    assert_eq!(references(&heif).len(), 1);
+    assert_eq!(heif.item_data(1), Some(Bytes::from_static(IMAGE)));
+}
+
+#[test]
+fn inject_heif_icc() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    let icc = Bytes::from_static(b"a new icc profile");
+    heif.set_icc_profile(Some(icc.clone()));
+    let heif = reencode(heif, &file);
+    assert_eq!(heif.icc_profile(), Some(icc));
+    assert_eq!(heif.colors().len(), 2);
+
+    // the properties following the profile are shifted
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    heif.set_icc_profile(None);
+    let heif = reencode(heif, &file);
+    assert_eq!(heif.icc_profile(), None);
+    let kinds = heif
+        .item_properties(1)
+        .iter()
+        .map(|property| property.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [BOX_ISPE, BOX_COLR, BOX_IROT, BOX_IMIR]);
+    assert!(heif.rotation().is_some());
+    assert!(heif.mirror().is_some());
+    assert_eq!(heif.exif(), Some(Bytes::from_static(EXIF)));
+}
+
+#[test]
+fn inject_heif_icc_add() {
+    let file = heic_with_note(false);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    assert_eq!(heif.icc_profile(), None);
+
+    heif.set_icc_profile(Some(Bytes::from_static(ICC)));
+    let heif = reencode(heif, &file);
+    assert_eq!(heif.icc_profile(), Some(Bytes::from_static(ICC)));
+
+    // the descriptive properties come before the transformative ones
+    let kinds = heif
+        .item_properties(1)
+        .iter()
+        .map(|property| property.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [BOX_ISPE, BOX_COLR, BOX_COLR, BOX_IROT, BOX_IMIR]);
+}
+
+#[test]
+fn inject_heif_idat() {
+    let file = heic_with_note(true);
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+
+    // the items of `idat` are relocated too
+    let meta = heif.boxes_mut().iter_mut().find(|b| b.kind() == BOX_META);
+    let idat = meta.and_then(|meta| meta.child_mut(BOX_IDAT)).unwrap();
+    *idat.content_mut() = IsoContent::Data(Bytes::from_static(b"unused"));
+
+    let heif = reencode(heif, &file);
+    assert_eq!(heif.item_data(3), Some(Bytes::from_static(NOTE)));
+    let idat = meta_child(&heif, BOX_IDAT).and_then(IsoBox::data);
+    assert_eq!(idat, Some(&Bytes::from_static(NOTE)));
+}
+
+/// Get the data of the first chunk of the track of `file`
+fn first_chunk(file: &Bytes) -> Bytes {
+    let boxes = isobmff::read_boxes(file.clone()).expect("parse boxes");
+    let moov = boxes.iter().find(|b| b.kind() == BOX_MOOV).unwrap();
+    let stbl = [*b"trak", *b"mdia", *b"minf", *b"stbl"]
+        .iter()
+        .fold(moov, |b, &kind| b.child(kind).unwrap());
+    let stco = stbl.child(*b"stco").and_then(IsoBox::data).unwrap();
+
+    let offset = u32::from_be_bytes(stco[8..12].try_into().unwrap()) as usize;
+    file.slice(offset..offset + SAMPLE.len())
+}
+
+#[test]
+fn inject_heif_sequence_noop() {
+    let file = avis();
+    let dimg = DynImage::from_bytes(file.clone())
+        .expect("parse")
+        .expect("heif");
+    assert!(matches!(dimg, DynImage::Heif(_)));
+
+    assert_eq!(dimg.len(), file.len());
+    assert_eq!(dimg.encoder().bytes(), file);
+    assert_eq!(first_chunk(&file), SAMPLE);
+}
+
+#[test]
+fn inject_heif_sequence() {
+    let file = avis();
+    let mut heif = Heif::from_bytes(file.clone()).expect("parse heif");
+    heif.set_exif(Some(Bytes::from_static(EXIF)));
+
+    // the chunk offsets follow the `mdat` box, moved by the bigger `meta` box
+    let out = heif.encoder().bytes();
+    assert_eq!(first_chunk(&out), SAMPLE);
+
+    let heif = Heif::from_bytes(out).expect("parse heif");
+    assert_eq!(heif.exif(), Some(Bytes::from_static(EXIF)));
+    assert_eq!(heif.item_data(1), Some(Bytes::from_static(IMAGE)));
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    wav::{
+        cue_chunk, Wav, WavAssociatedData, WavBext, WavCuePoint, WavSampleFormat, WavSampleLoop,
+        WavSampler, CHUNK_BEXT, CHUNK_CUE, CHUNK_DATA, CHUNK_FMT, CHUNK_IXML, CHUNK_LIST,
+    },
+    Error,
+};
+
+// 16-bit stereo PCM at 8000 Hz holding 12000 frames
+fn wav() -> Bytes {
+    let mut file = Vec::new();
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&(4 + 8 + 16 + 8 + 48000u32).to_le_bytes());
+    file.extend_from_slice(b"WAVE");
+    file.extend_from_slice(b"fmt ");
+    file.extend_from_slice(&16u32.to_le_bytes());
+    file.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    file.extend_from_slice(&2u16.to_le_bytes()); // channels
+    file.extend_from_slice(&8000u32.to_le_bytes()); // sample rate
+    file.extend_from_slice(&32000u32.to_le_bytes()); // byte rate
+    file.extend_from_slice(&4u16.to_le_bytes()); // block align
+    file.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    file.extend_from_slice(b"data");
+    file.extend_from_slice(&48000u32.to_le_bytes());
+    file.resize(file.len() + 48000, 0x55);
+    Bytes::from(file)
+}
+
+#[test]
+fn metadata_wav_format() {
+    let file = wav();
+    let wav = Wav::from_bytes(file.clone()).expect("parse wav");
+
+    let format = wav.format().expect("parse fmt");
+    assert_eq!(format.sample_format(), WavSampleFormat::Pcm);
+    assert_eq!(format.channels, 2);
+    assert_eq!(format.bits_per_sample, 16);
+    assert_eq!(format.to_bytes().unwrap(), file.slice(20..36));
+
+    assert_eq!(wav.data_range(), Some(44..48044));
+    assert_eq!(wav.frame_count(), Ok(12000));
+    assert_eq!(wav.duration(), Ok(Duration::from_millis(1500)));
+
+    assert_eq!(wav.len(), file.len() as u64);
+    assert_eq!(wav.encoder().expect("encoder").bytes(), file);
+}
+
+#[test]
+fn metadata_wav_pcmwaveformat() {
+    // an old PCM file, without the bits per sample
+    let mut file = wav().to_vec();
+    file.drain(34..36);
+    file[4..8].copy_from_slice(&(4 + 8 + 14 + 8 + 48000u32).to_le_bytes());
+    file[16..20].copy_from_slice(&14u32.to_le_bytes());
+    let file = Bytes::from(file);
+
+    let mut wav = Wav::from_bytes(file.clone()).expect("parse wav");
+    let mut format = wav.format().expect("parse fmt");
+    assert_eq!(format.bits_per_sample, 0);
+    wav.set_format(&format).unwrap();
+    assert_eq!(wav.clone().encoder().expect("encoder").bytes(), file);
+
+    format.bits_per_sample = 16;
+    wav.set_format(&format).unwrap();
+    assert_eq!(wav.encoder().expect("encoder").bytes(), self::wav());
+}
+
+#[test]
+fn metadata_wav_signature() {
+    let webp = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WEBP"),
+            subchunks: Vec::new(),
+        },
+    );
+    assert_eq!(Wav::new(webp), Err(Error::WrongSignature));
+
+    let mut wav = Wav::from_bytes(wav()).unwrap();
+    wav.remove_chunks_by_id(CHUNK_FMT);
+    assert_eq!(wav.format(), Err(Error::InvalidData));
+    assert_eq!(wav.frame_count(), Err(Error::InvalidData));
+}
+
+#[test]
+fn metadata_wav_bext_ixml() {
+    let file = wav();
+    let mut wav = Wav::from_bytes(file.clone()).unwrap();
+    assert_eq!(wav.bext(), Ok(None));
+
+    let bext = WavBext {
+        description: Bytes::from_static(b"Take 3"),
+        originator: Bytes::from_static(b"img-parts"),
+        origination_date: Bytes::from_static(b"2024-01-31"),
+        origination_time: Bytes::from_static(b"12:34:56"),
+        time_reference: 8000 * 3600,
+        loudness_value: -2300,
+        coding_history: Bytes::from_static(b"A=PCM,F=8000,W=16,M=stereo\r\n"),
+        ..WavBext::default()
+    };
+    wav.set_bext(Some(&bext));
+    wav.set_ixml(Some(Bytes::from_static(b"<BWFXML/>")));
+
+    let ids = wav
+        .chunks()
+        .iter()
+        .map(|chunk| chunk.id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [CHUNK_BEXT, CHUNK_FMT, CHUNK_DATA, CHUNK_IXML]);
+
+    let wav = Wav::from_bytes(wav.encoder().expect("encoder").bytes()).expect("reparse wav");
+    assert_eq!(wav.bext(), Ok(Some(bext)));
+    assert_eq!(wav.ixml(), Some(Bytes::from_static(b"<BWFXML/>")));
+    // the samples moved after the bext chunk, but aren't changed
+    let data = wav.data_range().unwrap();
+    assert_eq!(data.end - data.start, 48000);
+
+    let mut wav = wav;
+    wav.set_bext(None);
+    wav.set_ixml(None);
+    assert_eq!(wav.encoder().expect("encoder").bytes(), file);
+}
+
+#[test]
+fn metadata_wav_markers() {
+    let mut wav = Wav::from_bytes(wav()).unwrap();
+    assert_eq!(wav.cue_points(), Ok(Vec::new()));
+    assert_eq!(wav.associated_data(), Ok(Vec::new()));
+
+    let points = vec![WavCuePoint::new(1, 0), WavCuePoint::new(2, 6000)];
+    let entries = vec![
+        WavAssociatedData::Label {
+            cue_id: 1,
+            text: Bytes::from_static(b"Intro"),
+        },
+        WavAssociatedData::Note {
+            cue_id: 2,
+            text: Bytes::from_static(b"odd"),
+        },
+        WavAssociatedData::LabeledText {
+            cue_id: 2,
+            sample_length: 3000,
+            purpose: *b"rgn ",
+            country: 0,
+            language: 0,
+            dialect: 0,
+            code_page: 0,
+            text: Bytes::from_static(b"Chorus"),
+        },
+    ];
+    wav.set_cue_points(&points);
+    wav.set_associated_data(&entries);
+
+    assert_eq!(wav.chunk_by_id(CHUNK_CUE), Some(&cue_chunk(&points)));
+
+    let mut wav = Wav::from_bytes(wav.encoder().expect("encoder").bytes()).expect("reparse wav");
+    assert_eq!(wav.cue_points(), Ok(points));
+    assert_eq!(wav.associated_data(), Ok(entries));
+    assert!(wav.has_chunk(CHUNK_LIST));
+
+    wav.set_cue_points(&[]);
+    wav.set_associated_data(&[]);
+    assert_eq!(wav.encoder().expect("encoder").bytes(), self::wav());
+}
+
+#[test]
+fn metadata_wav_sampler() {
+    let mut wav = Wav::from_bytes(wav()).unwrap();
+
+    let sampler = WavSampler {
+        manufacturer: 0,
+        product: 0,
+        sample_period: 125_000,
+        midi_unity_note: 60,
+        midi_pitch_fraction: 0,
+        smpte_format: 0,
+        smpte_offset: 0,
+        loops: vec![WavSampleLoop {
+            cue_point_id: 1,
+            kind: 0,
+            start: 100,
+            end: 11_999,
+            fraction: 0,
+            play_count: 0,
+        }],
+        sampler_data: Bytes::from_static(b"xyz"),
+    };
+    wav.set_sampler(Some(&sampler)).unwrap();
+
+    let wav = Wav::from_bytes(wav.encoder().expect("encoder").bytes()).expect("reparse wav");
+    assert_eq!(wav.sampler(), Ok(Some(sampler)));
+}
@@ -0,0 +1,243 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    webp::{WebP, WebPAnimation, WebPFrame, CHUNK_ANMF, CHUNK_VP8},
+    Error,
+};
+
+#[test]
+fn path_riff_iter() {
+    let avi = avi();
+    let paths = avi
+        .iter()
+        .map(|(path, chunk)| (path.to_string(), chunk.id()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        [
+            ("RIFF(AVI )".to_owned(), *b"RIFF"),
+            ("RIFF(AVI )/LIST(hdrl)".to_owned(), *b"LIST"),
+            ("RIFF(AVI )/LIST(hdrl)/avih".to_owned(), *b"avih"),
+            ("RIFF(AVI )/LIST(hdrl)/LIST(strl)".to_owned(), *b"LIST"),
+            ("RIFF(AVI )/LIST(hdrl)/LIST(strl)/strh".to_owned(), *b"strh"),
+            ("RIFF(AVI )/LIST(hdrl)/LIST(strl)[1]".to_owned(), *b"LIST"),
+            (
+                "RIFF(AVI )/LIST(hdrl)/LIST(strl)[1]/strh".to_owned(),
+                *b"strh"
+            ),
+            ("RIFF(AVI )/JUNK".to_owned(), *b"JUNK"),
+            ("RIFF(AVI )/LIST(movi)".to_owned(), *b"LIST"),
+            ("RIFF(AVI )/LIST(movi)/00dc".to_owned(), *b"00dc"),
+            ("RIFF(AVI )/LIST(movi)/00dc[1]".to_owned(), *b"00dc"),
+        ]
+    );
+
+    // every yielded path leads back to its chunk
+    for (path, chunk) in avi.iter() {
+        assert_eq!(avi.chunk_by_path(&path.to_string()), Some(chunk));
+    }
+}
+
+#[test]
+fn path_riff_query() {
+    let avi = avi();
+
+    // the form type of the root chunk can be its own segment
+    let avih = avi
+        .chunk_by_path("RIFF/AVI /LIST(hdrl)/avih")
+        .expect("avih");
+    assert_eq!(avih.content().data(), Some(&Bytes::from_static(b"avih")));
+    assert_eq!(avi.chunk_by_path("RIFF(AVI )/LIST(hdrl)/avih"), Some(avih));
+
+    let strh = avi
+        .chunk_by_path("RIFF(AVI )/LIST(hdrl)/LIST[1]/strh")
+        .expect("strh");
+    assert_eq!(strh.content().data(), Some(&Bytes::from_static(b"strh 1")));
+
+    let frame = avi
+        .chunk_by_path("RIFF(AVI )/LIST(movi)/00dc[1]")
+        .expect("frame");
+    assert_eq!(
+        frame.content().data(),
+        Some(&Bytes::from_static(b"frame 1"))
+    );
+
+    assert_eq!(avi.chunk_by_path("RIFF(AVI )/LIST(movi)/00dc[2]"), None);
+    assert_eq!(avi.chunk_by_path("RIFF(AVI )/avih"), None);
+    assert_eq!(avi.chunk_by_path("RIFF(AVI )/JUNK/data"), None);
+    assert_eq!(avi.chunk_by_path("LIST/AVI "), None);
+    assert_eq!(avi.chunk_by_path("not a path"), None);
+}
+
+#[test]
+fn path_riff_webp() {
+    let still = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let frames = (0..3)
+        .map(|i| WebPFrame::from_webp(&still, 0, 0, i * 100).expect("frame"))
+        .collect();
+    let animation = WebPAnimation {
+        background_color: [0, 0, 0, 0],
+        loop_count: 0,
+    };
+    let webp = WebP::from_frames(animation, frames).expect("from frames");
+    let riff = RiffChunk::from_bytes(webp.encoder().bytes()).expect("parse riff");
+
+    let anmf = riff.chunk_by_path("RIFF(WEBP)/ANMF[2]").expect("anmf");
+    assert_eq!(anmf.id(), CHUNK_ANMF);
+    let frame = WebPFrame::from_bytes(anmf.content().data().expect("data")).expect("frame");
+    assert_eq!(frame.duration, 200);
+
+    // the chunks of the frames are found after the frame header
+    let vp8 = still.chunk_by_id(CHUNK_VP8).expect("vp8");
+    assert_eq!(
+        riff.chunk_by_path("RIFF/WEBP/ANMF[2]/VP8 ").as_deref(),
+        Some(vp8)
+    );
+    let paths = riff
+        .iter()
+        .map(|(path, _)| path.to_string())
+        .filter(|path| path.starts_with("RIFF(WEBP)/ANMF[1]"))
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["RIFF(WEBP)/ANMF[1]", "RIFF(WEBP)/ANMF[1]/VP8 "]);
+
+    // and edited by encoding the frame again
+    let mut riff = riff;
+    let removed = riff
+        .remove_chunk_by_path("RIFF(WEBP)/ANMF[1]/VP8 ")
+        .expect("remove");
+    assert_eq!(&removed, vp8);
+    assert_eq!(riff.chunk_by_path("RIFF(WEBP)/ANMF[1]/VP8 "), None);
+    let anmf = riff.chunk_by_path("RIFF(WEBP)/ANMF[1]").expect("anmf");
+    let frame = WebPFrame::from_bytes(anmf.content().data().expect("data")).expect("frame");
+    assert_eq!(frame.duration, 100);
+    assert!(frame.chunks().is_empty());
+}
+
+#[test]
+fn path_riff_edit() {
+    let mut avi = avi();
+
+    let frame = RiffChunk::new(*b"01wb", RiffContent::Data(Bytes::from_static(b"audio")));
+    avi.insert_chunk_by_path("RIFF(AVI )/LIST(movi)", 1, frame.clone())
+        .expect("insert");
+    assert_eq!(
+        avi.chunk_by_path("RIFF(AVI )/LIST(movi)/01wb").as_deref(),
+        Some(&frame)
+    );
+    assert_eq!(
+        avi.chunk_by_path("RIFF(AVI )/LIST(movi)/00dc[1]")
+            .and_then(|chunk| chunk.content().data().cloned()),
+        Some(Bytes::from_static(b"frame 1"))
+    );
+
+    let avih = RiffChunk::new(*b"avih", RiffContent::Data(Bytes::from_static(b"new")));
+    let old = avi
+        .replace_chunk_by_path("RIFF(AVI )/LIST(hdrl)/avih", avih.clone())
+        .expect("replace");
+    assert_eq!(old.content().data(), Some(&Bytes::from_static(b"avih")));
+    assert_eq!(
+        avi.chunk_by_path("RIFF(AVI )/LIST(hdrl)/avih").as_deref(),
+        Some(&avih)
+    );
+
+    let junk = avi.remove_chunk_by_path("RIFF(AVI )/JUNK").expect("remove");
+    assert_eq!(junk.id(), *b"JUNK");
+    assert_eq!(avi.chunk_by_path("RIFF(AVI )/JUNK"), None);
+
+    // the edits survive encoding
+    let reparsed = RiffChunk::from_bytes(avi.clone().encoder().bytes()).expect("reparse");
+    assert_eq!(reparsed, avi);
+}
+
+#[test]
+fn path_riff_edit_invalid() {
+    let mut avi = avi();
+    let chunk = RiffChunk::new(*b"JUNK", RiffContent::Data(Bytes::new()));
+
+    assert_eq!(
+        avi.insert_chunk_by_path("RIFF(AVI )/JUNK", 0, chunk.clone()),
+        Err(Error::InvalidData)
+    );
+    assert_eq!(
+        avi.insert_chunk_by_path("RIFF(AVI )/LIST(movi)", 3, chunk.clone()),
+        Err(Error::InvalidData)
+    );
+    assert_eq!(
+        avi.replace_chunk_by_path("RIFF(AVI )/idx1", chunk),
+        Err(Error::InvalidData)
+    );
+    assert_eq!(
+        avi.remove_chunk_by_path("RIFF(AVI )"),
+        Err(Error::InvalidData)
+    );
+    assert_eq!(avi, self::avi());
+}
+
+#[test]
+fn path_riff_walk_mut() {
+    let mut avi = avi();
+
+    let mut visited = Vec::new();
+    avi.walk_mut(|path, chunk| {
+        visited.push(path.to_string());
+
+        // drop the padding and anything below it
+        if let RiffContent::List { subchunks, .. } = chunk.content_mut() {
+            subchunks.retain(|chunk| chunk.id() != *b"JUNK");
+        }
+        if chunk.id() == *b"00dc" {
+            *chunk.content_mut() = RiffContent::Data(Bytes::from_static(b"empty"));
+        }
+    });
+
+    assert_eq!(visited.len(), 10);
+    assert!(!visited.iter().any(|path| path.ends_with("JUNK")));
+    assert!(avi
+        .iter()
+        .filter(|(_, chunk)| chunk.id() == *b"00dc")
+        .all(|(_, chunk)| chunk.content().data() == Some(&Bytes::from_static(b"empty"))));
+}
+
+fn avi() -> RiffChunk {
+    let data = |id: &[u8; 4], data: &'static [u8]| {
+        RiffChunk::new(*id, RiffContent::Data(Bytes::from_static(data)))
+    };
+    let list = |kind: &[u8; 4], subchunks: Vec<RiffChunk>| {
+        RiffChunk::new(
+            *b"LIST",
+            RiffContent::List {
+                kind: Some(*kind),
+                subchunks,
+            },
+        )
+    };
+
+    RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"AVI "),
+            subchunks: vec![
+                list(
+                    b"hdrl",
+                    vec![
+                        data(b"avih", b"avih"),
+                        list(b"strl", vec![data(b"strh", b"strh 0")]),
+                        list(b"strl", vec![data(b"strh", b"strh 1")]),
+                    ],
+                ),
+                data(b"JUNK", b"junk"),
+                list(
+                    b"movi",
+                    vec![data(b"00dc", b"frame 0"), data(b"00dc", b"frame 1")],
+                ),
+            ],
+        },
+    )
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
@@ -0,0 +1,298 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    jpeg::Jpeg,
+    raw::{Raw, RawPreviewSource},
+    tiff::{
+        Tiff, TiffByteOrder, TiffEntry, TiffIfd, TiffValue, TiffVariant, TAG_EXIF_IFD,
+        TAG_JPEG_INTERCHANGE_FORMAT, TAG_JPEG_INTERCHANGE_FORMAT_LENGTH, TAG_MAKE, TAG_MAKER_NOTE,
+        TAG_STRIP_BYTE_COUNTS, TAG_STRIP_OFFSETS, TAG_SUB_IFDS, TYPE_ASCII, TYPE_LONG,
+        TYPE_UNDEFINED,
+    },
+    Error, ImageEXIF,
+};
+
+// a minimal JPEG, with `marker` as its frame header
+fn jpeg(marker: u8, width: u16, height: u16) -> Bytes {
+    let mut b = BytesMut::new();
+    b.put_slice(&[0xFF, 0xD8]);
+    // the quantization and Huffman tables
+    b.put_slice(&[0xFF, 0xDB, 0, 67, 0]);
+    b.put_slice(&[1; 64]);
+    b.put_slice(&[0xFF, 0xC4, 0, 19, 0]);
+    b.put_slice(&[0; 16]);
+    b.put_slice(&[0xFF, marker, 0, 11, 8]);
+    b.put_u16(height);
+    b.put_u16(width);
+    b.put_slice(&[1, 1, 0x11, 0]);
+    b.put_slice(&[0xFF, 0xDA, 0, 8, 1, 1, 0, 0, 0x3F, 0]);
+    b.put_slice(&[0x12, 0x34, 0x56]);
+    b.put_slice(&[0xFF, 0xD9]);
+    b.freeze()
+}
+
+fn raw_entry(tag: u16, kind: u16, value: &[u8]) -> TiffEntry {
+    TiffEntry::new(tag, kind, TiffValue::Raw(Bytes::copy_from_slice(value)))
+}
+
+fn long(tag: u16, value: u32) -> TiffEntry {
+    raw_entry(tag, TYPE_LONG, &value.to_le_bytes())
+}
+
+// the entries storing `data` as a single strip
+fn strip(data: Bytes) -> [TiffEntry; 2] {
+    [
+        long(TAG_STRIP_BYTE_COUNTS, data.len() as u32),
+        TiffEntry::new(TAG_STRIP_OFFSETS, TYPE_LONG, TiffValue::Data(vec![data])),
+    ]
+}
+
+// the entries storing `data` as a JPEG thumbnail
+fn thumbnail(data: Bytes) -> [TiffEntry; 2] {
+    [
+        long(TAG_JPEG_INTERCHANGE_FORMAT_LENGTH, data.len() as u32),
+        TiffEntry::new(
+            TAG_JPEG_INTERCHANGE_FORMAT,
+            TYPE_LONG,
+            TiffValue::Data(vec![data]),
+        ),
+    ]
+}
+
+// a Nikon MakerNote, holding a PreviewIFD pointing to `preview`
+fn nikon_makernote(preview: &[u8]) -> Bytes {
+    let mut b = BytesMut::new();
+    b.put_slice(b"Nikon\0\x02\x10\0\0");
+    // the offsets are relative to the TIFF header
+    b.put_slice(b"II\x2A\0");
+    b.put_u32_le(8);
+    // IFD0 at 8, with a PreviewIFD at 26
+    b.put_u16_le(1);
+    b.put_slice(&[0x11, 0, 13, 0, 1, 0, 0, 0, 26, 0, 0, 0]);
+    b.put_u32_le(0);
+    // PreviewIFD at 26, with the preview at 56
+    b.put_u16_le(2);
+    b.put_slice(&[0x01, 0x02, 4, 0, 1, 0, 0, 0, 56, 0, 0, 0]);
+    b.put_slice(&[0x02, 0x02, 4, 0, 1, 0, 0, 0]);
+    b.put_u32_le(preview.len() as u32);
+    b.put_u32_le(0);
+    b.put_slice(preview);
+    b.freeze()
+}
+
+// a TIFF based RAW file, like a NEF or a CR2
+fn tiff_raw() -> Bytes {
+    let mut exif = TiffIfd::new();
+    exif.set_entry(raw_entry(
+        TAG_MAKER_NOTE,
+        TYPE_UNDEFINED,
+        &nikon_makernote(&jpeg(0xC0, 640, 480)),
+    ));
+
+    // the RAW image data, stored as a lossless JPEG
+    let sub_ifd = TiffIfd::from_entries(strip(jpeg(0xC3, 4000, 3000)).to_vec());
+
+    let mut ifd0 = TiffIfd::from_entries(strip(jpeg(0xC0, 400, 300)).to_vec());
+    ifd0.set_entry(raw_entry(TAG_MAKE, TYPE_ASCII, b"NIKON\0"));
+    ifd0.set_entry(TiffEntry::new(
+        TAG_SUB_IFDS,
+        TYPE_LONG,
+        TiffValue::Ifds(vec![sub_ifd]),
+    ));
+    ifd0.set_entry(TiffEntry::new(
+        TAG_EXIF_IFD,
+        TYPE_LONG,
+        TiffValue::Ifds(vec![exif]),
+    ));
+    let ifd1 = TiffIfd::from_entries(thumbnail(jpeg(0xC0, 160, 120)).to_vec());
+
+    let mut tiff = Tiff::new(TiffByteOrder::LittleEndian, TiffVariant::Classic);
+    tiff.ifds_mut().push(ifd0);
+    tiff.ifds_mut().push(ifd1);
+    tiff.encoder().bytes()
+}
+
+#[test]
+fn preview_raw_tiff() {
+    let file = tiff_raw();
+    let raw = Raw::from_bytes(file.clone()).expect("parse raw");
+
+    let previews = raw
+        .previews()
+        .iter()
+        .map(|preview| (preview.source(), preview.width(), preview.height()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        previews,
+        [
+            (RawPreviewSource::MakerNote, 640, 480),
+            (RawPreviewSource::Ifd, 400, 300),
+            (RawPreviewSource::Ifd, 160, 120),
+        ]
+    );
+
+    // the previews are sliced from the input
+    let largest = raw.largest_preview().expect("preview");
+    assert_eq!(largest.len(), jpeg(0xC0, 640, 480).len());
+    let entropy = largest.jpeg().segments().last().expect("scan").contents();
+    assert!(file.as_ptr_range().contains(&entropy.as_ptr()));
+
+    // the EXIF metadata can be copied onto the preview
+    let mut preview = largest.clone().into_jpeg();
+    preview.set_exif(raw.exif().cloned());
+    let preview = Jpeg::from_bytes(preview.encoder().bytes()).expect("parse preview");
+    let exif = Tiff::from_bytes(preview.exif().expect("exif")).expect("parse exif");
+    assert_eq!(
+        exif.ifds()[0].entry(TAG_MAKE).and_then(TiffEntry::raw),
+        Some(&Bytes::from_static(b"NIKON\0"))
+    );
+    assert!(exif.ifds()[0].exif_ifd().is_some());
+}
+
+#[test]
+fn preview_raw_rw2() {
+    let preview = jpeg(0xC0, 1920, 1080);
+    let ifd0 = TiffIfd::from_entries(vec![raw_entry(0x002E, TYPE_UNDEFINED, &preview)]);
+    let mut tiff = Tiff::new(TiffByteOrder::LittleEndian, TiffVariant::Classic);
+    tiff.ifds_mut().push(ifd0);
+
+    let mut file = tiff.encoder().bytes().to_vec();
+    file[2..4].copy_from_slice(b"U\0");
+
+    let raw = Raw::from_bytes(file.into()).expect("parse rw2");
+    assert_eq!(raw.previews().len(), 1);
+    assert_eq!(raw.previews()[0].width(), 1920);
+    assert_eq!(raw.exif(), None);
+}
+
+#[test]
+fn preview_raw_orf() {
+    let preview = jpeg(0xC0, 1600, 1200);
+    let mut makernote = BytesMut::new();
+    makernote.put_slice(b"OLYMPUS\0II\x03\0");
+    // IFD at 12, with the CameraSettings IFD at 30
+    makernote.put_u16_le(1);
+    makernote.put_slice(&[0x20, 0x20, 13, 0, 1, 0, 0, 0, 30, 0, 0, 0]);
+    makernote.put_u32_le(0);
+    // CameraSettings IFD at 30, with the preview at 72
+    makernote.put_u16_le(3);
+    makernote.put_slice(&[0x00, 0x01, 4, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+    makernote.put_slice(&[0x01, 0x01, 4, 0, 1, 0, 0, 0, 72, 0, 0, 0]);
+    makernote.put_slice(&[0x02, 0x01, 4, 0, 1, 0, 0, 0]);
+    makernote.put_u32_le(preview.len() as u32);
+    makernote.put_u32_le(0);
+    makernote.put_slice(&preview);
+
+    let exif = TiffIfd::from_entries(vec![raw_entry(TAG_MAKER_NOTE, TYPE_UNDEFINED, &makernote)]);
+    let ifd0 = TiffIfd::from_entries(vec![TiffEntry::new(
+        TAG_EXIF_IFD,
+        TYPE_LONG,
+        TiffValue::Ifds(vec![exif]),
+    )]);
+    let mut tiff = Tiff::new(TiffByteOrder::LittleEndian, TiffVariant::Classic);
+    tiff.ifds_mut().push(ifd0);
+
+    let mut file = tiff.encoder().bytes().to_vec();
+    file[2..4].copy_from_slice(b"RO");
+
+    let raw = Raw::from_bytes(file.into()).expect("parse orf");
+    assert_eq!(raw.previews().len(), 1);
+    assert_eq!(raw.previews()[0].source(), RawPreviewSource::MakerNote);
+    assert_eq!(raw.previews()[0].height(), 1200);
+}
+
+fn iso_box(kind: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+    let mut b = Vec::new();
+    b.extend_from_slice(&(8 + contents.len() as u32).to_be_bytes());
+    b.extend_from_slice(kind);
+    b.extend_from_slice(contents);
+    b
+}
+
+#[test]
+fn preview_raw_cr3() {
+    const UUID_CANON: [u8; 16] = [
+        0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0, 0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a,
+        0x48,
+    ];
+    const UUID_PREVIEW: [u8; 16] = [
+        0xea, 0xf4, 0x2b, 0x5e, 0x1c, 0x98, 0x4b, 0x88, 0xb9, 0xfb, 0xb7, 0xdc, 0x40, 0x6e, 0x4d,
+        0x16,
+    ];
+
+    let thumbnail = jpeg(0xC0, 160, 120);
+    let mut thmb = vec![0, 0, 0, 0, 0, 160, 0, 120];
+    thmb.extend_from_slice(&(thumbnail.len() as u32).to_be_bytes());
+    thmb.extend_from_slice(&[0, 1, 0, 0]);
+    thmb.extend_from_slice(&thumbnail);
+
+    let preview = jpeg(0xC0, 1620, 1080);
+    let mut prvw = vec![0, 0, 0, 0, 0, 1, 0x06, 0x54, 0x04, 0x38, 0, 1];
+    prvw.extend_from_slice(&(preview.len() as u32).to_be_bytes());
+    prvw.extend_from_slice(&preview);
+
+    // the IFD0 and EXIF IFD metadata are stored as separate TIFF files
+    let mut cmt1 = Tiff::new(TiffByteOrder::LittleEndian, TiffVariant::Classic);
+    cmt1.ifds_mut().push(TiffIfd::from_entries(vec![raw_entry(
+        TAG_MAKE, TYPE_ASCII, b"Canon\0",
+    )]));
+    let mut cmt2 = Tiff::new(TiffByteOrder::BigEndian, TiffVariant::Classic);
+    cmt2.ifds_mut()
+        .push(TiffIfd::from_entries(vec![raw_entry(0x8827, 3, &[0, 100])]));
+
+    let mut canon = UUID_CANON.to_vec();
+    canon.extend(iso_box(b"CMT1", &cmt1.encoder().bytes()));
+    canon.extend(iso_box(b"CMT2", &cmt2.encoder().bytes()));
+    canon.extend(iso_box(b"THMB", &thmb));
+
+    let mut uuid_preview = UUID_PREVIEW.to_vec();
+    uuid_preview.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+    uuid_preview.extend(iso_box(b"PRVW", &prvw));
+
+    let mut file = iso_box(b"ftyp", b"crx \0\0\0\x01crx isom");
+    file.extend(iso_box(b"moov", &iso_box(b"uuid", &canon)));
+    file.extend(iso_box(b"uuid", &uuid_preview));
+    file.extend(iso_box(b"mdat", &[0; 16]));
+
+    let raw = Raw::from_bytes(file.into()).expect("parse cr3");
+    let previews = raw
+        .previews()
+        .iter()
+        .map(|preview| (preview.source(), preview.width()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        previews,
+        [
+            (RawPreviewSource::Cr3Preview, 1620),
+            (RawPreviewSource::Cr3Thumbnail, 160)
+        ]
+    );
+    assert_eq!(
+        raw.smallest_preview().map(|preview| preview.len()),
+        Some(thumbnail.len())
+    );
+
+    let exif = Tiff::from_bytes(raw.exif().cloned().expect("exif")).expect("parse exif");
+    let ifd0 = &exif.ifds()[0];
+    assert_eq!(
+        ifd0.entry(TAG_MAKE).and_then(TiffEntry::raw),
+        Some(&Bytes::from_static(b"Canon\0"))
+    );
+    // the EXIF IFD was converted to the byte order of IFD0
+    assert_eq!(
+        ifd0.exif_ifd()
+            .and_then(|exif| exif.value_u64(0x8827, TiffByteOrder::LittleEndian)),
+        Some(100)
+    );
+}
+
+#[test]
+fn preview_raw_invalid() {
+    assert_eq!(
+        Raw::from_bytes(jpeg(0xC0, 1, 1)),
+        Err(Error::WrongSignature)
+    );
+
+    let mut file = tiff_raw().to_vec();
+    file.truncate(file.len() - 1);
+    assert_eq!(Raw::from_bytes(file.into()), Err(Error::Truncated));
+}
@@ -0,0 +1,78 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    png::{Png, PngChunk, PngChunkInfo, PngChunkOrdering},
+    ImageICC,
+};
+
+#[test]
+fn properties_png_remove_unsafe() {
+    let file = Bytes::from(fs::read("tests/images/P1133897.png").expect("read png"));
+    let mut png = Png::from_bytes(file).expect("parse png");
+    png.chunks_mut()
+        .insert(1, PngChunk::new(*b"prVt", Bytes::from_static(b"safe")));
+    png.chunks_mut()
+        .insert(1, PngChunk::new(*b"prVT", Bytes::from_static(b"unsafe")));
+    png.chunks_mut()
+        .insert(1, PngChunk::new(*b"prVU", Bytes::from_static(b"known")));
+
+    png.remove_unsafe_to_copy_chunks(&[*b"prVU"]);
+
+    let kinds = png
+        .chunks()
+        .iter()
+        .map(|chunk| chunk.kind())
+        .filter(|kind| kind != b"IDAT")
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            *b"IHDR", *b"prVU", *b"prVt", *b"gAMA", *b"cHRM", *b"bKGD", *b"pHYs", *b"tEXt",
+            *b"tEXt", *b"IEND"
+        ]
+    );
+    assert_eq!(png.chunks_by_type(*b"IDAT").count(), 16);
+}
+
+#[test]
+fn properties_png_remove_unsafe_keeps_registry() {
+    let file = Bytes::from(fs::read("tests/images/P1133897.png").expect("read png"));
+    let mut png = Png::from_bytes(file).expect("parse png");
+    png.set_icc_profile(Some(Bytes::from_static(b"profile")));
+    png.chunks_mut()
+        .insert(1, PngChunk::new(*b"prVT", Bytes::from_static(b"unsafe")));
+
+    png.recompress_image_data(1, usize::MAX)
+        .expect("recompress");
+    png.remove_unsafe_to_copy_chunks(&[]);
+
+    assert!(png.chunk_by_type(*b"prVT").is_none());
+    assert!(png.chunk_by_type(*b"iCCP").is_some());
+    assert_eq!(png.icc_profile(), Some(Bytes::from_static(b"profile")));
+}
+
+#[test]
+fn properties_png_registry() {
+    let info = PngChunkInfo::from_kind(*b"IDAT").expect("IDAT info");
+    assert!(info.multiple);
+    assert_eq!(info.ordering, PngChunkOrdering::Consecutive);
+
+    let info = PngChunk::new(*b"iCCP", Bytes::new())
+        .info()
+        .expect("iCCP info");
+    assert!(!info.multiple);
+    assert_eq!(info.ordering, PngChunkOrdering::BeforePlteAndIdat);
+    assert_eq!(info.description, "Embedded ICC profile");
+
+    let info = PngChunkInfo::from_kind(*b"fdAT").expect("fdAT info");
+    assert_eq!(info.ordering, PngChunkOrdering::AfterIdat);
+
+    assert!(PngChunk::new(*b"prVt", Bytes::new()).info().is_none());
+    assert!(PngChunkInfo::all()
+        .iter()
+        .all(
+            |info| PngChunk::new(info.kind, Bytes::new()).is_safe_to_copy()
+                == (info.kind[3] & 0x20 != 0)
+        ));
+}
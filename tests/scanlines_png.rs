@@ -0,0 +1,115 @@
+use std::fs;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use img_parts::{
+    png::{ColorType, Png, PngChunk, PngHeader},
+    Error,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+#[test]
+fn scanlines_png_filtered() {
+    let png = read_png("P1133897.png");
+    let header = png.header().expect("header");
+
+    let passes = png.filtered_scanlines(usize::MAX).expect("scanlines");
+    assert_eq!(passes.len(), 1);
+
+    let pass = &passes[0];
+    assert!(pass.is_filtered());
+    assert_eq!((pass.width(), pass.height()), (header.width, header.height));
+    assert_eq!(
+        pass.row_len(),
+        1 + (header.width * header.bits_per_pixel() as u32 / 8) as usize
+    );
+    assert_eq!(pass.rows().count(), header.height as usize);
+    assert!(pass.rows().all(|row| row[0] <= 4));
+}
+
+#[test]
+fn scanlines_png_unfiltered() {
+    let png = read_png("P1133897.png");
+    let header = png.header().expect("header");
+
+    let passes = png.unfiltered_scanlines(usize::MAX).expect("scanlines");
+    let pass = &passes[0];
+    assert!(!pass.is_filtered());
+    assert_eq!(
+        pass.data().len(),
+        (header.width * header.height * header.bits_per_pixel() as u32 / 8) as usize
+    );
+}
+
+#[test]
+fn scanlines_png_limit() {
+    let png = read_png("P1133897.png");
+
+    assert_eq!(png.filtered_scanlines(1024), Err(Error::LimitExceeded));
+    assert_eq!(png.unfiltered_scanlines(1024), Err(Error::LimitExceeded));
+}
+
+#[test]
+fn scanlines_png_tiny() {
+    let header = PngHeader {
+        width: 3,
+        height: 2,
+        bit_depth: 8,
+        color_type: ColorType::Grayscale,
+        interlaced: false,
+    };
+    // row 0 uses the Sub filter, row 1 the Up filter
+    let filtered = [1, 10, 5, 5, 2, 1, 2, 3];
+    let png = build_png(header, &filtered);
+
+    let passes = png.unfiltered_scanlines(64).expect("scanlines");
+    let rows = passes[0].rows().collect::<Vec<_>>();
+    assert_eq!(rows, [&[10, 15, 20], &[11, 17, 23]]);
+}
+
+#[test]
+fn scanlines_png_interlaced() {
+    let header = PngHeader {
+        width: 2,
+        height: 2,
+        bit_depth: 8,
+        color_type: ColorType::Grayscale,
+        interlaced: true,
+    };
+    // pass 1: pixel (0, 0), pass 6: pixel (1, 0), pass 7: pixels (0, 1) and (1, 1)
+    let filtered = [0, 1, 0, 2, 0, 3, 4];
+    let png = build_png(header, &filtered);
+
+    let passes = png.unfiltered_scanlines(64).expect("scanlines");
+    let sizes = passes
+        .iter()
+        .map(|pass| (pass.width(), pass.height()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sizes,
+        [(1, 1), (0, 0), (0, 0), (0, 0), (0, 0), (1, 1), (2, 1)]
+    );
+    assert_eq!(passes[0].data(), &[1][..]);
+    assert_eq!(passes[5].data(), &[2][..]);
+    assert_eq!(passes[6].data(), &[3, 4][..]);
+}
+
+fn read_png(input: &str) -> Png {
+    let file = Bytes::from(fs::read(format!("tests/images/{input}")).expect("read png"));
+    Png::from_bytes(file).expect("parse png")
+}
+
+fn build_png(header: PngHeader, filtered: &[u8]) -> Png {
+    let mut b = BytesMut::new();
+    b.put_slice(&[0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+    let compressed = Bytes::from(compress_to_vec_zlib(filtered, 6));
+    for chunk in [
+        PngChunk::new(*b"IHDR", header.to_bytes()),
+        PngChunk::new(*b"IDAT", compressed),
+        PngChunk::new(*b"IEND", Bytes::new()),
+    ] {
+        b.put(chunk.encoder().bytes());
+    }
+
+    Png::from_bytes(b.freeze()).expect("parse png")
+}
@@ -0,0 +1,100 @@
+use bytes::Bytes;
+use img_parts::riff::{RiffChunk, RiffContent, RiffPreset, RiffSchema};
+
+// a vendor format where `GRUP` chunks group other chunks without a form type
+struct VendorSchema;
+
+impl RiffSchema for VendorSchema {
+    fn has_subchunks(&self, id: [u8; 4]) -> bool {
+        matches!(&id, b"RIFF" | b"GRUP")
+    }
+
+    fn has_kind(&self, id: [u8; 4]) -> bool {
+        &id == b"RIFF"
+    }
+}
+
+#[test]
+fn schema_riff_custom() {
+    let file = vendor_file();
+
+    let generic = RiffChunk::from_bytes(file.clone()).expect("parse generic");
+    let grup = generic.chunk_by_path("RIFF(VNDR)/GRUP").expect("grup");
+    assert!(grup.content().data().is_some());
+
+    let riff = RiffChunk::from_bytes_with_schema(file.clone(), &VendorSchema).expect("parse");
+    let grup = riff.chunk_by_path("RIFF(VNDR)/GRUP").expect("grup");
+    let (kind, subchunks) = grup.content().list().expect("list");
+    assert_eq!(kind, None);
+    assert_eq!(subchunks.len(), 2);
+    assert_eq!(
+        riff.chunk_by_path("RIFF(VNDR)/GRUP/item[1]")
+            .and_then(|chunk| chunk.content().data().cloned()),
+        Some(Bytes::from_static(b"second"))
+    );
+
+    // both representations encode to the same file
+    assert_eq!(generic.encoder().bytes(), file);
+    assert_eq!(riff.encoder().bytes(), file);
+
+    let dynamic: &dyn RiffSchema = &VendorSchema;
+    assert!(RiffChunk::from_bytes_with_schema(file, dynamic).is_ok());
+}
+
+#[test]
+fn schema_riff_presets() {
+    let list = |id: &[u8; 4]| {
+        let riff = RiffChunk::new(
+            *b"RIFF",
+            RiffContent::List {
+                kind: Some(*b"TEST"),
+                subchunks: vec![RiffChunk::new(
+                    *id,
+                    RiffContent::List {
+                        kind: Some(*b"fram"),
+                        subchunks: vec![RiffChunk::new(
+                            *b"icon",
+                            RiffContent::Data(Bytes::from_static(b"icon")),
+                        )],
+                    },
+                )],
+            },
+        );
+        riff.encoder().bytes()
+    };
+    let is_list = |file: Bytes, preset: RiffPreset| {
+        let riff = RiffChunk::from_bytes_with_schema(file, &preset).expect("parse");
+        riff.content().list().expect("list").1[0]
+            .content()
+            .list()
+            .is_some()
+    };
+
+    assert!(is_list(list(b"LIST"), RiffPreset::Generic));
+    assert!(is_list(list(b"LIST"), RiffPreset::Wav));
+    assert!(is_list(list(b"LIST"), RiffPreset::Avi));
+    assert!(is_list(list(b"LIST"), RiffPreset::Ani));
+    assert!(!is_list(list(b"LIST"), RiffPreset::WebP));
+    assert!(!is_list(list(b"UNKN"), RiffPreset::Generic));
+    assert_eq!(RiffPreset::default(), RiffPreset::Generic);
+}
+
+fn vendor_file() -> Bytes {
+    let item =
+        |data: &'static [u8]| RiffChunk::new(*b"item", RiffContent::Data(Bytes::from_static(data)));
+
+    let riff = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"VNDR"),
+            subchunks: vec![RiffChunk::new(
+                *b"GRUP",
+                RiffContent::List {
+                    kind: None,
+                    subchunks: vec![item(b"first"), item(b"second")],
+                },
+            )],
+        },
+    );
+    riff.encoder().bytes()
+}
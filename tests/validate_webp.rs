@@ -0,0 +1,206 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    webp::{
+        WebP, WebPAnimation, WebPFrame, WebPViolation, CHUNK_ALPH, CHUNK_ANIM, CHUNK_ANMF,
+        CHUNK_EXIF, CHUNK_ICCP, CHUNK_VP8, CHUNK_VP8X, CHUNK_XMP,
+    },
+    ImageEXIF, ImageICC,
+};
+
+#[test]
+fn validate_webp_fixtures() {
+    for name in [
+        "P1133897.webp",
+        "P1133897_sRGB.webp",
+        "P1133897_AdobeRGB.webp",
+    ] {
+        let webp = WebP::from_bytes(read(name)).expect("parse webp");
+        assert_eq!(webp.validate(), vec![], "{name}");
+    }
+}
+
+#[test]
+fn validate_webp_misplaced() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    webp.set_icc_profile(Some(read("P1133897_sRGB.icc")));
+    webp.set_exif(Some(read("P1133897_sRGB.exif")));
+    assert_eq!(webp.validate(), vec![]);
+
+    // move the ICC profile after the image data
+    let iccp = webp.chunks_mut().remove(1);
+    webp.chunks_mut().push(iccp);
+    assert_eq!(
+        webp.validate(),
+        vec![WebPViolation::Misplaced {
+            index: 3,
+            id: CHUNK_ICCP
+        }]
+    );
+
+    webp.normalize();
+    assert_eq!(webp.validate(), vec![]);
+    assert_eq!(ids(&webp), [CHUNK_VP8X, CHUNK_ICCP, CHUNK_VP8, CHUNK_EXIF]);
+}
+
+#[test]
+fn validate_webp_exif_before_xmp() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    webp.chunks_mut().push(RiffChunk::new(
+        CHUNK_XMP,
+        RiffContent::Data(Bytes::from_static(b"<x:xmpmeta/>")),
+    ));
+    webp.set_exif(Some(read("P1133897_sRGB.exif")));
+
+    assert_eq!(ids(&webp), [CHUNK_VP8X, CHUNK_VP8, CHUNK_EXIF, CHUNK_XMP]);
+    assert_eq!(webp.validate(), vec![]);
+}
+
+#[test]
+fn validate_webp_normalize_unknown() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let unknown = RiffChunk::new(*b"UNKN", RiffContent::Data(Bytes::from_static(b"data")));
+    let exif = RiffChunk::new(CHUNK_EXIF, RiffContent::Data(read("P1133897_sRGB.exif")));
+    webp.chunks_mut().insert(0, exif);
+    webp.chunks_mut().insert(1, unknown);
+
+    webp.normalize();
+    assert_eq!(ids(&webp), [CHUNK_VP8X, CHUNK_VP8, CHUNK_EXIF, *b"UNKN"]);
+    assert_eq!(webp.validate(), vec![]);
+    assert!(webp.flags().expect("flags").exif());
+}
+
+#[test]
+fn validate_webp_incorrect_flags() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    webp.set_exif(Some(read("P1133897_sRGB.exif")));
+
+    // drop the EXIF chunk without updating VP8X
+    webp.chunks_mut().pop();
+    assert_eq!(
+        webp.validate(),
+        vec![WebPViolation::IncorrectFlags { index: 0 }]
+    );
+
+    webp.normalize();
+    assert_eq!(webp.validate(), vec![]);
+}
+
+#[test]
+fn validate_webp_missing_vp8x() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    assert!(!webp.has_chunk(CHUNK_VP8X));
+
+    let exif = RiffChunk::new(CHUNK_EXIF, RiffContent::Data(read("P1133897_sRGB.exif")));
+    webp.chunks_mut().push(exif);
+    assert_eq!(
+        webp.validate(),
+        vec![WebPViolation::MissingVP8X {
+            index: 1,
+            id: CHUNK_EXIF
+        }]
+    );
+}
+
+#[test]
+fn validate_webp_alpha_without_vp8() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    webp.set_exif(Some(read("P1133897_sRGB.exif")));
+    let alph = RiffChunk::new(CHUNK_ALPH, RiffContent::Data(Bytes::from_static(&[0])));
+    webp.chunks_mut().insert(1, alph.clone());
+    webp.update_vp8x();
+    assert_eq!(webp.validate(), vec![]);
+
+    // unknown chunks are skipped, and moved after the image data
+    let unknown = RiffChunk::new(*b"UNKN", RiffContent::Data(Bytes::new()));
+    webp.chunks_mut().insert(2, unknown);
+    assert_eq!(webp.validate(), vec![]);
+    webp.normalize();
+    assert_eq!(
+        ids(&webp),
+        [CHUNK_VP8X, CHUNK_ALPH, CHUNK_VP8, *b"UNKN", CHUNK_EXIF]
+    );
+
+    // the image data must follow the ALPH chunk
+    let alph = webp.chunks_mut().remove(1);
+    webp.chunks_mut().insert(3, alph);
+    assert_eq!(
+        webp.validate(),
+        vec![
+            WebPViolation::Misplaced {
+                index: 3,
+                id: CHUNK_ALPH
+            },
+            WebPViolation::AlphaWithoutVP8 { index: 3 }
+        ]
+    );
+}
+
+#[test]
+fn validate_webp_duplicate() {
+    let mut webp = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let vp8 = webp.chunk_by_id(CHUNK_VP8).expect("vp8").clone();
+    webp.chunks_mut().push(vp8);
+    assert_eq!(
+        webp.validate(),
+        vec![WebPViolation::Duplicate {
+            index: 1,
+            id: CHUNK_VP8
+        }]
+    );
+}
+
+#[test]
+fn validate_webp_animation() {
+    let still = WebP::from_bytes(read("P1133897.webp")).expect("parse webp");
+    let frame = WebPFrame::from_webp(&still, 0, 0, 100).expect("frame");
+    let animation = WebPAnimation {
+        background_color: [0, 0, 0, 0],
+        loop_count: 0,
+    };
+    let mut webp = WebP::from_frames(animation, vec![frame.clone(), frame]).expect("from frames");
+    assert_eq!(webp.validate(), vec![]);
+
+    // frames require the ANIM chunk
+    let pos = ids(&webp).iter().position(|id| *id == CHUNK_ANIM).unwrap();
+    let anim = webp.chunks_mut().remove(pos);
+    assert!(webp
+        .validate()
+        .contains(&WebPViolation::FrameWithoutAnimation { index: 1 }));
+
+    // still image data can't be mixed with frames
+    webp.chunks_mut().insert(pos, anim);
+    let vp8 = still.chunk_by_id(CHUNK_VP8).expect("vp8").clone();
+    webp.chunks_mut().insert(pos + 1, vp8);
+    assert_eq!(
+        webp.validate(),
+        vec![WebPViolation::StillImageInAnimation {
+            index: pos + 1,
+            id: CHUNK_VP8
+        }]
+    );
+    assert_eq!(ids(&webp)[pos + 2], CHUNK_ANMF);
+}
+
+#[test]
+fn validate_webp_missing_image() {
+    let webp = WebP::new(RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WEBP"),
+            subchunks: Vec::new(),
+        },
+    ))
+    .expect("webp");
+    assert_eq!(webp.validate(), vec![WebPViolation::MissingImage]);
+}
+
+fn ids(webp: &WebP) -> Vec<[u8; 4]> {
+    webp.chunks().iter().map(|chunk| chunk.id()).collect()
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}
@@ -0,0 +1,220 @@
+use bytes::{Buf, Bytes};
+use img_parts::{
+    riff::{RiffChunk, RiffContent, RiffVariant},
+    webp::WebP,
+    Error, ImageEncoder,
+};
+
+#[test]
+fn variant_riff_rifx() {
+    let file = Bytes::from_static(&[
+        b'R', b'I', b'F', b'X', 0, 0, 0, 16, // root
+        b'T', b'E', b'S', b'T', // form type
+        b'a', b'b', b'c', b'd', 0, 0, 0, 3, b'x', b'y', b'z', 0, // data with padding
+    ]);
+
+    let riff = RiffChunk::from_bytes(file.clone()).expect("parse rifx");
+    assert_eq!(riff.variant(), Some(RiffVariant::Rifx));
+    assert_eq!(
+        riff.chunk_by_path("RIFX(TEST)/abcd")
+            .and_then(|chunk| chunk.content().data().cloned()),
+        Some(Bytes::from_static(b"xyz"))
+    );
+    assert_eq!(riff.clone().encoder().bytes(), file);
+
+    let mut riff = riff;
+    assert_eq!(riff.promote_to_rf64(), Err(Error::InvalidData));
+}
+
+#[test]
+fn variant_riff_rf64_parse() {
+    let mut file = Vec::new();
+    file.extend_from_slice(b"RF64\xff\xff\xff\xffWAVE");
+    // ds64 with a table entry for the `big ` chunk
+    file.extend_from_slice(b"ds64");
+    file.extend_from_slice(&40u32.to_le_bytes());
+    file.extend_from_slice(&0u64.to_le_bytes()); // riff size, ignored
+    file.extend_from_slice(&5u64.to_le_bytes()); // data size
+    file.extend_from_slice(&7u64.to_le_bytes()); // sample count
+    file.extend_from_slice(&1u32.to_le_bytes());
+    file.extend_from_slice(b"big ");
+    file.extend_from_slice(&2u64.to_le_bytes());
+    file.extend_from_slice(b"big \xff\xff\xff\xffhi");
+    file.extend_from_slice(b"data\xff\xff\xff\xffhello\0");
+
+    let riff = RiffChunk::from_bytes(Bytes::from(file)).expect("parse rf64");
+    assert_eq!(riff.variant(), Some(RiffVariant::Rf64));
+    let data = |path: &str| {
+        riff.chunk_by_path(path)
+            .and_then(|chunk| chunk.content().data().cloned())
+    };
+    assert_eq!(data("RF64(WAVE)/big "), Some(Bytes::from_static(b"hi")));
+    assert_eq!(data("RF64(WAVE)/data"), Some(Bytes::from_static(b"hello")));
+
+    let encoded = riff.clone().encoder().bytes();
+    assert_eq!(&encoded[4..8], &[0xff; 4]);
+    let ds64 = ds64(&encoded);
+    assert_eq!(ds64.riff_size, riff.content().len());
+    assert_eq!(ds64.data_size, 5);
+    assert_eq!(ds64.sample_count, 7);
+    assert_eq!(ds64.table, []);
+
+    let reparsed = RiffChunk::from_bytes(encoded).expect("reparse");
+    assert_eq!(without_ds64(reparsed), without_ds64(riff));
+}
+
+#[test]
+fn variant_riff_rf64_missing_ds64() {
+    let file = Bytes::from_static(b"RF64\xff\xff\xff\xffWAVEdata\xff\xff\xff\xff");
+    assert_eq!(RiffChunk::from_bytes(file), Err(Error::InvalidData));
+}
+
+#[test]
+fn variant_riff_promote() {
+    let mut riff = wave(vec![
+        RiffChunk::new(*b"fmt ", RiffContent::Data(Bytes::from_static(&[0; 16]))),
+        RiffChunk::new(*b"data", RiffContent::Data(Bytes::from_static(b"samples"))),
+    ]);
+    assert!(!riff.requires_rf64());
+
+    riff.promote_to_rf64().expect("promote");
+    assert_eq!(riff.variant(), Some(RiffVariant::Rf64));
+    assert_eq!(riff.content().list().expect("list").1[0].id(), *b"ds64");
+
+    let encoded = riff.clone().encoder().bytes();
+    assert_eq!(&encoded[..4], b"RF64");
+    let ds64 = ds64(&encoded);
+    assert_eq!(ds64.riff_size, encoded.len() as u64 - 8);
+    assert_eq!(ds64.data_size, 7);
+    let reparsed = RiffChunk::from_bytes(encoded).expect("reparse");
+    assert_eq!(without_ds64(reparsed), without_ds64(riff.clone()));
+
+    // promoting again does nothing
+    let promoted = riff.clone();
+    riff.promote_to_rf64().expect("promote");
+    assert_eq!(riff, promoted);
+}
+
+#[test]
+fn variant_riff_oversized() {
+    // 257 chunks sharing the same 16 MiB buffer, more than 4 GiB in total
+    let block = Bytes::from(vec![0; 16 * 1024 * 1024]);
+    let list = RiffChunk::new(
+        *b"LIST",
+        RiffContent::List {
+            kind: Some(*b"blks"),
+            subchunks: (0..257)
+                .map(|_| RiffChunk::new(*b"blck", RiffContent::Data(block.clone())))
+                .collect(),
+        },
+    );
+    let mut riff = wave(vec![list]);
+    assert!(riff.requires_rf64());
+    assert!(riff.len() > u64::from(u32::MAX));
+
+    // RIFF can't hold the sizes
+    assert_eq!(riff.clone().try_encoder().err(), Some(Error::LimitExceeded));
+    let pieces = riff.clone().encoder().take(3).collect::<Vec<_>>();
+    assert_eq!(&pieces[0][4..8], &[0xff; 4]);
+    assert_eq!(&pieces[2][..], b"LIST\xff\xff\xff\xff");
+
+    riff.promote_to_rf64().expect("promote");
+    assert!(riff.clone().try_encoder().is_ok());
+    let list_len = riff
+        .chunk_by_path("RF64(WAVE)/LIST")
+        .expect("list")
+        .content()
+        .len();
+    let pieces = riff.clone().encoder().take(4).collect::<Vec<_>>();
+    let ds64 = ds64(&pieces[..3].concat());
+    assert_eq!(ds64.riff_size, riff.content().len());
+    assert_eq!(ds64.table, [(*b"LIST", list_len)]);
+    assert_eq!(&pieces[3][..], b"LIST\xff\xff\xff\xff");
+}
+
+#[test]
+fn variant_riff_rf64_grow() {
+    // promoted while it didn't need any entry in the ds64 table
+    let mut riff = wave(Vec::new());
+    riff.promote_to_rf64().expect("promote");
+    let len = riff.len();
+
+    let block = Bytes::from(vec![0; 16 * 1024 * 1024]);
+    let list = RiffChunk::new(
+        *b"LIST",
+        RiffContent::List {
+            kind: Some(*b"blks"),
+            subchunks: (0..257)
+                .map(|_| RiffChunk::new(*b"blck", RiffContent::Data(block.clone())))
+                .collect(),
+        },
+    );
+    let list_len = list.content().len();
+    riff.insert_chunk_by_path("RF64(WAVE)", 1, list)
+        .expect("insert");
+    // the table grows by one entry, even without going through `encoder`
+    assert_eq!(riff.len(), len + 12 + 8 + list_len);
+
+    let encoder = ImageEncoder::from(riff.clone());
+    let pieces = encoder.collect::<Vec<_>>();
+    assert_eq!(
+        pieces.iter().map(|piece| piece.len() as u64).sum::<u64>(),
+        riff.len()
+    );
+    let ds64 = ds64(&pieces[..3].concat());
+    assert_eq!(ds64.riff_size, riff.len() - 8);
+    assert_eq!(ds64.table, [(*b"LIST", list_len)]);
+}
+
+#[test]
+fn variant_riff_webp() {
+    let file = Bytes::from_static(b"RIFX\0\0\0\x04WEBP");
+    assert_eq!(WebP::from_bytes(file), Err(Error::WrongSignature));
+}
+
+struct Ds64 {
+    riff_size: u64,
+    data_size: u64,
+    sample_count: u64,
+    table: Vec<([u8; 4], u64)>,
+}
+
+// parse the ds64 chunk, placed right after the root chunk header
+fn ds64(file: &[u8]) -> Ds64 {
+    assert_eq!(&file[12..16], b"ds64");
+    let mut b = &file[20..];
+
+    let riff_size = b.get_u64_le();
+    let data_size = b.get_u64_le();
+    let sample_count = b.get_u64_le();
+    let table = (0..b.get_u32_le())
+        .map(|_| {
+            let mut id = [0; 4];
+            b.copy_to_slice(&mut id);
+            (id, b.get_u64_le())
+        })
+        .collect();
+
+    Ds64 {
+        riff_size,
+        data_size,
+        sample_count,
+        table,
+    }
+}
+
+// the sizes in the ds64 chunk are only updated while encoding
+fn without_ds64(mut riff: RiffChunk) -> RiffChunk {
+    riff.remove_chunk_by_path("RF64(WAVE)/ds64").expect("ds64");
+    riff
+}
+
+fn wave(subchunks: Vec<RiffChunk>) -> RiffChunk {
+    RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WAVE"),
+            subchunks,
+        },
+    )
+}
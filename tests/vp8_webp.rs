@@ -0,0 +1,70 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    vp8::{VP8FrameType, VP8Header},
+    webp::{WebP, CHUNK_VP8},
+    Error, ImageEXIF,
+};
+
+#[test]
+fn vp8_webp_header() {
+    let file = Bytes::from(fs::read("tests/images/P1133897.webp").expect("read webp"));
+    let webp = WebP::from_bytes(file).expect("parse webp");
+
+    let header = webp.vp8_header().expect("vp8 header");
+    assert_eq!(header.frame_type, VP8FrameType::KeyFrame);
+    assert!(header.show_frame);
+
+    let key_frame = header.key_frame.expect("key frame");
+    assert_eq!((key_frame.width, key_frame.height), (717, 512));
+    assert_eq!(
+        (key_frame.horizontal_scale, key_frame.vertical_scale),
+        (0, 0)
+    );
+    assert_eq!(key_frame.color_space, 0);
+    assert_eq!(webp.try_dimensions(), Ok((717, 512)));
+    assert_eq!(webp.dimensions(), Some((717, 512)));
+}
+
+#[test]
+fn vp8_webp_truncated() {
+    let mut webp = vp8_webp(&[0x00, 0x00, 0x00, 0x9d]);
+    assert_eq!(webp.try_dimensions(), Err(Error::Truncated));
+    assert_eq!(webp.dimensions(), None);
+    assert!(webp.vp8_header().is_none());
+
+    // editing the image doesn't panic either
+    webp.set_exif(Some(Bytes::from_static(b"II*\0")));
+    webp.set_exif(None);
+}
+
+#[test]
+fn vp8_webp_bad_magic() {
+    let webp = vp8_webp(&[0x00, 0x00, 0x00, 0x9d, 0x01, 0x2b, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(webp.try_dimensions(), Err(Error::WrongSignature));
+}
+
+#[test]
+fn vp8_webp_interframe() {
+    let webp = vp8_webp(&[0x01, 0x00, 0x00, 0x00]);
+    assert_eq!(webp.try_dimensions(), Err(Error::InvalidData));
+
+    let header = VP8Header::from_bytes(&[0x01, 0x00, 0x00]).expect("header");
+    assert_eq!(header.frame_type, VP8FrameType::InterFrame);
+}
+
+fn vp8_webp(vp8: &'static [u8]) -> WebP {
+    let riff = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WEBP"),
+            subchunks: vec![RiffChunk::new(
+                CHUNK_VP8,
+                RiffContent::Data(Bytes::from_static(vp8)),
+            )],
+        },
+    );
+    WebP::new(riff).expect("webp")
+}
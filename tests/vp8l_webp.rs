@@ -0,0 +1,66 @@
+use std::fs;
+
+use bytes::Bytes;
+use img_parts::{
+    riff::{RiffChunk, RiffContent},
+    vp8::VP8Kind,
+    webp::{WebP, CHUNK_ICCP, CHUNK_VP8L, CHUNK_VP8X},
+    ImageEXIF, ImageICC,
+};
+
+#[test]
+fn vp8l_webp_icc() {
+    let (mut webp, file) = build_vp8l_webp(true);
+    assert_eq!(webp.kind(), VP8Kind::VP8L);
+
+    let icc = read("P1133897_sRGB.icc");
+    webp.set_icc_profile(Some(icc.clone()));
+
+    let ids = webp.chunks().iter().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(ids, [CHUNK_VP8X, CHUNK_ICCP, CHUNK_VP8L]);
+    let flags = webp.flags().expect("flags");
+    assert!(flags.icc() && flags.alpha());
+    assert_eq!(webp.try_dimensions(), Ok((2, 3)));
+    assert_eq!(webp.icc_profile(), Some(icc));
+
+    webp.set_icc_profile(None);
+    assert_eq!(webp.kind(), VP8Kind::VP8L);
+    assert_eq!(webp.encoder().bytes(), file);
+}
+
+#[test]
+fn vp8l_webp_exif_no_alpha() {
+    let (mut webp, file) = build_vp8l_webp(false);
+
+    webp.set_exif(Some(read("P1133897_sRGB.exif")));
+    let ids = webp.chunks().iter().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(ids, [CHUNK_VP8X, CHUNK_VP8L, *b"EXIF"]);
+    let flags = webp.flags().expect("flags");
+    assert!(flags.exif() && !flags.alpha());
+
+    let reparsed = WebP::from_bytes(webp.clone().encoder().bytes()).expect("parse webp");
+    assert_eq!(reparsed, webp);
+
+    webp.set_exif(None);
+    assert_eq!(webp.encoder().bytes(), file);
+}
+
+fn build_vp8l_webp(alpha: bool) -> (WebP, Bytes) {
+    // 2x3 VP8L header followed by some bitstream bytes
+    let alpha_bit = if alpha { 0x10 } else { 0x00 };
+    let vp8l = Bytes::from(vec![0x2f, 0x01, 0x80, 0x00, alpha_bit, 0x12, 0x34]);
+
+    let riff = RiffChunk::new(
+        *b"RIFF",
+        RiffContent::List {
+            kind: Some(*b"WEBP"),
+            subchunks: vec![RiffChunk::new(CHUNK_VP8L, RiffContent::Data(vp8l))],
+        },
+    );
+    let file = riff.encoder().bytes();
+    (WebP::from_bytes(file.clone()).expect("parse webp"), file)
+}
+
+fn read(name: &str) -> Bytes {
+    Bytes::from(fs::read(format!("tests/images/{name}")).expect("read file"))
+}